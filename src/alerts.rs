@@ -0,0 +1,775 @@
+use crate::models::{AllMetrics, AllMetricsHistory};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Memory has to drop this far below its threshold before the breach clears
+const MEMORY_REARM_MARGIN: f64 = 5.0;
+
+/// Something worth telling the user about outside the menu bar
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alert {
+    ServiceCrashed {
+        reason: String,
+    },
+    CrashLoop {
+        crashes: usize,
+        window: Duration,
+        last_log: Option<String>,
+    },
+    ModelLoaded {
+        model: String,
+        took: Duration,
+    },
+    QueueBackedUp {
+        model: String,
+        queued: u32,
+    },
+    LatencyHigh {
+        model: String,
+        p95_ms: f64,
+    },
+    ThroughputLow {
+        model: String,
+        tps: f64,
+    },
+    ThroughputRegressed {
+        model: String,
+        tps: f64,
+        baseline: f64,
+    },
+    MemoryHigh {
+        percent: f64,
+    },
+    LlamaMemoryHigh {
+        gb: f64,
+    },
+    Resolved {
+        breach: Breach,
+    }, // A breach that was alerted has cleared
+}
+
+impl Alert {
+    /// Every kind, for validating and documenting notification categories
+    pub const KINDS: [&'static str; 9] = [
+        "service_crashed",
+        "crash_loop",
+        "model_loaded",
+        "queue_backed_up",
+        "latency_high",
+        "throughput_low",
+        "throughput_regressed",
+        "memory_high",
+        "llama_memory_high",
+    ];
+
+    /// Stable identifier, e.g. the webhook payload's `event`; a resolution
+    /// shares its breach's kind so it follows the same notification toggle
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Alert::Resolved { breach } => breach.kind(),
+            Alert::ServiceCrashed { .. } => "service_crashed",
+            Alert::CrashLoop { .. } => "crash_loop",
+            Alert::ModelLoaded { .. } => "model_loaded",
+            Alert::QueueBackedUp { .. } => "queue_backed_up",
+            Alert::LatencyHigh { .. } => "latency_high",
+            Alert::ThroughputLow { .. } => "throughput_low",
+            Alert::ThroughputRegressed { .. } => "throughput_regressed",
+            Alert::MemoryHigh { .. } => "memory_high",
+            Alert::LlamaMemoryHigh { .. } => "llama_memory_high",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Alert::ServiceCrashed { reason } => {
+                format!("llama-swap stopped unexpectedly: {reason}")
+            }
+            Alert::CrashLoop {
+                crashes,
+                window,
+                last_log,
+            } => {
+                let message = format!(
+                    "llama-swap crashed {crashes} times in {} minutes; automatic restarts stopped",
+                    window.as_secs() / 60
+                );
+                match last_log {
+                    Some(line) => format!("{message}. Last log line: {line}"),
+                    None => message,
+                }
+            }
+            Alert::ModelLoaded { model, took } => {
+                format!("{model} finished loading in {:.1}s", took.as_secs_f64())
+            }
+            Alert::QueueBackedUp { model, queued } => {
+                format!("{queued} requests are waiting for {model}")
+            }
+            Alert::LatencyHigh { model, p95_ms } => {
+                format!("{model} p95 latency is {p95_ms:.0} ms")
+            }
+            Alert::ThroughputLow { model, tps } => {
+                format!("{model} is generating only {tps:.1} tok/s")
+            }
+            Alert::ThroughputRegressed {
+                model,
+                tps,
+                baseline,
+            } => format!("{model} slowed to {tps:.1} tok/s from its usual {baseline:.1} tok/s"),
+            Alert::MemoryHigh { percent } => format!("System memory usage is at {percent:.0}%"),
+            Alert::LlamaMemoryHigh { gb } => format!("llama-swap processes are using {gb:.1} GB"),
+            Alert::Resolved { breach } => {
+                format!("Resolved: {} is back to normal", breach.subject())
+            }
+        }
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        matches!(self, Alert::Resolved { .. })
+    }
+
+    /// The threshold breach this alert announces, if it is one
+    fn breach(&self) -> Option<Breach> {
+        match self {
+            Alert::QueueBackedUp { model, .. } => Some(Breach::Queue(model.clone())),
+            Alert::LatencyHigh { model, .. } => Some(Breach::Latency(model.clone())),
+            Alert::ThroughputLow { model, .. } => Some(Breach::Throughput(model.clone())),
+            Alert::ThroughputRegressed { model, .. } => Some(Breach::Regression(model.clone())),
+            Alert::MemoryHigh { .. } => Some(Breach::SystemMemory),
+            Alert::LlamaMemoryHigh { .. } => Some(Breach::LlamaMemory),
+            Alert::ServiceCrashed { .. }
+            | Alert::CrashLoop { .. }
+            | Alert::ModelLoaded { .. }
+            | Alert::Resolved { .. } => None,
+        }
+    }
+
+    /// Alerts sharing a key share a cooldown; each model's alerts are separate
+    fn cooldown_key(&self) -> String {
+        match self {
+            Alert::ModelLoaded { model, .. }
+            | Alert::QueueBackedUp { model, .. }
+            | Alert::LatencyHigh { model, .. }
+            | Alert::ThroughputLow { model, .. }
+            | Alert::ThroughputRegressed { model, .. } => format!("{}:{model}", self.kind()),
+            _ => self.kind().to_string(),
+        }
+    }
+}
+
+/// User-configurable limits evaluated every poll; None disables a check
+#[derive(Debug, Clone, Default)]
+pub struct Thresholds {
+    pub memory_percent: Option<f64>,  // System memory usage
+    pub llama_memory_gb: Option<f64>, // Total across llama-swap processes
+    pub queue_depth: Option<u32>,     // Requests waiting on one model
+    pub latency_p95_ms: Option<f64>,
+    pub min_tps: Option<f64>, // Generation speed while requests are being processed
+    pub tps_regression_percent: Option<f64>, // Drop below the model's own baseline
+}
+
+impl Thresholds {
+    pub fn from_env() -> Self {
+        Self {
+            memory_percent: Some(*crate::constants::ALERT_MEMORY_THRESHOLD),
+            llama_memory_gb: *crate::constants::ALERT_LLAMA_MEMORY_GB,
+            queue_depth: Some(*crate::constants::ALERT_QUEUE_THRESHOLD).filter(|&n| n > 0),
+            latency_p95_ms: *crate::constants::ALERT_LATENCY_P95_MS,
+            min_tps: *crate::constants::ALERT_MIN_TPS,
+            tps_regression_percent: Some(*crate::constants::ALERT_TPS_REGRESSION_PERCENT)
+                .filter(|&percent| percent > 0.0),
+        }
+    }
+}
+
+/// Daily local time windows, e.g. 22:00–08:00, in which alerts are only logged
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuietHours {
+    windows: Vec<(u32, u32)>, // Start and end, in minutes since midnight
+}
+
+impl QuietHours {
+    /// Windows as `HH:MM-HH:MM` separated by commas; a window may span midnight
+    pub fn parse(spec: &str) -> Self {
+        let mut windows = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let window = entry
+                .split_once('-')
+                .and_then(|(start, end)| Some((parse_clock(start)?, parse_clock(end)?)));
+            match window {
+                Some(window) => windows.push(window),
+                None => crate::logging::warn(
+                    "alerts",
+                    "invalid_quiet_hours",
+                    &format!("Ignoring quiet hours {entry:?}; expected HH:MM-HH:MM"),
+                ),
+            }
+        }
+        Self { windows }
+    }
+
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        self.windows.iter().any(|&(start, end)| {
+            if start <= end {
+                (start..end).contains(&minute_of_day)
+            } else {
+                minute_of_day >= start || minute_of_day < end
+            }
+        })
+    }
+}
+
+/// Minutes since midnight from `HH:MM`
+fn parse_clock(text: &str) -> Option<u32> {
+    let (hours, minutes) = text.trim().split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// What to do with a raised alert
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delivery {
+    Send,
+    Quiet, // Within quiet hours: log it, don't send it
+    Skip,  // A duplicate within its cooldown
+}
+
+/// A threshold currently exceeded, for highlighting the matching menu row
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Breach {
+    SystemMemory,
+    LlamaMemory,
+    Queue(String), // Model name
+    Latency(String),
+    Throughput(String),
+    Regression(String), // Slower than the model's baseline
+}
+
+impl Breach {
+    /// Kind of the alert announcing this breach
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Breach::SystemMemory => "memory_high",
+            Breach::LlamaMemory => "llama_memory_high",
+            Breach::Queue(_) => "queue_backed_up",
+            Breach::Latency(_) => "latency_high",
+            Breach::Throughput(_) => "throughput_low",
+            Breach::Regression(_) => "throughput_regressed",
+        }
+    }
+
+    /// What went out of range, e.g. "qwen latency"
+    fn subject(&self) -> String {
+        match self {
+            Breach::SystemMemory => "system memory usage".to_string(),
+            Breach::LlamaMemory => "llama-swap memory usage".to_string(),
+            Breach::Queue(model) => format!("{model} queue"),
+            Breach::Latency(model) => format!("{model} latency"),
+            Breach::Throughput(model) | Breach::Regression(model) => {
+                format!("{model} generation speed")
+            }
+        }
+    }
+}
+
+/// Turns polled readings into alerts and rate-limits them for every channel
+///
+/// Each breach alerts when it begins, not on every poll it lasts, and sends a
+/// resolution when it clears if its alert went out. System memory also has to
+/// clearly drop back before clearing, so a value hovering at its threshold
+/// alerts once.
+#[derive(Debug)]
+pub struct AlertMonitor {
+    cooldown: Duration,
+    cooldown_overrides: HashMap<&'static str, Duration>, // By alert kind
+    thresholds: Thresholds,
+    quiet_hours: QuietHours,
+    breaches: HashSet<Breach>,
+    announced: HashSet<Breach>, // Breaches whose alert was sent and not yet resolved
+    last_sent: HashMap<String, Instant>,
+}
+
+impl AlertMonitor {
+    pub fn new(cooldown: Duration, thresholds: Thresholds) -> Self {
+        Self {
+            cooldown,
+            cooldown_overrides: HashMap::new(),
+            thresholds,
+            quiet_hours: QuietHours::default(),
+            breaches: HashSet::new(),
+            announced: HashSet::new(),
+            last_sent: HashMap::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self {
+            cooldown_overrides: parse_cooldowns(&crate::constants::ALERT_COOLDOWNS),
+            quiet_hours: QuietHours::parse(&crate::constants::QUIET_HOURS),
+            ..Self::new(
+                Duration::from_secs(*crate::constants::ALERT_COOLDOWN_SECS),
+                Thresholds::from_env(),
+            )
+        }
+    }
+
+    /// Check every threshold against the latest poll, returning alerts for
+    /// breaches that just began; ongoing ones are remembered for the menu
+    pub fn evaluate(
+        &mut self,
+        all_metrics: Option<&AllMetrics>,
+        history: &AllMetricsHistory,
+    ) -> Vec<Alert> {
+        let mut current = Vec::new();
+
+        let memory_percent = history.memory_usage_percent.iter().next().map(|v| v.value);
+        if let (Some(limit), Some(percent)) = (self.thresholds.memory_percent, memory_percent) {
+            let ongoing = self.breaches.contains(&Breach::SystemMemory);
+            if percent >= limit || (ongoing && percent >= limit - MEMORY_REARM_MARGIN) {
+                current.push((Breach::SystemMemory, Alert::MemoryHigh { percent }));
+            }
+        }
+
+        let llama_memory_gb = history
+            .total_llama_memory_mb
+            .iter()
+            .next()
+            .map_or(0.0, |v| v.value / 1024.0);
+        if self
+            .thresholds
+            .llama_memory_gb
+            .is_some_and(|limit| llama_memory_gb >= limit)
+        {
+            current.push((
+                Breach::LlamaMemory,
+                Alert::LlamaMemoryHigh {
+                    gb: llama_memory_gb,
+                },
+            ));
+        }
+
+        for model in all_metrics.map_or(&[][..], |all| &all.models) {
+            let name = &model.model_name;
+            let queued = model.metrics.requests_deferred;
+            if self
+                .thresholds
+                .queue_depth
+                .is_some_and(|limit| queued >= limit)
+            {
+                current.push((
+                    Breach::Queue(name.clone()),
+                    Alert::QueueBackedUp {
+                        model: name.clone(),
+                        queued,
+                    },
+                ));
+            }
+
+            let Some(model_history) = history.get_model_history(name) else {
+                continue;
+            };
+            if let Some(p95_ms) = model_history.latency.map(|latency| latency.p95_ms) {
+                if self
+                    .thresholds
+                    .latency_p95_ms
+                    .is_some_and(|limit| p95_ms >= limit)
+                {
+                    current.push((
+                        Breach::Latency(name.clone()),
+                        Alert::LatencyHigh {
+                            model: name.clone(),
+                            p95_ms,
+                        },
+                    ));
+                }
+            }
+            // An idle model generates nothing; only a slow busy one is a problem.
+            // This is the decode-speed gauge, which reads zero only until a
+            // request has completed, so a zero says nothing yet.
+            let tps = model_history.tps.iter().next().map(|v| v.value);
+            let busy_tps = tps.filter(|tps| model.metrics.requests_processing > 0 && *tps > 0.0);
+            if let (Some(limit), Some(tps)) = (self.thresholds.min_tps, busy_tps) {
+                if tps < limit {
+                    current.push((
+                        Breach::Throughput(name.clone()),
+                        Alert::ThroughputLow {
+                            model: name.clone(),
+                            tps,
+                        },
+                    ));
+                }
+            }
+            // Thermal throttling or swapping shows up as a model slower than it usually is
+            let baseline = model_history.tps_baseline.value();
+            if let (Some(percent), Some(tps), Some(baseline)) =
+                (self.thresholds.tps_regression_percent, busy_tps, baseline)
+            {
+                if tps < baseline * (1.0 - percent / 100.0) {
+                    current.push((
+                        Breach::Regression(name.clone()),
+                        Alert::ThroughputRegressed {
+                            model: name.clone(),
+                            tps,
+                            baseline,
+                        },
+                    ));
+                }
+            }
+        }
+
+        let previous = std::mem::take(&mut self.breaches);
+        let mut alerts = Vec::new();
+        for (breach, alert) in current {
+            if !previous.contains(&breach) {
+                alerts.push(alert);
+            }
+            self.breaches.insert(breach);
+        }
+        alerts.extend(
+            previous
+                .into_iter()
+                .filter(|breach| !self.breaches.contains(breach))
+                .map(|breach| Alert::Resolved { breach }),
+        );
+        alerts
+    }
+
+    pub fn is_breached(&self, breach: &Breach) -> bool {
+        self.breaches.contains(breach)
+    }
+
+    pub fn has_breaches(&self) -> bool {
+        !self.breaches.is_empty()
+    }
+
+    /// Whether to send, only log, or drop the alert
+    ///
+    /// `minute_of_day` is the local time, None if unknown. A breach muted by
+    /// quiet hours counts as not sent, so its resolution stays quiet too.
+    pub fn delivery(
+        &mut self,
+        alert: &Alert,
+        now: Instant,
+        minute_of_day: Option<u32>,
+    ) -> Delivery {
+        if !self.should_send(alert, now) {
+            return Delivery::Skip;
+        }
+        if !minute_of_day.is_some_and(|minute| self.quiet_hours.contains(minute)) {
+            return Delivery::Send;
+        }
+        if let Some(breach) = alert.breach() {
+            self.announced.remove(&breach);
+        }
+        Delivery::Quiet
+    }
+
+    /// Whether to send the alert: none like it went out within its kind's
+    /// cooldown, or for a resolution, the breach's own alert was sent
+    fn should_send(&mut self, alert: &Alert, now: Instant) -> bool {
+        if let Alert::Resolved { breach } = alert {
+            // A breach muted by the cooldown ends quietly too
+            return self.announced.remove(breach);
+        }
+
+        let key = alert.cooldown_key();
+        let cooldown = self
+            .cooldown_overrides
+            .get(alert.kind())
+            .copied()
+            .unwrap_or(self.cooldown);
+        if self
+            .last_sent
+            .get(&key)
+            .is_some_and(|&at| now.saturating_duration_since(at) < cooldown)
+        {
+            return false;
+        }
+        self.last_sent.insert(key, now);
+        if let Some(breach) = alert.breach() {
+            self.announced.insert(breach);
+        }
+        true
+    }
+}
+
+/// Per-kind cooldowns from `kind:secs` pairs separated by commas
+fn parse_cooldowns(spec: &str) -> HashMap<&'static str, Duration> {
+    let mut cooldowns = HashMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let parsed = entry.split_once(':').and_then(|(name, secs)| {
+            let kind = Alert::KINDS.iter().find(|&&kind| kind == name.trim())?;
+            Some((*kind, Duration::from_secs(secs.trim().parse().ok()?)))
+        });
+        match parsed {
+            Some((kind, cooldown)) => {
+                cooldowns.insert(kind, cooldown);
+            }
+            None => crate::logging::warn(
+                "alerts",
+                "invalid_cooldown",
+                &format!("Ignoring alert cooldown {entry:?}; expected kind:seconds"),
+            ),
+        }
+    }
+    cooldowns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Metrics, MetricsHistory, ModelMetrics, ModelState, TimestampedValue};
+
+    #[test]
+    fn test_cooldown_per_alert() {
+        let start = Instant::now();
+        let mut monitor = AlertMonitor::new(Duration::from_secs(300), Thresholds::default());
+        monitor.cooldown_overrides = parse_cooldowns("model_loaded:0, bogus:5, memory_high:x");
+        let crashed = Alert::ServiceCrashed {
+            reason: "process exited".to_string(),
+        };
+        let loaded = |model: &str| Alert::ModelLoaded {
+            model: model.to_string(),
+            took: Duration::from_secs(5),
+        };
+
+        assert!(monitor.should_send(&crashed, start));
+        assert!(!monitor.should_send(&crashed, start + Duration::from_secs(60)));
+        assert!(monitor.should_send(&crashed, start + Duration::from_secs(300)));
+
+        assert_eq!(monitor.cooldown_overrides.len(), 1);
+        assert!(monitor.should_send(&loaded("a"), start));
+        assert!(
+            monitor.should_send(&loaded("a"), start),
+            "no cooldown for loads"
+        );
+    }
+
+    fn memory_history(percent: f64) -> AllMetricsHistory {
+        let mut history = AllMetricsHistory::with_capacity(10);
+        history.memory_usage_percent.push(TimestampedValue {
+            timestamp: 0,
+            value: percent,
+        });
+        history
+    }
+
+    #[test]
+    fn test_memory_alert_rearms_below_margin() {
+        let thresholds = Thresholds {
+            memory_percent: Some(90.0),
+            ..Thresholds::default()
+        };
+        let mut monitor = AlertMonitor::new(Duration::ZERO, thresholds);
+        let mut evaluate = |percent| monitor.evaluate(None, &memory_history(percent));
+
+        assert_eq!(evaluate(80.0), vec![]);
+        assert_eq!(evaluate(91.0), vec![Alert::MemoryHigh { percent: 91.0 }]);
+        assert_eq!(evaluate(92.0), vec![], "already alerted");
+        assert_eq!(evaluate(88.0), vec![], "within the margin");
+        assert_eq!(evaluate(91.0), vec![]);
+        assert_eq!(
+            evaluate(84.0),
+            vec![Alert::Resolved {
+                breach: Breach::SystemMemory
+            }]
+        );
+        assert_eq!(evaluate(91.0).len(), 1);
+    }
+
+    #[test]
+    fn test_resolution_only_follows_a_sent_alert() {
+        let start = Instant::now();
+        let mut monitor = AlertMonitor::new(Duration::from_secs(300), Thresholds::default());
+        let high = Alert::MemoryHigh { percent: 95.0 };
+        let resolved = Alert::Resolved {
+            breach: Breach::SystemMemory,
+        };
+
+        assert!(monitor.should_send(&high, start));
+        assert!(monitor.should_send(&resolved, start));
+        assert_eq!(resolved.kind(), "memory_high");
+        assert_eq!(
+            resolved.message(),
+            "Resolved: system memory usage is back to normal"
+        );
+
+        // The condition flaps: the repeat is inside the cooldown, so is its resolution
+        assert!(!monitor.should_send(&high, start + Duration::from_secs(10)));
+        assert!(!monitor.should_send(&resolved, start + Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_quiet_hours_span_midnight() {
+        let quiet = QuietHours::parse("22:00-08:00, 12:30-13:00, 25:00-26:00");
+        assert_eq!(quiet.windows.len(), 2);
+        assert!(quiet.contains(23 * 60));
+        assert!(quiet.contains(0));
+        assert!(quiet.contains(7 * 60 + 59));
+        assert!(!quiet.contains(8 * 60));
+        assert!(quiet.contains(12 * 60 + 45));
+        assert!(!quiet.contains(13 * 60));
+        assert!(!QuietHours::parse("").contains(0));
+    }
+
+    #[test]
+    fn test_quiet_breach_resolves_quietly() {
+        let start = Instant::now();
+        let mut monitor = AlertMonitor::new(Duration::ZERO, Thresholds::default());
+        monitor.quiet_hours = QuietHours::parse("22:00-08:00");
+        let high = Alert::MemoryHigh { percent: 95.0 };
+        let resolved = Alert::Resolved {
+            breach: Breach::SystemMemory,
+        };
+
+        assert_eq!(
+            monitor.delivery(&high, start, Some(23 * 60)),
+            Delivery::Quiet
+        );
+        assert_eq!(
+            monitor.delivery(&resolved, start, Some(9 * 60)),
+            Delivery::Skip
+        );
+        assert_eq!(monitor.delivery(&high, start, Some(9 * 60)), Delivery::Send);
+        assert_eq!(monitor.delivery(&high, start, None), Delivery::Send);
+    }
+
+    fn running(name: &str, processing: u32, deferred: u32) -> ModelMetrics {
+        ModelMetrics {
+            model_name: name.to_string(),
+            model_state: ModelState::Running,
+            metrics: Metrics {
+                requests_processing: processing,
+                requests_deferred: deferred,
+                ..Metrics::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_regression_against_baseline() {
+        let thresholds = Thresholds {
+            tps_regression_percent: Some(30.0),
+            ..Thresholds::default()
+        };
+        let mut monitor = AlertMonitor::new(Duration::ZERO, thresholds);
+        let mut history = AllMetricsHistory::with_capacity(10);
+        let model_history = history.model_history_mut("qwen");
+        for _ in 0..30 {
+            model_history.tps_baseline.record(50.0);
+        }
+        let mut evaluate = |tps: f64, processing| {
+            let model_history = history.model_history_mut("qwen");
+            model_history.tps.push(TimestampedValue {
+                timestamp: 0,
+                value: tps,
+            });
+            let metrics = AllMetrics {
+                models: vec![running("qwen", processing, 0)],
+                proxy: None,
+            };
+            monitor.evaluate(Some(&metrics), &history)
+        };
+
+        assert_eq!(evaluate(40.0, 1), vec![], "20% slower is within tolerance");
+        assert_eq!(evaluate(0.0, 0), vec![], "idle");
+        assert_eq!(
+            evaluate(30.0, 1),
+            vec![Alert::ThroughputRegressed {
+                model: "qwen".to_string(),
+                tps: 30.0,
+                baseline: 50.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_no_regression_while_counters_wait_for_a_request() {
+        let thresholds = Thresholds {
+            min_tps: Some(10.0),
+            tps_regression_percent: Some(30.0),
+            ..Thresholds::default()
+        };
+        let mut monitor = AlertMonitor::new(Duration::ZERO, thresholds);
+        let mut history = AllMetricsHistory::with_capacity(10);
+        let busy = Metrics {
+            predicted_tokens_per_sec: 48.0,
+            requests_processing: 1,
+            tokens_predicted_total: 2000.0,
+            ..Metrics::default()
+        };
+        let model_history = history.model_history_mut("qwen");
+        for _ in 0..30 {
+            model_history.tps_baseline.record(50.0);
+        }
+        // A long generation: the token counter stands still between polls
+        model_history.push(&busy);
+        model_history.push(&busy);
+
+        let metrics = AllMetrics {
+            models: vec![ModelMetrics {
+                model_name: "qwen".to_string(),
+                model_state: ModelState::Running,
+                metrics: busy,
+            }],
+            proxy: None,
+        };
+        assert_eq!(monitor.evaluate(Some(&metrics), &history), vec![]);
+    }
+
+    #[test]
+    fn test_breaches_alert_once_while_ongoing() {
+        let thresholds = Thresholds {
+            queue_depth: Some(5),
+            min_tps: Some(10.0),
+            ..Thresholds::default()
+        };
+        let mut monitor = AlertMonitor::new(Duration::ZERO, thresholds);
+        let mut history = AllMetricsHistory::with_capacity(10);
+        let metrics = |qwen_queue| AllMetrics {
+            models: vec![running("qwen", 1, qwen_queue), running("llama", 0, 0)],
+            proxy: None,
+        };
+        for name in ["qwen", "llama"] {
+            history
+                .models
+                .entry(name.to_string())
+                .or_insert_with(|| MetricsHistory::with_capacity(10))
+                .tps
+                .push(TimestampedValue {
+                    timestamp: 0,
+                    value: 4.0,
+                });
+        }
+
+        let alerts = monitor.evaluate(Some(&metrics(6)), &history);
+        assert_eq!(
+            alerts,
+            vec![
+                Alert::QueueBackedUp {
+                    model: "qwen".to_string(),
+                    queued: 6,
+                },
+                Alert::ThroughputLow {
+                    model: "qwen".to_string(),
+                    tps: 4.0,
+                },
+            ],
+            "idle llama is slow but not busy"
+        );
+        assert!(monitor.is_breached(&Breach::Queue("qwen".to_string())));
+
+        assert!(monitor.evaluate(Some(&metrics(8)), &history).is_empty());
+        assert_eq!(
+            monitor.evaluate(Some(&metrics(2)), &history),
+            vec![Alert::Resolved {
+                breach: Breach::Queue("qwen".to_string())
+            }]
+        );
+        assert!(!monitor.is_breached(&Breach::Queue("qwen".to_string())));
+        assert!(monitor.is_breached(&Breach::Throughput("qwen".to_string())));
+
+        assert_eq!(monitor.evaluate(Some(&metrics(5)), &history).len(), 1);
+        assert_eq!(monitor.evaluate(None, &history).len(), 2, "both resolved");
+        assert!(!monitor.has_breaches());
+    }
+}
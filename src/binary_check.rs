@@ -0,0 +1,214 @@
+use crate::config_parser::LlamaSwapConfig;
+use crate::logging;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often binaries are re-checked when config.yaml hasn't changed
+/// (catches llama.cpp being installed, removed or rebuilt)
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Why a model's command can't be started
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryProblem {
+    NotFound,
+    NotExecutable,
+    WrongArchitecture { found: String },
+}
+
+/// A configured model whose command will fail before llama-swap even tries it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelWarning {
+    pub model: String,
+    pub program: String,
+    pub problem: BinaryProblem,
+}
+
+impl ModelWarning {
+    pub fn message(&self) -> String {
+        let name = Path::new(&self.program)
+            .file_name()
+            .map_or(self.program.clone(), |n| n.to_string_lossy().into_owned());
+
+        match &self.problem {
+            BinaryProblem::NotFound if is_bare_name(&self.program) => {
+                format!("command will fail: {name} not found in PATH")
+            }
+            BinaryProblem::NotFound => {
+                format!("command will fail: {name} not found at {}", self.program)
+            }
+            BinaryProblem::NotExecutable => {
+                format!("command will fail: {} is not executable", self.program)
+            }
+            BinaryProblem::WrongArchitecture { found } => format!(
+                "command will fail: {name} is built for {found}, not {}",
+                host_architecture()
+            ),
+        }
+    }
+}
+
+/// Keeps the per-model warnings current without re-running `file` on every poll
+#[derive(Debug, Default)]
+pub struct BinaryChecker {
+    config_modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+    warnings: Vec<ModelWarning>,
+}
+
+impl BinaryChecker {
+    pub fn warnings(&self) -> &[ModelWarning] {
+        &self.warnings
+    }
+
+    /// Re-check if config.yaml changed or the recheck interval elapsed
+    pub fn refresh(&mut self) {
+        let modified = crate::config_parser::modified_time();
+        let due = self
+            .last_check
+            .is_none_or(|at| at.elapsed() >= RECHECK_INTERVAL);
+        if !due && modified == self.config_modified {
+            return;
+        }
+
+        self.config_modified = modified;
+        self.last_check = Some(Instant::now());
+        self.warnings = match LlamaSwapConfig::load() {
+            Ok(Some(config)) => check_models(&config),
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                logging::debug(
+                    "binary_check",
+                    "config_unreadable",
+                    &format!("Skipping binary check: {e}"),
+                );
+                Vec::new()
+            }
+        };
+    }
+}
+
+/// Check the program of every configured model command
+pub fn check_models(config: &LlamaSwapConfig) -> Vec<ModelWarning> {
+    config
+        .models
+        .iter()
+        .filter_map(|(model, model_config)| {
+            let program = model_config.program()?;
+            let problem = check_program(program)?;
+            Some(ModelWarning {
+                model: model.clone(),
+                program: program.to_string(),
+                problem,
+            })
+        })
+        .collect()
+}
+
+/// Check one program path (or bare name resolved through PATH)
+pub fn check_program(program: &str) -> Option<BinaryProblem> {
+    let path = if is_bare_name(program) {
+        match find_in_path(program) {
+            Some(path) => path,
+            None => return Some(BinaryProblem::NotFound),
+        }
+    } else {
+        PathBuf::from(program)
+    };
+
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        return Some(BinaryProblem::NotFound);
+    };
+    if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+        return Some(BinaryProblem::NotExecutable);
+    }
+
+    check_architecture(&path)
+}
+
+fn is_bare_name(program: &str) -> bool {
+    !program.contains('/')
+}
+
+/// Resolve a bare program name the way a shell would
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Compare a Mach-O binary's architectures against this Mac's
+///
+/// Scripts and anything `file` can't identify are given the benefit of the doubt.
+fn check_architecture(path: &Path) -> Option<BinaryProblem> {
+    let output = Command::new("file").arg("-b").arg(path).output().ok()?;
+    let description = String::from_utf8_lossy(&output.stdout);
+    if !description.contains("Mach-O") || description.contains(host_architecture()) {
+        return None;
+    }
+
+    let found = ["arm64", "x86_64"]
+        .into_iter()
+        .find(|arch| description.contains(arch))
+        .unwrap_or("another architecture");
+    Some(BinaryProblem::WrongArchitecture {
+        found: found.to_string(),
+    })
+}
+
+/// Architecture name as `file` reports it for Mach-O binaries
+fn host_architecture() -> &'static str {
+    match std::env::consts::ARCH {
+        "aarch64" => "arm64",
+        arch => arch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_program() {
+        let dir = std::env::temp_dir().join(format!("binary-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("llama-server");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(
+            check_program(script.to_str().unwrap()),
+            Some(BinaryProblem::NotExecutable)
+        );
+
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(check_program(script.to_str().unwrap()), None);
+
+        let missing = dir.join("missing/llama-server");
+        assert_eq!(
+            check_program(missing.to_str().unwrap()),
+            Some(BinaryProblem::NotFound)
+        );
+        assert_eq!(
+            check_program("llama-server-definitely-not-installed"),
+            Some(BinaryProblem::NotFound)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_warning_message() {
+        let warning = ModelWarning {
+            model: "qwen".to_string(),
+            program: "/opt/llama.cpp/llama-server".to_string(),
+            problem: BinaryProblem::NotFound,
+        };
+        assert_eq!(
+            warning.message(),
+            "command will fail: llama-server not found at /opt/llama.cpp/llama-server"
+        );
+    }
+}
@@ -1,6 +1,4 @@
-use crate::constants::{
-    CHART_HEIGHT, CHART_WIDTH, COLOR_MEM_LINE, COLOR_PROMPT_LINE, COLOR_QUEUE_LINE, COLOR_TPS_LINE,
-};
+use crate::constants::{CHART_HEIGHT, CHART_WIDTH, PALETTE};
 use image::{DynamicImage, Rgba, RgbaImage};
 // Charts operate on Vec<f64> data for visualization
 
@@ -9,6 +7,10 @@ pub enum MetricType {
     Tps,
     Memory,
     Prompt,
+    Gpu,
+    KvCache,
+    Latency,
+    Power,
 
     Queue,
 }
@@ -16,11 +18,15 @@ pub enum MetricType {
 impl MetricType {
     fn color(self) -> (u8, u8, u8) {
         match self {
-            Self::Tps => COLOR_TPS_LINE,
-            Self::Memory => COLOR_MEM_LINE,
-            Self::Prompt => COLOR_PROMPT_LINE,
-
-            Self::Queue => COLOR_QUEUE_LINE,
+            Self::Tps => PALETTE.tps_line,
+            Self::Memory => PALETTE.mem_line,
+            Self::Prompt => PALETTE.prompt_line,
+            Self::Gpu => PALETTE.gpu_line,
+            Self::KvCache => PALETTE.kv_cache_line,
+            Self::Latency => PALETTE.latency_line,
+            Self::Power => PALETTE.power_line,
+
+            Self::Queue => PALETTE.queue_line,
         }
     }
 }
@@ -1,27 +1,406 @@
 use crate::constants::LAUNCH_AGENT_LABEL;
+use crate::export::ExportFormat;
+use crate::logging;
 use crate::types::error_helpers::{
     get_home_dir, with_context, CONNECT_API, CREATE_DIR, CREATE_FILE, EXEC_COMMAND, GET_USER_ID,
-    START_SERVICE, STOP_SERVICE,
+    PARSE_JSON, START_SERVICE, STOP_SERVICE,
 };
-use std::process::Command;
+use std::io::{BufRead, BufReader};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Commands that change service or model state; disabled in read-only mode
+pub const MUTATING_COMMANDS: &[&str] = &[
+    "do_start",
+    "do_stop",
+    "do_restart",
+    "do_unload",
+    "load_model",
+    "switch_model",
+    "do_install",
+    "do_reinstall",
+    "set_install_option",
+    "do_uninstall",
+    "do_install_binary",
+    "upgrade_binary",
+    "clear_history",
+    "restore_config",
+    "add_model",
+    "set_model_option",
+    // The request makes llama-swap load the model, which can swap out another
+    "test_prompt",
+];
+
+pub fn is_mutating_command(command: &str) -> bool {
+    MUTATING_COMMANDS.contains(&command)
+}
+
+/// What the test prompt asks, and how long an answer it allows
+const TEST_PROMPT: &str = "Reply with a short greeting.";
+const TEST_PROMPT_MAX_TOKENS: u32 = 32;
+
+/// How long load_model and switch_model wait for the model by default
+const DEFAULT_LOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A command with its parsed arguments, from the command line or a menu item's
+/// SwiftBar `paramN=` values
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandRequest {
+    Start,
+    Stop,
+    Restart,
+    /// `do_unload [model]`; every model when none is named
+    Unload {
+        model: Option<String>,
+    },
+    /// `load_model <model> [timeout]`
+    Load {
+        model: String,
+        timeout: Duration,
+    },
+    /// `switch_model <model> [timeout]`
+    Switch {
+        model: String,
+        timeout: Duration,
+    },
+    Install,
+    /// `do_reinstall`: rewrite the plist, starting the service again if it was running
+    Reinstall,
+    /// `set_install_option <option> <value>`, e.g. `keep_alive on_crash`
+    SetInstallOption(crate::install_options::InstallOption),
+    Uninstall,
+    InstallBinary,
+    UpgradeBinary,
+    OpenUi,
+    CopyApiEndpoint,
+    ViewLogs,
+    ViewLogsInConsole,
+    ViewConfig,
+    EditConfig,
+    RevealConfig,
+    ValidateConfig,
+    BackupConfig,
+    /// `restore_config <backup file name>`
+    RestoreConfig {
+        backup: String,
+    },
+    /// `add_model <path-to-gguf>`
+    AddModel {
+        path: String,
+    },
+    /// `set_model_option <model> <option> <value>`, e.g. `qwen parallel 4`
+    SetModelOption {
+        model: String,
+        option: crate::model_wizard::ModelOption,
+    },
+    /// `export_csv [path]` and `export_json [path]`
+    Export {
+        format: ExportFormat,
+        path: Option<String>,
+    },
+    ClearHistory,
+    /// `copy_csv <series> [model]`
+    CopyCsv {
+        series: String,
+        model: Option<String>,
+    },
+    /// `test_prompt <model>`
+    TestPrompt {
+        model: String,
+    },
+    /// `copy_model_name <model>`
+    CopyModelName {
+        model: String,
+    },
+    /// `copy_diagnostics [path]`
+    CopyDiagnostics {
+        path: Option<String>,
+    },
+}
+
+impl CommandRequest {
+    pub fn parse(command: &str, args: &[String]) -> crate::Result<Self> {
+        let max_args = match command {
+            "do_unload" | "export_csv" | "export_json" | "test_prompt" | "copy_model_name"
+            | "copy_diagnostics" | "restore_config" | "add_model" => 1,
+            "load_model" | "switch_model" | "copy_csv" | "set_install_option" => 2,
+            "set_model_option" => 3,
+            _ => 0,
+        };
+        if args.len() > max_args {
+            return Err(format!("{command} takes at most {max_args} argument(s)").into());
+        }
+        let optional = |i: usize| args.get(i).cloned();
+        let required = |what: &str| {
+            args.first()
+                .cloned()
+                .ok_or_else(|| format!("{command} requires {what}"))
+        };
+        let timeout = || {
+            args.get(1)
+                .map_or(Ok(DEFAULT_LOAD_TIMEOUT), |s| parse_duration(s))
+        };
+
+        Ok(match command {
+            "do_start" => Self::Start,
+            "do_stop" => Self::Stop,
+            "do_restart" => Self::Restart,
+            "do_unload" => Self::Unload { model: optional(0) },
+            "load_model" => Self::Load {
+                model: required("a model name")?,
+                timeout: timeout()?,
+            },
+            "switch_model" => Self::Switch {
+                model: required("a model name")?,
+                timeout: timeout()?,
+            },
+            "do_install" => Self::Install,
+            "do_reinstall" => Self::Reinstall,
+            "set_install_option" => {
+                Self::SetInstallOption(crate::install_options::InstallOption::parse(
+                    &required("an option name")?,
+                    &optional(1).ok_or("set_install_option requires a value")?,
+                )?)
+            }
+            "do_uninstall" => Self::Uninstall,
+            "do_install_binary" => Self::InstallBinary,
+            "upgrade_binary" => Self::UpgradeBinary,
+            "open_ui" => Self::OpenUi,
+            "copy_api_endpoint" => Self::CopyApiEndpoint,
+            "view_logs" => Self::ViewLogs,
+            "view_logs_console" => Self::ViewLogsInConsole,
+            "view_config" => Self::ViewConfig,
+            "edit_config" => Self::EditConfig,
+            "reveal_config" => Self::RevealConfig,
+            "validate_config" => Self::ValidateConfig,
+            "backup_config" => Self::BackupConfig,
+            "restore_config" => Self::RestoreConfig {
+                backup: required("a backup file name")?,
+            },
+            "add_model" => Self::AddModel {
+                path: required("a GGUF file path")?,
+            },
+            "set_model_option" => Self::SetModelOption {
+                model: required("a model name")?,
+                option: crate::model_wizard::ModelOption::parse(
+                    &optional(1).ok_or("set_model_option requires an option name")?,
+                    &optional(2).ok_or("set_model_option requires a value")?,
+                )?,
+            },
+            "export_csv" => Self::Export {
+                format: ExportFormat::Csv,
+                path: optional(0),
+            },
+            "export_json" => Self::Export {
+                format: ExportFormat::Json,
+                path: optional(0),
+            },
+            "clear_history" => Self::ClearHistory,
+            "copy_csv" => Self::CopyCsv {
+                series: required("a series name")?,
+                model: optional(1),
+            },
+            "test_prompt" => Self::TestPrompt {
+                model: required("a model name")?,
+            },
+            "copy_model_name" => Self::CopyModelName {
+                model: required("a model name")?,
+            },
+            "copy_diagnostics" => Self::CopyDiagnostics { path: optional(0) },
+            _ => return Err(format!("Unknown command: {command}").into()),
+        })
+    }
+
+    /// What a failure notification calls this command
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Start => "Start service",
+            Self::Stop => "Stop service",
+            Self::Restart => "Restart service",
+            Self::Unload { .. } => "Unload",
+            Self::Load { .. } => "Load model",
+            Self::Switch { .. } => "Switch model",
+            Self::Install => "Install service",
+            Self::Reinstall => "Reinstall service",
+            Self::SetInstallOption(_) => "Change install option",
+            Self::Uninstall => "Uninstall service",
+            Self::InstallBinary => "Install llama-swap",
+            Self::UpgradeBinary => "Upgrade llama-swap",
+            Self::OpenUi => "Open UI",
+            Self::CopyApiEndpoint => "Copy API endpoint",
+            Self::ViewLogs | Self::ViewLogsInConsole => "View logs",
+            Self::ViewConfig | Self::EditConfig => "Edit config",
+            Self::RevealConfig => "Reveal config",
+            Self::ValidateConfig => "Validate config",
+            Self::BackupConfig => "Back up config",
+            Self::RestoreConfig { .. } => "Restore config",
+            Self::AddModel { .. } => "Add model",
+            Self::SetModelOption { .. } => "Change model settings",
+            Self::Export { .. } => "Export metrics",
+            Self::ClearHistory => "Clear history",
+            Self::CopyCsv { .. } => "Copy CSV",
+            Self::TestPrompt { .. } => "Test prompt",
+            Self::CopyModelName { .. } => "Copy model name",
+            Self::CopyDiagnostics { .. } => "Copy diagnostics",
+        }
+    }
+
+    /// Run the command, returning what to tell the user about it (None when
+    /// the result is already visible, e.g. an opened window)
+    fn run(self) -> crate::Result<Option<String>> {
+        match self {
+            Self::Start => start_service().map(|()| Some("Llama-Swap service started".into())),
+            Self::Stop => stop_service().map(|()| Some("Llama-Swap service stopped".into())),
+            Self::Restart => {
+                restart_service().map(|()| Some("Llama-Swap service restarted".into()))
+            }
+            Self::Unload { model: None } => {
+                unload_models().map(|()| Some("All models unloaded".into()))
+            }
+            Self::Unload { model: Some(model) } => {
+                unload_model(&model).map(|()| Some(format!("{model} unloaded")))
+            }
+            Self::Load { model, timeout } => {
+                load_model(&model, timeout).map(|()| Some(format!("{model} loaded")))
+            }
+            Self::Switch { model, timeout } => {
+                unload_models()?;
+                load_model(&model, timeout).map(|()| Some(format!("Switched to {model}")))
+            }
+            Self::Install => {
+                install_service().map(|()| Some("Llama-Swap service installed".into()))
+            }
+            Self::Reinstall => {
+                reinstall_service().map(|()| Some("Llama-Swap service reinstalled".into()))
+            }
+            Self::SetInstallOption(option) => set_install_option(option).map(Some),
+            Self::Uninstall => {
+                uninstall_service().map(|()| Some("Llama-Swap service uninstalled".into()))
+            }
+            Self::InstallBinary => install_binary().map(Some),
+            Self::UpgradeBinary => upgrade_binary().map(Some),
+            Self::OpenUi => open_ui().map(|()| None),
+            Self::CopyApiEndpoint => {
+                copy_api_endpoint().map(|()| Some("API endpoint copied".into()))
+            }
+            Self::ViewLogs => {
+                view_file(&crate::constants::LOG_FILE_PATH, create_default_log).map(|()| None)
+            }
+            Self::ViewLogsInConsole => view_logs_in_console().map(|()| None),
+            Self::ViewConfig => {
+                backup_before_editing();
+                view_file(&crate::constants::CONFIG_FILE_PATH, create_default_config).map(|()| None)
+            }
+            Self::EditConfig => edit_config().map(|()| None),
+            Self::RevealConfig => reveal_config().map(|()| None),
+            Self::ValidateConfig => validate_config().map(Some),
+            Self::BackupConfig => crate::config_backup::backup().map(|path| {
+                Some(format!(
+                    "config.yaml backed up to {}",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ))
+            }),
+            Self::RestoreConfig { backup } => crate::config_backup::restore(&backup)
+                .map(|()| Some(format!("config.yaml restored from {backup}"))),
+            Self::AddModel { path } => {
+                let path = expand_tilde(&path)?;
+                crate::model_wizard::add_model(std::path::Path::new(&path)).map(Some)
+            }
+            Self::SetModelOption { model, option } => {
+                crate::model_wizard::set_model_option(&model, option).map(Some)
+            }
+            Self::Export { format, path } => export_metrics(format, path.as_deref()).map(|()| None),
+            Self::ClearHistory => clear_history().map(|()| Some("Metrics history cleared".into())),
+            Self::CopyCsv { series, model } => copy_series_csv(&series, model.as_deref()).map(Some),
+            Self::TestPrompt { model } => run_test_prompt(&model).map(Some),
+            Self::CopyModelName { model } => {
+                copy_to_clipboard(&model).map(|()| Some(format!("Copied {model}")))
+            }
+            Self::CopyDiagnostics { path } => copy_diagnostics(path.as_deref()).map(Some),
+        }
+    }
+}
+
+/// "90s", "5m", "1h" or plain seconds
+pub fn parse_duration(value: &str) -> crate::Result<Duration> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => value.split_at(at),
+        None => (value, "s"),
+    };
+    let seconds_per_unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return Err(format!("Invalid duration {value:?}; use e.g. 90s, 5m or 1h").into()),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration {value:?}; use e.g. 90s, 5m or 1h"))?;
+    Ok(Duration::from_secs(number * seconds_per_unit))
+}
 
 pub fn handle_command(command: &str) -> crate::Result<()> {
-    match command {
-        "do_start" => start_service(),
-        "do_stop" => stop_service(),
-        "do_restart" => restart_service(),
-        "do_unload" => unload_models(),
-        "do_install" => install_service(),
-        "do_uninstall" => uninstall_service(),
-        "open_ui" => open_ui(),
-        "view_logs" => view_file(&crate::constants::LOG_FILE_PATH, create_default_log),
-        "view_config" => view_file(&crate::constants::CONFIG_FILE_PATH, create_default_config),
-        _ => Err(format!("Unknown command: {command}").into()),
+    handle_command_with_args(command, &[])
+}
+
+/// Parse and run a command with its arguments
+pub fn handle_command_with_args(command: &str, args: &[String]) -> crate::Result<()> {
+    parse_permitted(command, args)?.run().map(|_| ())
+}
+
+/// Run a command clicked in the menu or typed in a terminal, and post how it went
+/// as a notification: SwiftBar discards the output of menu commands, so errors
+/// would otherwise only reach the plugin log
+pub fn handle_reported_command(command: &str, args: &[String]) -> crate::Result<()> {
+    let (title, result) = match parse_permitted(command, args) {
+        Ok(request) => (request.title(), request.run()),
+        Err(e) => ("Command", Err(e)),
+    };
+    let (title, message) = match &result {
+        Ok(None) => return Ok(()),
+        Ok(Some(message)) => ("Llama-Swap".to_string(), message.clone()),
+        Err(e) => (
+            format!("Llama-Swap: {title} failed"),
+            failure_excerpt(&e.to_string()),
+        ),
+    };
+    if let Err(e) = crate::notifications::post_titled(&title, &message) {
+        logging::warn(
+            "commands",
+            "notification_failed",
+            &format!("Failed to post {command} result: {e}"),
+        );
+    }
+    result.map(|_| ())
+}
+
+fn parse_permitted(command: &str, args: &[String]) -> crate::Result<CommandRequest> {
+    if *crate::constants::READ_ONLY_MODE && is_mutating_command(command) {
+        return Err(format!("Command '{command}' is disabled in read-only mode").into());
+    }
+    CommandRequest::parse(command, args)
+}
+
+/// An error's text collapsed to one line and cut to what a notification shows
+fn failure_excerpt(error: &str) -> String {
+    const MAX_CHARS: usize = 200;
+    let line = error.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > MAX_CHARS {
+        let cut: String = line.chars().take(MAX_CHARS).collect();
+        format!("{cut}…")
+    } else {
+        line
     }
 }
 
 fn start_service() -> crate::Result<()> {
-    eprintln!("Starting Llama-Swap service...");
+    logging::info(
+        "commands",
+        "service_starting",
+        "Starting Llama-Swap service...",
+    );
 
     ensure_service_installed()?;
     let service_context = ServiceContext::new()?;
@@ -38,7 +417,11 @@ fn start_service() -> crate::Result<()> {
         if let Ok(output) = bootstrap_output {
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                eprintln!("Bootstrap warning: {stderr}");
+                logging::warn(
+                    "commands",
+                    "bootstrap_warning",
+                    &format!("Bootstrap warning: {stderr}"),
+                );
             }
         }
     }
@@ -54,12 +437,21 @@ fn start_service() -> crate::Result<()> {
         return Err(format!("Failed to start service: {stderr}").into());
     }
 
-    eprintln!("Service started successfully");
+    logging::info(
+        "commands",
+        "service_started",
+        "Service started successfully",
+    );
+    track_startup();
     Ok(())
 }
 
 fn stop_service() -> crate::Result<()> {
-    eprintln!("Stopping Llama-Swap service...");
+    logging::info(
+        "commands",
+        "service_stopping",
+        "Stopping Llama-Swap service...",
+    );
 
     ensure_service_installed()?;
     let service_context = ServiceContext::new()?;
@@ -77,14 +469,25 @@ fn stop_service() -> crate::Result<()> {
         }
     }
 
-    eprintln!("Service stopped successfully");
+    crate::service::clear_start_request();
+    set_wanted_running(false);
+    logging::info(
+        "commands",
+        "service_stopped",
+        "Service stopped successfully",
+    );
     Ok(())
 }
 
 fn restart_service() -> crate::Result<()> {
-    eprintln!("Restarting Llama-Swap service...");
+    logging::info(
+        "commands",
+        "service_restarting",
+        "Restarting Llama-Swap service...",
+    );
 
     ensure_service_installed()?;
+    ensure_config_valid()?;
     let service_context = ServiceContext::new()?;
 
     // Use kickstart -k to kill and restart the service atomically
@@ -98,14 +501,43 @@ fn restart_service() -> crate::Result<()> {
         return Err(format!("Failed to restart service: {stderr}").into());
     }
 
-    eprintln!("Service restarted successfully");
+    logging::info(
+        "commands",
+        "service_restarted",
+        "Service restarted successfully",
+    );
+    track_startup();
     Ok(())
 }
 
+/// launchctl only reports that the process was spawned; let the streaming instance
+/// watch for the API to come up
+fn track_startup() {
+    if let Err(e) = crate::service::mark_start_requested() {
+        logging::warn(
+            "commands",
+            "startup_tracking_failed",
+            &format!("Failed to record start request: {e}"),
+        );
+    }
+    set_wanted_running(true);
+}
+
+/// Tell the watchdog whether an exit is a crash or what the user asked for
+fn set_wanted_running(wanted: bool) {
+    if let Err(e) = crate::service::set_wanted_running(wanted) {
+        logging::warn(
+            "commands",
+            "wanted_state_failed",
+            &format!("Failed to record whether the service should run: {e}"),
+        );
+    }
+}
+
 fn unload_models() -> crate::Result<()> {
-    eprintln!("Unloading models...");
+    logging::info("commands", "models_unloading", "Unloading models...");
 
-    let client = reqwest::blocking::Client::new();
+    let client = crate::http::api_client(None)?;
     let url = format!(
         "{}:{}/unload",
         *crate::constants::API_BASE_URL,
@@ -121,39 +553,257 @@ fn unload_models() -> crate::Result<()> {
     )?;
 
     if response.status().is_success() {
-        eprintln!("Models unloaded successfully");
+        logging::info(
+            "commands",
+            "models_unloaded",
+            "Models unloaded successfully",
+        );
         Ok(())
     } else {
         Err(format!("Failed to unload models: {}", response.status()).into())
     }
 }
 
+/// Ask llama-swap to load a model by requesting its upstream health check,
+/// which swaps the model in and answers once it's ready
+fn load_model(model: &str, timeout: Duration) -> crate::Result<()> {
+    logging::info(
+        "commands",
+        "model_loading",
+        &format!("Loading model {model}..."),
+    );
+
+    let client = crate::http::api_client(None)?;
+    let response = with_context(
+        client
+            .get(model_url(&["upstream", model, "health"])?)
+            .timeout(timeout)
+            .send(),
+        CONNECT_API,
+    )?;
+
+    if response.status().is_success() {
+        logging::info("commands", "model_loaded", &format!("Model {model} loaded"));
+        Ok(())
+    } else {
+        Err(format!("Failed to load model {model}: {}", response.status()).into())
+    }
+}
+
+/// Send a short chat completion to a loaded model and report how long it took
+fn run_test_prompt(model: &str) -> crate::Result<String> {
+    let client = crate::http::api_client(None)?;
+    let started = std::time::Instant::now();
+    let response = with_context(
+        client
+            .post(model_url(&["v1", "chat", "completions"])?)
+            .json(&serde_json::json!({
+                "model": model,
+                "messages": [{"role": "user", "content": TEST_PROMPT}],
+                "max_tokens": TEST_PROMPT_MAX_TOKENS,
+                "stream": false,
+            }))
+            .timeout(Duration::from_secs(120))
+            .send(),
+        CONNECT_API,
+    )?;
+
+    if !response.status().is_success() {
+        return Err(format!("{model} returned {}", response.status()).into());
+    }
+    let body: serde_json::Value = with_context(response.json(), PARSE_JSON)?;
+    let summary = test_prompt_summary(model, &body, started.elapsed());
+    logging::info("commands", "test_prompt", &summary);
+    Ok(summary)
+}
+
+/// "qwen replied in 1.2s · 12 tokens · 10.0 tok/s: “Hello there”"
+fn test_prompt_summary(model: &str, body: &serde_json::Value, elapsed: Duration) -> String {
+    let reply = body["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or_default()
+        .trim();
+    let mut summary = format!("{model} replied in {:.1}s", elapsed.as_secs_f64());
+    if let Some(tokens) = body["usage"]["completion_tokens"].as_u64() {
+        let tps = tokens as f64 / elapsed.as_secs_f64().max(0.001);
+        summary.push_str(&format!(" · {tokens} tokens · {tps:.1} tok/s"));
+    }
+    if !reply.is_empty() {
+        summary.push_str(&format!(": “{reply}”"));
+    }
+    summary
+}
+
+/// Stop one model, leaving any others running
+fn unload_model(model: &str) -> crate::Result<()> {
+    logging::info(
+        "commands",
+        "model_unloading",
+        &format!("Unloading model {model}..."),
+    );
+
+    let client = crate::http::api_client(None)?;
+    let response = with_context(
+        client
+            .post(model_url(&["api", "models", "unload", model])?)
+            .timeout(Duration::from_secs(30))
+            .send(),
+        CONNECT_API,
+    )?;
+
+    if response.status().is_success() {
+        logging::info(
+            "commands",
+            "model_unloaded",
+            &format!("Model {model} unloaded"),
+        );
+        Ok(())
+    } else {
+        Err(format!("Failed to unload model {model}: {}", response.status()).into())
+    }
+}
+
+/// API URL with each segment escaped, so model names with `/` stay one segment
+pub fn model_url(segments: &[&str]) -> crate::Result<reqwest::Url> {
+    let base = format!(
+        "{}:{}",
+        *crate::constants::API_BASE_URL,
+        *crate::constants::API_PORT
+    );
+    let mut url = with_context(reqwest::Url::parse(&base), "Invalid API URL")?;
+    url.path_segments_mut()
+        .map_err(|()| "Invalid API URL")?
+        .pop_if_empty()
+        .extend(segments);
+    Ok(url)
+}
+
 fn view_file(file_path: &str, default_content_fn: fn() -> &'static str) -> crate::Result<()> {
     let expanded_path = expand_tilde(file_path)?;
 
     ensure_file_exists(&expanded_path, default_content_fn)?;
+    run_open(&["-t", &expanded_path])
+}
 
-    let output = with_context(
-        Command::new("open").args(["-t", &expanded_path]).output(),
-        EXEC_COMMAND,
-    )?;
+/// Console.app follows the log as llama-swap writes to it
+fn view_logs_in_console() -> crate::Result<()> {
+    let path = expand_tilde(&crate::constants::LOG_FILE_PATH)?;
+    ensure_file_exists(&path, create_default_log)?;
+    run_open(&["-a", "Console", &path])
+}
+
+/// Open the config in the configured editor app, else `$EDITOR` in Terminal,
+/// else whatever app handles YAML files
+fn edit_config() -> crate::Result<()> {
+    let path = expand_tilde(&crate::constants::CONFIG_FILE_PATH)?;
+    ensure_file_exists(&path, create_default_config)?;
+    backup_before_editing();
+
+    if let Some(app) = crate::constants::EDITOR_APP.as_deref() {
+        return run_open(&["-a", app, &path]);
+    }
+    match std::env::var("EDITOR")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+    {
+        Some(editor) => {
+            let exe = with_context(std::env::current_exe(), EXEC_COMMAND)?;
+            let mut exe = vec![exe.to_string_lossy().into_owned()];
+            exe.extend(crate::launch_agents::instance_args());
+            let script = terminal_editor_script(&editor, &path, &exe);
+            let output = with_context(
+                Command::new("osascript").args(["-e", &script]).output(),
+                EXEC_COMMAND,
+            )?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Failed to open $EDITOR in Terminal: {stderr}").into());
+            }
+            Ok(())
+        }
+        None => run_open(&[&path]),
+    }
+}
+
+/// Keep a copy of config.yaml as it was before the user edits it, so a bad
+/// edit can be undone from Restore Config…
+fn backup_before_editing() {
+    if let Err(e) = crate::config_backup::backup() {
+        logging::debug(
+            "commands",
+            "config_backup_skipped",
+            &format!("Not backing up config.yaml before editing: {e}"),
+        );
+    }
+}
 
+/// Check config.yaml, reporting every problem found
+fn validate_config() -> crate::Result<String> {
+    match crate::config_validation::validate_config_file() {
+        None => Err(format!("{} doesn't exist", *crate::constants::CONFIG_FILE_PATH).into()),
+        Some(issues) if issues.is_empty() => Ok("config.yaml is valid".into()),
+        Some(issues) => Err(config_issues_message(&issues).into()),
+    }
+}
+
+/// Refuse to restart into a config llama-swap would reject, leaving the
+/// running service (and its loaded models) alone
+fn ensure_config_valid() -> crate::Result<()> {
+    match crate::config_validation::validate_config_file() {
+        Some(issues) if !issues.is_empty() => Err(config_issues_message(&issues).into()),
+        _ => Ok(()),
+    }
+}
+
+fn config_issues_message(issues: &[crate::config_validation::ConfigIssue]) -> String {
+    let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+    format!("config.yaml is invalid: {}", issues.join("; "))
+}
+
+fn reveal_config() -> crate::Result<()> {
+    let path = expand_tilde(&crate::constants::CONFIG_FILE_PATH)?;
+    ensure_file_exists(&path, create_default_config)?;
+    run_open(&["-R", &path])
+}
+
+fn run_open(args: &[&str]) -> crate::Result<()> {
+    let output = with_context(Command::new("open").args(args).output(), EXEC_COMMAND)?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(format!("Failed to open file: {stderr}").into());
     }
-
     Ok(())
 }
 
-fn open_ui() -> crate::Result<()> {
-    let ui_url = format!(
-        "{}:{}/ui/models",
+/// AppleScript running `$EDITOR <path>` in a new Terminal window, then
+/// validating the config once the editor exits
+fn terminal_editor_script(editor: &str, path: &str, exe: &[String]) -> String {
+    // Quote the paths for the shell, then the whole command for AppleScript
+    let quote = |s: &str| format!("'{}'", s.replace('\'', r"'\''"));
+    let exe: Vec<String> = exe.iter().map(|arg| quote(arg)).collect();
+    let command = format!(
+        "{editor} {}; {} validate_config",
+        quote(path),
+        exe.join(" ")
+    );
+    let command = command.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("tell application \"Terminal\"\nactivate\ndo script \"{command}\"\nend tell")
+}
+
+pub fn api_endpoint() -> String {
+    format!(
+        "{}:{}",
         *crate::constants::API_BASE_URL,
         *crate::constants::API_PORT
-    );
+    )
+}
 
-    let output = with_context(Command::new("open").arg(ui_url).output(), EXEC_COMMAND)?;
+pub fn ui_url() -> String {
+    format!("{}/ui/models", api_endpoint())
+}
+
+fn open_ui() -> crate::Result<()> {
+    let output = with_context(Command::new("open").arg(ui_url()).output(), EXEC_COMMAND)?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -163,6 +813,107 @@ fn open_ui() -> crate::Result<()> {
     Ok(())
 }
 
+/// Export the metrics history and reveal the file in Finder
+///
+/// Commands run in their own process, so the streaming instance is first asked
+/// to save its in-memory copy.
+fn export_metrics(format: ExportFormat, path: Option<&str>) -> crate::Result<()> {
+    let history = crate::persistence::load_current_metrics()?;
+    let dest = path.map(expand_tilde).transpose()?;
+    let path =
+        crate::export::export_history(&history, format, dest.as_deref().map(std::path::Path::new))?;
+
+    logging::info(
+        "commands",
+        "metrics_exported",
+        &format!("Exported metrics history to {}", path.display()),
+    );
+
+    let output = with_context(
+        Command::new("open").arg("-R").arg(&path).output(),
+        EXEC_COMMAND,
+    )?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to reveal export: {stderr}").into());
+    }
+
+    Ok(())
+}
+
+/// Write the diagnostics bundle to a file and copy it to the clipboard
+fn copy_diagnostics(path: Option<&str>) -> crate::Result<String> {
+    let mut state = crate::types::PluginState::new()?;
+    state.prime_cpu_usage();
+    state.update_state();
+    let bundle = crate::diagnostics::bundle(&state);
+
+    let dest = path.map(expand_tilde).transpose()?;
+    let file_name = format!("llama-swap-diagnostics-{}.md", crate::export::file_stamp());
+    let path = crate::export::output_path(dest.as_deref().map(std::path::Path::new), &file_name)?;
+    with_context(std::fs::write(&path, &bundle), CREATE_FILE)?;
+    copy_to_clipboard(&bundle)?;
+
+    logging::info(
+        "commands",
+        "diagnostics_written",
+        &format!("Wrote diagnostics to {} and copied them", path.display()),
+    );
+    Ok(format!(
+        "Diagnostics copied and saved to {}",
+        path.display()
+    ))
+}
+
+fn copy_api_endpoint() -> crate::Result<()> {
+    copy_to_clipboard(&api_endpoint())
+}
+
+/// Copy one chart's series as CSV, as the streaming instance has it now
+fn copy_series_csv(series: &str, model: Option<&str>) -> crate::Result<String> {
+    let history = crate::persistence::load_current_metrics()?;
+    let data = crate::export::find_series(&history, series, model)
+        .ok_or_else(|| format!("No recorded data for {series}"))?;
+
+    copy_to_clipboard(&crate::export::series_to_csv(series, data))?;
+    let message = format!("Copied {} {series} samples as CSV", data.len());
+    logging::info("commands", "series_copied", &message);
+    Ok(message)
+}
+
+fn copy_to_clipboard(text: &str) -> crate::Result<()> {
+    use std::io::Write;
+
+    let mut child = with_context(
+        Command::new("pbcopy")
+            .stdin(std::process::Stdio::piped())
+            .spawn(),
+        EXEC_COMMAND,
+    )?;
+    if let Some(mut stdin) = child.stdin.take() {
+        with_context(stdin.write_all(text.as_bytes()), EXEC_COMMAND)?;
+    }
+
+    let status = with_context(child.wait(), EXEC_COMMAND)?;
+    if !status.success() {
+        return Err("Failed to copy to the clipboard".into());
+    }
+
+    Ok(())
+}
+
+fn clear_history() -> crate::Result<()> {
+    crate::persistence::clear_metrics()?;
+
+    #[cfg(feature = "sqlite")]
+    if *crate::constants::HISTORY_DB_ENABLED {
+        crate::storage::MetricsStore::open_default()?.clear()?;
+    }
+
+    logging::info("commands", "history_cleared", "Cleared metrics history");
+    Ok(())
+}
+
 // Helper structs and functions
 
 struct ServiceContext {
@@ -175,7 +926,7 @@ impl ServiceContext {
     fn new() -> crate::Result<Self> {
         let user_id = get_user_id()?;
         let target_domain = format!("gui/{user_id}");
-        let service_target = format!("{target_domain}/{LAUNCH_AGENT_LABEL}");
+        let service_target = format!("{target_domain}/{}", *LAUNCH_AGENT_LABEL);
         let plist_path = get_plist_path()?;
 
         Ok(Self {
@@ -236,14 +987,15 @@ pub fn is_service_installed() -> crate::Result<bool> {
     Ok(std::path::Path::new(&plist_path).exists())
 }
 
-fn get_plist_path() -> crate::Result<String> {
+pub fn get_plist_path() -> crate::Result<String> {
     let home = get_home_dir()?;
     Ok(format!(
-        "{home}/Library/LaunchAgents/{LAUNCH_AGENT_LABEL}.plist"
+        "{home}/Library/LaunchAgents/{}.plist",
+        *LAUNCH_AGENT_LABEL
     ))
 }
 
-fn expand_tilde(path: &str) -> crate::Result<String> {
+pub fn expand_tilde(path: &str) -> crate::Result<String> {
     if path.starts_with("~/") {
         let home = get_home_dir()?;
         Ok(path.replacen('~', &home, 1))
@@ -257,7 +1009,11 @@ fn create_default_log() -> &'static str {
 }
 
 fn install_service() -> crate::Result<()> {
-    eprintln!("Installing Llama-Swap service...");
+    logging::info(
+        "commands",
+        "service_installing",
+        "Installing Llama-Swap service...",
+    );
 
     let binary_path = find_llama_swap_binary()?;
     let plist_content = generate_plist_content(&binary_path)?;
@@ -266,9 +1022,14 @@ fn install_service() -> crate::Result<()> {
 
     // If service is already loaded, unload it first to refresh the plist
     if crate::service::is_service_loaded() {
-        eprintln!("Unloading existing service to refresh plist...");
+        logging::info(
+            "commands",
+            "service_refreshing",
+            "Unloading existing service to refresh plist...",
+        );
         let _ = run_launchctl_command("bootout", &[&service_context.service_target]);
     }
+    crate::service::clear_start_request();
 
     // Create LaunchAgents directory if it doesn't exist
     if let Some(parent) = std::path::Path::new(&plist_path).parent() {
@@ -289,20 +1050,62 @@ fn install_service() -> crate::Result<()> {
         )?;
     }
 
-    eprintln!("Service plist installed successfully");
+    logging::info(
+        "commands",
+        "service_installed",
+        "Service plist installed successfully",
+    );
+    Ok(())
+}
+
+/// Bring the plist up to date; installing unloads the service, so one that
+/// was running is started again
+fn reinstall_service() -> crate::Result<()> {
+    let was_running = crate::service::service_pid().is_some();
+    install_service()?;
+    if was_running {
+        start_service()?;
+    }
     Ok(())
 }
 
+/// Save an install option and, when the service is installed, regenerate the
+/// plist and bootstrap it again so launchd picks the change up
+fn set_install_option(option: crate::install_options::InstallOption) -> crate::Result<String> {
+    let mut options = crate::install_options::InstallOptions::load();
+    if !options.set(option) {
+        return Ok("Install option unchanged".into());
+    }
+    options.save()?;
+
+    let [name, value] = option.args();
+    logging::audit("commands", &format!("Install option {name} set to {value}"));
+    if !std::path::Path::new(&get_plist_path()?).exists() {
+        return Ok("Install option saved; it applies when the service is installed".into());
+    }
+    reinstall_service()?;
+    Ok("Install option applied".into())
+}
+
 fn uninstall_service() -> crate::Result<()> {
-    eprintln!("Uninstalling Llama-Swap service...");
+    logging::info(
+        "commands",
+        "service_uninstalling",
+        "Uninstalling Llama-Swap service...",
+    );
 
     let service_context = ServiceContext::new()?;
 
     // Stop and unload from launchctl first
     if crate::service::is_service_loaded() {
-        eprintln!("Unloading service from launchctl...");
+        logging::info(
+            "commands",
+            "service_unloading",
+            "Unloading service from launchctl...",
+        );
         let _ = run_launchctl_command("bootout", &[&service_context.service_target]);
     }
+    set_wanted_running(false);
 
     let plist_path = get_plist_path()?;
 
@@ -312,19 +1115,105 @@ fn uninstall_service() -> crate::Result<()> {
             std::fs::remove_file(&plist_path),
             "Failed to remove plist file",
         )?;
-        eprintln!("Service uninstalled successfully");
+        logging::info(
+            "commands",
+            "service_uninstalled",
+            "Service uninstalled successfully",
+        );
     } else {
-        eprintln!("Service plist not found (already uninstalled)");
+        logging::info(
+            "commands",
+            "service_not_installed",
+            "Service plist not found (already uninstalled)",
+        );
     }
 
     Ok(())
 }
 
+/// Homebrew, which isn't on the PATH SwiftBar gives plugins
+pub fn find_brew() -> Option<&'static str> {
+    ["/opt/homebrew/bin/brew", "/usr/local/bin/brew"]
+        .into_iter()
+        .find(|path| std::path::Path::new(path).exists())
+}
+
+/// `brew install llama-swap`
+fn install_binary() -> crate::Result<String> {
+    run_brew(&["install", "llama-swap"])?;
+    Ok("llama-swap installed; install the service next".to_string())
+}
+
+/// `brew upgrade llama-swap`
+fn upgrade_binary() -> crate::Result<String> {
+    run_brew(&["upgrade", "llama-swap"])?;
+    Ok("llama-swap upgraded; restart the service to use the new version".to_string())
+}
+
+/// Run Homebrew, logging its output line by line as it arrives
+fn run_brew(args: &[&str]) -> crate::Result<()> {
+    let brew = find_brew().ok_or("Homebrew is not installed; get it from https://brew.sh")?;
+    let command = format!("brew {}", args.join(" "));
+    logging::info("commands", "brew_running", &format!("Running {command}..."));
+
+    let mut child = with_context(
+        Command::new(brew)
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn(),
+        EXEC_COMMAND,
+    )?;
+
+    // Drained on its own thread so neither pipe fills up and blocks brew
+    let stderr = child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut last_line = None;
+        for line in stderr
+            .into_iter()
+            .flat_map(|stderr| BufReader::new(stderr).lines().map_while(Result::ok))
+        {
+            logging::warn("commands", "brew_output", &line);
+            if !line.trim().is_empty() {
+                last_line = Some(line);
+            }
+        }
+        last_line
+    });
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            logging::info("commands", "brew_output", &line);
+        }
+    }
+
+    let last_error = stderr_reader.join().ok().flatten();
+    let status = with_context(child.wait(), EXEC_COMMAND)?;
+    if status.success() {
+        Ok(())
+    } else {
+        let reason = last_error.unwrap_or_else(|| status.to_string());
+        Err(format!("{command} failed: {}", reason.trim()).into())
+    }
+}
+
 pub fn find_llama_swap_binary() -> crate::Result<String> {
     // Run which in a shell context to load user PATH configs
     let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
-    let output = Command::new(&shell)
-        .args(["-i", "-c", "which llama-swap"]) // -i = interactive shell (loads .zshrc)
+    let mut command = Command::new(&shell);
+    command.args(["-i", "-c", "which llama-swap"]); // -i = interactive shell (loads .zshrc)
+
+    // An interactive shell enables job control on the controlling terminal and can leave
+    // it owned by another process group; run it in its own session when we have a tty
+    // (e.g. the `tui` subcommand) so the terminal stays ours.
+    // SAFETY: setsid() is async-signal-safe and touches no parent state.
+    unsafe {
+        command.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+
+    let output = command
         .output()
         .map_err(|_| "Failed to run which command in shell context")?;
 
@@ -338,11 +1227,13 @@ pub fn find_llama_swap_binary() -> crate::Result<String> {
     Err("llama-swap binary not found in PATH. Please install llama-swap first and ensure it's available in your PATH.".into())
 }
 
-fn generate_plist_content(binary_path: &str) -> crate::Result<String> {
+pub fn generate_plist_content(binary_path: &str) -> crate::Result<String> {
     let log_path = expand_tilde(&crate::constants::LOG_FILE_PATH)?;
     let working_dir = get_home_dir()?;
 
     let config_path = expand_tilde(&crate::constants::CONFIG_FILE_PATH)?;
+    let plugin_config = crate::plugin_config::get();
+    let extra = service_plist_entries(&plugin_config.service_args, &plugin_config.service_env)?;
 
     let plist = format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -358,52 +1249,251 @@ fn generate_plist_content(binary_path: &str) -> crate::Result<String> {
         <string>{}</string>
         <string>-listen</string>
         <string>:{}</string>
-    </array>
+{}    </array>
     <key>WorkingDirectory</key>
     <string>{}</string>
-    <key>RunAtLoad</key>
-    <false/>
-    <key>KeepAlive</key>
-    <false/>
-    <key>StandardOutPath</key>
+{}    <key>StandardOutPath</key>
     <string>{}</string>
     <key>StandardErrorPath</key>
     <string>{}</string>
-</dict>
+{}</dict>
 </plist>"#,
-        LAUNCH_AGENT_LABEL,
+        *LAUNCH_AGENT_LABEL,
         binary_path,
         config_path,
         *crate::constants::API_PORT,
+        extra.args,
         working_dir,
+        crate::install_options::InstallOptions::load().plist_entries(),
         log_path,
-        log_path
+        log_path,
+        extra.env
     );
 
     Ok(plist)
 }
 
+/// The plugin config's extra llama-swap arguments and environment, as plist XML
+struct ServicePlistEntries {
+    args: String, // <string> lines for ProgramArguments
+    env: String,  // An EnvironmentVariables entry, empty when there are none
+}
+
+fn service_plist_entries(
+    args: &[String],
+    env: &std::collections::BTreeMap<String, String>,
+) -> crate::Result<ServicePlistEntries> {
+    if let Some(arg) = args
+        .iter()
+        .find(|arg| matches!(flag_name(arg), Some("config" | "listen")))
+    {
+        return Err(format!("service_args can't set {arg}; the plugin sets it").into());
+    }
+    if let Some(name) = env
+        .keys()
+        .find(|name| name.is_empty() || name.contains('='))
+    {
+        return Err(format!("service_env has an invalid variable name: {name:?}").into());
+    }
+
+    let args = args
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", plist_escape(arg)))
+        .collect();
+    let env = if env.is_empty() {
+        String::new()
+    } else {
+        let vars: String = env
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "        <key>{}</key>\n        <string>{}</string>\n",
+                    plist_escape(name),
+                    plist_escape(value)
+                )
+            })
+            .collect();
+        format!("    <key>EnvironmentVariables</key>\n    <dict>\n{vars}    </dict>\n")
+    };
+    Ok(ServicePlistEntries { args, env })
+}
+
+/// The flag an argument sets, as Go's flag package reads it: one or two
+/// leading dashes, with any `=value` dropped
+fn flag_name(arg: &str) -> Option<&str> {
+    let name = arg.strip_prefix("--").or_else(|| arg.strip_prefix('-'))?;
+    Some(name.split_once('=').map_or(name, |(name, _)| name))
+}
+
+/// Escape text for a plist <string> or <key>
+fn plist_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
 fn create_default_config() -> &'static str {
     r#"# Llama-Swap Configuration
 models:
   "Qwen3-30B-A3B-128K":
-        cmd: >-
-        llama-server
-        --metrics
-        --port 8902
-        --model unsloth_Qwen3-30B-A3B-128K-GGUF_Qwen3-30B-A3B-128K-XXX.gguf
-        --n-gpu-layers 999
-        --flash-attn
-        --rope-scaling yarn
-        --rope-scale 4
-        --yarn-orig-ctx 32768
-        --ctx-size 131072
-        --cache-type-k q4_1
-        --cache-type-v q4_1
-        --batch-size 1024
-        --temp 0.6
-        --top-p 0.95
-        --top-k 20
-        --min-p 0
+    cmd: >-
+      llama-server
+      --metrics
+      --port 8902
+      --model unsloth_Qwen3-30B-A3B-128K-GGUF_Qwen3-30B-A3B-128K-XXX.gguf
+      --n-gpu-layers 999
+      --flash-attn
+      --rope-scaling yarn
+      --rope-scale 4
+      --yarn-orig-ctx 32768
+      --ctx-size 131072
+      --cache-type-k q4_1
+      --cache-type-v q4_1
+      --batch-size 1024
+      --temp 0.6
+      --top-p 0.95
+      --top-k 20
+      --min-p 0
 "#
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_url_escapes_model_name() {
+        let url = model_url(&["upstream", "org/qwen:q4", "health"]).unwrap();
+        assert_eq!(url.path(), "/upstream/org%2Fqwen:q4/health");
+    }
+
+    #[test]
+    fn test_mutating_commands() {
+        assert!(is_mutating_command("load_model"));
+        assert!(is_mutating_command("test_prompt"));
+        assert!(!is_mutating_command("copy_model_name"));
+        assert!(!is_mutating_command("export_metrics"));
+    }
+
+    #[test]
+    fn test_parse_command_arguments() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            CommandRequest::parse("do_unload", &[]).unwrap(),
+            CommandRequest::Unload { model: None }
+        );
+        assert_eq!(
+            CommandRequest::parse("load_model", &args(&["qwen", "10m"])).unwrap(),
+            CommandRequest::Load {
+                model: "qwen".to_string(),
+                timeout: Duration::from_secs(600),
+            }
+        );
+        assert_eq!(
+            CommandRequest::parse("export_csv", &args(&["~/out.csv"])).unwrap(),
+            CommandRequest::Export {
+                format: ExportFormat::Csv,
+                path: Some("~/out.csv".to_string()),
+            }
+        );
+        assert_eq!(
+            CommandRequest::parse("set_install_option", &args(&["keep_alive", "always"])).unwrap(),
+            CommandRequest::SetInstallOption(crate::install_options::InstallOption::KeepAlive(
+                crate::install_options::KeepAlive::Always
+            ))
+        );
+        assert!(CommandRequest::parse("set_install_option", &args(&["keep_alive"])).is_err());
+        assert_eq!(
+            CommandRequest::parse("set_model_option", &args(&["qwen", "parallel", "4"])).unwrap(),
+            CommandRequest::SetModelOption {
+                model: "qwen".to_string(),
+                option: crate::model_wizard::ModelOption::Parallel(Some(4)),
+            }
+        );
+        assert!(CommandRequest::parse("load_model", &[]).is_err());
+        assert!(CommandRequest::parse("load_model", &args(&["qwen", "soon"])).is_err());
+        assert!(CommandRequest::parse("do_start", &args(&["extra"])).is_err());
+        assert!(CommandRequest::parse("bogus", &[]).is_err());
+    }
+
+    #[test]
+    fn test_prompt_summary_reports_throughput() {
+        let body = serde_json::json!({
+            "choices": [{"message": {"role": "assistant", "content": " Hello there! "}}],
+            "usage": {"prompt_tokens": 14, "completion_tokens": 12},
+        });
+        assert_eq!(
+            test_prompt_summary("qwen", &body, Duration::from_millis(1200)),
+            "qwen replied in 1.2s · 12 tokens · 10.0 tok/s: “Hello there!”"
+        );
+        assert_eq!(
+            test_prompt_summary("qwen", &serde_json::json!({}), Duration::from_secs(2)),
+            "qwen replied in 2.0s"
+        );
+    }
+
+    #[test]
+    fn test_failure_excerpt_is_one_short_line() {
+        assert_eq!(
+            failure_excerpt(
+                "Failed to start service: Bootstrap failed: 5:\n  Input/output error\n"
+            ),
+            "Failed to start service: Bootstrap failed: 5: Input/output error"
+        );
+        assert_eq!(failure_excerpt(&"x".repeat(300)).chars().count(), 201);
+    }
+
+    #[test]
+    fn test_terminal_editor_script_quotes_path() {
+        assert_eq!(
+            terminal_editor_script(
+                "nvim",
+                "/Users/me/it's.yaml",
+                &["/usr/local/bin/plugin".to_string()]
+            ),
+            "tell application \"Terminal\"\nactivate\ndo script \"nvim '/Users/me/it'\\\\''s.yaml'; '/usr/local/bin/plugin' validate_config\"\nend tell"
+        );
+    }
+
+    #[test]
+    fn test_service_plist_entries() {
+        let env = [("GGML_METAL_PATH".to_string(), "/opt/a&b".to_string())].into();
+        let entries = service_plist_entries(&["-watch-config".to_string()], &env).unwrap();
+        assert_eq!(entries.args, "        <string>-watch-config</string>\n");
+        assert_eq!(
+            entries.env,
+            "    <key>EnvironmentVariables</key>\n    <dict>\n        <key>GGML_METAL_PATH</key>\n        <string>/opt/a&amp;b</string>\n    </dict>\n"
+        );
+
+        let none = service_plist_entries(&[], &Default::default()).unwrap();
+        assert_eq!((none.args.as_str(), none.env.as_str()), ("", ""));
+        for reserved in ["-listen", "--listen", "-listen=:9999", "--config=x"] {
+            assert!(
+                service_plist_entries(&[reserved.to_string()], &Default::default()).is_err(),
+                "{reserved}"
+            );
+        }
+        assert!(service_plist_entries(&["-configs".to_string()], &Default::default()).is_ok());
+        let bad_env = [("A=B".to_string(), String::new())].into();
+        assert!(service_plist_entries(&[], &bad_env).is_err());
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert_eq!(
+            crate::config_validation::validate(create_default_config()),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert!(parse_duration("5 minutes").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::types::error_helpers::with_context;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Backups kept; the oldest are deleted as new ones are made
+const MAX_BACKUPS: usize = 20;
+
+/// A copy of config.yaml in the backup directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub created: SystemTime,
+}
+
+impl Backup {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Copy config.yaml into the backup directory, unless the newest backup
+/// already holds the same contents; returns the backup that does
+pub fn backup() -> crate::Result<PathBuf> {
+    backup_to(&config_path()?, &backup_dir()?)
+}
+
+/// Backups, newest first
+pub fn list() -> Vec<Backup> {
+    backup_dir().map(|dir| list_in(&dir)).unwrap_or_default()
+}
+
+/// Replace config.yaml with a backup, first backing up the config it replaces
+/// so a restore can itself be undone
+pub fn restore(file_name: &str) -> crate::Result<()> {
+    restore_from(&config_path()?, &backup_dir()?, file_name)
+}
+
+fn backup_to(config: &Path, dir: &Path) -> crate::Result<PathBuf> {
+    let contents = with_context(std::fs::read(config), "Failed to read config.yaml")?;
+    let existing = list_in(dir);
+    if let Some(newest) = existing.first() {
+        if std::fs::read(&newest.path).is_ok_and(|newest| newest == contents) {
+            return Ok(newest.path.clone());
+        }
+    }
+
+    let stamp = crate::export::file_stamp();
+    let mut path = dir.join(format!("config-{stamp}.yaml"));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("config-{stamp}-{n}.yaml"));
+        n += 1;
+    }
+    // Written rather than copied so the backup's mtime records when it was made
+    crate::persistence::write_atomic(&path, &contents)?;
+
+    for old in existing.iter().skip(MAX_BACKUPS - 1) {
+        let _ = std::fs::remove_file(&old.path);
+    }
+    Ok(path)
+}
+
+fn list_in(dir: &Path) -> Vec<Backup> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<Backup> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("config-") && name.ends_with(".yaml")
+        })
+        .filter_map(|entry| {
+            let created = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some(Backup {
+                path: entry.path(),
+                created,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+    backups
+}
+
+fn restore_from(config: &Path, dir: &Path, file_name: &str) -> crate::Result<()> {
+    // Only names from the listing, never a path elsewhere on disk
+    let backup = list_in(dir)
+        .into_iter()
+        .find(|backup| backup.file_name() == file_name)
+        .ok_or_else(|| format!("No config backup named {file_name}"))?;
+    let contents = with_context(std::fs::read(&backup.path), "Failed to read config backup")?;
+
+    if config.exists() {
+        backup_to(config, dir)?;
+    }
+    crate::persistence::write_atomic(config, &contents)
+}
+
+fn backup_dir() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::CONFIG_BACKUP_DIR)?;
+    Ok(PathBuf::from(path))
+}
+
+fn config_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::CONFIG_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_and_restore() {
+        let dir = std::env::temp_dir().join(format!("config-backup-{}", std::process::id()));
+        let backups = dir.join("backups");
+        let config = dir.join("config.yaml");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(&config, "models: {a: {cmd: one}}\n").unwrap();
+        let first = backup_to(&config, &backups).unwrap();
+        // Unchanged contents reuse the newest backup
+        assert_eq!(backup_to(&config, &backups).unwrap(), first);
+
+        std::fs::write(&config, "models: {b: {cmd: two}}\n").unwrap();
+        let second = backup_to(&config, &backups).unwrap();
+        assert_ne!(second, first);
+        assert_eq!(list_in(&backups)[0].path, second);
+
+        let first_name = first.file_name().unwrap().to_string_lossy().into_owned();
+        restore_from(&config, &backups, &first_name).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&config).unwrap(),
+            "models: {a: {cmd: one}}\n"
+        );
+        assert!(restore_from(&config, &backups, "../config.yaml").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+use crate::logging;
+use crate::types::error_helpers::with_context;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// The parts of llama-swap's config.yaml the plugin cares about
+///
+/// Unknown keys are ignored so newer llama-swap options don't break parsing.
+#[derive(Debug, Default, Deserialize)]
+pub struct LlamaSwapConfig {
+    #[serde(default)]
+    pub models: BTreeMap<String, ModelConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ModelConfig {
+    #[serde(default)]
+    pub cmd: String,
+}
+
+impl ModelConfig {
+    /// The program the model's command launches, e.g. `/opt/llama.cpp/llama-server`
+    ///
+    /// Returns None for empty commands and for llama-swap macros (`${...}`), which
+    /// only llama-swap can expand.
+    pub fn program(&self) -> Option<&str> {
+        let program = self
+            .cmd
+            .split_whitespace()
+            .next()?
+            .trim_matches(|c| c == '"' || c == '\'');
+        (!program.is_empty() && !program.contains("${")).then_some(program)
+    }
+
+    pub fn concurrency(&self) -> Concurrency {
+        Concurrency::from_cmd(&self.cmd)
+    }
+}
+
+/// The request concurrency flags in a model's command; None where the command
+/// leaves llama-server's default
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Concurrency {
+    pub parallel: Option<u32>, // `--parallel`/`-np`: slots serving requests at once
+    pub cont_batching: Option<bool>, // `--cont-batching`/`-cb` or `--no-cont-batching`/`-nocb`
+}
+
+impl Concurrency {
+    pub fn from_cmd(cmd: &str) -> Self {
+        let mut concurrency = Self::default();
+        let mut words = cmd.split_whitespace();
+        while let Some(word) = words.next() {
+            match word {
+                "--parallel" | "-np" => {
+                    concurrency.parallel = words.next().and_then(|n| n.parse().ok());
+                }
+                "--cont-batching" | "-cb" => concurrency.cont_batching = Some(true),
+                "--no-cont-batching" | "-nocb" => concurrency.cont_batching = Some(false),
+                _ => {
+                    if let Some(n) = word.strip_prefix("--parallel=") {
+                        concurrency.parallel = n.parse().ok();
+                    }
+                }
+            }
+        }
+        concurrency
+    }
+
+    /// e.g. "Parallel Slots: 4 · Continuous Batching: Off"
+    pub fn summary(&self) -> String {
+        let parallel = self
+            .parallel
+            .map_or("Default".to_string(), |n| n.to_string());
+        let cont_batching = match self.cont_batching {
+            Some(true) => "On",
+            Some(false) => "Off",
+            None => "Default",
+        };
+        format!("Parallel Slots: {parallel} · Continuous Batching: {cont_batching}")
+    }
+}
+
+impl LlamaSwapConfig {
+    pub fn parse(yaml: &str) -> crate::Result<Self> {
+        with_context(serde_yaml::from_str(yaml), "Failed to parse config.yaml")
+    }
+
+    /// Read the configured config.yaml, if it exists
+    pub fn load() -> crate::Result<Option<Self>> {
+        let path = config_file_path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let yaml = with_context(std::fs::read_to_string(&path), "Failed to read config.yaml")?;
+        Self::parse(&yaml).map(Some)
+    }
+}
+
+/// Configured model names and concurrency settings, re-read only when
+/// config.yaml changes
+#[derive(Debug, Default)]
+pub struct ConfiguredModels {
+    config_modified: Option<SystemTime>,
+    names: Vec<String>, // Sorted, as in the config's BTreeMap
+    concurrency: BTreeMap<String, Concurrency>,
+}
+
+impl ConfiguredModels {
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn concurrency(&self, model: &str) -> Option<Concurrency> {
+        self.concurrency.get(model).copied()
+    }
+
+    pub fn refresh(&mut self) {
+        let modified = modified_time();
+        if modified == self.config_modified {
+            return;
+        }
+
+        self.config_modified = modified;
+        let models = match LlamaSwapConfig::load() {
+            Ok(Some(config)) => config.models,
+            Ok(None) => BTreeMap::new(),
+            Err(e) => {
+                logging::debug(
+                    "config_parser",
+                    "config_unreadable",
+                    &format!("Not listing configured models: {e}"),
+                );
+                BTreeMap::new()
+            }
+        };
+        self.names = models.keys().cloned().collect();
+        self.concurrency = models
+            .iter()
+            .map(|(name, model)| (name.clone(), model.concurrency()))
+            .collect();
+    }
+}
+
+/// Last modification time of config.yaml, used to skip re-parsing an unchanged file
+pub fn modified_time() -> Option<SystemTime> {
+    let path = config_file_path().ok()?;
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn config_file_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::CONFIG_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_model_programs() {
+        let config = LlamaSwapConfig::parse(
+            r#"
+healthCheckTimeout: 60
+models:
+  "qwen":
+    cmd: >-
+      /opt/llama.cpp/llama-server
+      --port 8902
+  "macro":
+    cmd: ${llama} --port 8903
+  "proxy-only":
+    proxy: http://127.0.0.1:9000
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.models.len(), 3);
+        assert_eq!(
+            config.models["qwen"].program(),
+            Some("/opt/llama.cpp/llama-server")
+        );
+        assert_eq!(config.models["macro"].program(), None);
+        assert_eq!(config.models["proxy-only"].program(), None);
+    }
+
+    #[test]
+    fn test_concurrency_from_cmd() {
+        let concurrency = Concurrency::from_cmd("llama-server --port 8902 -np 4 -nocb");
+        assert_eq!(
+            concurrency,
+            Concurrency {
+                parallel: Some(4),
+                cont_batching: Some(false),
+            }
+        );
+        assert_eq!(
+            concurrency.summary(),
+            "Parallel Slots: 4 · Continuous Batching: Off"
+        );
+        assert_eq!(
+            Concurrency::from_cmd("llama-server --parallel=2 --cont-batching").parallel,
+            Some(2)
+        );
+        assert_eq!(
+            Concurrency::from_cmd("llama-server --port 8902"),
+            Concurrency::default()
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::SystemTime;
+
+/// Something in config.yaml that will stop llama-swap starting or a model loading
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    pub line: Option<usize>, // 1-based
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Check config.yaml's syntax and the parts of its schema llama-swap can't run without
+///
+/// Beyond valid YAML: `models` is a map, each model has a non-empty `cmd`
+/// string, and no two models (or a model and llama-swap itself) listen on the
+/// same `--port`. serde_yaml values carry no positions, so line numbers for
+/// schema problems are found by searching the text for the offending key.
+pub fn validate(yaml: &str) -> Vec<ConfigIssue> {
+    let root: Value = match serde_yaml::from_str(yaml) {
+        Ok(root) => root,
+        Err(e) => {
+            return vec![ConfigIssue {
+                line: e.location().map(|location| location.line()),
+                message: syntax_message(&e),
+            }];
+        }
+    };
+    let locator = Locator::new(yaml);
+
+    let models = match &root {
+        Value::Null => None,
+        Value::Mapping(root) => root.get("models"),
+        _ => {
+            return vec![ConfigIssue {
+                line: Some(1),
+                message: "the config must be a map of settings".to_string(),
+            }];
+        }
+    };
+    let models = match models {
+        Some(Value::Mapping(models)) if !models.is_empty() => models,
+        Some(Value::Mapping(_)) | Some(Value::Null) | None => {
+            return vec![ConfigIssue {
+                line: locator.models,
+                message: "no models are configured".to_string(),
+            }];
+        }
+        Some(_) => {
+            return vec![ConfigIssue {
+                line: locator.models,
+                message: "`models` must be a map of model names to settings".to_string(),
+            }];
+        }
+    };
+
+    let mut issues = Vec::new();
+    let mut ports: BTreeMap<u16, Vec<(String, Option<usize>)>> = BTreeMap::new();
+    for (name, model) in models {
+        let name = match name {
+            Value::String(name) => name.clone(),
+            other => serde_yaml::to_string(other)
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        };
+        let model_line = locator.model_line(&name);
+        let has_cmd = matches!(model, Value::Mapping(model) if model.contains_key("cmd"));
+        match check_model(model) {
+            Ok(cmd) => {
+                if let Some(port) = cmd_port(cmd) {
+                    let line = locator.line_after(model_line, "--port");
+                    ports.entry(port).or_default().push((name, line));
+                }
+            }
+            Err(problem) => {
+                let line = if has_cmd {
+                    locator.line_after(model_line, "cmd:")
+                } else {
+                    model_line
+                };
+                issues.push(ConfigIssue {
+                    line,
+                    message: format!("{name}: {problem}"),
+                });
+            }
+        }
+    }
+
+    let api_port = *crate::constants::API_PORT;
+    for (port, users) in &ports {
+        if *port == api_port {
+            for (name, line) in users {
+                issues.push(ConfigIssue {
+                    line: *line,
+                    message: format!("{name}: port {port} is llama-swap's own port"),
+                });
+            }
+        }
+        if let [(first, _), rest @ ..] = users.as_slice() {
+            for (name, line) in rest {
+                issues.push(ConfigIssue {
+                    line: *line,
+                    message: format!("{name}: port {port} is already used by {first}"),
+                });
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// The model's command, or why it has none llama-swap can run
+fn check_model(model: &Value) -> Result<&str, &'static str> {
+    let Value::Mapping(model) = model else {
+        return Err("settings must be a map with a `cmd`");
+    };
+    match model.get("cmd") {
+        None => Err("missing `cmd`"),
+        Some(Value::String(cmd)) if cmd.trim().is_empty() => Err("`cmd` is empty"),
+        Some(Value::String(cmd)) => Ok(cmd),
+        Some(_) => Err("`cmd` must be a string"),
+    }
+}
+
+/// The port a command passes with `--port 8080` or `--port=8080`
+pub fn cmd_port(cmd: &str) -> Option<u16> {
+    let mut words = cmd.split_whitespace();
+    while let Some(word) = words.next() {
+        if word == "--port" {
+            return words.next()?.parse().ok();
+        }
+        if let Some(port) = word.strip_prefix("--port=") {
+            return port.parse().ok();
+        }
+    }
+    None
+}
+
+/// serde_yaml's message without the position it also reports separately
+fn syntax_message(error: &serde_yaml::Error) -> String {
+    let message = error.to_string();
+    match message.find(" at line ") {
+        Some(at) => message[..at].to_string(),
+        None => message,
+    }
+}
+
+/// Finds the lines keys appear on
+struct Locator<'a> {
+    lines: Vec<&'a str>,
+    models: Option<usize>,
+}
+
+impl<'a> Locator<'a> {
+    fn new(yaml: &'a str) -> Self {
+        let lines: Vec<&str> = yaml.lines().collect();
+        let models = lines
+            .iter()
+            .position(|line| is_key(line, "models"))
+            .map(|index| index + 1);
+        Self { lines, models }
+    }
+
+    /// The line declaring a model under `models:`
+    fn model_line(&self, name: &str) -> Option<usize> {
+        let start = self.models?;
+        self.lines[start..]
+            .iter()
+            .position(|line| is_key(line.trim_start(), name))
+            .map(|index| start + index + 1)
+    }
+
+    /// The first line at or after `start` containing `needle`
+    fn line_after(&self, start: Option<usize>, needle: &str) -> Option<usize> {
+        let start = start?;
+        self.lines[start - 1..]
+            .iter()
+            .position(|line| line.contains(needle))
+            .map(|index| start + index)
+    }
+}
+
+/// Whether a line starts a `key:`, bare or quoted
+pub fn is_key(line: &str, key: &str) -> bool {
+    ["", "\"", "'"].iter().any(|quote| {
+        line.strip_prefix(quote)
+            .and_then(|rest| rest.strip_prefix(key))
+            .and_then(|rest| rest.strip_prefix(quote))
+            .is_some_and(|rest| rest.starts_with(':'))
+    })
+}
+
+/// The current config.yaml's problems, re-checked only when the file changes
+#[derive(Debug, Default)]
+pub struct ConfigValidation {
+    config_modified: Option<SystemTime>,
+    issues: Vec<ConfigIssue>,
+}
+
+impl ConfigValidation {
+    pub fn issues(&self) -> &[ConfigIssue] {
+        &self.issues
+    }
+
+    pub fn refresh(&mut self) {
+        let modified = crate::config_parser::modified_time();
+        if modified == self.config_modified {
+            return;
+        }
+
+        self.config_modified = modified;
+        self.issues = validate_config_file().unwrap_or_default();
+    }
+}
+
+/// Validate the configured config.yaml; None when it doesn't exist yet
+pub fn validate_config_file() -> Option<Vec<ConfigIssue>> {
+    let path = crate::commands::expand_tilde(&crate::constants::CONFIG_FILE_PATH).ok()?;
+    match std::fs::read_to_string(&path) {
+        Ok(yaml) => Some(validate(&yaml)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => Some(vec![ConfigIssue {
+            line: None,
+            message: format!("can't read {path}: {e}"),
+        }]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_config_has_no_issues() {
+        let yaml = r#"
+models:
+  "qwen":
+    cmd: >-
+      llama-server
+      --port 8902
+  llama:
+    cmd: llama-server --port=8903
+"#;
+        assert_eq!(validate(yaml), Vec::new());
+    }
+
+    #[test]
+    fn test_syntax_error_has_line() {
+        let issues = validate("models:\n  qwen:\n    cmd: [unclosed\n");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].line.is_some());
+        assert!(!issues[0].message.contains(" at line "));
+    }
+
+    #[test]
+    fn test_schema_issues_have_lines() {
+        let yaml = r#"models:
+  "qwen":
+    cmd: llama-server --port 8902
+  llama:
+    cmd: llama-server --port 8902
+  gemma:
+    proxy: http://127.0.0.1:9000
+  phi:
+    cmd:
+      - llama-server
+"#;
+        assert_eq!(
+            validate(yaml)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "line 5: llama: port 8902 is already used by qwen",
+                "line 6: gemma: missing `cmd`",
+                "line 9: phi: `cmd` must be a string",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_models() {
+        assert_eq!(
+            validate("healthCheckTimeout: 60\n")[0].message,
+            "no models are configured"
+        );
+        assert_eq!(
+            validate("models: []\n")[0].to_string(),
+            "line 1: `models` must be a map of model names to settings"
+        );
+    }
+}
@@ -2,7 +2,28 @@ use std::env;
 use std::sync::LazyLock;
 
 // Service configuration
-pub const LAUNCH_AGENT_LABEL: &str = "com.user.llama-swap"; // This one stays const as it's rarely changed
+pub const UI_CHECK_INTERVAL_SECS: u64 = 30;
+pub const DEFAULT_LAUNCH_AGENT_LABEL: &str = "com.user.llama-swap"; // Named instances append ".<name>"
+
+// Named instance managed by this plugin, e.g. "embeddings", for running several
+// llama-swap services side by side; unset (or "default") is the default instance.
+// Gives the launch agent, config.yaml, service log and state files their own names.
+pub static INSTANCE: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_INSTANCE")
+        .ok()
+        .map(|s| instance_name(&s))
+        .filter(|s| !s.is_empty() && s != "default")
+});
+
+pub static LAUNCH_AGENT_LABEL: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_LAUNCH_AGENT_LABEL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| match INSTANCE.as_deref() {
+            Some(name) => format!("{DEFAULT_LAUNCH_AGENT_LABEL}.{name}"),
+            None => DEFAULT_LAUNCH_AGENT_LABEL.to_string(),
+        })
+});
 
 // API configuration (configurable via env vars)
 pub static API_BASE_URL: LazyLock<String> = LazyLock::new(|| {
@@ -23,6 +44,26 @@ pub static API_TIMEOUT_SECS: LazyLock<u64> = LazyLock::new(|| {
         .unwrap_or(1)
 });
 
+// PEM bundle of extra CAs to trust, for an https base URL behind a proxy with a private CA
+pub static CA_CERT_PATH: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_CA_CERT_PATH")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// PEM client certificate for proxies that require mutual TLS (the key may be in the same file)
+pub static CLIENT_CERT_PATH: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_CLIENT_CERT_PATH")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+pub static CLIENT_KEY_PATH: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_CLIENT_KEY_PATH")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
 // Update timing (configurable via env vars)
 pub static STREAMING_MODE: LazyLock<bool> = LazyLock::new(|| {
     env::var("LLAMA_SWAP_STREAMING_MODE")
@@ -31,6 +72,253 @@ pub static STREAMING_MODE: LazyLock<bool> = LazyLock::new(|| {
         .unwrap_or(true)
 });
 
+// Long-term SQLite history (requires the `sqlite` cargo feature)
+#[cfg(feature = "sqlite")]
+pub static HISTORY_DB_ENABLED: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_HISTORY_DB")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false)
+});
+
+#[cfg(feature = "sqlite")]
+pub static HISTORY_DB_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_HISTORY_DB_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/history.db")
+    })
+});
+
+#[cfg(feature = "sqlite")]
+pub static HISTORY_DB_RETENTION_DAYS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_HISTORY_DB_RETENTION_DAYS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(7)
+});
+
+// OpenMetrics snapshot for node_exporter's textfile collector (unset disables)
+pub static OPENMETRICS_FILE_PATH: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_OPENMETRICS_FILE_PATH")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// Serve the same snapshot over HTTP for Prometheus to scrape, e.g. "127.0.0.1:9877" (unset disables)
+pub static EXPORTER_ADDR: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_EXPORTER_ADDR")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// StatsD agent to push per-model gauges to each poll, e.g. "127.0.0.1:8125" (unset disables)
+pub static STATSD_ADDR: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_STATSD_ADDR")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// Tag gauges with the model DogStatsD-style instead of putting it in the metric name
+pub static STATSD_DOGSTATSD: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_STATSD_DOGSTATSD")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false)
+});
+
+// MQTT broker for state and metrics (requires the `mqtt` cargo feature; unset disables)
+#[cfg(feature = "mqtt")]
+pub static MQTT_HOST: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_MQTT_HOST")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+#[cfg(feature = "mqtt")]
+pub static MQTT_PORT: LazyLock<u16> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_MQTT_PORT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1883)
+});
+
+#[cfg(feature = "mqtt")]
+pub static MQTT_TOPIC_PREFIX: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_MQTT_TOPIC_PREFIX").unwrap_or_else(|_| "llama-swap".to_string())
+});
+
+#[cfg(feature = "mqtt")]
+pub static MQTT_USERNAME: LazyLock<Option<String>> =
+    LazyLock::new(|| env::var("LLAMA_SWAP_MQTT_USERNAME").ok());
+
+#[cfg(feature = "mqtt")]
+pub static MQTT_PASSWORD: LazyLock<Option<String>> =
+    LazyLock::new(|| env::var("LLAMA_SWAP_MQTT_PASSWORD").ok());
+
+// Slack/Discord-compatible webhook that receives every alert (unset disables)
+pub static WEBHOOK_URL: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_WEBHOOK_URL")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// Minimum time between two alerts of the same kind (the webhook name predates native notifications)
+pub static ALERT_COOLDOWN_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ALERT_COOLDOWN_SECS")
+        .or_else(|_| env::var("LLAMA_SWAP_WEBHOOK_COOLDOWN_SECS"))
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(300)
+});
+
+// Per-kind cooldown overrides, e.g. "model_loaded:0,queue_backed_up:900"
+pub static ALERT_COOLDOWNS: LazyLock<String> =
+    LazyLock::new(|| env::var("LLAMA_SWAP_ALERT_COOLDOWNS").unwrap_or_default());
+
+// How far (percent) below its own baseline a busy model's generation speed must drop to alert (0 disables)
+pub static ALERT_TPS_REGRESSION_PERCENT: LazyLock<f64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ALERT_TPS_REGRESSION_PERCENT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30.0)
+});
+
+// Menu bar icon: the llama image, or an SF Symbol rendered by SwiftBar
+pub static ICON_STYLE: LazyLock<crate::icons::IconStyle> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ICON_STYLE")
+        .map(|s| crate::icons::IconStyle::from_env_value(&s))
+        .unwrap_or(crate::icons::IconStyle::Image)
+});
+
+// SF Symbol name for the symbol icon style, e.g. "brain" or "cpu"
+pub static ICON_SYMBOL: LazyLock<String> =
+    LazyLock::new(|| env::var("LLAMA_SWAP_ICON_SYMBOL").unwrap_or_else(|_| "brain".to_string()));
+
+// Custom menu bar icon PNG, replacing the bundled llama (the dark one defaults to the same file)
+pub static ICON_PATH: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ICON_PATH")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+pub static ICON_DARK_PATH: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ICON_DARK_PATH")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// Colour the status dot by Llama's share of system memory while models are loaded
+pub static DOT_MEMORY_GRADIENT: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_DOT_MEMORY_GRADIENT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false)
+});
+
+// Draw the number of loaded models on the menu bar icon
+pub static SHOW_MODEL_COUNT: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_SHOW_MODEL_COUNT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(true)
+});
+
+// Local time windows in which alerts are only logged, e.g. "22:00-08:00" (empty disables)
+pub static QUIET_HOURS: LazyLock<String> =
+    LazyLock::new(|| env::var("LLAMA_SWAP_QUIET_HOURS").unwrap_or_default());
+
+// System memory usage (percent) that raises an alert
+pub static ALERT_MEMORY_THRESHOLD: LazyLock<f64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ALERT_MEMORY_THRESHOLD")
+        .or_else(|_| env::var("LLAMA_SWAP_WEBHOOK_MEMORY_THRESHOLD"))
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(90.0)
+});
+
+// Requests waiting on one model that raise an alert (0 disables)
+pub static ALERT_QUEUE_THRESHOLD: LazyLock<u32> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ALERT_QUEUE_THRESHOLD")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(5)
+});
+
+// Memory used by all llama-swap processes together, in GB, that raises an alert (unset disables)
+pub static ALERT_LLAMA_MEMORY_GB: LazyLock<Option<f64>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ALERT_LLAMA_MEMORY_GB")
+        .ok()
+        .and_then(|s| s.parse().ok())
+});
+
+// A model's p95 request latency, in milliseconds, that raises an alert (unset disables)
+pub static ALERT_LATENCY_P95_MS: LazyLock<Option<f64>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ALERT_LATENCY_P95_MS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+});
+
+// Generation speed below which a busy model raises an alert (unset disables)
+pub static ALERT_MIN_TPS: LazyLock<Option<f64>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ALERT_MIN_TPS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+});
+
+// Alert kinds posted to Notification Center, comma-separated or "all" (empty disables)
+pub static NOTIFICATIONS: LazyLock<String> =
+    LazyLock::new(|| env::var("LLAMA_SWAP_NOTIFICATIONS").unwrap_or_default());
+
+// Automatic recovery policies, e.g. "api_unresponsive:120:restart:2" (see policies.rs)
+pub static RECOVERY_POLICIES: LazyLock<String> =
+    LazyLock::new(|| env::var("LLAMA_SWAP_RECOVERY_POLICIES").unwrap_or_default());
+
+// Restart the service when it exits while it was meant to be running
+pub static WATCHDOG: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_WATCHDOG")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(true)
+});
+
+// Most watchdog restarts in any hour before it leaves a crashing service down
+pub static WATCHDOG_MAX_RESTARTS: LazyLock<usize> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_WATCHDOG_MAX_RESTARTS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(3)
+});
+
+// Script run on every agent/display state transition (see hooks.rs; unset disables)
+pub static HOOK_SCRIPT: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_HOOK_SCRIPT")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// Longest polling interval while the API keeps failing (see PollingMode::Backoff)
+pub static MAX_BACKOFF_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_MAX_BACKOFF_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(60)
+});
+
+// Power draw via powermetrics (needs a passwordless sudo rule for it)
+pub static POWER_METRICS_ENABLED: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_POWER_METRICS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false)
+});
+
+// Access control (configurable via env vars)
+pub static READ_ONLY_MODE: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_READ_ONLY")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false)
+});
+
 // Chart configuration (configurable via env vars)
 pub static CHART_WIDTH: LazyLock<u32> = LazyLock::new(|| {
     env::var("LLAMA_SWAP_CHART_WIDTH")
@@ -46,28 +334,200 @@ pub static CHART_HEIGHT: LazyLock<u32> = LazyLock::new(|| {
         .unwrap_or(20)
 });
 
+pub static HISTORY_RETENTION_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_HISTORY_RETENTION_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|&secs| secs > 0)
+        .unwrap_or(300) // 5 minutes
+});
+
+// Samples older than the retention window are averaged into coarse buckets
+// instead of being dropped (0 disables downsampling)
+pub static DOWNSAMPLE_RETENTION_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_DOWNSAMPLE_RETENTION_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(3600) // 1 hour
+});
+
+pub static DOWNSAMPLE_BUCKET_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_DOWNSAMPLE_BUCKET_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|&secs| secs > 0)
+        .unwrap_or(30)
+});
+
 pub static HISTORY_SIZE: LazyLock<usize> = LazyLock::new(|| {
     env::var("LLAMA_SWAP_HISTORY_SIZE")
         .ok()
         .and_then(|s| s.parse().ok())
-        .unwrap_or(300) // 5 minutes at 1-second intervals
+        .unwrap_or(*HISTORY_RETENTION_SECS as usize) // Retention window at 1-second intervals
 });
 
 // File paths (configurable via env vars, using home directory expansion)
 pub static LOG_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
     env::var("LLAMA_SWAP_LOG_FILE_PATH")
-        .unwrap_or_else(|_| "~/Library/Logs/LlamaSwap.log".to_string())
+        .unwrap_or_else(|_| instance_path("~/Library/Logs/LlamaSwap.log"))
+});
+
+// Plugin's own logs (configurable via env vars)
+pub static PLUGIN_LOG_FORMAT: LazyLock<crate::logging::LogFormat> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_PLUGIN_LOG_FORMAT")
+        .map(|s| crate::logging::LogFormat::from_env_value(&s))
+        .unwrap_or(crate::logging::LogFormat::Text)
+});
+
+pub static PLUGIN_LOG_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_PLUGIN_LOG_FILE_PATH")
+        .unwrap_or_else(|_| "~/Library/Logs/LlamaSwapSwiftBar.log".to_string())
+});
+
+// Identical log lines within this window are collapsed into a "repeated N times" summary
+pub static LOG_DEDUP_WINDOW_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_LOG_DEDUP_WINDOW_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(60) // 0 disables deduplication
+});
+
+pub static LOG_RATE_LIMIT_PER_MIN: LazyLock<usize> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_LOG_RATE_LIMIT_PER_MIN")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(120) // 0 disables rate limiting
+});
+
+pub static AUDIT_LOG_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_AUDIT_LOG_FILE_PATH")
+        .unwrap_or_else(|_| "~/Library/Logs/LlamaSwapSwiftBar-audit.log".to_string())
 });
 
 pub static CONFIG_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
     env::var("LLAMA_SWAP_CONFIG_FILE_PATH")
-        .unwrap_or_else(|_| "~/.llamaswap/config.yaml".to_string())
+        .unwrap_or_else(|_| instance_path("~/.llamaswap/config.yaml"))
+});
+
+// App that "Open in Editor" uses for the config, e.g. "Visual Studio Code"
+// (unset uses $EDITOR in Terminal, then the default app for YAML files)
+pub static EDITOR_APP: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_EDITOR_APP")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// Metrics persistence (configurable via env vars)
+pub static METRICS_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_METRICS_FILE_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/metrics_history.json")
+    })
+});
+
+pub static SESSIONS_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_SESSIONS_FILE_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/sessions.json")
+    })
+});
+
+// Optional JSON file for settings that don't fit in an environment variable
+pub static PLUGIN_CONFIG_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_PLUGIN_CONFIG_FILE_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/config.json")
+    })
+});
+
+pub static EVENTS_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_EVENTS_FILE_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/events.json")
+    })
+});
+
+// Hardware summary detected on first run
+pub static HARDWARE_CACHE_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_HARDWARE_CACHE_FILE_PATH").unwrap_or_else(|_| {
+        "~/Library/Application Support/llama-swap-swiftbar/hardware.json".to_string()
+    })
+});
+
+// Check GitHub for a newer llama-swap release every six hours
+pub static UPDATE_CHECK: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_UPDATE_CHECK")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(true)
+});
+
+// Result of the last update check
+pub static UPDATE_CACHE_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_UPDATE_CACHE_FILE_PATH").unwrap_or_else(|_| {
+        "~/Library/Application Support/llama-swap-swiftbar/update-check.json".to_string()
+    })
+});
+
+// Timestamped copies of config.yaml, made by backup_config and before edits
+pub static CONFIG_BACKUP_DIR: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_CONFIG_BACKUP_DIR").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/config-backups")
+    })
+});
+
+// Pidfile used to keep a single streaming instance running
+pub static PID_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_PID_FILE_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/streaming.pid")
+    })
+});
+
+// Written by start/restart so the streaming instance can time out a start that never comes up
+pub static STARTUP_MARKER_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_STARTUP_MARKER_FILE_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/startup")
+    })
+});
+
+// Present while the user wants the service running (set by Start/Restart, cleared by Stop)
+pub static WANTED_RUNNING_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_WANTED_RUNNING_FILE_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/wanted-running")
+    })
+});
+
+// RunAtLoad and KeepAlive choices for the generated plist, set from the Install Options menu
+pub static INSTALL_OPTIONS_FILE_PATH: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_INSTALL_OPTIONS_FILE_PATH").unwrap_or_else(|_| {
+        instance_path("~/Library/Application Support/llama-swap-swiftbar/install-options.json")
+    })
+});
+
+// A started service whose API hasn't answered within this window is reported as failed
+pub static STARTUP_TIMEOUT_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_STARTUP_TIMEOUT_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30)
+});
+
+// Destination for "Export Metrics…"
+pub static EXPORT_DIR: LazyLock<String> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_EXPORT_DIR").unwrap_or_else(|_| "~/Downloads".to_string())
+});
+
+pub static PERSIST_INTERVAL_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_PERSIST_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30) // 0 disables periodic checkpoints
 });
 
 pub const COLOR_TPS_LINE: (u8, u8, u8) = (0, 255, 127); // Spring green - Generation speed
 pub const COLOR_PROMPT_LINE: (u8, u8, u8) = (255, 215, 0); // Gold - Prompt speed
 pub const COLOR_MEM_LINE: (u8, u8, u8) = (0, 191, 255); // Deep sky blue - Memory
 pub const COLOR_QUEUE_LINE: (u8, u8, u8) = (255, 99, 71); // Tomato - Queue size
+pub const COLOR_GPU_LINE: (u8, u8, u8) = (218, 112, 214); // Orchid - GPU utilization
+pub const COLOR_KV_CACHE_LINE: (u8, u8, u8) = (147, 112, 219); // Medium purple - KV cache usage
+pub const COLOR_LATENCY_LINE: (u8, u8, u8) = (255, 140, 0); // Dark orange - Request latency
+pub const COLOR_POWER_LINE: (u8, u8, u8) = (255, 69, 0); // Orange red - Package power
 
 // Program state color palette (RGB)
 pub const COLOR_BLUE: (u8, u8, u8) = (0, 122, 255); // Processing/Active
@@ -75,6 +535,7 @@ pub const COLOR_GREEN: (u8, u8, u8) = (52, 199, 89); // Ready/Success
 pub const COLOR_YELLOW: (u8, u8, u8) = (255, 255, 0); // Loading/Starting
 pub const COLOR_GREY: (u8, u8, u8) = (142, 142, 147); // Idle/No Model
 pub const COLOR_RED: (u8, u8, u8) = (255, 59, 48); // Error/Not Loaded
+pub const COLOR_PURPLE: (u8, u8, u8) = (175, 82, 222); // Degraded/Unresponsive
 
 // Semantic color mappings for program states
 pub const COLOR_PROCESSING_QUEUE: (u8, u8, u8) = COLOR_BLUE;
@@ -82,9 +543,228 @@ pub const COLOR_MODEL_READY: (u8, u8, u8) = COLOR_GREEN;
 pub const COLOR_MODEL_LOADING: (u8, u8, u8) = COLOR_YELLOW;
 pub const COLOR_SERVICE_NO_MODEL: (u8, u8, u8) = COLOR_GREY;
 pub const COLOR_SERVICE_STOPPED: (u8, u8, u8) = COLOR_RED;
+pub const COLOR_SERVICE_DEGRADED: (u8, u8, u8) = COLOR_PURPLE;
 pub const COLOR_AGENT_STARTING: (u8, u8, u8) = COLOR_YELLOW;
 pub const COLOR_AGENT_NOT_LOADED: (u8, u8, u8) = COLOR_RED;
 
+pub const COLOR_ERROR_BADGE: (u8, u8, u8) = (255, 149, 0); // Orange - Persistent API errors
+pub const COLOR_WARNING_BADGE: (u8, u8, u8) = (255, 204, 0); // Yellow - Alert threshold exceeded
+
+// Okabe–Ito colours, distinguishable with the common forms of colour blindness
+pub const CB_BLUE: (u8, u8, u8) = (0, 114, 178);
+pub const CB_BLUISH_GREEN: (u8, u8, u8) = (0, 158, 115);
+pub const CB_ORANGE: (u8, u8, u8) = (230, 159, 0);
+pub const CB_SKY_BLUE: (u8, u8, u8) = (86, 180, 233);
+pub const CB_VERMILLION: (u8, u8, u8) = (213, 94, 0);
+pub const CB_REDDISH_PURPLE: (u8, u8, u8) = (204, 121, 167);
+pub const CB_YELLOW: (u8, u8, u8) = (240, 228, 66);
+
+/// State, badge and chart line colours (RGB)
+#[derive(Debug)]
+pub struct Palette {
+    pub processing_queue: (u8, u8, u8),
+    pub model_ready: (u8, u8, u8),
+    pub model_loading: (u8, u8, u8),
+    pub service_no_model: (u8, u8, u8),
+    pub service_stopped: (u8, u8, u8),
+    pub service_degraded: (u8, u8, u8),
+    pub agent_starting: (u8, u8, u8),
+    pub agent_not_loaded: (u8, u8, u8),
+    pub error_badge: (u8, u8, u8),
+    pub warning_badge: (u8, u8, u8),
+    pub tps_line: (u8, u8, u8),
+    pub prompt_line: (u8, u8, u8),
+    pub mem_line: (u8, u8, u8),
+    pub queue_line: (u8, u8, u8),
+    pub gpu_line: (u8, u8, u8),
+    pub kv_cache_line: (u8, u8, u8),
+    pub latency_line: (u8, u8, u8),
+    pub power_line: (u8, u8, u8),
+}
+
+pub const DEFAULT_PALETTE: Palette = Palette {
+    processing_queue: COLOR_PROCESSING_QUEUE,
+    model_ready: COLOR_MODEL_READY,
+    model_loading: COLOR_MODEL_LOADING,
+    service_no_model: COLOR_SERVICE_NO_MODEL,
+    service_stopped: COLOR_SERVICE_STOPPED,
+    service_degraded: COLOR_SERVICE_DEGRADED,
+    agent_starting: COLOR_AGENT_STARTING,
+    agent_not_loaded: COLOR_AGENT_NOT_LOADED,
+    error_badge: COLOR_ERROR_BADGE,
+    warning_badge: COLOR_WARNING_BADGE,
+    tps_line: COLOR_TPS_LINE,
+    prompt_line: COLOR_PROMPT_LINE,
+    mem_line: COLOR_MEM_LINE,
+    queue_line: COLOR_QUEUE_LINE,
+    gpu_line: COLOR_GPU_LINE,
+    kv_cache_line: COLOR_KV_CACHE_LINE,
+    latency_line: COLOR_LATENCY_LINE,
+    power_line: COLOR_POWER_LINE,
+};
+
+// Red/green/blue states become vermillion/bluish green/blue, so "ready" and
+// "stopped" no longer differ by hue alone
+pub const COLOR_BLIND_PALETTE: Palette = Palette {
+    processing_queue: CB_BLUE,
+    model_ready: CB_BLUISH_GREEN,
+    model_loading: CB_ORANGE,
+    service_no_model: COLOR_GREY,
+    service_stopped: CB_VERMILLION,
+    service_degraded: CB_REDDISH_PURPLE,
+    agent_starting: CB_ORANGE,
+    agent_not_loaded: CB_VERMILLION,
+    error_badge: CB_VERMILLION,
+    warning_badge: CB_YELLOW,
+    tps_line: CB_BLUISH_GREEN,
+    prompt_line: CB_YELLOW,
+    mem_line: CB_SKY_BLUE,
+    queue_line: CB_VERMILLION,
+    gpu_line: CB_REDDISH_PURPLE,
+    kv_cache_line: CB_BLUE,
+    latency_line: CB_ORANGE,
+    power_line: CB_VERMILLION,
+};
+
+// Colour-blind-safe palette for the icon, menu and charts
+pub static COLOR_BLIND: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_COLOR_BLIND_PALETTE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false)
+});
+
+pub static PALETTE: LazyLock<&'static Palette> = LazyLock::new(|| {
+    if *COLOR_BLIND {
+        &COLOR_BLIND_PALETTE
+    } else {
+        &DEFAULT_PALETTE
+    }
+});
+
 // Icon configuration
-pub const STATUS_DOT_SIZE: u32 = 10;
-pub const STATUS_DOT_OFFSET: u32 = 1; // From bottom-right corner
+pub static STATUS_DOT_SIZE: LazyLock<u32> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_STATUS_DOT_SIZE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map_or(10, |size: u32| size.clamp(5, 24))
+});
+
+pub static STATUS_DOT_OFFSET: LazyLock<u32> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_STATUS_DOT_OFFSET")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map_or(1, |offset: u32| offset.min(8)) // From the dot's corner
+});
+
+// tl, tr, bl or br; the model count and badge move aside if the dot takes their corner
+pub static STATUS_DOT_CORNER: LazyLock<crate::icons::Corner> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_STATUS_DOT_CORNER")
+        .map(|s| crate::icons::Corner::from_env_value(&s))
+        .unwrap_or(crate::icons::Corner::BottomRight)
+});
+
+pub static STATUS_DOT_STYLE: LazyLock<crate::icons::DotStyle> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_STATUS_DOT_STYLE")
+        .map(|s| crate::icons::DotStyle::from_env_value(&s))
+        .unwrap_or(crate::icons::DotStyle::Filled)
+});
+pub const ERROR_BADGE_SIZE: u32 = 16;
+pub const ERROR_BADGE_OFFSET: u32 = 1; // From top-right corner
+pub const MODEL_COUNT_SCALE: u32 = 3; // Pixels per font pixel of the 3×5 digits
+pub const MODEL_COUNT_OFFSET: u32 = 1; // From bottom-left corner
+
+// Hysteresis between "Model ready" and "Processing queue" to stop the icon flickering
+pub static DISPLAY_DEBOUNCE_POLLS: LazyLock<usize> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_DISPLAY_DEBOUNCE_POLLS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(2) // 0 or 1 switches on the first poll
+});
+
+pub static DISPLAY_MIN_DWELL_SECS: LazyLock<u64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_DISPLAY_MIN_DWELL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(3)
+});
+
+// Consecutive API errors before the error badge is drawn on the menu bar icon
+pub static ERROR_BADGE_THRESHOLD: LazyLock<usize> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ERROR_BADGE_THRESHOLD")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(5)
+});
+
+// Blink the error badge on alternate frames while the plugin is failing (streaming mode)
+pub static FLASH_ERROR_ICON: LazyLock<bool> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_FLASH_ERROR_ICON")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false)
+});
+
+// Oldest llama-swap version that doesn't get a warning under Advanced, e.g. "140" (unset disables)
+pub static MIN_LLAMA_SWAP_VERSION: LazyLock<Option<String>> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_MIN_VERSION")
+        .ok()
+        .filter(|s| !s.is_empty())
+});
+
+// Per-model upstream error rate (percent of polls) above which it's shown in red
+pub static UPSTREAM_ERROR_RATE_THRESHOLD: LazyLock<f64> = LazyLock::new(|| {
+    env::var("LLAMA_SWAP_ERROR_RATE_THRESHOLD")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(5.0)
+});
+
+/// Keep only the characters that are safe in a launchd label and a file name
+pub fn instance_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        .collect()
+}
+
+/// A default path, given the instance's name so instances don't share files:
+/// "~/.llamaswap/config.yaml" is "~/.llamaswap/config-embeddings.yaml" for
+/// the "embeddings" instance, and unchanged for the default one
+fn instance_path(path: &str) -> String {
+    match INSTANCE.as_deref() {
+        Some(name) => path_for_instance(path, name),
+        None => path.to_string(),
+    }
+}
+
+fn path_for_instance(path: &str, name: &str) -> String {
+    let (dir, file) = path.rsplit_once('/').unwrap_or(("", path));
+    let file = match file.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{stem}-{name}.{ext}"),
+        _ => format!("{file}-{name}"),
+    };
+    if dir.is_empty() {
+        file
+    } else {
+        format!("{dir}/{file}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_for_instance() {
+        assert_eq!(
+            path_for_instance("~/.llamaswap/config.yaml", "embeddings"),
+            "~/.llamaswap/config-embeddings.yaml"
+        );
+        assert_eq!(
+            path_for_instance("~/Library/Application Support/x/startup", "chat"),
+            "~/Library/Application Support/x/startup-chat"
+        );
+        assert_eq!(instance_name(" chat 2/../"), "chat2");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::types::PluginState;
+use std::fmt::Write;
+use std::process::Command;
+
+/// Service log lines included in the bundle
+const LOG_LINES: usize = 50;
+
+/// Everything a bug report against llama-swap or the plugin usually needs, as Markdown
+///
+/// State, service checks, versions, current metrics, the LaunchAgent plist and
+/// the end of the service log.
+pub fn bundle(state: &PluginState) -> String {
+    let mut out = String::new();
+    let display_state = state.get_display_state();
+    let status = &state.service_status;
+
+    let _ = writeln!(out, "# Llama-Swap Diagnostics");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "Generated {}",
+        crate::logging::format_rfc3339(std::time::SystemTime::now())
+    );
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Versions");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "- Plugin: {}", env!("CARGO_PKG_VERSION"));
+    let llama_swap = state
+        .versions
+        .llama_swap
+        .clone()
+        .or_else(crate::versions::llama_swap_version);
+    let _ = writeln!(
+        out,
+        "- llama-swap: {}",
+        llama_swap.as_deref().unwrap_or("not found")
+    );
+    if let Some(ref build) = state.versions.llama_cpp {
+        let _ = writeln!(out, "- llama.cpp: {build}");
+    }
+    let macos = command_output("sw_vers", &["-productVersion"]);
+    let _ = writeln!(out, "- macOS: {}", macos.as_deref().unwrap_or("unknown"));
+    if let Some(ref hardware) = state.hardware {
+        let _ = writeln!(
+            out,
+            "- Hardware: {}, {} cores, {:.0} GB",
+            hardware.chip,
+            hardware.total_cores,
+            hardware.memory_gb()
+        );
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## State");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "- Display: {display_state:?} ({})",
+        display_state.status_message()
+    );
+    let _ = writeln!(out, "- Agent: {}", state.agent_state.name());
+    let _ = writeln!(out, "- Service: {}", status.status_description());
+    let _ = writeln!(out, "- Plist installed: {}", status.plist_installed);
+    let _ = writeln!(out, "- launchctl loaded: {}", status.launchctl_loaded);
+    let _ = writeln!(out, "- Process running: {}", status.process_running);
+    let _ = writeln!(out, "- API responsive: {}", status.api_responsive);
+    let _ = writeln!(out, "- Web UI available: {}", status.ui_available);
+    if let Some(pid) = status.pid {
+        let _ = writeln!(out, "- PID: {pid}");
+    }
+    let _ = writeln!(out, "- API endpoint: {}", crate::commands::api_endpoint());
+    if let Some(launchd) = state.launchd.details() {
+        let _ = writeln!(out, "- {}", launchd.summary());
+    }
+    let options = crate::install_options::InstallOptions::load();
+    let _ = writeln!(
+        out,
+        "- Install options: run at load {}, keep alive {}, priority {}",
+        options.run_at_load,
+        options.keep_alive.as_str(),
+        options.priority_summary()
+    );
+    if let Some(ref problem) = state.port_problem {
+        let _ = writeln!(
+            out,
+            "- Port: {}",
+            problem.message(*crate::constants::API_PORT)
+        );
+    }
+    let _ = writeln!(out, "- Consecutive errors: {}", state.error_count);
+
+    let configured = state.configured_models.names();
+    if !configured.is_empty() {
+        let _ = writeln!(out, "- Configured models: {}", configured.join(", "));
+    }
+    for warning in state.binary_checker.warnings() {
+        let _ = writeln!(out, "- {}: {}", warning.model, warning.message());
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Metrics");
+    let _ = writeln!(out);
+    code_block(&mut out, "", crate::text::render(state).trim_end());
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## LaunchAgent");
+    let _ = writeln!(out);
+    let plist = crate::commands::get_plist_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok());
+    match plist {
+        Some(plist) => code_block(&mut out, "xml", plist.trim_end()),
+        None => {
+            let _ = writeln!(out, "Not installed");
+        }
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Service Log");
+    let _ = writeln!(out);
+    let lines = crate::service::tail_service_log(LOG_LINES);
+    if lines.is_empty() {
+        let _ = writeln!(
+            out,
+            "Empty or missing: {}",
+            *crate::constants::LOG_FILE_PATH
+        );
+    } else {
+        code_block(&mut out, "", &lines.join("\n"));
+    }
+
+    out
+}
+
+/// Fenced code block, with a longer fence when the contents contain one
+fn code_block(out: &mut String, lang: &str, contents: &str) {
+    let fence = if contents.contains("```") {
+        "````"
+    } else {
+        "```"
+    };
+    let _ = writeln!(out, "{fence}{lang}");
+    let _ = writeln!(out, "{contents}");
+    let _ = writeln!(out, "{fence}");
+}
+
+/// First line of a command's output, or None if it fails
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?.trim();
+    (!line.is_empty()).then(|| line.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_has_each_section() {
+        let state = PluginState::new().unwrap();
+        let bundle = bundle(&state);
+
+        assert!(bundle.starts_with("# Llama-Swap Diagnostics\n"));
+        for section in [
+            "## Versions",
+            "## State",
+            "## Metrics",
+            "## LaunchAgent",
+            "## Service Log",
+        ] {
+            assert!(bundle.contains(section), "missing {section}");
+        }
+        assert!(bundle.contains("- API responsive: false"));
+    }
+
+    #[test]
+    fn test_code_block_fence_outlasts_contents() {
+        let mut out = String::new();
+        code_block(&mut out, "", "a ``` b");
+        assert_eq!(out, "````\na ``` b\n````\n");
+    }
+}
@@ -0,0 +1,176 @@
+use crate::types::error_helpers::{with_context, PARSE_JSON};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+/// Number of transitions kept on disk
+const EVENTS_KEPT: usize = 500;
+
+/// Number of display and polling transitions kept in memory
+const TRANSIENT_EVENTS_KEPT: usize = 20;
+
+/// Which state machine a transition belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Agent,   // AgentState (service lifecycle)
+    Display, // DisplayState (what the menu bar icon shows)
+    Polling, // PollingMode
+    Model,   // A model's ModelStateMachine
+    Alert,   // An alert raised (from is empty, to is the message)
+}
+
+impl EventKind {
+    /// Display and polling changes come with every burst of requests; writing
+    /// them to disk would push the rarer lifecycle transitions out of the log
+    pub fn is_persisted(self) -> bool {
+        !matches!(self, EventKind::Display | EventKind::Polling)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EventKind::Agent => "Agent",
+            EventKind::Display => "Display",
+            EventKind::Polling => "Polling",
+            EventKind::Model => "Model",
+            EventKind::Alert => "Alert",
+        }
+    }
+}
+
+/// One state transition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateEvent {
+    pub timestamp: u64,
+    pub kind: EventKind,
+    pub from: String,
+    pub to: String,
+    // What made the transition happen (absent in logs written before it was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Rolling log of state transitions
+///
+/// Agent, model and alert events are kept across plugin restarts. They are
+/// rare, so the whole log is rewritten (atomically) each time one is recorded;
+/// that keeps the file readable even after a crash. Display and polling
+/// transitions only go to a short in-memory ring.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EventLog {
+    pub events: VecDeque<StateEvent>, // Oldest first
+    #[serde(skip)]
+    pub transient: VecDeque<StateEvent>, // Display and polling, oldest first
+}
+
+impl EventLog {
+    /// Append a transition and drop the oldest entries beyond the retention
+    /// limit; returns whether the on-disk log needs saving
+    pub fn record(
+        &mut self,
+        timestamp: u64,
+        kind: EventKind,
+        from: &str,
+        to: &str,
+        reason: Option<&str>,
+    ) -> bool {
+        let (events, kept) = if kind.is_persisted() {
+            (&mut self.events, EVENTS_KEPT)
+        } else {
+            (&mut self.transient, TRANSIENT_EVENTS_KEPT)
+        };
+        events.push_back(StateEvent {
+            timestamp,
+            kind,
+            from: from.to_string(),
+            to: to.to_string(),
+            reason: reason.map(str::to_string),
+        });
+        while events.len() > kept {
+            events.pop_front();
+        }
+        kind.is_persisted()
+    }
+
+    /// Most recent agent, model and alert events, newest first
+    pub fn recent(&self, limit: usize) -> impl Iterator<Item = &StateEvent> {
+        self.events.iter().rev().take(limit)
+    }
+
+    /// Most recent display and polling transitions of this run, newest first
+    pub fn recent_transient(&self, limit: usize) -> impl Iterator<Item = &StateEvent> {
+        self.transient.iter().rev().take(limit)
+    }
+
+    /// Most recent transition of `kind` into state `to`
+    pub fn last_transition_to(&self, kind: EventKind, to: &str) -> Option<&StateEvent> {
+        self.events
+            .iter()
+            .rev()
+            .find(|event| event.kind == kind && event.to == to)
+    }
+
+    pub fn load() -> crate::Result<Self> {
+        let path = events_file_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let json = with_context(std::fs::read_to_string(&path), "Failed to read event log")?;
+        let mut log: Self = with_context(serde_json::from_str(&json), PARSE_JSON)?;
+        // Logs written before display and polling changes were kept in memory
+        log.events.retain(|event| event.kind.is_persisted());
+        Ok(log)
+    }
+
+    pub fn save(&self) -> crate::Result<()> {
+        let path = events_file_path()?;
+        let json = with_context(serde_json::to_string(self), "Failed to serialize event log")?;
+        crate::persistence::write_atomic(&path, json.as_bytes())
+    }
+}
+
+fn events_file_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::EVENTS_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_keeps_most_recent_events() {
+        let mut log = EventLog::default();
+        for i in 0..(EVENTS_KEPT as u64 + 10) {
+            assert!(log.record(i, EventKind::Agent, "Stopped", "Running", None));
+        }
+        // A day of request bursts doesn't push the lifecycle events out
+        for i in 0..1000 {
+            assert!(!log.record(
+                9_000 + i,
+                EventKind::Display,
+                "Model ready",
+                "Processing queue...",
+                Some("1 request processing"),
+            ));
+        }
+
+        assert_eq!(log.events.len(), EVENTS_KEPT);
+        assert_eq!(log.events.front().unwrap().timestamp, 10);
+        assert_eq!(log.transient.len(), TRANSIENT_EVENTS_KEPT);
+
+        let recent: Vec<_> = log.recent(2).collect();
+        assert_eq!(recent[0].kind, EventKind::Agent);
+        assert_eq!(recent[0].timestamp, EVENTS_KEPT as u64 + 9);
+        let transient: Vec<_> = log.recent_transient(1).collect();
+        assert_eq!(transient[0].timestamp, 9_999);
+        assert_eq!(transient[0].reason.as_deref(), Some("1 request processing"));
+
+        let started = log.last_transition_to(EventKind::Agent, "Running").unwrap();
+        assert_eq!(started.timestamp, EVENTS_KEPT as u64 + 9);
+        assert!(log
+            .last_transition_to(EventKind::Agent, "Degraded")
+            .is_none());
+    }
+}
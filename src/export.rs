@@ -0,0 +1,281 @@
+use crate::models::{AllMetricsHistory, TimestampedValue};
+use crate::types::error_helpers::{with_context, CREATE_DIR, CREATE_FILE};
+use circular_queue::CircularQueue;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// File format for "Export Metrics…"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// One sample in an export, flattened so CSV and JSON share the same shape
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ExportRow<'a> {
+    pub timestamp: u64,
+    pub series: &'static str,
+    pub model: Option<&'a str>,
+    pub value: f64,
+}
+
+/// Flatten the history into rows, oldest first within each series
+pub fn rows(history: &AllMetricsHistory) -> Vec<ExportRow<'_>> {
+    let mut rows = Vec::new();
+
+    let system: [(&'static str, &CircularQueue<TimestampedValue>); 9] = [
+        ("cpu_usage_percent", &history.cpu_usage_percent),
+        ("memory_usage_percent", &history.memory_usage_percent),
+        ("used_memory_gb", &history.used_memory_gb),
+        ("total_llama_memory_mb", &history.total_llama_memory_mb),
+        ("gpu_usage_percent", &history.gpu_usage_percent),
+        ("gpu_memory_mb", &history.gpu_memory_mb),
+        ("power_watts", &history.power_watts),
+        ("swap_used_gb", &history.swap_used_gb),
+        ("memory_pressure", &history.memory_pressure),
+    ];
+    for (series, data) in system {
+        push_series(&mut rows, series, None, data);
+    }
+
+    let mut model_names: Vec<&String> = history.models.keys().collect();
+    model_names.sort();
+    for name in model_names {
+        let model = &history.models[name];
+        let series: [(&'static str, &CircularQueue<TimestampedValue>); 9] = [
+            ("tps", &model.tps),
+            ("prompt_tps", &model.prompt_tps),
+            ("memory_mb", &model.memory_mb),
+            ("queue_size", &model.queue_size),
+            ("kv_cache_percent", &model.kv_cache_percent),
+            ("latency_p95_ms", &model.latency_p95_ms),
+            ("upstream_errors", &model.upstream_errors),
+            ("throughput_tps", &model.throughput_tps),
+            ("prompt_throughput_tps", &model.prompt_throughput_tps),
+        ];
+        for (series, data) in series {
+            push_series(&mut rows, series, Some(name), data);
+        }
+    }
+
+    rows
+}
+
+/// Look up one series by its export name (`model` is required for per-model series)
+pub fn find_series<'a>(
+    history: &'a AllMetricsHistory,
+    series: &str,
+    model: Option<&str>,
+) -> Option<&'a CircularQueue<TimestampedValue>> {
+    match (series, model) {
+        ("cpu_usage_percent", None) => Some(&history.cpu_usage_percent),
+        ("memory_usage_percent", None) => Some(&history.memory_usage_percent),
+        ("used_memory_gb", None) => Some(&history.used_memory_gb),
+        ("total_llama_memory_mb", None) => Some(&history.total_llama_memory_mb),
+        ("gpu_usage_percent", None) => Some(&history.gpu_usage_percent),
+        ("gpu_memory_mb", None) => Some(&history.gpu_memory_mb),
+        ("power_watts", None) => Some(&history.power_watts),
+        ("swap_used_gb", None) => Some(&history.swap_used_gb),
+        ("memory_pressure", None) => Some(&history.memory_pressure),
+        (_, Some(model)) => {
+            let history = history.get_model_history(model)?;
+            match series {
+                "tps" => Some(&history.tps),
+                "prompt_tps" => Some(&history.prompt_tps),
+                "memory_mb" => Some(&history.memory_mb),
+                "queue_size" => Some(&history.queue_size),
+                "kv_cache_percent" => Some(&history.kv_cache_percent),
+                "latency_p95_ms" => Some(&history.latency_p95_ms),
+                "upstream_errors" => Some(&history.upstream_errors),
+                "throughput_tps" => Some(&history.throughput_tps),
+                "prompt_throughput_tps" => Some(&history.prompt_throughput_tps),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A single chart's data as CSV, oldest first, with a readable UTC time column
+pub fn series_to_csv(series: &str, data: &CircularQueue<TimestampedValue>) -> String {
+    let mut csv = format!("timestamp,time,{}\n", csv_field(series));
+    for v in data.iter().rev() {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(v.timestamp);
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            v.timestamp,
+            crate::logging::format_rfc3339(time),
+            v.value
+        ));
+    }
+    csv
+}
+
+pub fn to_csv(history: &AllMetricsHistory) -> String {
+    let mut csv = String::from("timestamp,series,model,value\n");
+    for row in rows(history) {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            row.timestamp,
+            row.series,
+            csv_field(row.model.unwrap_or_default()),
+            row.value
+        ));
+    }
+    csv
+}
+
+pub fn to_json(history: &AllMetricsHistory) -> crate::Result<String> {
+    with_context(
+        serde_json::to_string_pretty(&rows(history)),
+        "Failed to serialize metrics export",
+    )
+}
+
+/// Write the history to `dest`, or a timestamped file in the export directory,
+/// returning its path
+pub fn export_history(
+    history: &AllMetricsHistory,
+    format: ExportFormat,
+    dest: Option<&Path>,
+) -> crate::Result<PathBuf> {
+    let file_name = format!("llama-swap-metrics-{}.{}", file_stamp(), format.extension());
+    let path = output_path(dest, &file_name)?;
+
+    let contents = match format {
+        ExportFormat::Csv => to_csv(history),
+        ExportFormat::Json => to_json(history)?,
+    };
+    with_context(std::fs::write(&path, contents), CREATE_FILE)?;
+
+    Ok(path)
+}
+
+/// Where to write a file: `dest` itself, or `file_name` inside it when it is a
+/// directory (the export directory when no destination is given)
+pub fn output_path(dest: Option<&Path>, file_name: &str) -> crate::Result<PathBuf> {
+    let path = match dest {
+        Some(dest) if !dest.is_dir() => dest.to_path_buf(),
+        Some(dir) => dir.join(file_name),
+        None => {
+            let dir = PathBuf::from(crate::commands::expand_tilde(
+                &crate::constants::EXPORT_DIR,
+            )?);
+            dir.join(file_name)
+        }
+    };
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        with_context(std::fs::create_dir_all(dir), CREATE_DIR)?;
+    }
+    Ok(path)
+}
+
+/// The current UTC time for file names, e.g. "20240229T123456Z"
+pub fn file_stamp() -> String {
+    // "2024-02-29T12:34:56.789Z" -> "20240229T123456Z"
+    let stamp: String = crate::logging::format_rfc3339(SystemTime::now())[..19]
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    format!("{stamp}Z")
+}
+
+fn push_series<'a>(
+    rows: &mut Vec<ExportRow<'a>>,
+    series: &'static str,
+    model: Option<&'a str>,
+    data: &CircularQueue<TimestampedValue>,
+) {
+    rows.extend(data.iter().rev().map(|v| ExportRow {
+        timestamp: v.timestamp,
+        series,
+        model,
+        value: v.value,
+    }));
+}
+
+/// Quote a CSV field when it contains separators or quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Metrics;
+
+    #[test]
+    fn test_csv_export() {
+        let mut history = AllMetricsHistory::with_capacity(10);
+        history.cpu_usage_percent.push(TimestampedValue {
+            timestamp: 100,
+            value: 12.5,
+        });
+        history.cpu_usage_percent.push(TimestampedValue {
+            timestamp: 101,
+            value: 13.0,
+        });
+        history
+            .model_history_mut("org/model,q4")
+            .tps
+            .push(TimestampedValue {
+                timestamp: 101,
+                value: 40.0,
+            });
+
+        assert_eq!(
+            to_csv(&history),
+            "timestamp,series,model,value\n\
+             100,cpu_usage_percent,,12.5\n\
+             101,cpu_usage_percent,,13\n\
+             101,tps,\"org/model,q4\",40\n"
+        );
+    }
+
+    #[test]
+    fn test_series_csv() {
+        let mut history = AllMetricsHistory::with_capacity(10);
+        history
+            .model_history_mut("m")
+            .prompt_tps
+            .push(TimestampedValue {
+                timestamp: 60,
+                value: 512.5,
+            });
+
+        assert!(find_series(&history, "prompt_tps", None).is_none());
+        assert!(find_series(&history, "prompt_tps", Some("other")).is_none());
+        let data = find_series(&history, "prompt_tps", Some("m")).unwrap();
+        assert_eq!(
+            series_to_csv("prompt_tps", data),
+            "timestamp,time,prompt_tps\n60,1970-01-01T00:01:00.000Z,512.5\n"
+        );
+    }
+
+    #[test]
+    fn test_json_export() {
+        let mut history = AllMetricsHistory::with_capacity(10);
+        history.model_history_mut("m").push(&Metrics::default());
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&history).unwrap()).unwrap();
+        let rows = json.as_array().unwrap();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0]["series"], "tps");
+        assert_eq!(rows[0]["model"], "m");
+    }
+}
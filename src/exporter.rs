@@ -0,0 +1,166 @@
+use crate::logging;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long to wait before retrying a port another instance still holds
+const BIND_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A scraper that stops sending mid-request doesn't hold up the next one
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Serves the latest OpenMetrics snapshot on `GET /metrics`
+///
+/// The collector thread renders a snapshot each poll and hands it over with
+/// `update`; scrapes never wait on polling.
+#[derive(Debug)]
+pub struct Exporter {
+    snapshot: Arc<Mutex<String>>,
+}
+
+impl Exporter {
+    /// Listen on `addr` in the background
+    ///
+    /// When SwiftBar restarts the plugin the previous instance may still hold the
+    /// port for a moment, so binding is retried until it succeeds.
+    pub fn start(addr: &str) -> Self {
+        let snapshot = Arc::new(Mutex::new(String::new()));
+        let addr = addr.to_string();
+        let shared = Arc::clone(&snapshot);
+
+        std::thread::spawn(move || {
+            let mut warned = false;
+            loop {
+                match TcpListener::bind(&addr) {
+                    Ok(listener) => {
+                        logging::info(
+                            "exporter",
+                            "listening",
+                            &format!("Serving metrics on http://{addr}/metrics"),
+                        );
+                        serve(&listener, &shared);
+                        return;
+                    }
+                    Err(e) => {
+                        if !warned {
+                            logging::warn(
+                                "exporter",
+                                "bind_failed",
+                                &format!("Can't listen on {addr} yet, retrying: {e}"),
+                            );
+                            warned = true;
+                        }
+                        std::thread::sleep(BIND_RETRY_INTERVAL);
+                    }
+                }
+            }
+        });
+
+        Self { snapshot }
+    }
+
+    /// Replace the snapshot served to scrapers
+    pub fn update(&self, text: String) {
+        if let Ok(mut snapshot) = self.snapshot.lock() {
+            *snapshot = text;
+        }
+    }
+}
+
+fn serve(listener: &TcpListener, snapshot: &Mutex<String>) {
+    for stream in listener.incoming().flatten() {
+        if let Err(e) = handle(stream, snapshot) {
+            logging::debug(
+                "exporter",
+                "request_failed",
+                &format!("Failed to answer scrape: {e}"),
+            );
+        }
+    }
+}
+
+/// Answer one HTTP/1.x request and close the connection
+fn handle(stream: TcpStream, snapshot: &Mutex<String>) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers; nothing in them changes the response
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+
+    let body = snapshot.lock().map(|s| s.clone()).unwrap_or_default();
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") if body.is_empty() => (
+            "503 Service Unavailable",
+            "text/plain",
+            "No metrics collected yet\n".to_string(),
+        ),
+        ("GET", "/metrics") => ("200 OK", CONTENT_TYPE, body),
+        ("GET", _) => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn request(addr: std::net::SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serves_latest_snapshot() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let snapshot = Arc::new(Mutex::new(String::new()));
+        let exporter = Exporter {
+            snapshot: Arc::clone(&snapshot),
+        };
+        std::thread::spawn(move || serve(&listener, &snapshot));
+
+        let response = request(addr, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 503"));
+
+        exporter.update("llama_swap_up 1\n# EOF\n".to_string());
+        let response = request(addr, "GET /metrics?x=1 HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.ends_with("\r\n\r\nllama_swap_up 1\n# EOF\n"));
+
+        let response = request(addr, "GET / HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 404"));
+        let response = request(addr, "POST /metrics HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 405"));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::types::error_helpers::with_context;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Sanity limits so a corrupt header can't ask for gigabytes
+const MAX_KV_COUNT: u64 = 1 << 20;
+const MAX_STRING_BYTES: u64 = 16 << 20;
+
+/// The parts of a GGUF file's metadata that shape a llama-server command
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GgufInfo {
+    pub name: Option<String>,         // general.name
+    pub architecture: Option<String>, // general.architecture, e.g. "qwen3"
+    pub context_length: Option<u64>,  // <arch>.context_length, the trained context
+    pub block_count: Option<u64>,     // <arch>.block_count, the layer count
+}
+
+/// Read the metadata key-values at the start of a GGUF file
+///
+/// Only the header is read; tensor data is never touched.
+pub fn read_info(path: &Path) -> crate::Result<GgufInfo> {
+    let file = with_context(std::fs::File::open(path), "Failed to open GGUF file")?;
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    with_context(reader.read_exact(&mut magic), "Failed to read GGUF header")?;
+    if &magic != b"GGUF" {
+        return Err(format!("{} is not a GGUF file", path.display()).into());
+    }
+    let version = read_u32(&mut reader)?;
+    if version < 2 {
+        return Err(format!("GGUF version {version} is not supported").into());
+    }
+    let _tensor_count = read_u64(&mut reader)?;
+    let kv_count = read_u64(&mut reader)?;
+    if kv_count > MAX_KV_COUNT {
+        return Err("GGUF header is corrupt".into());
+    }
+
+    // Architecture-prefixed keys may come before general.architecture
+    let mut info = GgufInfo::default();
+    let mut numbers = Vec::new();
+    for _ in 0..kv_count {
+        let key = read_string(&mut reader)?;
+        let value_type = read_u32(&mut reader)?;
+        match (key.as_str(), value_type) {
+            ("general.name", STRING) => info.name = Some(read_string(&mut reader)?),
+            ("general.architecture", STRING) => {
+                info.architecture = Some(read_string(&mut reader)?);
+            }
+            (key, _) if key.ends_with(".context_length") || key.ends_with(".block_count") => {
+                match read_integer(&mut reader, value_type)? {
+                    Some(number) => numbers.push((key.to_string(), number)),
+                    None => skip_value(&mut reader, value_type)?,
+                }
+            }
+            _ => skip_value(&mut reader, value_type)?,
+        }
+    }
+
+    if let Some(ref arch) = info.architecture {
+        for (key, number) in numbers {
+            match key.strip_prefix(arch.as_str()) {
+                Some(".context_length") => info.context_length = Some(number),
+                Some(".block_count") => info.block_count = Some(number),
+                _ => {}
+            }
+        }
+    }
+    Ok(info)
+}
+
+// GGUF metadata value types
+const STRING: u32 = 8;
+const ARRAY: u32 = 9;
+
+/// Size in bytes of a fixed-size value type
+fn fixed_size(value_type: u32) -> Option<u64> {
+    match value_type {
+        0 | 1 | 7 => Some(1), // u8, i8, bool
+        2 | 3 => Some(2),     // u16, i16
+        4..=6 => Some(4),     // u32, i32, f32
+        10..=12 => Some(8),   // u64, i64, f64
+        _ => None,
+    }
+}
+
+/// An unsigned or non-negative integer value, consumed; None (nothing consumed)
+/// for other types
+fn read_integer(reader: &mut impl Read, value_type: u32) -> crate::Result<Option<u64>> {
+    Ok(match value_type {
+        0 => Some(u64::from(read_bytes::<1>(reader)?[0])),
+        2 => Some(u64::from(u16::from_le_bytes(read_bytes(reader)?))),
+        4 => Some(u64::from(read_u32(reader)?)),
+        5 => u64::try_from(i32::from_le_bytes(read_bytes(reader)?)).ok(),
+        10 => Some(read_u64(reader)?),
+        11 => u64::try_from(i64::from_le_bytes(read_bytes(reader)?)).ok(),
+        _ => None,
+    })
+}
+
+fn skip_value(reader: &mut (impl Read + Seek), value_type: u32) -> crate::Result<()> {
+    if let Some(size) = fixed_size(value_type) {
+        return skip(reader, size);
+    }
+    match value_type {
+        STRING => {
+            let len = read_u64(reader)?;
+            skip(reader, len)
+        }
+        ARRAY => {
+            let item_type = read_u32(reader)?;
+            let count = read_u64(reader)?;
+            match fixed_size(item_type) {
+                Some(size) => skip(reader, size.saturating_mul(count)),
+                None => (0..count).try_for_each(|_| skip_value(reader, item_type)),
+            }
+        }
+        other => Err(format!("Unknown GGUF value type {other}").into()),
+    }
+}
+
+fn skip(reader: &mut impl Seek, bytes: u64) -> crate::Result<()> {
+    let offset = i64::try_from(bytes).map_err(|_| "GGUF header is corrupt")?;
+    with_context(
+        reader.seek(SeekFrom::Current(offset)),
+        "Failed to read GGUF header",
+    )?;
+    Ok(())
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> crate::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    with_context(reader.read_exact(&mut bytes), "Failed to read GGUF header")?;
+    Ok(bytes)
+}
+
+fn read_u32(reader: &mut impl Read) -> crate::Result<u32> {
+    read_bytes(reader).map(u32::from_le_bytes)
+}
+
+fn read_u64(reader: &mut impl Read) -> crate::Result<u64> {
+    read_bytes(reader).map(u64::from_le_bytes)
+}
+
+fn read_string(reader: &mut impl Read) -> crate::Result<String> {
+    let len = read_u64(reader)?;
+    if len > MAX_STRING_BYTES {
+        return Err("GGUF header is corrupt".into());
+    }
+    let mut bytes = vec![0u8; len as usize];
+    with_context(reader.read_exact(&mut bytes), "Failed to read GGUF header")?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_string(out: &mut Vec<u8>, s: &str) {
+        out.extend((s.len() as u64).to_le_bytes());
+        out.extend(s.as_bytes());
+    }
+
+    #[test]
+    fn test_read_info() {
+        let mut gguf = b"GGUF".to_vec();
+        gguf.extend(3u32.to_le_bytes());
+        gguf.extend(0u64.to_le_bytes()); // Tensors
+        gguf.extend(5u64.to_le_bytes()); // Key-values
+
+        push_string(&mut gguf, "qwen3.context_length");
+        gguf.extend(4u32.to_le_bytes());
+        gguf.extend(40960u32.to_le_bytes());
+        push_string(&mut gguf, "tokenizer.ggml.tokens");
+        gguf.extend(ARRAY.to_le_bytes());
+        gguf.extend(STRING.to_le_bytes());
+        gguf.extend(2u64.to_le_bytes());
+        push_string(&mut gguf, "<s>");
+        push_string(&mut gguf, "</s>");
+        push_string(&mut gguf, "general.architecture");
+        gguf.extend(STRING.to_le_bytes());
+        push_string(&mut gguf, "qwen3");
+        push_string(&mut gguf, "general.name");
+        gguf.extend(STRING.to_le_bytes());
+        push_string(&mut gguf, "Qwen3 8B");
+        push_string(&mut gguf, "qwen3.block_count");
+        gguf.extend(10u32.to_le_bytes());
+        gguf.extend(36u64.to_le_bytes());
+
+        let path = std::env::temp_dir().join(format!("gguf-{}.gguf", std::process::id()));
+        std::fs::write(&path, &gguf).unwrap();
+        let info = read_info(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            info,
+            GgufInfo {
+                name: Some("Qwen3 8B".to_string()),
+                architecture: Some("qwen3".to_string()),
+                context_length: Some(40960),
+                block_count: Some(36),
+            }
+        );
+    }
+}
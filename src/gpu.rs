@@ -0,0 +1,64 @@
+use std::process::Command;
+
+/// One reading of the GPU's performance statistics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpuSample {
+    pub utilization_percent: f64,
+    pub memory_in_use_mb: f64, // Unified memory wired for the GPU, all processes
+}
+
+/// Sample GPU utilization from the IOAccelerator's performance statistics
+///
+/// `ioreg` needs no privileges (unlike powermetrics) and reports the same device
+/// utilization Activity Monitor shows. Returns None where it isn't available.
+pub fn sample() -> Option<GpuSample> {
+    let output = Command::new("ioreg")
+        .args(["-r", "-d", "1", "-w", "0", "-c", "IOAccelerator"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    parse_ioreg(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Extract utilization and in-use memory from `ioreg -c IOAccelerator` output
+pub fn parse_ioreg(output: &str) -> Option<GpuSample> {
+    let utilization = stat_value(output, "Device Utilization %")?;
+    let memory_bytes = stat_value(output, "In use system memory").unwrap_or(0.0);
+
+    Some(GpuSample {
+        utilization_percent: utilization.clamp(0.0, 100.0),
+        memory_in_use_mb: memory_bytes / (1024.0 * 1024.0),
+    })
+}
+
+/// Value of `"key"=123` inside the PerformanceStatistics dictionary
+fn stat_value(output: &str, key: &str) -> Option<f64> {
+    let needle = format!("\"{key}\"=");
+    let start = output.find(&needle)? + needle.len();
+    let digits: String = output[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ioreg() {
+        let output = r#"+-o AGXAcceleratorG14X  <class AGXAcceleratorG14X, id 0x100000281, registered>
+    {
+      "PerformanceStatistics" = {"In use system memory (driver)"=0,"Alloc system memory"=2937061376,"Tiler Utilization %"=31,"Renderer Utilization %"=30,"Device Utilization %"=42,"In use system memory"=1073741824}
+    }
+"#;
+
+        let sample = parse_ioreg(output).unwrap();
+        assert_eq!(sample.utilization_percent, 42.0);
+        assert_eq!(sample.memory_in_use_mb, 1024.0);
+
+        assert_eq!(parse_ioreg("no accelerator here"), None);
+    }
+}
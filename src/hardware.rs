@@ -0,0 +1,179 @@
+use crate::logging;
+use crate::types::error_helpers::{with_context, PARSE_JSON};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Share of unified memory macOS lets the GPU wire by default (recommendedMaxWorkingSetSize)
+const GPU_MEMORY_FRACTION: f64 = 0.75;
+
+/// Headroom for KV cache, compute buffers and the runtime itself
+const RUNTIME_OVERHEAD_GB: f64 = 2.0;
+
+/// Approximate bits per weight of common GGUF quantizations
+const QUANTIZATIONS: &[(&str, f64)] = &[("Q4_K_M", 4.85), ("Q8_0", 8.5), ("F16", 16.0)];
+
+/// What this machine can run, detected once and cached on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HardwareInfo {
+    pub chip: String,
+    pub total_cores: usize,
+    pub performance_cores: Option<usize>,
+    pub efficiency_cores: Option<usize>,
+    pub gpu_cores: Option<usize>,
+    pub memory_bytes: u64,
+    pub metal_support: Option<String>, // e.g. "Metal 3"; None when unknown
+}
+
+impl HardwareInfo {
+    pub fn memory_gb(&self) -> f64 {
+        self.memory_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+    }
+
+    /// Load the cached summary, detecting (and caching) it on first run
+    pub fn load_or_detect() -> Option<Self> {
+        if let Ok(Some(cached)) = load_cached() {
+            return Some(cached);
+        }
+
+        let info = detect()?;
+        if let Err(e) = save_cached(&info) {
+            logging::warn(
+                "hardware",
+                "cache_failed",
+                &format!("Failed to cache hardware summary: {e}"),
+            );
+        }
+        Some(info)
+    }
+}
+
+/// Largest model (in billions of parameters) that fits each quantization
+///
+/// A rough guide: weights must fit in the GPU's share of unified memory after
+/// leaving room for context and runtime buffers.
+pub fn model_fit_guide(memory_gb: f64) -> Vec<(&'static str, f64)> {
+    let usable_gb = (memory_gb * GPU_MEMORY_FRACTION - RUNTIME_OVERHEAD_GB).max(0.0);
+
+    QUANTIZATIONS
+        .iter()
+        .map(|&(name, bits)| {
+            let bytes_per_param = bits / 8.0;
+            let max_params_b = usable_gb * 1024.0 * 1024.0 * 1024.0 / bytes_per_param / 1e9;
+            (name, max_params_b)
+        })
+        .collect()
+}
+
+fn detect() -> Option<HardwareInfo> {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    system.refresh_cpu_list(sysinfo::CpuRefreshKind::new());
+
+    let total_cores = system.physical_core_count().unwrap_or(system.cpus().len());
+    if total_cores == 0 {
+        return None;
+    }
+
+    let chip = sysctl("machdep.cpu.brand_string")
+        .or_else(|| system.cpus().first().map(|cpu| cpu.brand().to_string()))
+        .unwrap_or_else(|| "Unknown".to_string());
+    let (gpu_cores, metal_support) = detect_gpu();
+
+    Some(HardwareInfo {
+        chip,
+        total_cores,
+        // Apple Silicon reports P and E clusters as perflevel0/1
+        performance_cores: sysctl("hw.perflevel0.physicalcpu").and_then(|s| s.parse().ok()),
+        efficiency_cores: sysctl("hw.perflevel1.physicalcpu").and_then(|s| s.parse().ok()),
+        gpu_cores,
+        memory_bytes: system.total_memory(),
+        metal_support,
+    })
+}
+
+fn sysctl(name: &str) -> Option<String> {
+    let output = Command::new("sysctl").args(["-n", name]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// GPU core count and Metal family from system_profiler (slow, hence the cache)
+fn detect_gpu() -> (Option<usize>, Option<String>) {
+    let Some(output) = Command::new("system_profiler")
+        .args(["SPDisplaysDataType", "-json"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+    else {
+        return (None, None);
+    };
+
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(&output.stdout) else {
+        return (None, None);
+    };
+    let Some(gpu) = json["SPDisplaysDataType"].get(0) else {
+        return (None, None);
+    };
+
+    let cores = gpu["sppci_cores"].as_str().and_then(|s| s.parse().ok());
+    // "spdisplays_metal3" -> "Metal 3"
+    let metal = gpu["spdisplays_mtlgpufamilysupport"]
+        .as_str()
+        .and_then(|family| family.strip_prefix("spdisplays_metal"))
+        .map(|version| format!("Metal {version}"));
+
+    (cores, metal)
+}
+
+fn load_cached() -> crate::Result<Option<HardwareInfo>> {
+    let path = cache_file_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let json = with_context(
+        std::fs::read_to_string(&path),
+        "Failed to read hardware summary",
+    )?;
+    with_context(serde_json::from_str(&json), PARSE_JSON).map(Some)
+}
+
+fn save_cached(info: &HardwareInfo) -> crate::Result<()> {
+    let path = cache_file_path()?;
+    let json = with_context(
+        serde_json::to_string(info),
+        "Failed to serialize hardware summary",
+    )?;
+    crate::persistence::write_atomic(&path, json.as_bytes())
+}
+
+fn cache_file_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::HARDWARE_CACHE_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_fit_guide() {
+        let guide = model_fit_guide(32.0);
+        let q4 = guide.iter().find(|(name, _)| *name == "Q4_K_M").unwrap().1;
+        let f16 = guide.iter().find(|(name, _)| *name == "F16").unwrap().1;
+
+        // 32 GB * 0.75 - 2 GB = 22 GB usable
+        assert!((q4 - 38.97).abs() < 0.1, "q4 = {q4}");
+        assert!((f16 - 11.81).abs() < 0.1, "f16 = {f16}");
+
+        // Too little memory for anything
+        assert!(model_fit_guide(2.0)
+            .iter()
+            .all(|(_, params)| *params == 0.0));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::events::EventKind;
+use crate::logging;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// A hook still running after this long is killed
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Run the user's state transition hook, if one is configured
+///
+/// The script gets the machine (`agent` or `display`) and the old and new state
+/// as arguments, and the same values in `LLAMA_SWAP_HOOK_*` environment
+/// variables. It runs in the background so a slow script never delays polling.
+pub fn on_transition(kind: EventKind, from: &str, to: &str) {
+    let Some(script) = crate::constants::HOOK_SCRIPT.as_deref() else {
+        return;
+    };
+    let Ok(script) = crate::commands::expand_tilde(script) else {
+        return;
+    };
+
+    run_hook(&script, kind, from, to);
+}
+
+fn run_hook(script: &str, kind: EventKind, from: &str, to: &str) {
+    let machine = kind.label().to_lowercase();
+    let spawned = Command::new(script)
+        .args([machine.as_str(), from, to])
+        .env("LLAMA_SWAP_HOOK_MACHINE", &machine)
+        .env("LLAMA_SWAP_HOOK_FROM", from)
+        .env("LLAMA_SWAP_HOOK_TO", to)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    match spawned {
+        Ok(child) => {
+            let description = format!("{script} {machine} {from} {to}");
+            std::thread::spawn(move || reap(child, &description));
+        }
+        Err(e) => logging::warn(
+            "hooks",
+            "spawn_failed",
+            &format!("Failed to run hook {script}: {e}"),
+        ),
+    }
+}
+
+/// Wait for a hook to finish (so it doesn't linger as a zombie), killing it on timeout
+fn reap(mut child: Child, description: &str) {
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return,
+            Ok(Some(status)) => {
+                logging::warn(
+                    "hooks",
+                    "hook_failed",
+                    &format!("Hook `{description}` exited with {status}"),
+                );
+                return;
+            }
+            Ok(None) if started.elapsed() >= HOOK_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                logging::warn(
+                    "hooks",
+                    "hook_timeout",
+                    &format!(
+                        "Hook `{description}` killed after {}s",
+                        HOOK_TIMEOUT.as_secs()
+                    ),
+                );
+                return;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_hook_receives_transition() {
+        let dir = std::env::temp_dir().join(format!("hooks-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("out");
+        let script = dir.join("hook.sh");
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\necho \"$1 $2 $3 $LLAMA_SWAP_HOOK_TO\" > {}\n",
+                output.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        run_hook(
+            script.to_str().unwrap(),
+            EventKind::Display,
+            "ModelReady",
+            "ModelProcessingQueue",
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut contents = String::new();
+        while Instant::now() < deadline {
+            contents = std::fs::read_to_string(&output).unwrap_or_default();
+            if !contents.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(
+            contents.trim(),
+            "display ModelReady ModelProcessingQueue ModelProcessingQueue"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+use crate::types::error_helpers::with_context;
+use reqwest::blocking::Client;
+use reqwest::{Certificate, Identity};
+use std::time::Duration;
+
+/// An HTTP client for llama-swap's API, trusting the configured CA bundle and
+/// presenting the configured client certificate
+///
+/// The bundled roots are Mozilla's, so llama-swap behind a reverse proxy with
+/// a private CA needs `LLAMA_SWAP_CA_CERT_PATH`; proxies that require mutual
+/// TLS need the client certificate too.
+pub fn api_client(timeout: Option<Duration>) -> crate::Result<Client> {
+    build_client(
+        timeout,
+        crate::constants::CA_CERT_PATH.as_deref(),
+        crate::constants::CLIENT_CERT_PATH.as_deref(),
+        crate::constants::CLIENT_KEY_PATH.as_deref(),
+    )
+}
+
+fn build_client(
+    timeout: Option<Duration>,
+    ca_cert: Option<&str>,
+    client_cert: Option<&str>,
+    client_key: Option<&str>,
+) -> crate::Result<Client> {
+    let mut builder = Client::builder();
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    if let Some(path) = ca_cert {
+        let pem = read_pem(path, "CA bundle")?;
+        let certs = with_context(Certificate::from_pem_bundle(&pem), "Invalid CA bundle")?;
+        if certs.is_empty() {
+            return Err(format!("No certificates in CA bundle {path}").into());
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    if let Some(path) = client_cert {
+        // The key may sit in the certificate's file or its own
+        let mut pem = read_pem(path, "client certificate")?;
+        if let Some(key_path) = client_key {
+            pem.push(b'\n');
+            pem.extend(read_pem(key_path, "client key")?);
+        }
+        let identity = with_context(Identity::from_pem(&pem), "Invalid client certificate")?;
+        builder = builder.identity(identity);
+    }
+
+    with_context(builder.build(), "Failed to create HTTP client")
+}
+
+fn read_pem(path: &str, what: &str) -> crate::Result<Vec<u8>> {
+    let path = crate::commands::expand_tilde(path)?;
+    with_context(
+        std::fs::read(&path),
+        &format!("Failed to read {what} {path}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tls_cert.pem");
+    const KEY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tls_key.pem");
+
+    #[test]
+    fn test_build_client_with_tls_files() {
+        assert!(build_client(None, Some(CERT), Some(CERT), Some(KEY)).is_ok());
+        // A certificate without its key isn't an identity
+        assert!(build_client(None, None, Some(CERT), None).is_err());
+        assert!(build_client(None, Some(KEY), None, None).is_err());
+        assert!(build_client(None, Some("/nonexistent/ca.pem"), None, None).is_err());
+    }
+}
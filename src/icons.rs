@@ -1,22 +1,130 @@
 use image::{DynamicImage, Rgba, RgbaImage};
 use png::{BitDepth, ColorType, Encoder, PixelDimensions, Unit};
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock, PoisonError};
 
 use crate::constants::{
-    COLOR_AGENT_NOT_LOADED, COLOR_AGENT_STARTING, COLOR_MODEL_LOADING, COLOR_MODEL_READY,
-    COLOR_PROCESSING_QUEUE, COLOR_SERVICE_NO_MODEL, COLOR_SERVICE_STOPPED, STATUS_DOT_OFFSET,
-    STATUS_DOT_SIZE,
+    ERROR_BADGE_OFFSET, ERROR_BADGE_SIZE, MODEL_COUNT_OFFSET, MODEL_COUNT_SCALE, PALETTE,
+    STATUS_DOT_CORNER, STATUS_DOT_OFFSET, STATUS_DOT_SIZE, STATUS_DOT_STYLE,
 };
+use crate::logging;
 
 use base64::{engine::general_purpose::STANDARD as B64, Engine};
 
-pub const DARK_BASE_ICON_BYTES: &[u8] = include_bytes!("../assets/llama-48-white.png");
+/// Single template asset; only its alpha channel is used (see `tint`)
+pub const BASE_ICON_BYTES: &[u8] = include_bytes!("../assets/llama-48.png");
 
-pub const LIGHT_BASE_ICON_BYTES: &[u8] = include_bytes!("../assets/llama-48.png");
+/// Side length custom icons are scaled to, matching the bundled asset
+const ICON_SIZE: u32 = 48;
+
+/// Foreground colours for the generated Light and Dark Mode variants
+const LIGHT_MODE_TINT: (u8, u8, u8) = (0, 0, 0);
+const DARK_MODE_TINT: (u8, u8, u8) = (255, 255, 255);
+
+/// 3×5 pixel digits, one row per byte with the leftmost pixel in bit 2
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b010, 0b010, 0b010], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+];
+
+/// Shown after a 9 for ten or more models
+const PLUS: [u8; 5] = [0b000, 0b010, 0b111, 0b010, 0b000];
+
+/// 5×5 state glyphs replacing the status dot in template icons (leftmost pixel in bit 4)
+const GLYPH_READY: [u8; 5] = [0b01110, 0b11111, 0b11111, 0b11111, 0b01110]; // ●
+const GLYPH_PROCESSING: [u8; 5] = [0b10000, 0b11100, 0b11111, 0b11100, 0b10000]; // ▶
+const GLYPH_TRANSITION: [u8; 5] = [0b01110, 0b10001, 0b10001, 0b10001, 0b01110]; // ○
+const GLYPH_NO_MODEL: [u8; 5] = [0b00000, 0b00000, 0b11111, 0b00000, 0b00000]; // –
+const GLYPH_DEGRADED: [u8; 5] = [0b01110, 0b11001, 0b11001, 0b11001, 0b01110]; // ◐
+const GLYPH_STOPPED: [u8; 5] = [0b10001, 0b01010, 0b00100, 0b01010, 0b10001]; // ×
+const GLYPH_STARTING: [u8; 5] = [0b00000, 0b00000, 0b00000, 0b00000, 0b10101]; // …
+
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
 
 /// 1 inch / 0.0254 m × 144 dpi  ≈ 5 669 px per metre
 const RETINA_PPM: u32 = 5_669;
 
+/// How the menu bar icon is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IconStyle {
+    Image,    // Llama PNG (or a custom one) with a coloured status dot
+    Symbol,   // SwiftBar SF Symbol coloured by state; follows Dark Mode by itself
+    Template, // Monochrome template image tinted by macOS, with a state glyph
+}
+
+impl IconStyle {
+    pub fn from_env_value(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "symbol" | "sf" | "sfsymbol" => IconStyle::Symbol,
+            "template" | "monochrome" => IconStyle::Template,
+            _ => IconStyle::Image,
+        }
+    }
+}
+
+/// Icon corner the status dot is drawn in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub fn from_env_value(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "tl" | "top-left" => Corner::TopLeft,
+            "tr" | "top-right" => Corner::TopRight,
+            "bl" | "bottom-left" => Corner::BottomLeft,
+            _ => Corner::BottomRight,
+        }
+    }
+
+    /// Top-left pixel of a `width`×`height` box `offset` pixels in from this corner
+    fn origin(self, (w, h): (u32, u32), (width, height): (u32, u32), offset: u32) -> (u32, u32) {
+        let right = w.saturating_sub(offset + width);
+        let bottom = h.saturating_sub(offset + height);
+        match self {
+            Corner::TopLeft => (offset, offset),
+            Corner::TopRight => (right, offset),
+            Corner::BottomLeft => (offset, bottom),
+            Corner::BottomRight => (right, bottom),
+        }
+    }
+}
+
+/// How the status dot is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DotStyle {
+    Filled,
+    Ring, // Outline with a transparent centre
+}
+
+impl DotStyle {
+    pub fn from_env_value(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ring" | "outline" => DotStyle::Ring,
+            _ => DotStyle::Filled,
+        }
+    }
+}
+
+/// Overlay drawn on top of the base icon in addition to the status dot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IconOverlay {
+    None,
+    ErrorBadge,   // Exclamation badge for persistent API errors
+    WarningBadge, // Same badge in yellow while an alert threshold is exceeded
+}
+
 /// Cached icon images for maximum performance
 struct IconCache {
     processing_queue: bitbar::attr::Image,
@@ -24,45 +132,177 @@ struct IconCache {
     model_loading: bitbar::attr::Image,
     service_no_model: bitbar::attr::Image,
     service_stopped: bitbar::attr::Image,
+    service_degraded: bitbar::attr::Image,
     agent_starting: bitbar::attr::Image,
     agent_not_loaded: bitbar::attr::Image,
 }
 
+/// Icon key for the uncached variants: state, overlay, model count and dot colour
+type CountedIconKey = (
+    crate::state_model::DisplayState,
+    IconOverlay,
+    usize,
+    Option<(u8, u8, u8)>,
+);
+
+static BASE_ICONS: OnceLock<(RgbaImage, RgbaImage)> = OnceLock::new();
 static ICON_CACHE: OnceLock<IconCache> = OnceLock::new();
+static ERROR_BADGE_ICON_CACHE: OnceLock<IconCache> = OnceLock::new();
+static WARNING_BADGE_ICON_CACHE: OnceLock<IconCache> = OnceLock::new();
+// Only the latest counted icon is kept: the count changes rarely, so it's rebuilt rarely
+static COUNTED_ICON: Mutex<Option<(CountedIconKey, bitbar::attr::Image)>> = Mutex::new(None);
+
+/// Light and Dark Mode variants of the base icon, decoded once
+///
+/// User-supplied icons take precedence; if they can't be loaded the bundled
+/// llama is used and the reason is logged.
+fn base_icons() -> &'static (RgbaImage, RgbaImage) {
+    BASE_ICONS.get_or_init(|| {
+        let Some((light, dark)) = custom_icon_paths() else {
+            return bundled_base_icons();
+        };
+        match load_custom_icons(&light, dark.as_deref()) {
+            Ok(icons) => icons,
+            Err(e) => {
+                logging::warn(
+                    "icons",
+                    "custom_icon_failed",
+                    &format!("Using the bundled icon: {e}"),
+                );
+                bundled_base_icons()
+            }
+        }
+    })
+}
+
+fn bundled_base_icons() -> (RgbaImage, RgbaImage) {
+    let template = image::load_from_memory(BASE_ICON_BYTES)
+        .expect("Failed to load base icon")
+        .to_rgba8();
+    (
+        tint(&template, LIGHT_MODE_TINT),
+        tint(&template, DARK_MODE_TINT),
+    )
+}
+
+/// Light and optional Dark Mode icon paths: the environment, then the plugin config
+fn custom_icon_paths() -> Option<(String, Option<String>)> {
+    if let Some(light) = crate::constants::ICON_PATH.clone() {
+        return Some((light, crate::constants::ICON_DARK_PATH.clone()));
+    }
+    let icon = crate::plugin_config::get().icon.as_ref()?;
+    Some((icon.light.clone(), icon.dark.clone()))
+}
 
-/// Initialize the icon cache (called once at startup)
-fn init_icon_cache() -> IconCache {
-    // Load and decode the base icons once
-    let base_icon_dark =
-        image::load_from_memory(DARK_BASE_ICON_BYTES).expect("Failed to load dark base icon");
-    let base_rgba_dark = base_icon_dark.to_rgba8();
+/// Load user-supplied icons, using the light one for both modes without a dark one
+fn load_custom_icons(light: &str, dark: Option<&str>) -> crate::Result<(RgbaImage, RgbaImage)> {
+    let light = load_custom_icon(light)?;
+    let dark = match dark {
+        Some(path) => load_custom_icon(path)?,
+        None => light.clone(),
+    };
+    Ok((light, dark))
+}
+
+/// Decode a square PNG and scale it to the bundled icon's size
+fn load_custom_icon(path: &str) -> crate::Result<RgbaImage> {
+    let path = crate::commands::expand_tilde(path)?;
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    decode_custom_icon(&bytes).map_err(|e| format!("{path}: {e}").into())
+}
+
+fn decode_custom_icon(bytes: &[u8]) -> crate::Result<RgbaImage> {
+    let icon = image::load_from_memory_with_format(bytes, image::ImageFormat::Png)
+        .map_err(|e| format!("not a valid PNG ({e})"))?
+        .to_rgba8();
+    let (w, h) = icon.dimensions();
+    if w != h || w < ICON_SIZE / 2 {
+        return Err(format!(
+            "icon must be square and at least {0}x{0} pixels, got {w}x{h}",
+            ICON_SIZE / 2
+        )
+        .into());
+    }
+    if w == ICON_SIZE {
+        return Ok(icon);
+    }
+    Ok(image::imageops::resize(
+        &icon,
+        ICON_SIZE,
+        ICON_SIZE,
+        image::imageops::FilterType::Lanczos3,
+    ))
+}
 
-    let base_icon_light =
-        image::load_from_memory(LIGHT_BASE_ICON_BYTES).expect("Failed to load light base icon");
-    let base_rgba_light = base_icon_light.to_rgba8();
+/// Initialize the icon cache for an overlay variant (called once per variant)
+fn init_icon_cache(overlay: IconOverlay) -> IconCache {
+    let (base_rgba_light, base_rgba_dark) = base_icons();
 
     // Create themed images for each program state
-    let processing_queue =
-        create_themed_status_icon(&base_rgba_light, &base_rgba_dark, COLOR_PROCESSING_QUEUE)
-            .expect("Failed to create processing queue icon");
-    let model_ready =
-        create_themed_status_icon(&base_rgba_light, &base_rgba_dark, COLOR_MODEL_READY)
-            .expect("Failed to create model ready icon");
-    let model_loading =
-        create_themed_status_icon(&base_rgba_light, &base_rgba_dark, COLOR_MODEL_LOADING)
-            .expect("Failed to create model loading icon");
-    let service_no_model =
-        create_themed_status_icon(&base_rgba_light, &base_rgba_dark, COLOR_SERVICE_NO_MODEL)
-            .expect("Failed to create service no model icon");
-    let service_stopped =
-        create_themed_status_icon(&base_rgba_light, &base_rgba_dark, COLOR_SERVICE_STOPPED)
-            .expect("Failed to create service stopped icon");
-    let agent_starting =
-        create_themed_status_icon(&base_rgba_light, &base_rgba_dark, COLOR_AGENT_STARTING)
-            .expect("Failed to create agent starting icon");
-    let agent_not_loaded =
-        create_themed_status_icon(&base_rgba_light, &base_rgba_dark, COLOR_AGENT_NOT_LOADED)
-            .expect("Failed to create agent not loaded icon");
+    let processing_queue = create_themed_status_icon(
+        base_rgba_light,
+        base_rgba_dark,
+        PALETTE.processing_queue,
+        overlay,
+        0,
+    )
+    .expect("Failed to create processing queue icon");
+    let model_ready = create_themed_status_icon(
+        base_rgba_light,
+        base_rgba_dark,
+        PALETTE.model_ready,
+        overlay,
+        0,
+    )
+    .expect("Failed to create model ready icon");
+    let model_loading = create_themed_status_icon(
+        base_rgba_light,
+        base_rgba_dark,
+        PALETTE.model_loading,
+        overlay,
+        0,
+    )
+    .expect("Failed to create model loading icon");
+    let service_no_model = create_themed_status_icon(
+        base_rgba_light,
+        base_rgba_dark,
+        PALETTE.service_no_model,
+        overlay,
+        0,
+    )
+    .expect("Failed to create service no model icon");
+    let service_stopped = create_themed_status_icon(
+        base_rgba_light,
+        base_rgba_dark,
+        PALETTE.service_stopped,
+        overlay,
+        0,
+    )
+    .expect("Failed to create service stopped icon");
+    let service_degraded = create_themed_status_icon(
+        base_rgba_light,
+        base_rgba_dark,
+        PALETTE.service_degraded,
+        overlay,
+        0,
+    )
+    .expect("Failed to create service degraded icon");
+    let agent_starting = create_themed_status_icon(
+        base_rgba_light,
+        base_rgba_dark,
+        PALETTE.agent_starting,
+        overlay,
+        0,
+    )
+    .expect("Failed to create agent starting icon");
+    let agent_not_loaded = create_themed_status_icon(
+        base_rgba_light,
+        base_rgba_dark,
+        PALETTE.agent_not_loaded,
+        overlay,
+        0,
+    )
+    .expect("Failed to create agent not loaded icon");
 
     IconCache {
         processing_queue,
@@ -70,25 +310,45 @@ fn init_icon_cache() -> IconCache {
         model_loading,
         service_no_model,
         service_stopped,
+        service_degraded,
         agent_starting,
         agent_not_loaded,
     }
 }
 
-/// Create a themed status icon (light,dark format) with status dot
+/// Recolour every pixel of a template image, keeping its alpha channel
+///
+/// Any source colour works: a black, white or coloured silhouette produces the
+/// same light/dark pair, so only one asset is needed per icon.
+pub fn tint(template: &RgbaImage, color: (u8, u8, u8)) -> RgbaImage {
+    let mut tinted = template.clone();
+    for pixel in tinted.pixels_mut() {
+        *pixel = Rgba([color.0, color.1, color.2, pixel[3]]);
+    }
+    tinted
+}
+
+/// Create a themed status icon (light,dark format) with status dot, optional
+/// overlay and, when non-zero, the number of loaded models
 fn create_themed_status_icon(
     light_base: &RgbaImage,
     dark_base: &RgbaImage,
     color: (u8, u8, u8),
+    overlay: IconOverlay,
+    model_count: usize,
 ) -> crate::Result<bitbar::attr::Image> {
     // Create light version
     let mut light_icon = light_base.clone();
     draw_status_dot(&mut light_icon, color);
+    draw_overlay(&mut light_icon, overlay);
+    draw_model_count(&mut light_icon, model_count, LIGHT_MODE_TINT);
     let light_b64 = rgba_to_base64(&light_icon)?;
 
     // Create dark version
     let mut dark_icon = dark_base.clone();
     draw_status_dot(&mut dark_icon, color);
+    draw_overlay(&mut dark_icon, overlay);
+    draw_model_count(&mut dark_icon, model_count, DARK_MODE_TINT);
     let dark_b64 = rgba_to_base64(&dark_icon)?;
 
     // one comma → SwiftBar shows first in Light Mode, second in Dark Mode
@@ -96,6 +356,27 @@ fn create_themed_status_icon(
     Ok(bitbar::attr::Image::from(themed_image_data))
 }
 
+/// Create a monochrome template icon: macOS tints it like a native menu bar
+/// icon, so state is shown by a glyph's shape instead of the dot's colour
+fn create_template_icon(
+    base: &RgbaImage,
+    state: crate::state_model::DisplayState,
+    overlay: IconOverlay,
+    model_count: usize,
+) -> crate::Result<bitbar::attr::Image> {
+    let mut icon = tint(base, LIGHT_MODE_TINT);
+    draw_state_glyph(&mut icon, state_glyph(state));
+    if overlay != IconOverlay::None {
+        // Colour can't tell the badges apart here, and the mark is cut out
+        draw_badge(&mut icon, LIGHT_MODE_TINT, Rgba([0, 0, 0, 0]));
+    }
+    draw_model_count(&mut icon, model_count, LIGHT_MODE_TINT);
+    Ok(bitbar::attr::Image {
+        base64_data: rgba_to_base64(&icon)?,
+        is_template: true,
+    })
+}
+
 /// Convert RGBA image to base64 PNG string (helper)
 fn rgba_to_base64(rgba: &RgbaImage) -> crate::Result<String> {
     let buffer = encode_rgba_to_png(rgba)?;
@@ -105,21 +386,128 @@ fn rgba_to_base64(rgba: &RgbaImage) -> crate::Result<String> {
 /// Get cached display state icon image
 pub fn get_display_state_icon(
     state: crate::state_model::DisplayState,
+    overlay: IconOverlay,
 ) -> &'static bitbar::attr::Image {
     use crate::state_model::DisplayState;
-    let cache = ICON_CACHE.get_or_init(init_icon_cache);
+    let cache = match overlay {
+        IconOverlay::None => ICON_CACHE.get_or_init(|| init_icon_cache(IconOverlay::None)),
+        IconOverlay::ErrorBadge => {
+            ERROR_BADGE_ICON_CACHE.get_or_init(|| init_icon_cache(IconOverlay::ErrorBadge))
+        }
+        IconOverlay::WarningBadge => {
+            WARNING_BADGE_ICON_CACHE.get_or_init(|| init_icon_cache(IconOverlay::WarningBadge))
+        }
+    };
 
     match state {
         DisplayState::ModelProcessingQueue => &cache.processing_queue,
         DisplayState::ModelReady => &cache.model_ready,
-        DisplayState::ModelLoading => &cache.model_loading,
+        DisplayState::ModelLoading | DisplayState::ModelStopping => &cache.model_loading,
         DisplayState::ServiceLoadedNoModel => &cache.service_no_model,
-        DisplayState::ServiceStopped => &cache.service_stopped,
+        DisplayState::ServiceStopped | DisplayState::StartupFailed | DisplayState::CrashLoop => {
+            &cache.service_stopped
+        }
+        DisplayState::ServiceDegraded => &cache.service_degraded,
         DisplayState::AgentStarting => &cache.agent_starting,
         DisplayState::AgentNotLoaded => &cache.agent_not_loaded,
     }
 }
 
+/// Menu bar icon, with the number of loaded models when there are any and the
+/// status dot in `dot_color` instead of the state's colour when given
+///
+/// Plain colour icons come from the per-state caches; counted, recoloured and
+/// template icons are rebuilt only when their key changes.
+pub fn get_menu_bar_icon(
+    state: crate::state_model::DisplayState,
+    overlay: IconOverlay,
+    model_count: usize,
+    dot_color: Option<(u8, u8, u8)>,
+) -> bitbar::attr::Image {
+    let template = *crate::constants::ICON_STYLE == IconStyle::Template;
+    let model_count = if *crate::constants::SHOW_MODEL_COUNT {
+        model_count
+    } else {
+        0
+    };
+    if model_count == 0 && !template && dot_color.is_none() {
+        return get_display_state_icon(state, overlay).clone();
+    }
+
+    let key = (state, overlay, model_count, dot_color);
+    let mut cached = COUNTED_ICON.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some((_, icon)) = cached.as_ref().filter(|(cached_key, _)| *cached_key == key) {
+        return icon.clone();
+    }
+
+    let (light, dark) = base_icons();
+    let icon = if template {
+        create_template_icon(light, state, overlay, model_count)
+    } else {
+        let color = dot_color.unwrap_or_else(|| state_color(state));
+        create_themed_status_icon(light, dark, color, overlay, model_count)
+    };
+    match icon {
+        Ok(icon) => {
+            *cached = Some((key, icon.clone()));
+            icon
+        }
+        Err(_) => get_display_state_icon(state, overlay).clone(),
+    }
+}
+
+/// Status dot colour for a display state
+fn state_color(state: crate::state_model::DisplayState) -> (u8, u8, u8) {
+    use crate::state_model::DisplayState;
+    match state {
+        DisplayState::ModelProcessingQueue => PALETTE.processing_queue,
+        DisplayState::ModelReady => PALETTE.model_ready,
+        DisplayState::ModelLoading | DisplayState::ModelStopping => PALETTE.model_loading,
+        DisplayState::ServiceLoadedNoModel => PALETTE.service_no_model,
+        DisplayState::ServiceStopped | DisplayState::StartupFailed | DisplayState::CrashLoop => {
+            PALETTE.service_stopped
+        }
+        DisplayState::ServiceDegraded => PALETTE.service_degraded,
+        DisplayState::AgentStarting => PALETTE.agent_starting,
+        DisplayState::AgentNotLoaded => PALETTE.agent_not_loaded,
+    }
+}
+
+/// Green → yellow → red as Llama's share of system memory grows
+///
+/// Quantised to 5% steps so the icon is only rebuilt when the colour visibly changes.
+pub fn memory_gradient_color(fraction: f64) -> (u8, u8, u8) {
+    let fraction = (fraction.clamp(0.0, 1.0) * 20.0).round() / 20.0;
+    let (from, to, t) = if fraction < 0.5 {
+        (PALETTE.model_ready, PALETTE.model_loading, fraction * 2.0)
+    } else {
+        (
+            PALETTE.model_loading,
+            PALETTE.service_stopped,
+            (fraction - 0.5) * 2.0,
+        )
+    };
+    let mix = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+    (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+/// Template icon glyph for a display state, grouped like the status dot colours
+fn state_glyph(state: crate::state_model::DisplayState) -> [u8; 5] {
+    use crate::state_model::DisplayState;
+    match state {
+        DisplayState::ModelProcessingQueue => GLYPH_PROCESSING,
+        DisplayState::ModelReady => GLYPH_READY,
+        DisplayState::ModelLoading | DisplayState::ModelStopping => GLYPH_TRANSITION,
+        DisplayState::ServiceLoadedNoModel => GLYPH_NO_MODEL,
+        DisplayState::ServiceStopped | DisplayState::StartupFailed | DisplayState::CrashLoop => {
+            GLYPH_STOPPED
+        }
+        DisplayState::ServiceDegraded => GLYPH_DEGRADED,
+        DisplayState::AgentStarting => GLYPH_STARTING,
+        DisplayState::AgentNotLoaded => GLYPH_STOPPED,
+    }
+}
+
 /// Convert chart image to menu image (for charts only)
 pub fn chart_to_menu_image(chart: &DynamicImage) -> crate::Result<bitbar::attr::Image> {
     rgba_to_menu_image(&chart.to_rgba8())
@@ -159,20 +547,274 @@ fn encode_rgba_to_png(rgba: &RgbaImage) -> crate::Result<Vec<u8>> {
 /// Draw the dot only inside its bounding box (≈ 5× faster than naive approach)
 fn draw_status_dot(icon: &mut RgbaImage, color: (u8, u8, u8)) {
     let (w, h) = icon.dimensions();
-    let r = (STATUS_DOT_SIZE / 2) as i32;
-    let cx = w as i32 - STATUS_DOT_OFFSET as i32 - r;
-    let cy = h as i32 - STATUS_DOT_OFFSET as i32 - r;
+    let size = *STATUS_DOT_SIZE;
+    let (x0, y0) = STATUS_DOT_CORNER.origin((w, h), (size, size), *STATUS_DOT_OFFSET);
+    let r = (size / 2) as i32;
+    let cx = x0 as i32 + r;
+    let cy = y0 as i32 + r;
     let r_sq = r * r;
+    // A ring is half the radius thick (at least 2px) and clears the centre
+    let hole_sq = match *STATUS_DOT_STYLE {
+        DotStyle::Filled => None,
+        DotStyle::Ring => Some((r - (r / 2).max(2)).pow(2)),
+    };
     let px = Rgba([color.0, color.1, color.2, 255]);
+    let clear = Rgba([0, 0, 0, 0]);
 
     // Only iterate over the bounding box of the circle
     for y in (cy - r).max(0)..=(cy + r).min(h as i32 - 1) {
         for x in (cx - r).max(0)..=(cx + r).min(w as i32 - 1) {
             let dx = x - cx;
             let dy = y - cy;
-            if dx * dx + dy * dy <= r_sq {
+            let d_sq = dx * dx + dy * dy;
+            if hole_sq.is_some_and(|hole_sq| d_sq < hole_sq) {
+                icon.put_pixel(x as u32, y as u32, clear);
+            } else if d_sq <= r_sq {
                 icon.put_pixel(x as u32, y as u32, px);
             }
         }
     }
 }
+
+/// Corner for the model count and badge: their usual one unless the dot is there
+fn free_corner(usual: Corner, fallback: Corner) -> Corner {
+    if *STATUS_DOT_CORNER == usual {
+        fallback
+    } else {
+        usual
+    }
+}
+
+/// Draw the count in the bottom-left corner (bottom-right if the status dot is
+/// there) in the icon's foreground colour, clearing a margin around it so it
+/// stays legible over the silhouette
+fn draw_model_count(icon: &mut RgbaImage, count: usize, color: (u8, u8, u8)) {
+    if count == 0 {
+        return;
+    }
+
+    let text = if count > 9 {
+        "9+".to_string()
+    } else {
+        count.to_string()
+    };
+    let scale = MODEL_COUNT_SCALE;
+    let (glyph_w, glyph_h, gap) = (3 * scale, 5 * scale, scale);
+    let width = text.chars().count() as u32 * (glyph_w + gap) - gap;
+    let corner = free_corner(Corner::BottomLeft, Corner::BottomRight);
+    let (box_w, box_h) = (width + 2, glyph_h + 2);
+    let (x0, y0) = corner.origin(icon.dimensions(), (box_w, box_h), MODEL_COUNT_OFFSET);
+    let (left, top) = (x0 + 1, y0 + 1);
+
+    // Knock out the background, one pixel wider than the text all round
+    let clear = Rgba([0, 0, 0, 0]);
+    for y in y0..y0 + box_h {
+        for x in x0..x0 + box_w {
+            icon.put_pixel(x, y, clear);
+        }
+    }
+
+    let px = Rgba([color.0, color.1, color.2, 255]);
+    for (i, ch) in text.chars().enumerate() {
+        let glyph = match ch.to_digit(10) {
+            Some(digit) => DIGITS[digit as usize],
+            None => PLUS,
+        };
+        let x0 = left + i as u32 * (glyph_w + gap);
+        draw_glyph(icon, &glyph, 3, (x0, top), scale, px);
+    }
+}
+
+/// Draw a state glyph where the status dot would be, clearing a margin around it
+fn draw_state_glyph(icon: &mut RgbaImage, glyph: [u8; 5]) {
+    let (w, h) = icon.dimensions();
+    let size = *STATUS_DOT_SIZE;
+    let scale = size / 5;
+    let (left, top) = STATUS_DOT_CORNER.origin((w, h), (size, size), *STATUS_DOT_OFFSET);
+
+    let clear = Rgba([0, 0, 0, 0]);
+    for y in top.saturating_sub(1)..(top + size + 1).min(h) {
+        for x in left.saturating_sub(1)..(left + size + 1).min(w) {
+            icon.put_pixel(x, y, clear);
+        }
+    }
+
+    let (r, g, b) = LIGHT_MODE_TINT;
+    draw_glyph(icon, &glyph, 5, (left, top), scale, Rgba([r, g, b, 255]));
+}
+
+/// Draw a bitmap glyph, one row per byte with the leftmost of `cols` pixels in
+/// the highest bit, each pixel as a `scale`×`scale` square
+fn draw_glyph(
+    icon: &mut RgbaImage,
+    rows: &[u8],
+    cols: u32,
+    (x0, y0): (u32, u32),
+    scale: u32,
+    px: Rgba<u8>,
+) {
+    for (row, bits) in rows.iter().enumerate() {
+        for col in 0..cols {
+            if bits & (1 << (cols - 1 - col)) == 0 {
+                continue;
+            }
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    icon.put_pixel(x0 + col * scale + dx, y0 + row as u32 * scale + dy, px);
+                }
+            }
+        }
+    }
+}
+
+fn draw_overlay(icon: &mut RgbaImage, overlay: IconOverlay) {
+    match overlay {
+        IconOverlay::None => {}
+        IconOverlay::ErrorBadge => draw_badge(icon, PALETTE.error_badge, WHITE),
+        IconOverlay::WarningBadge => draw_badge(icon, PALETTE.warning_badge, WHITE),
+    }
+}
+
+/// Draw a filled badge with an exclamation mark in the top-right corner
+/// (top-left if the status dot is there)
+fn draw_badge(icon: &mut RgbaImage, color: (u8, u8, u8), mark: Rgba<u8>) {
+    let (w, h) = icon.dimensions();
+    let corner = free_corner(Corner::TopRight, Corner::TopLeft);
+    let size = (ERROR_BADGE_SIZE, ERROR_BADGE_SIZE);
+    let (x0, y0) = corner.origin((w, h), size, ERROR_BADGE_OFFSET);
+    let r = (ERROR_BADGE_SIZE / 2) as i32;
+    let cx = x0 as i32 + r;
+    let cy = y0 as i32 + r;
+    let r_sq = r * r;
+    let badge = Rgba([color.0, color.1, color.2, 255]);
+
+    for y in (cy - r).max(0)..=(cy + r).min(h as i32 - 1) {
+        for x in (cx - r).max(0)..=(cx + r).min(w as i32 - 1) {
+            let dx = x - cx;
+            let dy = y - cy;
+            if dx * dx + dy * dy > r_sq {
+                continue;
+            }
+
+            // Exclamation mark: a 2px stem above a 2px dot, centred on the badge
+            let in_column = (-1..=0).contains(&dx);
+            let in_stem = (-r + 3..=r / 3).contains(&dy);
+            let in_dot = (r - 4..=r - 3).contains(&dy);
+            let px = if in_column && (in_stem || in_dot) {
+                mark
+            } else {
+                badge
+            };
+            icon.put_pixel(x as u32, y as u32, px);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tint_keeps_alpha() {
+        let template = image::load_from_memory(BASE_ICON_BYTES).unwrap().to_rgba8();
+        let dark = tint(&template, DARK_MODE_TINT);
+
+        assert_eq!(dark.dimensions(), template.dimensions());
+        for (source, tinted) in template.pixels().zip(dark.pixels()) {
+            assert_eq!(*tinted, Rgba([255, 255, 255, source[3]]));
+        }
+    }
+
+    #[test]
+    fn test_decode_custom_icon_validates_and_scales() {
+        let encode = |w, h| {
+            let mut bytes = Vec::new();
+            RgbaImage::from_pixel(w, h, Rgba([200, 0, 0, 255]))
+                .write_to(
+                    &mut std::io::Cursor::new(&mut bytes),
+                    image::ImageOutputFormat::Png,
+                )
+                .unwrap();
+            bytes
+        };
+
+        let icon = decode_custom_icon(&encode(96, 96)).unwrap();
+        assert_eq!(icon.dimensions(), (ICON_SIZE, ICON_SIZE));
+        assert_eq!(*icon.get_pixel(24, 24), Rgba([200, 0, 0, 255]));
+
+        assert!(decode_custom_icon(&encode(64, 32)).is_err());
+        assert!(decode_custom_icon(&encode(16, 16)).is_err());
+        assert!(decode_custom_icon(b"not a png").is_err());
+    }
+
+    #[test]
+    fn test_corner_origin() {
+        let icon = (48, 48);
+        assert_eq!(Corner::TopLeft.origin(icon, (10, 10), 1), (1, 1));
+        assert_eq!(Corner::TopRight.origin(icon, (10, 10), 1), (37, 1));
+        assert_eq!(Corner::BottomLeft.origin(icon, (10, 10), 1), (1, 37));
+        assert_eq!(Corner::BottomRight.origin(icon, (10, 12), 1), (37, 35));
+        assert_eq!(Corner::from_env_value("TL"), Corner::TopLeft);
+        assert_eq!(Corner::from_env_value("bogus"), Corner::BottomRight);
+    }
+
+    #[test]
+    fn test_memory_gradient_color() {
+        assert_eq!(memory_gradient_color(0.0), PALETTE.model_ready);
+        assert_eq!(memory_gradient_color(0.5), PALETTE.model_loading);
+        assert_eq!(memory_gradient_color(1.0), PALETTE.service_stopped);
+        assert_eq!(memory_gradient_color(1.7), PALETTE.service_stopped);
+        // Same 5% step, same colour
+        assert_eq!(memory_gradient_color(0.26), memory_gradient_color(0.24));
+    }
+
+    #[test]
+    fn test_template_icon_draws_state_glyph() {
+        use crate::state_model::DisplayState;
+        let base = RgbaImage::from_pixel(48, 48, Rgba([120, 40, 200, 255]));
+        let icon =
+            create_template_icon(&base, DisplayState::ModelReady, IconOverlay::None, 0).unwrap();
+        assert!(icon.is_template);
+        assert!(
+            !icon.base64_data.contains(','),
+            "one image, not a light/dark pair"
+        );
+
+        let mut drawn = tint(&base, LIGHT_MODE_TINT);
+        draw_state_glyph(&mut drawn, GLYPH_STOPPED);
+        let (left, top) = (
+            48 - *STATUS_DOT_OFFSET - *STATUS_DOT_SIZE,
+            48 - *STATUS_DOT_OFFSET - *STATUS_DOT_SIZE,
+        );
+        // × has its corners set and its top middle clear
+        assert_eq!(*drawn.get_pixel(left, top), Rgba([0, 0, 0, 255]));
+        assert_eq!(*drawn.get_pixel(left + 4, top), Rgba([0, 0, 0, 0]));
+        assert_eq!(*drawn.get_pixel(left - 1, top - 1), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_model_count_is_drawn_bottom_left() {
+        let mut icon = RgbaImage::from_pixel(48, 48, Rgba([0, 0, 0, 255]));
+        draw_model_count(&mut icon, 1, (255, 255, 255));
+
+        let scale = MODEL_COUNT_SCALE;
+        let left = MODEL_COUNT_OFFSET + 1;
+        let top = 48 - MODEL_COUNT_OFFSET - 1 - 5 * scale;
+        // "1" has its top-middle pixel set and its top-left pixel clear
+        assert_eq!(
+            *icon.get_pixel(left + scale, top),
+            Rgba([255, 255, 255, 255])
+        );
+        assert_eq!(*icon.get_pixel(left, top), Rgba([0, 0, 0, 0]));
+        assert_eq!(*icon.get_pixel(left - 1, top - 1), Rgba([0, 0, 0, 0]));
+        assert_eq!(
+            *icon.get_pixel(24, 10),
+            Rgba([0, 0, 0, 255]),
+            "rest untouched"
+        );
+
+        let before = icon.clone();
+        draw_model_count(&mut icon, 0, (255, 255, 255));
+        assert_eq!(icon, before);
+    }
+}
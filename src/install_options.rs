@@ -0,0 +1,284 @@
+use crate::types::error_helpers::with_context;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// When launchd restarts llama-swap after it exits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeepAlive {
+    #[default]
+    Never,
+    Always,
+    OnCrash, // Only after a non-zero exit
+}
+
+impl KeepAlive {
+    pub const ALL: [Self; 3] = [Self::Never, Self::Always, Self::OnCrash];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Always => "always",
+            Self::OnCrash => "on_crash",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Never => "Never",
+            Self::Always => "Always",
+            Self::OnCrash => "After Crashes Only",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|keep_alive| keep_alive.as_str() == value)
+    }
+}
+
+/// launchd's ProcessType: how much CPU, I/O and timer slack llama-swap gets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessType {
+    #[default]
+    Standard,
+    Background, // Throttled, so batch inference leaves foreground apps responsive
+    Adaptive,   // Throttled except while handling an XPC request
+    Interactive,
+}
+
+impl ProcessType {
+    pub const ALL: [Self; 4] = [
+        Self::Standard,
+        Self::Background,
+        Self::Adaptive,
+        Self::Interactive,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Background => "background",
+            Self::Adaptive => "adaptive",
+            Self::Interactive => "interactive",
+        }
+    }
+
+    /// The plist value, which is also the menu label
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::Background => "Background",
+            Self::Adaptive => "Adaptive",
+            Self::Interactive => "Interactive",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+/// Nice values offered in the menu; an agent can lower its priority, not raise it
+pub const NICE_CHOICES: [i32; 4] = [0, 5, 10, 19];
+
+/// launchd settings written into the generated plist, chosen from the menu
+///
+/// The defaults match the plist as it always was: the service only runs when
+/// started from the menu, and the plugin's watchdog (not launchd) restarts it.
+/// Priority settings left at their defaults are omitted from the plist.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstallOptions {
+    pub run_at_load: bool, // Start whenever the agent is loaded, i.e. at login
+    pub keep_alive: KeepAlive,
+    pub process_type: ProcessType,
+    pub nice: i32, // 0 to 20; higher is lower priority
+    pub low_priority_io: bool,
+}
+
+/// One option change, as given to `set_install_option`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstallOption {
+    RunAtLoad(bool),
+    KeepAlive(KeepAlive),
+    ProcessType(ProcessType),
+    Nice(i32),
+    LowPriorityIo(bool),
+}
+
+impl InstallOption {
+    /// `run_at_load on|off`, `keep_alive never|always|on_crash`,
+    /// `process_type standard|background|adaptive|interactive`, `nice 0-20`
+    /// or `low_priority_io on|off`
+    pub fn parse(option: &str, value: &str) -> crate::Result<Self> {
+        match option {
+            "run_at_load" => parse_switch(option, value).map(Self::RunAtLoad),
+            "keep_alive" => KeepAlive::parse(value).map(Self::KeepAlive).ok_or_else(|| {
+                format!("keep_alive must be never, always or on_crash, not {value}").into()
+            }),
+            "process_type" => ProcessType::parse(value)
+                .map(Self::ProcessType)
+                .ok_or_else(|| {
+                    format!(
+                        "process_type must be standard, background, adaptive or interactive, not {value}"
+                    )
+                    .into()
+                }),
+            "nice" => value
+                .parse()
+                .ok()
+                .filter(|nice| (0..=20).contains(nice))
+                .map(Self::Nice)
+                .ok_or_else(|| format!("nice must be from 0 to 20, not {value}").into()),
+            "low_priority_io" => parse_switch(option, value).map(Self::LowPriorityIo),
+            _ => Err(format!("Unknown install option: {option}").into()),
+        }
+    }
+
+    /// The option's name and value as `set_install_option` takes them
+    pub fn args(self) -> [String; 2] {
+        let switch = |on: bool| if on { "on" } else { "off" }.to_string();
+        match self {
+            Self::RunAtLoad(on) => ["run_at_load".into(), switch(on)],
+            Self::KeepAlive(keep_alive) => ["keep_alive".into(), keep_alive.as_str().into()],
+            Self::ProcessType(kind) => ["process_type".into(), kind.as_str().into()],
+            Self::Nice(nice) => ["nice".into(), nice.to_string()],
+            Self::LowPriorityIo(on) => ["low_priority_io".into(), switch(on)],
+        }
+    }
+}
+
+fn parse_switch(option: &str, value: &str) -> crate::Result<bool> {
+    match value {
+        "on" | "true" => Ok(true),
+        "off" | "false" => Ok(false),
+        _ => Err(format!("{option} must be on or off, not {value}").into()),
+    }
+}
+
+impl InstallOptions {
+    /// Saved options, or the defaults when none were saved or the file is unreadable
+    pub fn load() -> Self {
+        let Ok(path) = options_file_path() else {
+            return Self::default();
+        };
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> crate::Result<()> {
+        let path = options_file_path()?;
+        let json = with_context(
+            serde_json::to_string(self),
+            "Failed to serialize install options",
+        )?;
+        crate::persistence::write_atomic(&path, json.as_bytes())
+    }
+
+    /// Apply a change, returning whether anything changed
+    pub fn set(&mut self, option: InstallOption) -> bool {
+        let before = *self;
+        match option {
+            InstallOption::RunAtLoad(on) => self.run_at_load = on,
+            InstallOption::KeepAlive(keep_alive) => self.keep_alive = keep_alive,
+            InstallOption::ProcessType(kind) => self.process_type = kind,
+            InstallOption::Nice(nice) => self.nice = nice,
+            InstallOption::LowPriorityIo(on) => self.low_priority_io = on,
+        }
+        *self != before
+    }
+
+    /// Priority settings for service details, e.g. "Background · nice 10 · low-priority I/O"
+    pub fn priority_summary(&self) -> String {
+        let mut parts = vec![self.process_type.label().to_string()];
+        if self.nice != 0 {
+            parts.push(format!("nice {}", self.nice));
+        }
+        if self.low_priority_io {
+            parts.push("low-priority I/O".to_string());
+        }
+        parts.join(" · ")
+    }
+
+    /// The RunAtLoad, KeepAlive and priority entries of the plist's top-level dict
+    pub fn plist_entries(&self) -> String {
+        let run_at_load = if self.run_at_load {
+            "<true/>"
+        } else {
+            "<false/>"
+        };
+        let keep_alive = match self.keep_alive {
+            KeepAlive::Never => "<false/>",
+            KeepAlive::Always => "<true/>",
+            // A clean exit (Stop, or llama-swap quitting on its own) stays down
+            KeepAlive::OnCrash => {
+                "<dict>\n        <key>SuccessfulExit</key>\n        <false/>\n    </dict>"
+            }
+        };
+        let mut entries = format!(
+            "    <key>RunAtLoad</key>\n    {run_at_load}\n    <key>KeepAlive</key>\n    {keep_alive}\n"
+        );
+        if self.process_type != ProcessType::Standard {
+            entries.push_str(&format!(
+                "    <key>ProcessType</key>\n    <string>{}</string>\n",
+                self.process_type.label()
+            ));
+        }
+        if self.nice != 0 {
+            entries.push_str(&format!(
+                "    <key>Nice</key>\n    <integer>{}</integer>\n",
+                self.nice
+            ));
+        }
+        if self.low_priority_io {
+            entries.push_str("    <key>LowPriorityIO</key>\n    <true/>\n");
+        }
+        entries
+    }
+}
+
+fn options_file_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::INSTALL_OPTIONS_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_apply_options() {
+        let mut options = InstallOptions::default();
+        assert!(!options.set(InstallOption::parse("run_at_load", "off").unwrap()));
+        assert!(options.set(InstallOption::parse("keep_alive", "on_crash").unwrap()));
+        assert_eq!(options.keep_alive, KeepAlive::OnCrash);
+        assert_eq!(
+            InstallOption::parse("run_at_load", "on").unwrap().args(),
+            ["run_at_load", "on"]
+        );
+        assert!(InstallOption::parse("keep_alive", "sometimes").is_err());
+        assert!(InstallOption::parse("nice", "-5").is_err());
+        assert!(InstallOption::parse("renice", "on").is_err());
+
+        assert_eq!(
+            options.plist_entries(),
+            "    <key>RunAtLoad</key>\n    <false/>\n    <key>KeepAlive</key>\n    <dict>\n        <key>SuccessfulExit</key>\n        <false/>\n    </dict>\n"
+        );
+
+        options.set(InstallOption::parse("process_type", "background").unwrap());
+        options.set(InstallOption::parse("nice", "10").unwrap());
+        options.set(InstallOption::parse("low_priority_io", "on").unwrap());
+        assert_eq!(
+            options.priority_summary(),
+            "Background · nice 10 · low-priority I/O"
+        );
+        assert!(options.plist_entries().ends_with(
+            "    <key>ProcessType</key>\n    <string>Background</string>\n    <key>Nice</key>\n    <integer>10</integer>\n    <key>LowPriorityIO</key>\n    <true/>\n"
+        ));
+    }
+}
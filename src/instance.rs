@@ -0,0 +1,124 @@
+use crate::logging;
+use crate::types::error_helpers::with_context;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a previous instance to shut down before carrying on
+const TAKEOVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ownership of the streaming pidfile
+///
+/// SwiftBar can start a new copy of the plugin (e.g. on refresh) while the old
+/// streaming instance is still alive. The newest instance wins: it asks the
+/// previous one to shut down (SIGTERM, handled like Ctrl-C so history is
+/// persisted) and waits for it before loading state, so two pollers never run
+/// against the API or the persistence files at the same time.
+pub struct InstanceGuard {
+    path: PathBuf,
+    pid: u32,
+}
+
+impl InstanceGuard {
+    /// Take over the pidfile, stopping any previous streaming instance first
+    pub fn acquire() -> crate::Result<Self> {
+        let path = PathBuf::from(crate::commands::expand_tilde(
+            &crate::constants::PID_FILE_PATH,
+        )?);
+        let pid = std::process::id();
+
+        if let Some(previous) = read_pid(&path).filter(|&p| p != pid && is_plugin_process(p)) {
+            logging::info(
+                "instance",
+                "takeover",
+                &format!("Stopping previous plugin instance (pid {previous})"),
+            );
+            stop_process(previous);
+        }
+
+        crate::persistence::write_atomic(&path, format!("{pid}\n").as_bytes())?;
+        Ok(Self { path, pid })
+    }
+
+    /// True once a newer instance has written its pid over ours
+    pub fn is_superseded(&self) -> bool {
+        read_pid(&self.path).is_some_and(|pid| pid != self.pid)
+    }
+}
+
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        // Leave the file alone if a newer instance already owns it
+        if !self.is_superseded() {
+            let _ = with_context(std::fs::remove_file(&self.path), "Failed to remove pidfile");
+        }
+    }
+}
+
+fn read_pid(path: &PathBuf) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Whether `pid` is alive and running the same executable as us (pids get reused)
+fn is_plugin_process(pid: u32) -> bool {
+    let Ok(current_exe) = std::env::current_exe() else {
+        return false;
+    };
+
+    let sys_pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[sys_pid]),
+        sysinfo::ProcessRefreshKind::new().with_exe(sysinfo::UpdateKind::Always),
+    );
+
+    system
+        .process(sys_pid)
+        .and_then(|process| process.exe())
+        .is_some_and(|exe| exe == current_exe)
+}
+
+fn stop_process(pid: u32) {
+    // SAFETY: kill() has no memory-safety preconditions
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return;
+    }
+
+    let deadline = Instant::now() + TAKEOVER_TIMEOUT;
+    while Instant::now() < deadline {
+        // Signal 0 only checks whether the process still exists
+        if unsafe { libc::kill(pid as libc::pid_t, 0) } != 0 {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    logging::warn(
+        "instance",
+        "takeover_timeout",
+        &format!("Previous plugin instance (pid {pid}) is still running"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_tracks_pidfile_ownership() {
+        let dir = std::env::temp_dir().join(format!("llama-swap-instance-{}", std::process::id()));
+        let path = dir.join("streaming.pid");
+        std::env::set_var("LLAMA_SWAP_PID_FILE_PATH", &path);
+
+        let guard = InstanceGuard::acquire().unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+        assert!(!guard.is_superseded());
+
+        // A newer instance overwrote the pidfile; ours must leave it in place
+        std::fs::write(&path, "1\n").unwrap();
+        assert!(guard.is_superseded());
+        drop(guard);
+        assert_eq!(read_pid(&path), Some(1));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
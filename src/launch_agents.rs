@@ -0,0 +1,174 @@
+use crate::constants::{DEFAULT_LAUNCH_AGENT_LABEL, LAUNCH_AGENT_LABEL};
+use crate::types::ServiceStatus;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the other instances are asked of launchctl again while
+/// ~/Library/LaunchAgents is unchanged
+const RESCAN_INTERVAL: Duration = Duration::from_secs(15);
+
+/// An installed llama-swap launch agent: the default instance or a named one
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchAgent {
+    pub name: Option<String>, // None for the default instance
+    pub label: String,
+    pub port: Option<u16>, // From the plist's `-listen :<port>`
+    pub loaded: bool,
+    pub pid: Option<u32>,
+}
+
+impl LaunchAgent {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("default")
+    }
+
+    /// This plugin's own instance
+    pub fn is_current(&self) -> bool {
+        self.label == *LAUNCH_AGENT_LABEL
+    }
+
+    /// Arguments that make a plugin command act on this instance
+    pub fn instance_args(&self) -> Vec<String> {
+        vec!["--instance".to_string(), self.display_name().to_string()]
+    }
+}
+
+/// Arguments that point a plugin command at this plugin's instance
+///
+/// Menu actions and scripts run the plugin binary again without the wrapper
+/// script's environment, so a named instance has to be passed along.
+pub fn instance_args() -> Vec<String> {
+    match crate::constants::INSTANCE.as_deref() {
+        Some(name) => vec!["--instance".to_string(), name.to_string()],
+        None => Vec::new(),
+    }
+}
+
+/// When the launch agents were last discovered, so polls in between only
+/// update this plugin's own agent
+#[derive(Debug, Default)]
+pub struct AgentScan {
+    dir_modified: Option<SystemTime>,
+    last_scan: Option<Instant>,
+}
+
+impl AgentScan {
+    /// Discover the agents again if a plist was added, removed or replaced, or
+    /// the rescan interval elapsed
+    pub fn refresh(&mut self, agents: &mut Vec<LaunchAgent>, current: &ServiceStatus) {
+        let modified =
+            agents_dir().and_then(|dir| std::fs::metadata(dir).and_then(|m| m.modified()).ok());
+        let due = self
+            .last_scan
+            .is_none_or(|at| at.elapsed() >= RESCAN_INTERVAL);
+        if due || modified != self.dir_modified {
+            self.dir_modified = modified;
+            self.last_scan = Some(Instant::now());
+            *agents = discover(current);
+            return;
+        }
+
+        if let Some(agent) = agents.iter_mut().find(|agent| agent.is_current()) {
+            agent.loaded = current.launchctl_loaded;
+            agent.pid = current.pid;
+        }
+    }
+}
+
+fn agents_dir() -> Option<PathBuf> {
+    let home = crate::types::error_helpers::get_home_dir().ok()?;
+    Some(Path::new(&home).join("Library/LaunchAgents"))
+}
+
+/// Installed llama-swap launch agents, default instance first
+///
+/// This plugin's own agent takes its state from `current`, already polled;
+/// the others are asked of launchctl.
+pub fn discover(current: &ServiceStatus) -> Vec<LaunchAgent> {
+    let Some(entries) = agents_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+
+    let mut agents: Vec<LaunchAgent> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let name = parse_agent_file_name(&file_name.to_string_lossy())?;
+            let label = match name {
+                Some(ref name) => format!("{DEFAULT_LAUNCH_AGENT_LABEL}.{name}"),
+                None => DEFAULT_LAUNCH_AGENT_LABEL.to_string(),
+            };
+            let port = std::fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|plist| listen_port(&plist));
+            let mut agent = LaunchAgent {
+                name,
+                label,
+                port,
+                loaded: false,
+                pid: None,
+            };
+            if agent.is_current() {
+                agent.loaded = current.launchctl_loaded;
+                agent.pid = current.pid;
+            } else {
+                (agent.loaded, agent.pid) = crate::service::label_state(&agent.label);
+            }
+            Some(agent)
+        })
+        .collect();
+    agents.sort_by(|a, b| a.name.cmp(&b.name));
+    agents
+}
+
+/// The instance a launch agent plist belongs to: Some(None) for the default
+/// instance's `com.user.llama-swap.plist`, Some(Some(name)) for
+/// `com.user.llama-swap.<name>.plist`, None for anything else
+fn parse_agent_file_name(file_name: &str) -> Option<Option<String>> {
+    let rest = file_name
+        .strip_suffix(".plist")?
+        .strip_prefix(DEFAULT_LAUNCH_AGENT_LABEL)?;
+    if rest.is_empty() {
+        return Some(None);
+    }
+    let name = rest.strip_prefix('.')?;
+    (!name.is_empty() && crate::constants::instance_name(name) == name)
+        .then(|| Some(name.to_string()))
+}
+
+/// The port in the plist's `-listen :<port>` arguments, as install_service writes them
+fn listen_port(plist: &str) -> Option<u16> {
+    let mut strings = plist.lines().filter_map(|line| {
+        line.trim()
+            .strip_prefix("<string>")?
+            .strip_suffix("</string>")
+    });
+    strings.find(|s| *s == "-listen")?;
+    strings.next()?.rsplit(':').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_agent_file_name() {
+        assert_eq!(
+            parse_agent_file_name("com.user.llama-swap.plist"),
+            Some(None)
+        );
+        assert_eq!(
+            parse_agent_file_name("com.user.llama-swap.embeddings.plist"),
+            Some(Some("embeddings".to_string()))
+        );
+        assert_eq!(parse_agent_file_name("com.user.llama-swapper.plist"), None);
+        assert_eq!(parse_agent_file_name("com.apple.Finder.plist"), None);
+    }
+
+    #[test]
+    fn test_listen_port() {
+        let plist = "<array>\n    <string>/opt/homebrew/bin/llama-swap</string>\n    <string>-listen</string>\n    <string>:45787</string>\n</array>";
+        assert_eq!(listen_port(plist), Some(45787));
+        assert_eq!(listen_port("<array></array>"), None);
+    }
+}
@@ -1,14 +1,58 @@
 // Module declarations
+pub mod alerts;
+pub mod binary_check;
 pub mod charts;
 pub mod commands;
+pub mod config_backup;
+pub mod config_parser;
+pub mod config_validation;
 pub mod constants;
+pub mod diagnostics;
+pub mod events;
+pub mod export;
+pub mod exporter;
+pub mod gguf;
+pub mod gpu;
+pub mod hardware;
+pub mod hooks;
+pub mod http;
 pub mod icons;
+pub mod install_options;
+pub mod instance;
+pub mod launch_agents;
+pub mod log_analysis;
+pub mod logging;
 pub mod menu;
 pub mod metrics;
+pub mod model_wizard;
 pub mod models;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod notifications;
+pub mod openmetrics;
+pub mod persistence;
+pub mod plist_drift;
+pub mod plugin_config;
+pub mod policies;
+pub mod port_check;
+pub mod power;
+pub mod remote;
 pub mod service;
+pub mod sessions;
+pub mod ssh_tunnel;
 pub mod state_model;
+pub mod statsd;
+pub mod status;
+#[cfg(feature = "sqlite")]
+pub mod storage;
+pub mod text;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod types;
+pub mod updates;
+pub mod versions;
+pub mod watchdog;
+pub mod webhooks;
 
 // Re-export error type is now in types module
 
@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How far back errors and warnings are counted
+pub const RECENT_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Most of the log read in one poll; anything before it is skipped
+const MAX_SCAN_BYTES: u64 = 256 * 1024;
+
+/// How much of the log is read on the first scan to find the last error
+const FIRST_SCAN_BYTES: u64 = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    /// Error or warning, from the words llama-swap and llama-server log with
+    pub fn classify(line: &str) -> Option<Self> {
+        let line = line.to_ascii_lowercase();
+        if line.contains("error") {
+            Some(Self::Error)
+        } else if line.contains("warn") {
+            Some(Self::Warning)
+        } else {
+            None
+        }
+    }
+}
+
+/// Counts errors and warnings in the service log as it grows
+///
+/// Only the bytes appended since the last poll are read. Log lines carry no
+/// reliable timestamp, so each one is dated by the poll that first saw it;
+/// lines already in the log when the plugin starts only supply the last error.
+#[derive(Debug, Default)]
+pub struct LogAnalyzer {
+    offset: Option<u64>, // End of the last complete line read
+    recent: VecDeque<(Instant, Severity)>,
+    last_error: Option<(Instant, String)>,
+}
+
+impl LogAnalyzer {
+    /// Read whatever the service log gained since the last poll
+    pub fn refresh(&mut self) {
+        if let Ok(path) = crate::commands::expand_tilde(&crate::constants::LOG_FILE_PATH) {
+            self.scan(Path::new(&path), Instant::now());
+        }
+    }
+
+    pub fn scan(&mut self, path: &Path, now: Instant) {
+        self.prune(now);
+
+        let Ok(mut file) = std::fs::File::open(path) else {
+            return;
+        };
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        let first_scan = self.offset.is_none();
+        let previous = self.offset;
+        let start = match self.offset {
+            None => len.saturating_sub(FIRST_SCAN_BYTES),
+            Some(offset) if offset > len => 0, // Truncated or rotated
+            Some(offset) => offset.max(len.saturating_sub(MAX_SCAN_BYTES)),
+        };
+
+        let mut bytes = Vec::new();
+        if file.seek(SeekFrom::Start(start)).is_err() || file.read_to_end(&mut bytes).is_err() {
+            return;
+        }
+        // A line still being written is read whole on the next poll
+        let Some(end) = bytes.iter().rposition(|&b| b == b'\n') else {
+            self.offset.get_or_insert(start);
+            return;
+        };
+        self.offset = Some(start + end as u64 + 1);
+
+        let text = String::from_utf8_lossy(&bytes[..end]);
+        let mut lines = text.lines();
+        if start > 0 && previous != Some(start) {
+            lines.next(); // Starting mid-file, the first line is probably cut off
+        }
+
+        for line in lines {
+            let Some(severity) = Severity::classify(line) else {
+                continue;
+            };
+            if severity == Severity::Error {
+                self.last_error = Some((now, line.trim().to_string()));
+            }
+            if !first_scan {
+                self.recent.push_back((now, severity));
+            }
+        }
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|&(seen, _)| now.duration_since(seen) > RECENT_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Errors and warnings seen within the last five minutes
+    pub fn recent_counts(&self) -> (usize, usize) {
+        let errors = self
+            .recent
+            .iter()
+            .filter(|(_, severity)| *severity == Severity::Error)
+            .count();
+        (errors, self.recent.len() - errors)
+    }
+
+    /// The most recent error line, and how long ago it was seen
+    pub fn last_error(&self) -> Option<(Duration, &str)> {
+        self.last_error
+            .as_ref()
+            .map(|(seen, line)| (seen.elapsed(), line.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_scan_counts_only_new_lines() {
+        let dir = std::env::temp_dir().join(format!("log-analysis-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("llama-swap.log");
+        std::fs::write(&path, "old error before startup\nstarted\n").unwrap();
+
+        let start = Instant::now();
+        let mut analyzer = LogAnalyzer::default();
+        analyzer.scan(&path, start);
+        assert_eq!(analyzer.recent_counts(), (0, 0));
+        assert_eq!(analyzer.last_error().unwrap().1, "old error before startup");
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, "warn: slow\nerror: out of memory\nerror: half wri").unwrap();
+        analyzer.scan(&path, start + Duration::from_secs(10));
+        assert_eq!(analyzer.recent_counts(), (1, 1));
+        assert_eq!(analyzer.last_error().unwrap().1, "error: out of memory");
+
+        // The unfinished line is counted once it ends
+        writeln!(file, "tten").unwrap();
+        analyzer.scan(&path, start + Duration::from_secs(20));
+        assert_eq!(analyzer.recent_counts(), (2, 1));
+        assert_eq!(analyzer.last_error().unwrap().1, "error: half written");
+
+        analyzer.scan(&path, start + RECENT_WINDOW + Duration::from_secs(15));
+        assert_eq!(analyzer.recent_counts(), (1, 0));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
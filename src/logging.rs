@@ -0,0 +1,395 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Severity of a plugin log event
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Output format for the plugin's own logs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Text, // Plain lines on stderr (picked up by SwiftBar)
+    Json, // Plain lines on stderr plus JSON lines appended to the plugin log file
+}
+
+impl LogFormat {
+    pub fn from_env_value(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// One structured log line
+#[derive(Debug, Serialize)]
+pub struct LogRecord<'a> {
+    pub ts: String,
+    pub level: LogLevel,
+    pub module: &'a str,
+    pub event: &'a str,
+    pub details: &'a str,
+}
+
+/// Window over which the rate limit is enforced
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct RepeatedLine {
+    last_emitted: Instant,
+    suppressed: usize,
+    level: LogLevel,
+    details: String,
+}
+
+/// Decides which log lines are written, so a failing poll doesn't flood the log
+///
+/// A line identical to one emitted within the dedup window is counted instead of
+/// written, and at most `rate_limit` lines are written per minute. Whenever a line
+/// does get written, summaries for everything suppressed so far are written first.
+#[derive(Debug)]
+pub struct LogLimiter {
+    dedup_window: Duration,
+    rate_limit: usize,
+    recent: HashMap<String, RepeatedLine>,
+    emitted_at: VecDeque<Instant>,
+    rate_limited: usize,
+}
+
+impl LogLimiter {
+    pub fn new(dedup_window: Duration, rate_limit: usize) -> Self {
+        Self {
+            dedup_window,
+            rate_limit,
+            recent: HashMap::new(),
+            emitted_at: VecDeque::new(),
+            rate_limited: 0,
+        }
+    }
+
+    fn from_env() -> Self {
+        Self::new(
+            Duration::from_secs(*crate::constants::LOG_DEDUP_WINDOW_SECS),
+            *crate::constants::LOG_RATE_LIMIT_PER_MIN,
+        )
+    }
+
+    /// Returns None if the line should be suppressed, otherwise the summary
+    /// lines (level, text) to write before it
+    pub fn admit(
+        &mut self,
+        level: LogLevel,
+        module: &str,
+        event: &str,
+        details: &str,
+        now: Instant,
+    ) -> Option<Vec<(LogLevel, String)>> {
+        let key = format!("{module}|{event}|{details}");
+
+        if !self.dedup_window.is_zero() {
+            if let Some(line) = self.recent.get_mut(&key) {
+                if now.duration_since(line.last_emitted) < self.dedup_window {
+                    line.suppressed += 1;
+                    return None;
+                }
+            }
+        }
+
+        if self.rate_limit > 0 {
+            while self
+                .emitted_at
+                .front()
+                .is_some_and(|at| now.duration_since(*at) >= RATE_LIMIT_WINDOW)
+            {
+                self.emitted_at.pop_front();
+            }
+            if self.emitted_at.len() >= self.rate_limit {
+                self.rate_limited += 1;
+                return None;
+            }
+            self.emitted_at.push_back(now);
+        }
+
+        let summaries = self.take_summaries();
+
+        if !self.dedup_window.is_zero() {
+            let window = self.dedup_window;
+            self.recent
+                .retain(|_, line| now.duration_since(line.last_emitted) < window);
+            self.recent.insert(
+                key,
+                RepeatedLine {
+                    last_emitted: now,
+                    suppressed: 0,
+                    level,
+                    details: details.to_string(),
+                },
+            );
+        }
+
+        Some(summaries)
+    }
+
+    /// Summaries of everything suppressed so far, resetting the counters
+    fn take_summaries(&mut self) -> Vec<(LogLevel, String)> {
+        let mut summaries: Vec<(LogLevel, String)> = self
+            .recent
+            .values_mut()
+            .filter(|line| line.suppressed > 0)
+            .map(|line| {
+                let count = std::mem::take(&mut line.suppressed);
+                (
+                    line.level,
+                    format!("Last message repeated {count} times: {}", line.details),
+                )
+            })
+            .collect();
+
+        if self.rate_limited > 0 {
+            summaries.push((
+                LogLevel::Warn,
+                format!(
+                    "{} log lines suppressed by rate limit",
+                    std::mem::take(&mut self.rate_limited)
+                ),
+            ));
+        }
+
+        summaries
+    }
+}
+
+static LIMITER: LazyLock<Mutex<LogLimiter>> = LazyLock::new(|| Mutex::new(LogLimiter::from_env()));
+
+/// Set while a live terminal view owns the terminal; lines then go to the plugin log file
+static STDERR_DISABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn disable_stderr() {
+    STDERR_DISABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn debug(module: &str, event: &str, details: &str) {
+    log(LogLevel::Debug, module, event, details);
+}
+
+pub fn info(module: &str, event: &str, details: &str) {
+    log(LogLevel::Info, module, event, details);
+}
+
+pub fn warn(module: &str, event: &str, details: &str) {
+    log(LogLevel::Warn, module, event, details);
+}
+
+pub fn error(module: &str, event: &str, details: &str) {
+    log(LogLevel::Error, module, event, details);
+}
+
+/// Emit a log event in the configured format, subject to deduplication and rate limiting
+pub fn log(level: LogLevel, module: &str, event: &str, details: &str) {
+    let admitted = LIMITER
+        .lock()
+        .map(|mut limiter| limiter.admit(level, module, event, details, Instant::now()))
+        .unwrap_or(Some(Vec::new()));
+    let Some(summaries) = admitted else {
+        return;
+    };
+
+    for (summary_level, summary) in &summaries {
+        write_event(*summary_level, "logging", "repeated", summary);
+    }
+    write_event(level, module, event, details);
+}
+
+/// Write one log event
+///
+/// stderr receives the human-readable line so SwiftBar's plugin log keeps working
+/// (the plugin log file takes its place in the terminal UI); JSON mode additionally
+/// appends a structured record to the log file.
+fn write_event(level: LogLevel, module: &str, event: &str, details: &str) {
+    let json = *crate::constants::PLUGIN_LOG_FORMAT == LogFormat::Json;
+
+    if !STDERR_DISABLED.load(Ordering::Relaxed) {
+        eprintln!("{details}");
+    } else if !json {
+        let line = format!(
+            "{} {level:?} {details}\n",
+            format_rfc3339(SystemTime::now())
+        );
+        let _ = append_line(&crate::constants::PLUGIN_LOG_FILE_PATH, &line);
+    }
+
+    if json {
+        let record = LogRecord {
+            ts: format_rfc3339(SystemTime::now()),
+            level,
+            module,
+            event,
+            details,
+        };
+        if let Err(e) = append_json_line(&record) {
+            if !STDERR_DISABLED.load(Ordering::Relaxed) {
+                eprintln!("Failed to write plugin log: {e}");
+            }
+        }
+    }
+}
+
+/// Record an action the plugin took on the user's behalf
+///
+/// Audit entries always go to the audit log file (regardless of log format) so
+/// automatic restarts and similar actions can be reviewed after the fact.
+pub fn audit(event: &str, details: &str) {
+    log(LogLevel::Warn, "audit", event, details);
+
+    let line = format!("{} {event} {details}\n", format_rfc3339(SystemTime::now()));
+    if let Err(e) = append_line(&crate::constants::AUDIT_LOG_FILE_PATH, &line) {
+        eprintln!("Failed to write audit log: {e}");
+    }
+}
+
+fn append_json_line(record: &LogRecord) -> crate::Result<()> {
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    append_line(&crate::constants::PLUGIN_LOG_FILE_PATH, &line)
+}
+
+fn append_line(path: &str, line: &str) -> crate::Result<()> {
+    let path = crate::commands::expand_tilde(path)?;
+    if let Some(parent) = std::path::Path::new(&path).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Format a timestamp as UTC RFC 3339 with millisecond precision
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let millis = since_epoch.subsec_millis();
+
+    let days = (secs / 86_400) as i64;
+    let secs_of_day = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{millis:03}Z",
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) civil date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's days-to-civil algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(format_rfc3339(time), "2024-02-29T12:34:56.789Z");
+    }
+
+    #[test]
+    fn test_limiter_collapses_repeated_lines() {
+        let mut limiter = LogLimiter::new(Duration::from_secs(60), 0);
+        let start = Instant::now();
+        let admit = |limiter: &mut LogLimiter, details: &str, secs: u64| {
+            limiter.admit(
+                LogLevel::Warn,
+                "state",
+                "metrics_error",
+                details,
+                start + Duration::from_secs(secs),
+            )
+        };
+
+        assert_eq!(admit(&mut limiter, "fetch failed", 0), Some(Vec::new()));
+        assert_eq!(admit(&mut limiter, "fetch failed", 3), None);
+        assert_eq!(admit(&mut limiter, "fetch failed", 6), None);
+
+        // A different line flushes the pending count first
+        assert_eq!(
+            admit(&mut limiter, "recovered", 9),
+            Some(vec![(
+                LogLevel::Warn,
+                "Last message repeated 2 times: fetch failed".to_string()
+            )])
+        );
+
+        // The same line is written again once the window has passed
+        assert_eq!(admit(&mut limiter, "fetch failed", 61), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_limiter_rate_limits_distinct_lines() {
+        let mut limiter = LogLimiter::new(Duration::ZERO, 2);
+        let start = Instant::now();
+
+        for (i, expected) in [true, true, false, false].into_iter().enumerate() {
+            let admitted = limiter.admit(LogLevel::Info, "m", "e", &i.to_string(), start);
+            assert_eq!(admitted.is_some(), expected);
+        }
+
+        let summaries = limiter
+            .admit(LogLevel::Info, "m", "e", "later", start + RATE_LIMIT_WINDOW)
+            .unwrap();
+        assert_eq!(
+            summaries,
+            vec![(
+                LogLevel::Warn,
+                "2 log lines suppressed by rate limit".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_log_record_serialization() {
+        let record = LogRecord {
+            ts: "2024-02-29T12:34:56.789Z".to_string(),
+            level: LogLevel::Warn,
+            module: "commands",
+            event: "bootstrap_warning",
+            details: "already loaded",
+        };
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            json,
+            r#"{"ts":"2024-02-29T12:34:56.789Z","level":"warn","module":"commands","event":"bootstrap_warning","details":"already loaded"}"#
+        );
+    }
+}
@@ -1,18 +1,62 @@
+mod alerts;
+mod binary_check;
 mod charts;
 mod commands;
+mod config_backup;
+mod config_parser;
+mod config_validation;
 mod constants;
+mod diagnostics;
+mod events;
+mod export;
+mod exporter;
+mod gguf;
+mod gpu;
+mod hardware;
+mod hooks;
+mod http;
 mod icons;
+mod install_options;
+mod instance;
+mod launch_agents;
+mod log_analysis;
+mod logging;
 mod menu;
 mod metrics;
+mod model_wizard;
 mod models;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod notifications;
+mod openmetrics;
+mod persistence;
+mod plist_drift;
+mod plugin_config;
+mod policies;
+mod port_check;
+mod power;
+mod remote;
 mod service;
+mod sessions;
+mod ssh_tunnel;
 mod state_model;
+mod statsd;
+mod status;
+#[cfg(feature = "sqlite")]
+mod storage;
+mod text;
+#[cfg(feature = "tui")]
+mod tui;
 mod types;
+mod updates;
+mod versions;
+mod watchdog;
+mod webhooks;
 
 // All imports are now handled in types.rs
 use crate::types::{PluginState, Result};
 use std::error::Error;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 // Removed AtomicBool import as we now use channels for shutdown signaling
 use std::sync::mpsc;
 // Removed thread import as adaptive_sleep no longer uses thread::sleep
@@ -32,7 +76,7 @@ fn main() {
 
 fn setup_panic_handler() {
     std::panic::set_hook(Box::new(|panic_info| {
-        eprintln!("Plugin panic: {panic_info:?}");
+        logging::error("main", "panic", &format!("Plugin panic: {panic_info:?}"));
         if let Ok(menu) = menu::build_error_menu("Plugin encountered an error") {
             print!("{menu}");
         }
@@ -40,7 +84,7 @@ fn setup_panic_handler() {
 }
 
 fn handle_error(error: Box<dyn Error>) {
-    eprintln!("Plugin error: {error:?}");
+    logging::error("main", "plugin_error", &format!("Plugin error: {error:?}"));
     if let Ok(menu) = menu::build_error_menu(&format!("Error: {error}")) {
         print!("{menu}");
     }
@@ -48,85 +92,246 @@ fn handle_error(error: Box<dyn Error>) {
 }
 
 fn run() -> Result<()> {
-    if let Some(command) = std::env::args().nth(1) {
-        return commands::handle_command(&command);
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // `--instance <name>` picks the instance before any setting is read; menu
+    // actions pass it since they run without the wrapper script's environment
+    if args.len() >= 2 && args[0] == "--instance" {
+        std::env::set_var("LLAMA_SWAP_INSTANCE", &args[1]);
+        args.drain(..2);
+    }
+    if let Some((command, rest)) = args.split_first() {
+        if command == "tui" {
+            return run_tui();
+        }
+        if command == "status_json" || command == "--json" {
+            return run_status_json();
+        }
+        if command == "status_line" {
+            return run_status_line();
+        }
+        if command == "watch" {
+            return text::run(true);
+        }
+        return commands::handle_reported_command(command, rest);
     }
 
     let is_swiftbar = std::env::var("SWIFTBAR").is_ok();
 
     if *constants::STREAMING_MODE && is_swiftbar {
         run_streaming_mode()
+    } else if !is_swiftbar && io::stdout().is_terminal() {
+        // Run by hand: readable text; piped output keeps the SwiftBar markup
+        text::run(false)
     } else {
         run_once()
     }
 }
 
+/// What the collector thread hands to the render loop
+enum Message {
+    Frame(String),
+    Shutdown,       // Ctrl-C / SIGTERM
+    Failed(String), // The collector hit an error it can't recover from
+    Finished,       // The collector stopped (shut down or superseded)
+}
+
+/// Sends `Finished` however the collector returns, so the render loop never
+/// waits on a thread that is gone
+///
+/// Only unwinding runs this on a panic: release builds abort instead, which
+/// takes the render loop down with the collector.
+struct FinishOnDrop(mpsc::Sender<Message>);
+
+impl Drop for FinishOnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.send(Message::Finished);
+    }
+}
+
 fn run_streaming_mode() -> Result<()> {
-    let shutdown_rx = setup_shutdown_handler()?;
+    let (tx, rx) = mpsc::channel();
+    setup_shutdown_handler(tx.clone())?;
+    // Claim the pidfile before loading state so a previous instance can persist first
+    let instance = instance::InstanceGuard::acquire()
+        .inspect_err(|e| {
+            logging::warn(
+                "instance",
+                "pidfile_failed",
+                &format!("Failed to claim plugin pidfile: {e}"),
+            )
+        })
+        .ok();
     let mut state = PluginState::new()?;
+    state.streaming = true;
+    // Only the long-running instance serves scrapes
+    state.start_exporter();
 
-    eprintln!("Starting adaptive polling mode");
+    logging::info(
+        "main",
+        "streaming_started",
+        "Starting adaptive polling mode",
+    );
+
+    // Polling (HTTP calls, launchctl, CPU sampling) runs on its own thread and
+    // sends each finished frame over; the render loop only writes them out
+    let (stop_tx, stop_rx) = mpsc::channel();
+    let collector = std::thread::spawn(move || run_collector(state, instance, &tx, &stop_rx));
+
+    let mut result = Ok(());
+    for message in &rx {
+        match message {
+            Message::Frame(frame) => {
+                print!("~~~\n{frame}");
+                io::stdout().flush()?;
+            }
+            // The collector persists history before reporting Finished
+            Message::Shutdown => {
+                let _ = stop_tx.send(());
+            }
+            Message::Failed(error) => {
+                result = Err(error.into());
+                break;
+            }
+            Message::Finished => break,
+        }
+    }
+    if collector.join().is_err() {
+        return Err("Metrics collector panicked".into());
+    }
+
+    logging::info("main", "shutdown", "Plugin shutting down gracefully");
+    result
+}
+
+fn run_collector(
+    mut state: PluginState,
+    instance: Option<instance::InstanceGuard>,
+    tx: &mpsc::Sender<Message>,
+    stop_rx: &mpsc::Receiver<()>,
+) {
+    let _finished = FinishOnDrop(tx.clone());
 
     loop {
         let loop_start = Instant::now();
 
-        let frame = render_frame(&mut state)?;
-        print!("~~~\n{frame}");
-        io::stdout().flush()?;
+        match render_frame(&mut state) {
+            Ok(frame) => {
+                if tx.send(Message::Frame(frame)).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                let _ = tx.send(Message::Failed(e.to_string()));
+                return;
+            }
+        }
+
+        state.apply_recovery_policies();
+        state.checkpoint_metrics();
 
         let sleep_duration = state.polling_mode.interval();
-        adaptive_sleep(sleep_duration, &shutdown_rx);
+        // Stop if we received a shutdown signal during sleep
+        if adaptive_sleep(sleep_duration, stop_rx) {
+            break;
+        }
 
-        // Check if we received a shutdown signal during sleep
-        if shutdown_rx.try_recv().is_ok() {
+        // A newer instance took over without managing to stop us
+        if is_superseded(&instance) {
+            logging::info(
+                "main",
+                "superseded",
+                "Another plugin instance has taken over; exiting",
+            );
             break;
         }
 
         log_slow_iteration(loop_start, &state);
     }
 
-    eprintln!("Plugin shutting down gracefully");
-    Ok(())
+    // A superseded instance's history is stale; the newer one owns the file now
+    if *constants::PERSIST_INTERVAL_SECS > 0 && !is_superseded(&instance) {
+        state.persist_metrics();
+    }
+}
+
+fn is_superseded(instance: &Option<instance::InstanceGuard>) -> bool {
+    instance.as_ref().is_some_and(|guard| guard.is_superseded())
+}
+
+#[cfg(feature = "tui")]
+fn run_tui() -> Result<()> {
+    tui::run()
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui() -> Result<()> {
+    Err("This build does not include the terminal UI; rebuild with --features tui".into())
 }
 
 fn run_once() -> Result<()> {
     let mut state = PluginState::new()?;
+    state.prime_cpu_usage();
     let frame = render_frame(&mut state)?;
     print!("{frame}");
     Ok(())
 }
 
+/// One poll, printed as JSON for scripts instead of a SwiftBar menu
+fn run_status_json() -> Result<()> {
+    let mut state = PluginState::new()?;
+    state.prime_cpu_usage();
+    state.update_state();
+    let snapshot = status::StatusSnapshot::from_state(&state);
+    println!("{}", serde_json::to_string_pretty(&snapshot)?);
+    Ok(())
+}
+
+fn run_status_line() -> Result<()> {
+    let mut state = PluginState::new()?;
+    state.prime_cpu_usage();
+    state.update_state();
+    println!("{}", text::status_line(&state));
+    Ok(())
+}
+
 fn render_frame(state: &mut PluginState) -> Result<String> {
     state.update_state();
+    state.export_openmetrics();
+    state.forward_statsd();
+    #[cfg(feature = "mqtt")]
+    state.publish_mqtt();
     menu::build_menu(state)
 }
 
-fn setup_shutdown_handler() -> Result<mpsc::Receiver<()>> {
-    let (tx, rx) = mpsc::channel();
-
+fn setup_shutdown_handler(tx: mpsc::Sender<Message>) -> Result<()> {
     ctrlc::set_handler(move || {
-        let _ = tx.send(()); // Ignore send errors if receiver is dropped
+        let _ = tx.send(Message::Shutdown); // Ignore send errors if receiver is dropped
     })?;
 
-    Ok(rx)
+    Ok(())
 }
 
-fn adaptive_sleep(duration: Duration, shutdown_rx: &mpsc::Receiver<()>) {
-    let _ = shutdown_rx.recv_timeout(duration);
-    // If recv_timeout returns Ok(()), we got a shutdown signal
-    // If it returns Err(RecvTimeoutError::Timeout), the duration elapsed
-    // If it returns Err(RecvTimeoutError::Disconnected), the sender was dropped (also treat as shutdown)
-    // In all cases, we just return - the caller will check if shutdown was requested
+fn adaptive_sleep(duration: Duration, shutdown_rx: &mpsc::Receiver<()>) -> bool {
+    // Ok(()) means a shutdown signal arrived; a dropped sender is treated the same way.
+    // Only a timeout means the full duration elapsed normally.
+    !matches!(
+        shutdown_rx.recv_timeout(duration),
+        Err(mpsc::RecvTimeoutError::Timeout)
+    )
 }
 
 fn log_slow_iteration(loop_start: Instant, state: &PluginState) {
     if cfg!(debug_assertions) {
         let loop_duration = loop_start.elapsed();
         if loop_duration > Duration::from_millis(500) {
-            eprintln!(
-                "Slow loop iteration: {:?} (mode: {})",
-                loop_duration,
-                state.polling_mode.description()
+            logging::debug(
+                "main",
+                "slow_iteration",
+                &format!(
+                    "Slow loop iteration: {:?} (mode: {})",
+                    loop_duration,
+                    state.polling_mode.description()
+                ),
             );
         }
     }
@@ -1,4 +1,6 @@
-use crate::models::{AllMetricsHistory, MetricsHistory, TimestampedValue};
+use crate::alerts::{AlertMonitor, Breach};
+use crate::log_analysis::{LogAnalyzer, Severity};
+use crate::models::{AllMetricsHistory, MemoryPressure, MetricsHistory, TimestampedValue};
 use crate::state_model::DisplayState;
 use crate::{charts, icons};
 use bitbar::{ContentItem, Menu, MenuItem};
@@ -7,6 +9,21 @@ use circular_queue::CircularQueue;
 // Use the shared PluginState
 use crate::types::PluginState;
 
+/// Number of state transitions listed under Advanced → Event History
+const EVENT_HISTORY_ITEMS: usize = 20;
+
+/// Service log lines shown when a start failed
+const STARTUP_LOG_LINES: usize = 10;
+
+/// How much of each service log line fits in the menu
+const LOG_LINE_CHARS: usize = 120;
+
+/// Service log lines listed under Recent Logs
+const RECENT_LOG_LINES: usize = 20;
+
+/// Config backups offered under Restore Config…
+const RESTORE_MENU_BACKUPS: usize = 10;
+
 /// Helper function to create colored menu items
 fn create_colored_item(text: &str, color: &str) -> ContentItem {
     ContentItem::new(text).color(color).unwrap()
@@ -14,14 +31,32 @@ fn create_colored_item(text: &str, color: &str) -> ContentItem {
 
 /// Helper function to create command menu items
 fn create_command_item(text: &str, exe_path: &str, action: &str) -> crate::Result<ContentItem> {
-    let command = bitbar::attr::Command::try_from((exe_path, action))?;
+    let command = plugin_command(exe_path, &[action]);
     Ok(ContentItem::new(text).command(command)?)
 }
 
+/// Run the plugin binary with `args`, acting on this menu's instance
+fn plugin_command(exe_path: &str, args: &[&str]) -> bitbar::attr::Command {
+    let mut params = crate::launch_agents::instance_args();
+    params.extend(args.iter().map(|arg| arg.to_string()));
+    bitbar::attr::Command::from(bitbar::attr::Params::new(exe_path.to_string(), params))
+}
+
 /// Convert program state color names to hex codes
 fn get_hex_color(color: &str) -> &'static str {
+    if *crate::constants::COLOR_BLIND {
+        return match color {
+            "red" => "#D55E00",    // Vermillion
+            "purple" => "#CC79A7", // Reddish purple
+            "yellow" => "#E69F00", // Orange
+            "green" => "#009E73",  // Bluish green
+            "blue" => "#0072B2",
+            _ => "#8E8E93",
+        };
+    }
     match color {
         "red" => "#FF3B30",    // Problems/action required
+        "purple" => "#AF52DE", // Degraded/unresponsive
         "grey" => "#8E8E93",   // Idle/neutral
         "yellow" => "#FF9500", // Transitional/loading
         "green" => "#34C759",  // Ready with models
@@ -30,6 +65,124 @@ fn get_hex_color(color: &str) -> &'static str {
     }
 }
 
+/// Submenu of recent config.yaml backups, each restoring that backup
+fn restore_config_item(exe: &str) -> Option<ContentItem> {
+    if *crate::constants::READ_ONLY_MODE {
+        return None;
+    }
+    let backups = crate::config_backup::list();
+    if backups.is_empty() {
+        return None;
+    }
+
+    let now = std::time::SystemTime::now();
+    let items: Vec<MenuItem> = backups
+        .iter()
+        .take(RESTORE_MENU_BACKUPS)
+        .map(|backup| {
+            let name = backup.file_name();
+            let age = now.duration_since(backup.created).unwrap_or_default();
+            let command = plugin_command(exe, &["restore_config", &name]);
+            let text = format!("{} ago — {name}", format_duration(age.as_secs()));
+            MenuItem::Content(ContentItem::new(text).command(command).unwrap())
+        })
+        .collect();
+    Some(ContentItem::new(":clock.arrow.circlepath: Restore Config…").sub(items))
+}
+
+/// "Install Options" submenu: whether launchd starts the service at login,
+/// restarts it when it exits and at what priority it runs; a change rewrites
+/// the plist
+fn install_options_item(exe: &str) -> Option<ContentItem> {
+    use crate::install_options::{InstallOption, InstallOptions, KeepAlive, ProcessType};
+
+    if *crate::constants::READ_ONLY_MODE {
+        return None;
+    }
+    let options = InstallOptions::load();
+    let item = |text: String, option: InstallOption| {
+        let [name, value] = option.args();
+        let command = plugin_command(exe, &["set_install_option", &name, &value]);
+        MenuItem::Content(ContentItem::new(text).command(command).unwrap())
+    };
+
+    let toggle = |on: bool| if on { ":checkmark.square:" } else { ":square:" };
+    let choice = |selected: bool| if selected { ":checkmark:" } else { ":circle:" };
+    let header = |text: &str| MenuItem::Content(create_colored_item(text, "#666666"));
+
+    let mut items = vec![item(
+        format!("{} Start at Login", toggle(options.run_at_load)),
+        InstallOption::RunAtLoad(!options.run_at_load),
+    )];
+    items.push(MenuItem::Sep);
+    items.push(header("Restart When It Exits"));
+    for keep_alive in KeepAlive::ALL {
+        items.push(item(
+            format!(
+                "{} {}",
+                choice(keep_alive == options.keep_alive),
+                keep_alive.label()
+            ),
+            InstallOption::KeepAlive(keep_alive),
+        ));
+    }
+
+    items.push(MenuItem::Sep);
+    items.push(header("Process Type"));
+    for kind in ProcessType::ALL {
+        items.push(item(
+            format!("{} {}", choice(kind == options.process_type), kind.label()),
+            InstallOption::ProcessType(kind),
+        ));
+    }
+    items.push(MenuItem::Sep);
+    items.push(header("Nice"));
+    for nice in crate::install_options::NICE_CHOICES {
+        let label = match nice {
+            0 => "0 (Normal)".to_string(),
+            19 => "19 (Lowest)".to_string(),
+            _ => nice.to_string(),
+        };
+        items.push(item(
+            format!("{} {label}", choice(nice == options.nice)),
+            InstallOption::Nice(nice),
+        ));
+    }
+    items.push(MenuItem::Sep);
+    items.push(item(
+        format!("{} Low-Priority I/O", toggle(options.low_priority_io)),
+        InstallOption::LowPriorityIo(!options.low_priority_io),
+    ));
+    Some(ContentItem::new(":gearshape.2: Install Options").sub(items))
+}
+
+/// A service log line in a monospaced font, truncated to fit the menu
+fn log_line_item(line: &str) -> ContentItem {
+    let text = if line.chars().count() > LOG_LINE_CHARS {
+        let truncated: String = line.chars().take(LOG_LINE_CHARS).collect();
+        format!("{truncated}…")
+    } else {
+        line.to_string()
+    };
+    let item = ContentItem::new(text).font("Menlo").size(11);
+    match log_line_color(line) {
+        Some(color) => item.color(color).unwrap(),
+        None => item,
+    }
+}
+
+/// Red for error lines and orange for warnings
+fn log_line_color(line: &str) -> Option<&'static str> {
+    match Severity::classify(line)? {
+        Severity::Error => Some(get_hex_color("red")),
+        Severity::Warning => Some(get_hex_color("yellow")),
+    }
+}
+
+fn rgb_to_hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
+
 /// Menu command configuration
 struct MenuCommand {
     icon: &'static str,
@@ -58,6 +211,10 @@ static CONTROL_COMMANDS: &[MenuCommand] = &[
             DisplayState::ModelProcessingQueue,
             DisplayState::ModelReady,
             DisplayState::ServiceLoadedNoModel,
+            DisplayState::ModelStopping,
+            DisplayState::ServiceDegraded,
+            DisplayState::StartupFailed,
+            DisplayState::CrashLoop,
             DisplayState::AgentStarting,
         ],
     },
@@ -69,21 +226,86 @@ static CONTROL_COMMANDS: &[MenuCommand] = &[
     },
 ];
 
-static FILE_COMMANDS: &[MenuCommand] = &[MenuCommand {
-    icon: ":gearshape:",
-    label: "Edit Llama-Swap Configuration",
-    action: "view_config",
-    states: &[], // Available in all states
-}];
+static FILE_COMMANDS: &[MenuCommand] = &[
+    MenuCommand {
+        icon: ":doc.text:",
+        label: "Open in TextEdit",
+        action: "view_config",
+        states: &[], // Available in all states
+    },
+    MenuCommand {
+        icon: ":chevron.left.forwardslash.chevron.right:",
+        label: "Open in Editor",
+        action: "edit_config",
+        states: &[],
+    },
+    MenuCommand {
+        icon: ":folder:",
+        label: "Reveal in Finder",
+        action: "reveal_config",
+        states: &[],
+    },
+    MenuCommand {
+        icon: ":checkmark.seal:",
+        label: "Validate",
+        action: "validate_config",
+        states: &[],
+    },
+    MenuCommand {
+        icon: ":externaldrive.badge.timemachine:",
+        label: "Back Up Now",
+        action: "backup_config",
+        states: &[],
+    },
+];
+/// States in which the API is responsive
+const API_STATES: &[DisplayState] = &[
+    DisplayState::ModelProcessingQueue,
+    DisplayState::ModelReady,
+    DisplayState::ServiceLoadedNoModel,
+];
+
 static UI_COMMAND: MenuCommand = MenuCommand {
     icon: ":globe:",
     label: "Open Llama-Swap UI",
     action: "open_ui",
-    states: &[
-        DisplayState::ModelProcessingQueue,
-        DisplayState::ModelReady,
-        DisplayState::ServiceLoadedNoModel,
-    ], // Only when API is responsive
+    states: API_STATES, // Only when API is responsive (and the UI loads)
+};
+
+static EXPORT_COMMANDS: &[MenuCommand] = &[
+    MenuCommand {
+        icon: ":tablecells:",
+        label: "As CSV",
+        action: "export_csv",
+        states: &[],
+    },
+    MenuCommand {
+        icon: ":curlybraces:",
+        label: "As JSON",
+        action: "export_json",
+        states: &[],
+    },
+];
+
+static COPY_ENDPOINT_COMMAND: MenuCommand = MenuCommand {
+    icon: ":doc.on.clipboard:",
+    label: "Copy API Endpoint",
+    action: "copy_api_endpoint",
+    states: API_STATES, // Offered in place of the UI when only the API answers
+};
+
+static DIAGNOSTICS_COMMAND: MenuCommand = MenuCommand {
+    icon: ":stethoscope:",
+    label: "Copy Diagnostics",
+    action: "copy_diagnostics",
+    states: &[],
+};
+
+static CLEAR_HISTORY_COMMAND: MenuCommand = MenuCommand {
+    icon: ":trash.slash:",
+    label: "Clear Metrics History",
+    action: "clear_history",
+    states: &[],
 };
 
 static RESTART_COMMAND: MenuCommand = MenuCommand {
@@ -93,6 +315,32 @@ static RESTART_COMMAND: MenuCommand = MenuCommand {
     states: &[], // Available in all states
 };
 
+/// Offered when config.yaml was saved after the running service started
+static CONFIG_CHANGED_COMMAND: MenuCommand = MenuCommand {
+    icon: ":arrow.clockwise.circle.fill:",
+    label: "Config changed — Restart to apply",
+    action: "do_restart",
+    states: &[],
+};
+
+static REINSTALL_COMMAND: MenuCommand = MenuCommand {
+    icon: ":arrow.triangle.2.circlepath.circle.fill:",
+    label: "Service definition outdated — Reinstall",
+    action: "do_reinstall",
+    states: &[],
+};
+
+static VIEW_LOGS_COMMAND: MenuCommand = MenuCommand {
+    icon: ":doc.text.magnifyingglass:",
+    label: "View Service Logs",
+    action: "view_logs",
+    states: &[
+        DisplayState::ServiceDegraded,
+        DisplayState::StartupFailed,
+        DisplayState::CrashLoop,
+    ],
+};
+
 static INSTALL_COMMAND: MenuCommand = MenuCommand {
     icon: ":arrow.down.doc:",
     label: "Install Llama-Swap Service",
@@ -100,6 +348,19 @@ static INSTALL_COMMAND: MenuCommand = MenuCommand {
     states: &[DisplayState::AgentNotLoaded], // Only when not installed
 };
 
+static INSTALL_BINARY_COMMAND: MenuCommand = MenuCommand {
+    icon: ":shippingbox:",
+    label: "Install llama-swap with Homebrew",
+    action: "do_install_binary",
+    states: &[DisplayState::AgentNotLoaded], // Only when the binary is missing
+};
+
+/// Offered when llama-swap isn't on the PATH and Homebrew can install it
+fn install_binary_item(exe_str: &str) -> Option<ContentItem> {
+    crate::commands::find_brew()?;
+    INSTALL_BINARY_COMMAND.create_item(exe_str).ok()
+}
+
 static UNINSTALL_COMMAND: MenuCommand = MenuCommand {
     icon: ":trash:",
     label: "Uninstall Llama-Swap Service",
@@ -107,12 +368,30 @@ static UNINSTALL_COMMAND: MenuCommand = MenuCommand {
     states: &[], // Available when installed (all states except AgentNotLoaded)
 };
 
+/// "Open Llama-Swap UI" when the web UI loads, otherwise "Copy API Endpoint"
+fn ui_command(service_status: &crate::types::ServiceStatus) -> &'static MenuCommand {
+    if service_status.ui_available {
+        &UI_COMMAND
+    } else {
+        &COPY_ENDPOINT_COMMAND
+    }
+}
+
 impl MenuCommand {
     fn is_available_for_state(&self, state: DisplayState) -> bool {
         self.states.is_empty() || self.states.contains(&state)
     }
 
+    /// Mutating commands are hidden entirely when the plugin runs in read-only mode
+    fn is_permitted(&self) -> bool {
+        !(*crate::constants::READ_ONLY_MODE && crate::commands::is_mutating_command(self.action))
+    }
+
     fn create_item(&self, exe_path: &str) -> crate::Result<ContentItem> {
+        if !self.is_permitted() {
+            return Err(format!("{} is disabled in read-only mode", self.action).into());
+        }
+
         let text = format!("{} {}", self.icon, self.label);
         create_command_item(&text, exe_path, self.action)
     }
@@ -137,6 +416,8 @@ struct MetricConfig<'a> {
     format_fn: fn(f64) -> String,
     display_type: MetricDisplayType,
     history: MetricHistory<'a>,
+    series: &'static str,   // Export name, used by "Copy Data (CSV)"
+    model: Option<&'a str>, // Owning model for per-model series
 }
 
 impl MetricHistory<'_> {
@@ -183,16 +464,36 @@ impl MetricHistory<'_> {
 
 struct MenuBuilder {
     items: Vec<MenuItem>,
+    symbol_title: Option<String>, // Raw title line for the SF Symbol icon style
 }
 
 impl MenuBuilder {
     fn new() -> Self {
-        Self { items: Vec::new() }
+        Self {
+            items: Vec::new(),
+            symbol_title: None,
+        }
     }
 
-    fn add_title(&mut self, display_state: DisplayState) {
-        let icon = icons::get_display_state_icon(display_state);
-        let item = ContentItem::new("").image(icon.clone()).unwrap();
+    fn add_title(
+        &mut self,
+        display_state: DisplayState,
+        overlay: icons::IconOverlay,
+        model_count: usize,
+        dot_color: Option<(u8, u8, u8)>,
+    ) {
+        if *crate::constants::ICON_STYLE == icons::IconStyle::Symbol {
+            self.symbol_title = Some(symbol_title_line(
+                &crate::constants::ICON_SYMBOL,
+                display_state,
+                overlay,
+                model_count,
+            ));
+            return;
+        }
+
+        let icon = icons::get_menu_bar_icon(display_state, overlay, model_count, dot_color);
+        let item = ContentItem::new("").image(icon).unwrap();
         self.items.push(MenuItem::Content(item));
     }
 
@@ -201,6 +502,153 @@ impl MenuBuilder {
         let color = get_hex_color(display_state.icon_color());
         let status_item = create_colored_item(message, color);
         self.items.push(MenuItem::Content(status_item));
+        if let Some(ref name) = *crate::constants::INSTANCE {
+            self.items.push(MenuItem::Content(create_colored_item(
+                &format!("Instance: {name}"),
+                "#666666",
+            )));
+        }
+    }
+
+    /// Why an unresponsive service's API can't be reached, and what to do about it
+    fn add_port_problem(&mut self, problem: &crate::port_check::PortProblem) {
+        let port = *crate::constants::API_PORT;
+        self.items.push(MenuItem::Content(create_colored_item(
+            &format!(":network.slash: {}", problem.message(port)),
+            get_hex_color("red"),
+        )));
+        self.items.push(MenuItem::Content(create_colored_item(
+            problem.suggested_fix(),
+            "#666666",
+        )));
+    }
+
+    fn add_uptime(&mut self, started_at: u64) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let uptime = now.saturating_sub(started_at);
+        let item = create_colored_item(&format!("Uptime: {}", format_duration(uptime)), "#666666");
+        self.items.push(MenuItem::Content(item));
+    }
+
+    /// "3 errors in last 5m — click to view" and the latest error, while the log has any
+    fn add_log_summary(&mut self, analyzer: &LogAnalyzer, exe: &str) {
+        let (errors, warnings) = analyzer.recent_counts();
+        let (count, noun, color) = match (errors, warnings) {
+            (0, 0) => return,
+            (0, n) => (n, "warning", get_hex_color("yellow")),
+            (n, _) => (n, "error", get_hex_color("red")),
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        let window = format_duration(crate::log_analysis::RECENT_WINDOW.as_secs());
+        let text = format!(
+            ":exclamationmark.bubble: {count} {noun}{plural} in last {window} — click to view"
+        );
+        if let Ok(item) = create_command_item(&text, exe, "view_logs") {
+            self.items
+                .push(MenuItem::Content(item.color(color).unwrap()));
+        }
+
+        if errors > 0 {
+            if let Some((ago, line)) = analyzer.last_error() {
+                let line = format!("{} ago: {line}", format_duration(ago.as_secs()));
+                self.items.push(MenuItem::Content(log_line_item(&line)));
+            }
+        }
+    }
+
+    fn add_watchdog_status(&mut self, watchdog: &crate::watchdog::Watchdog) {
+        let restarts = watchdog.restarts_last_hour();
+        let plural = if restarts == 1 { "" } else { "s" };
+        if watchdog.crash_loop() {
+            self.items.push(MenuItem::Content(create_colored_item(
+                &format!(
+                    ":exclamationmark.octagon: Kept crashing after {restarts} automatic restart{plural}; restarts stopped until it is started again"
+                ),
+                get_hex_color("red"),
+            )));
+        } else if let Some(wait) = watchdog.restart_in() {
+            self.items.push(MenuItem::Content(create_colored_item(
+                &format!(
+                    ":arrow.counterclockwise.circle: Crashed; restarting in {}",
+                    format_duration(wait.as_secs().max(1))
+                ),
+                get_hex_color("yellow"),
+            )));
+        } else if watchdog.gave_up() {
+            self.items.push(MenuItem::Content(create_colored_item(
+                &format!(
+                    ":exclamationmark.octagon: Crashed again after {restarts} automatic restart{plural} this hour; not restarting"
+                ),
+                get_hex_color("red"),
+            )));
+        } else if restarts > 0 {
+            self.items.push(MenuItem::Content(create_colored_item(
+                &format!(
+                    ":arrow.counterclockwise.circle: Restarted after a crash {restarts}× in the last hour"
+                ),
+                get_hex_color("yellow"),
+            )));
+        }
+    }
+
+    /// Tail of the service log, which usually says why a start failed
+    fn add_startup_failure_log(&mut self) {
+        let lines = crate::service::tail_service_log(STARTUP_LOG_LINES);
+        if lines.is_empty() {
+            return;
+        }
+
+        self.add_separator();
+        self.add_header("Last Service Log Lines");
+        for line in lines {
+            self.items.push(MenuItem::Content(log_line_item(&line)));
+        }
+    }
+
+    /// Upgrades with Homebrew when it's installed, otherwise opens the release page
+    fn add_update_available(&mut self, latest: &str, exe: &str) {
+        let text = format!(":arrow.down.circle: Update available ({latest})");
+        let item = if crate::commands::find_brew().is_some() && !*crate::constants::READ_ONLY_MODE {
+            create_command_item(&text, exe, "upgrade_binary")
+        } else {
+            ContentItem::new(text)
+                .href(crate::updates::LATEST_RELEASE_PAGE)
+                .map_err(Into::into)
+        };
+        if let Ok(item) = item {
+            self.items.push(MenuItem::Content(
+                item.color(get_hex_color("blue")).unwrap(),
+            ));
+        }
+    }
+
+    /// The end of the service log, so a misbehaving model can be checked from the menu
+    fn add_recent_logs(&mut self, exe: &str) {
+        let lines = crate::service::tail_service_log(RECENT_LOG_LINES);
+        if lines.is_empty() {
+            return;
+        }
+
+        let mut submenu: Vec<MenuItem> = lines
+            .iter()
+            .map(|line| MenuItem::Content(log_line_item(line)))
+            .collect();
+        submenu.push(MenuItem::Sep);
+        let open_items = [
+            (":doc.text.magnifyingglass: Open Full Log", "view_logs"),
+            (":terminal: Follow in Console", "view_logs_console"),
+        ];
+        for (text, action) in open_items {
+            if let Ok(item) = create_command_item(text, exe, action) {
+                submenu.push(MenuItem::Content(item));
+            }
+        }
+
+        let item = ContentItem::new(":text.alignleft: Recent Logs").sub(submenu);
+        self.items.push(MenuItem::Content(item));
     }
 
     fn add_separator(&mut self) {
@@ -217,8 +665,11 @@ impl MenuBuilder {
         model_name: &str,
         history: &MetricsHistory,
         current_metrics: &crate::models::Metrics,
+        alerts: &AlertMonitor,
     ) {
         self.add_header(model_name);
+        let breached =
+            |breach: fn(String) -> Breach| alerts.is_breached(&breach(model_name.into()));
 
         if let Some(item) = Self::create_metric(&MetricConfig {
             name: "Prompt Processing",
@@ -228,6 +679,8 @@ impl MenuBuilder {
             format_fn: format_tps,
             display_type: MetricDisplayType::Simple,
             history: MetricHistory::Model(history),
+            series: "prompt_tps",
+            model: Some(model_name),
         }) {
             self.items.push(item);
         }
@@ -240,22 +693,561 @@ impl MenuBuilder {
             format_fn: format_tps,
             display_type: MetricDisplayType::Simple,
             history: MetricHistory::Model(history),
+            series: "tps",
+            model: Some(model_name),
         }) {
-            self.items.push(item);
+            let slow = breached(Breach::Throughput) || breached(Breach::Regression);
+            self.items.push(with_alert_color(item, slow));
+        }
+        if breached(Breach::Regression) {
+            self.add_tps_regression(history);
+        }
+
+        // Builds without the KV cache gauges report zero throughout; don't show a flat line
+        if history.kv_cache_percent.iter().any(|v| v.value > 0.0) {
+            if let Some(item) = Self::create_metric(&MetricConfig {
+                name: "KV Cache",
+                primary_data: &history.kv_cache_percent,
+                secondary_data: None,
+                chart_type: charts::MetricType::KvCache,
+                format_fn: format_percent,
+                display_type: MetricDisplayType::Simple,
+                history: MetricHistory::Model(history),
+                series: "kv_cache_percent",
+                model: Some(model_name),
+            }) {
+                self.items.push(item);
+            }
+        }
+
+        self.add_latency(model_name, history, breached(Breach::Latency));
+        self.add_upstream_errors(history);
+        self.add_slots(&current_metrics.slots);
+        self.add_extra_metrics(&current_metrics.extra);
+        self.add_queue_status(current_metrics, history, breached(Breach::Queue));
+    }
+
+    /// Under Generation while the model runs well below its usual speed
+    fn add_tps_regression(&mut self, history: &MetricsHistory) {
+        let (Some(baseline), Some(latest)) =
+            (history.tps_baseline.value(), history.tps.iter().next())
+        else {
+            return;
+        };
+        let drop = (1.0 - latest.value / baseline) * 100.0;
+        let item = create_colored_item(
+            &format!(
+                "{drop:.0}% below its usual {} (throttling or swapping?)",
+                format_tps(baseline)
+            ),
+            get_hex_color("red"),
+        );
+        self.items.push(MenuItem::Content(item));
+    }
+
+    /// Rows for the metrics listed in the plugin config's extra_metrics
+    fn add_extra_metrics(&mut self, extra: &[(String, f64)]) {
+        for (label, value) in extra {
+            let item = ContentItem::new(format!("{label}: {}", format_number(*value)));
+            self.items.push(MenuItem::Content(item));
         }
+    }
+
+    /// Runs a tiny completion and posts the latency and tok/s as a notification
+    fn add_test_prompt(&mut self, model_name: &str, exe: &str) {
+        let command = plugin_command(exe, &["test_prompt", model_name]);
+        let item = ContentItem::new(":play.circle: Run Test Prompt")
+            .command(command)
+            .unwrap();
+        self.items.push(MenuItem::Content(item));
+    }
 
-        self.add_queue_status(current_metrics, history);
+    /// The exact name clients put in an OpenAI-compatible request's `model` field
+    fn add_copy_model_name(&mut self, model_name: &str, exe: &str) {
+        let command = plugin_command(exe, &["copy_model_name", model_name]);
+        let item = ContentItem::new(":doc.on.doc: Copy Model Name")
+            .command(command)
+            .unwrap();
+        self.items.push(MenuItem::Content(item));
     }
 
-    fn add_system_metrics_section(&mut self, history: &AllMetricsHistory) {
+    /// The concurrency flags in the model's config.yaml command, and an
+    /// "Edit Model Settings…" submenu that rewrites them in its entry
+    fn add_model_settings(
+        &mut self,
+        model_name: &str,
+        concurrency: Option<crate::config_parser::Concurrency>,
+        exe: &str,
+    ) {
+        use crate::model_wizard::ModelOption;
+
+        let Some(concurrency) = concurrency else {
+            return;
+        };
+        self.items.push(MenuItem::Content(ContentItem::new(format!(
+            ":slider.horizontal.3: {}",
+            concurrency.summary()
+        ))));
+        if *crate::constants::READ_ONLY_MODE {
+            return;
+        }
+
+        let item = |text: String, option: ModelOption| {
+            let [name, value] = option.args();
+            let command = plugin_command(exe, &["set_model_option", model_name, &name, &value]);
+            MenuItem::Content(ContentItem::new(text).command(command).unwrap())
+        };
+        let choice = |selected: bool| if selected { ":checkmark:" } else { ":circle:" };
+        let header = |text: &str| MenuItem::Content(create_colored_item(text, "#666666"));
+
+        let mut items = vec![header("Parallel Slots")];
+        items.push(item(
+            format!("{} Default", choice(concurrency.parallel.is_none())),
+            ModelOption::Parallel(None),
+        ));
+        for slots in crate::model_wizard::PARALLEL_CHOICES {
+            items.push(item(
+                format!("{} {slots}", choice(concurrency.parallel == Some(slots))),
+                ModelOption::Parallel(Some(slots)),
+            ));
+        }
+        items.push(MenuItem::Sep);
+        items.push(header("Continuous Batching"));
+        for (label, on) in [("Default", None), ("On", Some(true)), ("Off", Some(false))] {
+            items.push(item(
+                format!("{} {label}", choice(concurrency.cont_batching == on)),
+                ModelOption::ContBatching(on),
+            ));
+        }
+        let edit = ContentItem::new(":pencil: Edit Model Settings…").sub(items);
+        self.items.push(MenuItem::Content(edit));
+    }
+
+    /// Most recent load time, with earlier loads of the same model for comparison
+    fn add_load_time(&mut self, recent: &[std::time::Duration]) {
+        let Some(last) = recent.last() else {
+            return;
+        };
+
+        let mut item =
+            create_colored_item(&format!("Loaded in {}", format_load_time(*last)), "#666666");
+        if recent.len() > 1 {
+            let average = recent.iter().sum::<std::time::Duration>() / recent.len() as u32;
+            let mut submenu = vec![
+                MenuItem::Content(ContentItem::new(format!(
+                    "Average of last {}: {}",
+                    recent.len(),
+                    format_load_time(average)
+                ))),
+                MenuItem::Sep,
+            ];
+            submenu.extend(
+                recent.iter().rev().map(|duration| {
+                    MenuItem::Content(ContentItem::new(format_load_time(*duration)))
+                }),
+            );
+            item = item.sub(submenu);
+        }
+
+        self.items.push(MenuItem::Content(item));
+    }
+
+    /// Only interesting with `--parallel` > 1, where requests share the model
+    fn add_slots(&mut self, slots: &[crate::models::SlotInfo]) {
+        if slots.len() < 2 {
+            return;
+        }
+
+        let busy = slots.iter().filter(|slot| slot.is_processing).count();
+        let color = if busy > 0 { "#FFA500" } else { "#666666" };
+        let mut item = create_colored_item(&format!("Slots: {busy}/{} busy", slots.len()), color);
+
+        let submenu: Vec<MenuItem> = slots
+            .iter()
+            .map(|slot| {
+                let text = if slot.is_processing {
+                    format!(
+                        "Slot {}: busy — {} tokens generated",
+                        slot.id,
+                        format_count(slot.n_decoded)
+                    )
+                } else {
+                    format!("Slot {}: idle", slot.id)
+                };
+                MenuItem::Content(ContentItem::new(format!(
+                    "{text} (context {})",
+                    format_count(slot.n_ctx)
+                )))
+            })
+            .collect();
+        item = item.sub(submenu);
+
+        self.items.push(MenuItem::Content(item));
+    }
+
+    /// Only shown once a poll has failed; red above the configured threshold
+    fn add_upstream_errors(&mut self, history: &MetricsHistory) {
+        let Some(error_rate) = history.upstream_error_rate().filter(|&rate| rate > 0.0) else {
+            return;
+        };
+
+        let color = if error_rate > *crate::constants::UPSTREAM_ERROR_RATE_THRESHOLD {
+            get_hex_color("red")
+        } else {
+            get_hex_color("yellow")
+        };
+        let failed = history
+            .upstream_errors
+            .iter()
+            .filter(|v| v.value > 0.0)
+            .count();
+        let item = create_colored_item(
+            &format!("Upstream errors: {}", format_percent(error_rate)),
+            color,
+        )
+        .sub(vec![MenuItem::Content(ContentItem::new(format!(
+            "{failed} of the last {} metrics requests failed with a server error",
+            history.upstream_errors.len()
+        )))]);
+        self.items.push(MenuItem::Content(item));
+    }
+
+    fn add_latency(&mut self, model_name: &str, history: &MetricsHistory, breached: bool) {
+        let Some(latency) = history.latency else {
+            return;
+        };
+
+        let mut item = ContentItem::new(format!("Latency: p95 {}", format_ms(latency.p95_ms)));
+        if !history.latency_p95_ms.is_empty() {
+            add_chart(
+                &mut item,
+                &history.latency_p95_ms,
+                charts::MetricType::Latency,
+            );
+        }
+
+        let mut submenu = vec![
+            MenuItem::Content(ContentItem::new(format!(
+                "p50: {}",
+                format_ms(latency.p50_ms)
+            ))),
+            MenuItem::Content(ContentItem::new(format!(
+                "p95: {}",
+                format_ms(latency.p95_ms)
+            ))),
+            MenuItem::Content(ContentItem::new(format!(
+                "p99: {}",
+                format_ms(latency.p99_ms)
+            ))),
+        ];
+        if let Some(copy_item) = copy_csv_item("latency_p95_ms", Some(model_name)) {
+            submenu.push(MenuItem::Sep);
+            submenu.push(copy_item);
+        }
+        item = item.sub(submenu);
+
+        self.items
+            .push(with_alert_color(MenuItem::Content(item), breached));
+    }
+
+    /// A remote host's models and their speed; remote hosts are only watched,
+    /// so there are no service controls here
+    fn add_remote_host(&mut self, remote: &crate::remote::RemoteHostState) {
+        self.add_separator();
+        self.add_header(&format!(":server.rack: {}", remote.host.name));
+        self.add_remote_host_details(remote);
+    }
+
+    /// Everything under a remote host's header: tunnel, models and Web UI link
+    fn add_remote_host_details(&mut self, remote: &crate::remote::RemoteHostState) {
+        use crate::remote::RemoteStatus;
+
+        if let Some(ref tunnel) = remote.tunnel {
+            self.add_tunnel_status(tunnel);
+        }
+
+        let Some(ref metrics) = remote.metrics else {
+            let (text, color) = match remote.status {
+                RemoteStatus::Unknown => ("Connecting…".to_string(), get_hex_color("grey")),
+                _ => (
+                    format!(
+                        ":xmark.circle: Unreachable for {}",
+                        format_duration(remote.status_age().as_secs())
+                    ),
+                    get_hex_color("red"),
+                ),
+            };
+            let mut item = create_colored_item(&text, color);
+            if let Some(ref error) = remote.last_error {
+                item = item.sub(vec![MenuItem::Content(create_colored_item(
+                    error, "#666666",
+                ))]);
+            }
+            self.items.push(MenuItem::Content(item));
+            return;
+        };
+
+        if metrics.models.is_empty() {
+            self.items.push(MenuItem::Content(create_colored_item(
+                "Online — no models loaded",
+                get_hex_color("grey"),
+            )));
+        }
+        let mut models = metrics.models.clone();
+        models.sort_by(|a, b| a.model_name.cmp(&b.model_name));
+        for model in &models {
+            let (text, color) = match model.model_state {
+                crate::models::ModelState::Running => {
+                    let busy = model.metrics.requests_processing > 0;
+                    (
+                        format!(
+                            ":cpu: {} — {} · {}",
+                            model.model_name,
+                            format_tps(model.metrics.predicted_tokens_per_sec),
+                            model.metrics.queue_status()
+                        ),
+                        get_hex_color(if busy { "blue" } else { "green" }),
+                    )
+                }
+                crate::models::ModelState::Loading => (
+                    format!(":hourglass: {} — loading…", model.model_name),
+                    get_hex_color("yellow"),
+                ),
+                crate::models::ModelState::Stopping => (
+                    format!(":stop.circle: {} — unloading…", model.model_name),
+                    get_hex_color("grey"),
+                ),
+                crate::models::ModelState::Unknown => (
+                    format!(":circle: {}", model.model_name),
+                    get_hex_color("grey"),
+                ),
+            };
+            self.items
+                .push(MenuItem::Content(create_colored_item(&text, color)));
+        }
+
+        let ui = ContentItem::new(":safari: Open Web UI")
+            .href(format!("{}/ui/models", remote.endpoint()))
+            .unwrap();
+        self.items.push(MenuItem::Content(ui));
+    }
+
+    /// "3 hosts · 5 models · 112.0 tok/s · 12.3 GB" across this Mac and the
+    /// remote hosts, with each host's section a level down. The memory is this
+    /// Mac's llama-server processes; llama-swap doesn't report a remote host's.
+    fn add_fleet_summary(&mut self, state: &PluginState) {
+        use crate::remote::RemoteStatus;
+
+        if state.remote_hosts.is_empty() {
+            return;
+        }
+
+        let model_count = |count: usize| {
+            let plural = if count == 1 { "" } else { "s" };
+            format!("{count} model{plural}")
+        };
+        let (mut models, mut tps) = state
+            .current_all_metrics
+            .as_ref()
+            .map_or((0, 0.0), crate::models::AllMetrics::running_totals);
+        let memory_mb = state
+            .metrics_history
+            .total_llama_memory_mb
+            .iter()
+            .next()
+            .map_or(0.0, |v| v.value);
+
+        let mut submenu = vec![MenuItem::Content(ContentItem::new(format!(
+            ":laptopcomputer: This Mac — {} · {} · {}",
+            model_count(models),
+            format_tps(tps),
+            format_memory_mb(memory_mb)
+        )))];
+        for remote in &state.remote_hosts {
+            let name = &remote.host.name;
+            let text = match (remote.status, remote.metrics.as_ref()) {
+                (_, Some(metrics)) => {
+                    let (host_models, host_tps) = metrics.running_totals();
+                    models += host_models;
+                    tps += host_tps;
+                    format!(
+                        ":server.rack: {name} — {} · {}",
+                        model_count(host_models),
+                        format_tps(host_tps)
+                    )
+                }
+                (RemoteStatus::Unknown, None) => format!(":server.rack: {name} — connecting…"),
+                (_, None) => format!(":server.rack: {name} — unreachable"),
+            };
+            let mut details = MenuBuilder::new();
+            details.add_remote_host_details(remote);
+            submenu.push(MenuItem::Content(ContentItem::new(text).sub(details.items)));
+        }
+
+        let title = format!(
+            ":square.grid.2x2: {} hosts · {} · {} · {}",
+            state.remote_hosts.len() + 1,
+            model_count(models),
+            format_tps(tps),
+            format_memory_mb(memory_mb)
+        );
+        self.items
+            .push(MenuItem::Content(ContentItem::new(title).sub(submenu)));
+    }
+
+    fn add_tunnel_status(&mut self, tunnel: &crate::ssh_tunnel::SshTunnel) {
+        use crate::ssh_tunnel::TunnelHealth;
+
+        let destination = tunnel.destination();
+        let (text, color) = match tunnel.health() {
+            TunnelHealth::Up => (
+                format!(
+                    ":lock.fill: SSH tunnel via {destination} · up {}",
+                    format_duration(tunnel.uptime().unwrap_or_default().as_secs())
+                ),
+                get_hex_color("green"),
+            ),
+            TunnelHealth::Starting => (
+                format!(":lock.open: SSH tunnel via {destination} connecting…"),
+                get_hex_color("yellow"),
+            ),
+            TunnelHealth::Down => (
+                match tunnel.retry_in() {
+                    Some(retry) => format!(
+                        ":exclamationmark.triangle.fill: SSH tunnel via {destination} down · retrying in {}",
+                        format_duration(retry.as_secs())
+                    ),
+                    None => format!(
+                        ":exclamationmark.triangle.fill: SSH tunnel via {destination} down"
+                    ),
+                },
+                get_hex_color("red"),
+            ),
+        };
+        let mut item = create_colored_item(&text, color);
+        if let Some(ref error) = tunnel.last_error {
+            item = item.sub(vec![MenuItem::Content(create_colored_item(
+                error, "#666666",
+            ))]);
+        }
+        self.items.push(MenuItem::Content(item));
+    }
+
+    /// A model being unloaded stays listed, greyed out, until llama-swap drops it
+    fn add_stopping_model(&mut self, model_name: &str) {
+        let item = create_colored_item(
+            &format!(":stop.circle: {model_name} — unloading…"),
+            get_hex_color("grey"),
+        );
+        self.items.push(MenuItem::Content(item));
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn add_long_term_history(&mut self, store: &crate::storage::MetricsStore, model_name: &str) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut submenu = Vec::new();
+        for (label, window_secs) in crate::storage::SUMMARY_WINDOWS {
+            let Ok(summary) = store.window_summary(model_name, now.saturating_sub(*window_secs))
+            else {
+                continue;
+            };
+            if summary.samples == 0 {
+                continue;
+            }
+
+            submenu.push(MenuItem::Content(ContentItem::new(format!(
+                "{label}: avg {} · peak {} · avg queue {:.1} · {} samples",
+                format_tps(summary.avg_tps),
+                format_tps(summary.peak_tps),
+                summary.avg_queue,
+                summary.samples
+            ))));
+            if summary.peak_memory_mb > 0.0 {
+                submenu.push(MenuItem::Content(ContentItem::new(format!(
+                    "{label}: peak memory {}",
+                    format_memory_mb(summary.peak_memory_mb)
+                ))));
+            }
+        }
+
+        if submenu.is_empty() {
+            return;
+        }
+
+        let item = ContentItem::new(":clock.arrow.circlepath: Long-term History").sub(submenu);
+        self.items.push(MenuItem::Content(item));
+    }
+
+    fn add_today_section(&mut self, sessions: &crate::sessions::SessionLog) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let Some(today) = sessions.day(&crate::sessions::local_date(now)) else {
+            return;
+        };
+        if today.models.is_empty() {
+            return;
+        }
+
+        let mut submenu: Vec<MenuItem> = today
+            .models
+            .iter()
+            .map(|(model_name, stats)| {
+                let mut text = format!(
+                    "{model_name}: {} tokens · {} active",
+                    format_count(stats.tokens_decoded),
+                    format_duration(stats.active_secs)
+                );
+                if stats.peak_memory_mb > 0.0 {
+                    text.push_str(&format!(
+                        " · peak {}",
+                        format_memory_mb(stats.peak_memory_mb)
+                    ));
+                }
+                MenuItem::Content(ContentItem::new(text))
+            })
+            .collect();
+        if today.peak_llama_memory_mb > 0.0 {
+            submenu.push(MenuItem::Sep);
+            submenu.push(MenuItem::Content(ContentItem::new(format!(
+                "Peak Llama Memory: {}",
+                format_memory_mb(today.peak_llama_memory_mb)
+            ))));
+        }
+
+        self.add_separator();
+        let item = ContentItem::new(format!(
+            ":calendar: Today: {} tokens · {} active",
+            format_count(today.total_tokens()),
+            format_duration(today.total_active_secs())
+        ))
+        .sub(submenu);
+        self.items.push(MenuItem::Content(item));
+    }
+
+    fn add_system_metrics_section(
+        &mut self,
+        history: &AllMetricsHistory,
+        energy: &crate::power::EnergyMeter,
+        system: &sysinfo::System,
+        alerts: &AlertMonitor,
+    ) {
         let has_cpu = !history.cpu_usage_percent.is_empty();
+        let has_gpu = !history.gpu_usage_percent.is_empty();
+        let has_power = !history.power_watts.is_empty();
+        let pressure = history.current_memory_pressure();
         let has_memory =
             !history.memory_usage_percent.is_empty() && !history.used_memory_gb.is_empty();
         let has_llama_memory =
             !history.total_llama_memory_mb.is_empty() && !history.used_memory_gb.is_empty();
 
         // Only show header if we have any metrics to display
-        if !has_cpu && !has_memory && !has_llama_memory {
+        if !has_cpu && !has_gpu && !has_power && !has_memory && !has_llama_memory {
             return;
         }
 
@@ -270,11 +1262,57 @@ impl MenuBuilder {
                 format_fn: format_percent,
                 display_type: MetricDisplayType::Simple,
                 history: MetricHistory::System(history, "CPU"),
+                series: "cpu_usage_percent",
+                model: None,
             }) {
                 self.items.push(item);
             }
         }
 
+        // CPU alone understates the load of Metal-offloaded models
+        if has_gpu {
+            if let Some(item) = Self::create_metric(&MetricConfig {
+                name: "GPU",
+                primary_data: &history.gpu_usage_percent,
+                secondary_data: None,
+                chart_type: charts::MetricType::Gpu,
+                format_fn: format_percent,
+                display_type: MetricDisplayType::Simple,
+                history: MetricHistory::System(history, "GPU"),
+                series: "gpu_usage_percent",
+                model: None,
+            }) {
+                self.items.push(item);
+            }
+        }
+
+        if has_power {
+            if let Some(item) = Self::create_metric(&MetricConfig {
+                name: "Power",
+                primary_data: &history.power_watts,
+                secondary_data: None,
+                chart_type: charts::MetricType::Power,
+                format_fn: format_watts,
+                display_type: MetricDisplayType::Simple,
+                history: MetricHistory::System(history, "Power"),
+                series: "power_watts",
+                model: None,
+            }) {
+                self.items.push(item);
+            }
+            if let Some(session) = energy.session() {
+                let item = create_colored_item(
+                    &format!(
+                        "Energy this session: {:.2} Wh over {}",
+                        session.watt_hours,
+                        format_duration(session.started.elapsed().as_secs())
+                    ),
+                    "#666666",
+                );
+                self.items.push(MenuItem::Content(item));
+            }
+        }
+
         if has_memory {
             if let Some(item) = Self::create_metric(&MetricConfig {
                 name: "Memory",
@@ -284,19 +1322,209 @@ impl MenuBuilder {
                 format_fn: format_percent,
                 display_type: MetricDisplayType::SystemMemory,
                 history: MetricHistory::System(history, "Memory"),
+                series: "memory_usage_percent",
+                model: None,
             }) {
-                self.items.push(item);
+                self.items.push(with_pressure_color(item, pressure));
             }
+            self.add_memory_pressure(history, pressure);
         }
 
         if has_llama_memory {
-            self.add_llama_process_breakdown(history);
+            self.add_llama_process_breakdown(
+                history,
+                system,
+                alerts.is_breached(&Breach::LlamaMemory),
+            );
+        }
+    }
+
+    /// Swap and pressure line under Memory, only once either is worth a look
+    fn add_memory_pressure(
+        &mut self,
+        history: &AllMetricsHistory,
+        pressure: Option<MemoryPressure>,
+    ) {
+        let swap_gb = history.swap_used_gb.iter().next().map_or(0.0, |v| v.value);
+        let pressure = pressure.unwrap_or(MemoryPressure::Normal);
+        if swap_gb < 0.05 && pressure == MemoryPressure::Normal {
+            return;
+        }
+
+        let text = format!("Swap: {swap_gb:.1} GB · pressure {}", pressure.label());
+        let item = with_pressure_color(
+            MenuItem::Content(create_colored_item(&text, "#666666")),
+            Some(pressure),
+        );
+        self.items.push(item);
+    }
+
+    /// Counters from llama-swap itself, since the proxy started
+    fn add_proxy_section(&mut self, proxy: &crate::models::ProxyMetrics) {
+        self.add_separator();
+        self.add_header("llama-swap Proxy");
+
+        self.items.push(MenuItem::Content(ContentItem::new(format!(
+            "Requests: {} · Swaps: {}",
+            format_count(proxy.requests_total as u64),
+            format_count(proxy.swaps_total as u64)
+        ))));
+
+        if let Some(error_rate) = proxy.error_rate_percent() {
+            let color = if proxy.upstream_errors_total > 0.0 {
+                get_hex_color("yellow")
+            } else {
+                "#666666"
+            };
+            let item = create_colored_item(
+                &format!(
+                    "Upstream errors: {} ({})",
+                    format_count(proxy.upstream_errors_total as u64),
+                    format_percent(error_rate)
+                ),
+                color,
+            );
+            self.items.push(MenuItem::Content(item));
+        }
+    }
+
+    fn add_hardware_section(&mut self, hardware: &crate::hardware::HardwareInfo) {
+        let memory_gb = hardware.memory_gb();
+        let mut submenu = vec![MenuItem::Content(ContentItem::new(format!(
+            "Chip: {}",
+            hardware.chip
+        )))];
+
+        let cpu_text = match (hardware.performance_cores, hardware.efficiency_cores) {
+            (Some(p), Some(e)) => format!(
+                "CPU: {} cores ({p} performance + {e} efficiency)",
+                hardware.total_cores
+            ),
+            _ => format!("CPU: {} cores", hardware.total_cores),
+        };
+        submenu.push(MenuItem::Content(ContentItem::new(cpu_text)));
+
+        let gpu_text = match (hardware.gpu_cores, hardware.metal_support.as_deref()) {
+            (Some(cores), Some(metal)) => Some(format!("GPU: {cores} cores · {metal}")),
+            (Some(cores), None) => Some(format!("GPU: {cores} cores")),
+            (None, Some(metal)) => Some(format!("GPU: {metal}")),
+            (None, None) => None,
+        };
+        if let Some(text) = gpu_text {
+            submenu.push(MenuItem::Content(ContentItem::new(text)));
+        }
+
+        submenu.push(MenuItem::Content(ContentItem::new(format!(
+            "Unified Memory: {memory_gb:.0} GB"
+        ))));
+
+        submenu.push(MenuItem::Sep);
+        submenu.push(MenuItem::Content(create_colored_item(
+            "Model sizes that fit (rough guide)",
+            "#666666",
+        )));
+        for (quantization, max_params_b) in crate::hardware::model_fit_guide(memory_gb) {
+            let text = if max_params_b < 1.0 {
+                format!("{quantization}: not enough memory")
+            } else if max_params_b < 10.0 {
+                format!("{quantization}: up to ~{max_params_b:.1}B parameters")
+            } else {
+                format!("{quantization}: up to ~{max_params_b:.0}B parameters")
+            };
+            submenu.push(MenuItem::Content(ContentItem::new(text)));
         }
+
+        let title = format!(
+            ":desktopcomputer: This Mac: {} · {memory_gb:.0} GB",
+            hardware.chip
+        );
+        self.items
+            .push(MenuItem::Content(ContentItem::new(title).sub(submenu)));
     }
 
-    fn add_llama_process_breakdown(&mut self, history: &AllMetricsHistory) {
-        let system = sysinfo::System::new_all();
-        let processes = crate::metrics::get_detailed_llama_processes(&system);
+    /// Every model in config.yaml, with a Load action for those not running
+    fn add_configured_models(&mut self, configured: &[String], state: &PluginState, exe: &str) {
+        if configured.is_empty() {
+            return;
+        }
+
+        let loaded = configured
+            .iter()
+            .filter(|name| state.model_states.contains_key(*name))
+            .count();
+        let can_load = state.service_status.api_responsive && !*crate::constants::READ_ONLY_MODE;
+
+        let mut submenu = Vec::new();
+        for name in configured {
+            let item = if state.model_states.contains_key(name) {
+                create_colored_item(
+                    &format!(":checkmark.circle.fill: {name}"),
+                    get_hex_color("green"),
+                )
+            } else if can_load {
+                ContentItem::new(format!(":play.circle: Load {name}"))
+                    .command(plugin_command(exe, &["load_model", name]))
+                    .unwrap()
+            } else {
+                create_colored_item(&format!(":circle: {name}"), "#666666")
+            };
+            submenu.push(MenuItem::Content(item));
+        }
+
+        let title = format!(
+            ":square.stack.3d.up: Models ({loaded}/{} loaded)",
+            configured.len()
+        );
+        self.items
+            .push(MenuItem::Content(ContentItem::new(title).sub(submenu)));
+    }
+
+    /// Problems that would make llama-swap reject config.yaml; each opens the editor
+    fn add_config_issues(&mut self, issues: &[crate::config_validation::ConfigIssue], exe: &str) {
+        if issues.is_empty() {
+            return;
+        }
+
+        self.add_separator();
+        self.add_header("Configuration Errors");
+        for issue in issues {
+            let text = format!(":xmark.octagon: {issue}");
+            if let Ok(item) = create_command_item(&text, exe, "edit_config") {
+                self.items
+                    .push(MenuItem::Content(item.color(get_hex_color("red")).unwrap()));
+            }
+        }
+    }
+
+    /// Configured models whose command can't start, flagged before a load is attempted
+    fn add_model_config_warnings(&mut self, warnings: &[crate::binary_check::ModelWarning]) {
+        if warnings.is_empty() {
+            return;
+        }
+
+        self.add_separator();
+        self.add_header("Model Configuration");
+        for warning in warnings {
+            let item = create_colored_item(
+                &format!(
+                    ":exclamationmark.triangle: {}: {}",
+                    warning.model,
+                    warning.message()
+                ),
+                get_hex_color("red"),
+            );
+            self.items.push(MenuItem::Content(item));
+        }
+    }
+
+    /// Uses the process list refreshed by this poll rather than enumerating again
+    fn add_llama_process_breakdown(
+        &mut self,
+        history: &AllMetricsHistory,
+        system: &sysinfo::System,
+        breached: bool,
+    ) {
+        let processes = crate::metrics::get_detailed_llama_processes(system);
 
         if processes.is_empty() {
             return;
@@ -307,6 +1535,9 @@ impl MenuBuilder {
         // Create main header item with chart
         let header_text = format!("Llama Processes: {}", format_memory_mb(total_memory_mb));
         let mut header_item = ContentItem::new(header_text);
+        if breached {
+            header_item = header_item.color(get_hex_color("red")).unwrap();
+        }
 
         // Add the memory trend chart
         if !history.total_llama_memory_mb.is_empty() {
@@ -349,6 +1580,9 @@ impl MenuBuilder {
             processes.len(),
             if processes.len() == 1 { "" } else { "es" }
         ))));
+        if let Some(copy_item) = copy_csv_item("total_llama_memory_mb", None) {
+            submenu.push(copy_item);
+        }
 
         header_item = header_item.sub(submenu);
         self.items.push(MenuItem::Content(header_item));
@@ -370,13 +1604,17 @@ impl MenuBuilder {
         let mut item = ContentItem::new(label);
 
         add_chart(&mut item, config.primary_data, config.chart_type);
-        let submenu = config.history.build_submenu(
+        let mut submenu = config.history.build_submenu(
             &insights,
             config.primary_data,
             config.secondary_data,
             config.format_fn,
             &config.display_type,
         );
+        if let Some(copy_item) = copy_csv_item(config.series, config.model) {
+            submenu.push(MenuItem::Sep);
+            submenu.push(copy_item);
+        }
         item = item.sub(submenu);
 
         Some(MenuItem::Content(item))
@@ -386,15 +1624,17 @@ impl MenuBuilder {
         &mut self,
         current_metrics: &crate::models::Metrics,
         history: &MetricsHistory,
+        breached: bool,
     ) {
-        let queue_status = current_metrics.queue_status();
-        let total_queue = current_metrics.requests_processing + current_metrics.requests_deferred;
-        let color =
-            if current_metrics.requests_processing > 0 || current_metrics.requests_deferred > 0 {
-                "#FFA500"
-            } else {
-                "#666666"
-            };
+        let queue_status = current_metrics.queue_status();
+        let total_queue = current_metrics.requests_processing + current_metrics.requests_deferred;
+        let color = if breached {
+            get_hex_color("red")
+        } else if current_metrics.requests_processing > 0 || current_metrics.requests_deferred > 0 {
+            "#FFA500"
+        } else {
+            "#666666"
+        };
 
         let mut queue_item = create_colored_item(&format!("Queue: {queue_status}"), color);
 
@@ -439,8 +1679,9 @@ impl MenuBuilder {
         match display_state {
             DisplayState::ModelReady | DisplayState::ModelProcessingQueue => {
                 // When models are running, prioritize Open UI for quick access
-                if UI_COMMAND.is_available_for_state(display_state) {
-                    if let Ok(item) = UI_COMMAND.create_item(exe_str) {
+                let ui_command = ui_command(service_status);
+                if ui_command.is_available_for_state(display_state) {
+                    if let Ok(item) = ui_command.create_item(exe_str) {
                         actions.push(item);
                     }
                 }
@@ -457,8 +1698,9 @@ impl MenuBuilder {
             }
             DisplayState::ServiceLoadedNoModel => {
                 // When service is loaded but no models, prioritize Open UI for quick access
-                if UI_COMMAND.is_available_for_state(display_state) {
-                    if let Ok(item) = UI_COMMAND.create_item(exe_str) {
+                let ui_command = ui_command(service_status);
+                if ui_command.is_available_for_state(display_state) {
+                    if let Ok(item) = ui_command.create_item(exe_str) {
                         actions.push(item);
                     }
                 }
@@ -482,9 +1724,13 @@ impl MenuBuilder {
             DisplayState::AgentNotLoaded => {
                 // When agent not loaded, prioritize installation or starting
                 if !service_status.plist_installed {
-                    if let Ok(item) = INSTALL_COMMAND.create_item(exe_str) {
-                        actions.push(item);
-                    }
+                    let binary_missing = crate::commands::find_llama_swap_binary().is_err();
+                    let item = if binary_missing {
+                        install_binary_item(exe_str)
+                    } else {
+                        INSTALL_COMMAND.create_item(exe_str).ok()
+                    };
+                    actions.extend(item);
                 } else if service_status.plist_installed && !service_status.is_fully_running() {
                     if let Some(start_cmd) =
                         CONTROL_COMMANDS.iter().find(|c| c.action == "do_start")
@@ -495,6 +1741,22 @@ impl MenuBuilder {
                     }
                 }
             }
+            DisplayState::StartupFailed | DisplayState::CrashLoop => {
+                // Usually a config or binary problem; fix it, then try again
+                for command in [&VIEW_LOGS_COMMAND, &RESTART_COMMAND] {
+                    if let Ok(item) = command.create_item(exe_str) {
+                        actions.push(item);
+                    }
+                }
+            }
+            DisplayState::ServiceDegraded => {
+                // A hung server usually needs a restart; the logs explain why it hung
+                for command in [&RESTART_COMMAND, &VIEW_LOGS_COMMAND] {
+                    if let Ok(item) = command.create_item(exe_str) {
+                        actions.push(item);
+                    }
+                }
+            }
             DisplayState::AgentStarting => {
                 // When starting, allow stopping to cancel
                 if let Some(stop_cmd) = CONTROL_COMMANDS.iter().find(|c| c.action == "do_stop") {
@@ -503,8 +1765,8 @@ impl MenuBuilder {
                     }
                 }
             }
-            DisplayState::ModelLoading => {
-                // During model loading, no immediate action needed
+            DisplayState::ModelLoading | DisplayState::ModelStopping => {
+                // During model loading/unloading, no immediate action needed
                 // Could add stop service if needed, but loading is usually quick
             }
         }
@@ -517,6 +1779,88 @@ impl MenuBuilder {
         }
     }
 
+    /// "Instances": every installed llama-swap launch agent, with Start/Stop
+    /// for the ones other plugins (or none) show
+    fn add_instances(&mut self, agents: &[crate::launch_agents::LaunchAgent], exe: &str) {
+        if agents.len() < 2 {
+            return;
+        }
+
+        let mut submenu = Vec::new();
+        for agent in agents {
+            let status = match agent.pid {
+                Some(pid) => format!("Running (PID {pid})"),
+                None if agent.loaded => "Stopped".to_string(),
+                None => "Not loaded".to_string(),
+            };
+            let mut text = format!("{} — {status}", agent.display_name());
+            if let Some(port) = agent.port {
+                text.push_str(&format!(" · port {port}"));
+            }
+            if agent.is_current() {
+                text.push_str(" (this menu)");
+            }
+            let color = if agent.pid.is_some() {
+                get_hex_color("green")
+            } else {
+                get_hex_color("grey")
+            };
+            let mut item = create_colored_item(&text, color);
+
+            if !agent.is_current() && !*crate::constants::READ_ONLY_MODE {
+                let (label, action) = if agent.pid.is_some() {
+                    (":stop.fill: Stop", "do_stop")
+                } else {
+                    (":play.fill: Start", "do_start")
+                };
+                let mut args = agent.instance_args();
+                args.push(action.to_string());
+                let command =
+                    bitbar::attr::Command::from(bitbar::attr::Params::new(exe.to_string(), args));
+                let action_item = ContentItem::new(label).command(command).unwrap();
+                item = item.sub(vec![MenuItem::Content(action_item)]);
+            }
+            submenu.push(MenuItem::Content(item));
+        }
+
+        let running = agents.iter().filter(|agent| agent.pid.is_some()).count();
+        let item = ContentItem::new(format!(
+            ":square.stack.3d.up: Instances ({running}/{} running)",
+            agents.len()
+        ))
+        .sub(submenu);
+        self.items.push(MenuItem::Content(item));
+    }
+
+    /// "Switch Model…": unload whatever is running and load the chosen model
+    fn add_model_switcher(&mut self, display_state: DisplayState, state: &PluginState, exe: &str) {
+        let configured = state.configured_models.names();
+        let service_up = matches!(
+            display_state,
+            DisplayState::ModelReady
+                | DisplayState::ModelProcessingQueue
+                | DisplayState::ServiceLoadedNoModel
+        );
+        if !service_up || configured.is_empty() || *crate::constants::READ_ONLY_MODE {
+            return;
+        }
+
+        let mut submenu = Vec::new();
+        for name in configured {
+            let item = if state.model_states.contains_key(name) {
+                create_colored_item(&format!(":checkmark: {name}"), "#666666")
+            } else {
+                ContentItem::new(name)
+                    .command(plugin_command(exe, &["switch_model", name]))
+                    .unwrap()
+            };
+            submenu.push(MenuItem::Content(item));
+        }
+
+        let item = ContentItem::new(":arrow.triangle.swap: Switch Model…").sub(submenu);
+        self.items.push(MenuItem::Content(item));
+    }
+
     fn add_settings_section(
         &mut self,
         display_state: DisplayState,
@@ -548,9 +1892,9 @@ impl MenuBuilder {
                     }
                 ))
                 .color(if binary_available {
-                    "#34C759"
+                    get_hex_color("green")
                 } else {
-                    "#FF9500"
+                    get_hex_color("yellow")
                 })
                 .unwrap(),
             ));
@@ -570,9 +1914,9 @@ impl MenuBuilder {
                     }
                 ))
                 .color(if service_status.plist_installed {
-                    "#34C759"
+                    get_hex_color("green")
                 } else {
-                    "#FF9500"
+                    get_hex_color("yellow")
                 })
                 .unwrap(),
             ));
@@ -588,15 +1932,20 @@ impl MenuBuilder {
                     service_status.status_description()
                 ))
                 .color(if service_status.is_fully_running() {
-                    "#34C759"
+                    get_hex_color("green")
                 } else {
-                    "#FF9500"
+                    get_hex_color("yellow")
                 })
                 .unwrap(),
             ));
 
             // Show plist management actions based on actual plist state
             submenu.push(MenuItem::Sep);
+            if !binary_available {
+                if let Some(item) = install_binary_item(exe_str) {
+                    submenu.push(MenuItem::Content(item));
+                }
+            }
             if !service_status.plist_installed {
                 if let Ok(item) = INSTALL_COMMAND.create_item(exe_str) {
                     submenu.push(MenuItem::Content(item));
@@ -632,29 +1981,68 @@ impl MenuBuilder {
                 }
             }
         }
+        if let Some(item) = install_options_item(exe_str) {
+            submenu.push(MenuItem::Content(item));
+        }
 
         submenu.push(MenuItem::Sep);
 
-        // Add UI command when API is available
-        if UI_COMMAND.is_available_for_state(display_state) {
-            if let Ok(item) = UI_COMMAND.create_item(exe_str) {
+        // Add UI command (or the endpoint fallback) when API is available
+        let ui_command = ui_command(service_status);
+        if ui_command.is_available_for_state(display_state) {
+            if let Ok(item) = ui_command.create_item(exe_str) {
                 submenu.push(MenuItem::Content(item));
             }
         }
-
-        // Add file action commands
-        for command in FILE_COMMANDS {
-            if let Ok(item) = command.create_item(exe_str) {
+        // The endpoint stays copyable for pointing clients at it
+        if service_status.ui_available
+            && COPY_ENDPOINT_COMMAND.is_available_for_state(display_state)
+        {
+            if let Ok(item) = COPY_ENDPOINT_COMMAND.create_item(exe_str) {
                 submenu.push(MenuItem::Content(item));
             }
         }
 
+        let mut config_items: Vec<MenuItem> = FILE_COMMANDS
+            .iter()
+            .filter_map(|command| command.create_item(exe_str).ok())
+            .map(MenuItem::Content)
+            .collect();
+        if let Some(restore) = restore_config_item(exe_str) {
+            config_items.push(MenuItem::Content(restore));
+        }
+        submenu.push(MenuItem::Content(
+            ContentItem::new(":gearshape: Edit Llama-Swap Configuration").sub(config_items),
+        ));
+
+        let export_items: Vec<MenuItem> = EXPORT_COMMANDS
+            .iter()
+            .filter_map(|command| command.create_item(exe_str).ok())
+            .map(MenuItem::Content)
+            .collect();
+        submenu.push(MenuItem::Content(
+            ContentItem::new(":square.and.arrow.up: Export Metrics…").sub(export_items),
+        ));
+        if let Ok(item) = DIAGNOSTICS_COMMAND.create_item(exe_str) {
+            submenu.push(MenuItem::Content(item));
+        }
+        if let Ok(item) = CLEAR_HISTORY_COMMAND.create_item(exe_str) {
+            submenu.push(MenuItem::Content(item));
+        }
+
         submenu.push(MenuItem::Sep);
         submenu.push(MenuItem::Content(create_colored_item(
             "Llama-Swap Swiftbar Plugin",
             "#666666",
         )));
 
+        if *crate::constants::READ_ONLY_MODE {
+            submenu.push(MenuItem::Content(create_colored_item(
+                ":eye: Read-only mode (service controls disabled)",
+                "#666666",
+            )));
+        }
+
         // Debug actions - always available
         let refresh_item = ContentItem::new(":arrow.clockwise: Force Plugin Refresh").refresh();
         submenu.push(MenuItem::Content(refresh_item));
@@ -699,6 +2087,26 @@ impl MenuBuilder {
                 "✗"
             }
         ))));
+        if let Some(launchd) = state.launchd.details() {
+            let summary = launchd.summary();
+            submenu.push(MenuItem::Content(if launchd.is_throttled() {
+                create_colored_item(&summary, get_hex_color("yellow"))
+            } else {
+                ContentItem::new(summary)
+            }));
+        }
+        if service_status.plist_installed {
+            submenu.push(MenuItem::Content(ContentItem::new(format!(
+                "Priority: {}",
+                crate::install_options::InstallOptions::load().priority_summary()
+            ))));
+        }
+        if let Some(ref problem) = state.port_problem {
+            submenu.push(MenuItem::Content(create_colored_item(
+                &problem.message(*crate::constants::API_PORT),
+                get_hex_color("red"),
+            )));
+        }
 
         submenu.push(MenuItem::Content(ContentItem::new(format!(
             "Polling Mode: {} | API Errors: {} | Metrics: {}",
@@ -711,22 +2119,193 @@ impl MenuBuilder {
             }
         ))));
 
+        let versions = &state.versions;
+        submenu.push(MenuItem::Content(ContentItem::new(format!(
+            "llama-swap: {} | llama.cpp: {}",
+            versions.llama_swap.as_deref().unwrap_or("unknown"),
+            versions.llama_cpp.as_deref().unwrap_or("unknown")
+        ))));
+        if let Some(minimum) = versions.outdated_minimum() {
+            submenu.push(MenuItem::Content(create_colored_item(
+                &format!(
+                    ":exclamationmark.triangle: llama-swap is older than {minimum}; please upgrade"
+                ),
+                get_hex_color("yellow"),
+            )));
+        }
+
         // Show model states if any
         if !state.model_states.is_empty() {
-            for (model_name, model_state) in &state.model_states {
-                submenu.push(MenuItem::Content(ContentItem::new(format!(
-                    "{model_name}: {model_state:?}"
-                ))));
+            let now = std::time::Instant::now();
+            for (model_name, machine) in &state.model_states {
+                let mut text = format!("{model_name}: {:?}", machine.state());
+                if let Some(loading) = machine.loading_for(now) {
+                    text.push_str(&format!(" for {}", format_duration(loading.as_secs())));
+                } else if let Some(loaded_in) = machine.last_load_duration() {
+                    text.push_str(&format!(
+                        " (loaded in {})",
+                        format_duration(loaded_in.as_secs())
+                    ));
+                }
+                if machine.is_flapping(now) {
+                    text.push_str(" ⚠ flapping");
+                }
+                submenu.push(MenuItem::Content(ContentItem::new(text)));
             }
         }
 
+        submenu.push(MenuItem::Sep);
+        submenu.push(build_event_history_item(&state.events));
+
+        if !state.recovery.is_empty() {
+            submenu.push(build_recovery_item(&state.recovery));
+        }
+
         let mut settings_item = ContentItem::new(":gearshape.fill: Advanced");
         settings_item = settings_item.sub(submenu);
         self.items.push(MenuItem::Content(settings_item));
     }
 
-    fn build(self) -> Menu {
-        Menu(self.items)
+    fn build(self) -> String {
+        let menu = Menu(self.items).to_string();
+        match self.symbol_title {
+            Some(title) => format!("{title}\n{menu}"),
+            None => menu,
+        }
+    }
+}
+
+/// Menu bar title using SwiftBar's `sfimage`, coloured by state like the status dot
+///
+/// Written by hand because bitbar has no `sfcolor` parameter. Badged states show
+/// a warning triangle in the badge colour instead of the configured symbol.
+fn symbol_title_line(
+    symbol: &str,
+    display_state: DisplayState,
+    overlay: icons::IconOverlay,
+    model_count: usize,
+) -> String {
+    let badge = "exclamationmark.triangle.fill";
+    let (symbol, color) = match overlay {
+        icons::IconOverlay::None => (symbol, get_hex_color(display_state.icon_color()).into()),
+        icons::IconOverlay::ErrorBadge => {
+            (badge, rgb_to_hex(crate::constants::PALETTE.error_badge))
+        }
+        icons::IconOverlay::WarningBadge => {
+            (badge, rgb_to_hex(crate::constants::PALETTE.warning_badge))
+        }
+    };
+    let count = if model_count > 0 && *crate::constants::SHOW_MODEL_COUNT {
+        model_count.to_string()
+    } else {
+        String::new()
+    };
+    format!("{count} | sfimage={symbol} sfcolor={color}")
+}
+
+/// Submenu listing the most recent agent, model and alert events, with this
+/// run's display and polling transitions nested below
+fn build_event_history_item(events: &crate::events::EventLog) -> MenuItem {
+    let mut submenu: Vec<MenuItem> = events
+        .recent(EVENT_HISTORY_ITEMS)
+        .map(|event| MenuItem::Content(ContentItem::new(event_text(event))))
+        .collect();
+
+    if submenu.is_empty() {
+        submenu.push(MenuItem::Content(create_colored_item(
+            "No state changes recorded",
+            "#666666",
+        )));
+    }
+
+    // Display and polling changes are frequent and only kept for this run
+    let transient: Vec<MenuItem> = events
+        .recent_transient(EVENT_HISTORY_ITEMS)
+        .map(|event| MenuItem::Content(ContentItem::new(event_text(event))))
+        .collect();
+    if !transient.is_empty() {
+        submenu.push(MenuItem::Sep);
+        submenu.push(MenuItem::Content(
+            ContentItem::new("Display & Polling").sub(transient),
+        ));
+    }
+
+    MenuItem::Content(ContentItem::new(":clock.arrow.circlepath: Event History").sub(submenu))
+}
+
+/// "5m ago — Agent: Stopped → Running (reason)"
+fn event_text(event: &crate::events::StateEvent) -> String {
+    let at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(event.timestamp);
+    let mut text = if event.from.is_empty() {
+        format!(
+            "{} — {}: {}",
+            format_time_ago(at),
+            event.kind.label(),
+            event.to
+        )
+    } else {
+        format!(
+            "{} — {}: {} → {}",
+            format_time_ago(at),
+            event.kind.label(),
+            event.from,
+            event.to
+        )
+    };
+    if let Some(ref reason) = event.reason {
+        text.push_str(&format!(" ({reason})"));
+    }
+    text
+}
+
+/// Submenu listing configured recovery policies and the actions they have taken
+fn build_recovery_item(engine: &crate::policies::PolicyEngine) -> MenuItem {
+    let mut submenu: Vec<MenuItem> = engine
+        .policies()
+        .map(|policy| MenuItem::Content(ContentItem::new(policy.description())))
+        .collect();
+
+    submenu.push(MenuItem::Sep);
+    if engine.recent_actions.is_empty() {
+        submenu.push(MenuItem::Content(create_colored_item(
+            "No automatic actions taken",
+            "#666666",
+        )));
+    }
+    for action in &engine.recent_actions {
+        let color = if action.outcome == "succeeded" {
+            get_hex_color("green")
+        } else {
+            get_hex_color("yellow")
+        };
+        submenu.push(MenuItem::Content(create_colored_item(
+            &format!(
+                "{} — {}: {}",
+                format_time_ago(action.at),
+                action.policy,
+                action.outcome
+            ),
+            color,
+        )));
+    }
+
+    let title = match engine.recent_actions.front() {
+        Some(latest) => format!(
+            ":wrench.and.screwdriver: Automatic Recovery (last action {})",
+            format_time_ago(latest.at)
+        ),
+        None => ":wrench.and.screwdriver: Automatic Recovery".to_string(),
+    };
+    MenuItem::Content(ContentItem::new(title).sub(submenu))
+}
+
+fn format_time_ago(at: std::time::SystemTime) -> String {
+    let secs = at.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+    match secs {
+        s if s < 60 => format!("{s}s ago"),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86_400 => format!("{}h {}m ago", s / 3600, (s % 3600) / 60),
+        s => format!("{}d ago", s / 86_400),
     }
 }
 
@@ -746,6 +2325,44 @@ fn build_label(
     }
 }
 
+/// Yellow or red text for elevated memory pressure
+fn with_pressure_color(item: MenuItem, pressure: Option<MemoryPressure>) -> MenuItem {
+    let color = match pressure {
+        Some(MemoryPressure::Warning) => get_hex_color("yellow"),
+        Some(MemoryPressure::Critical) => get_hex_color("red"),
+        _ => return item,
+    };
+    match item {
+        MenuItem::Content(content) => MenuItem::Content(content.color(color).unwrap()),
+        item => item,
+    }
+}
+
+/// Red text for a row whose alert threshold is exceeded
+fn with_alert_color(item: MenuItem, breached: bool) -> MenuItem {
+    match item {
+        MenuItem::Content(content) if breached => {
+            MenuItem::Content(content.color(get_hex_color("red")).unwrap())
+        }
+        item => item,
+    }
+}
+
+/// "Copy Data (CSV)" action for one chart's series
+fn copy_csv_item(series: &str, model: Option<&str>) -> Option<MenuItem> {
+    let exe = std::env::current_exe().ok()?;
+    let exe_str = exe.to_str()?;
+
+    let command = match model {
+        Some(model) => plugin_command(exe_str, &["copy_csv", series, model]),
+        None => plugin_command(exe_str, &["copy_csv", series]),
+    };
+    let item = ContentItem::new(":doc.on.clipboard: Copy Data (CSV)")
+        .command(command)
+        .ok()?;
+    Some(MenuItem::Content(item))
+}
+
 fn add_chart(
     item: &mut ContentItem,
     data: &CircularQueue<TimestampedValue>,
@@ -766,6 +2383,8 @@ fn get_system_stats(metric_name: &str, history: &AllMetricsHistory) -> crate::mo
     match metric_name {
         "CPU" => history.get_cpu_stats(),
         "Memory" => history.get_system_memory_stats(),
+        "GPU" => history.get_gpu_stats(),
+        "Power" => history.get_power_stats(),
         "Llama Memory" => history.get_memory_stats(),
         _ => unreachable!(),
     }
@@ -888,15 +2507,59 @@ fn calculate_total_system_memory(history: &AllMetricsHistory) -> f64 {
     }
 }
 
-fn format_tps(v: f64) -> String {
+pub(crate) fn format_tps(v: f64) -> String {
     format!("{v:.1} tok/s")
 }
 
-fn format_percent(v: f64) -> String {
+pub(crate) fn format_percent(v: f64) -> String {
     format!("{v:.1}%")
 }
 
-fn format_memory_mb(mb: f64) -> String {
+fn format_load_time(duration: std::time::Duration) -> String {
+    match duration.as_secs() {
+        secs if secs < 60 => format!("{:.1}s", duration.as_secs_f64()),
+        secs => format_duration(secs),
+    }
+}
+
+fn format_watts(w: f64) -> String {
+    format!("{w:.1} W")
+}
+
+fn format_ms(ms: f64) -> String {
+    if ms >= 1000.0 {
+        format!("{:.2}s", ms / 1000.0)
+    } else {
+        format!("{ms:.0}ms")
+    }
+}
+
+fn format_count(n: u64) -> String {
+    match n {
+        n if n >= 1_000_000 => format!("{:.1}M", n as f64 / 1_000_000.0),
+        n if n >= 1_000 => format!("{:.1}k", n as f64 / 1_000.0),
+        n => n.to_string(),
+    }
+}
+
+/// Values of unknown meaning: whole numbers as they are, others to two decimals
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+fn format_duration(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{s}s"),
+        s if s < 3600 => format!("{}m", s / 60),
+        s => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+    }
+}
+
+pub(crate) fn format_memory_mb(mb: f64) -> String {
     if mb >= 1024.0 {
         format!("{:.1} GB", mb / 1024.0)
     } else {
@@ -906,12 +2569,39 @@ fn format_memory_mb(mb: f64) -> String {
 
 pub fn build_menu(state: &PluginState) -> crate::Result<String> {
     let mut menu = MenuBuilder::new();
+    let exe = std::env::current_exe().unwrap();
+    let exe_str = exe.to_str().unwrap();
 
     let display_state = state.get_display_state();
 
-    menu.add_title(display_state);
+    menu.add_title(
+        display_state,
+        state.get_icon_overlay(),
+        state.model_states.len(),
+        state.memory_dot_color(display_state),
+    );
     menu.add_separator();
+    if !state.remote_hosts.is_empty() {
+        menu.add_fleet_summary(state);
+        menu.add_separator();
+    }
     menu.add_status_message(display_state);
+    if let Some(started_at) = state.service_started_at {
+        menu.add_uptime(started_at);
+    }
+    menu.add_watchdog_status(&state.watchdog);
+    if display_state == DisplayState::ServiceDegraded {
+        if let Some(ref problem) = state.port_problem {
+            menu.add_port_problem(problem);
+        }
+    }
+    menu.add_log_summary(&state.log_analyzer, exe_str);
+    if matches!(
+        display_state,
+        DisplayState::StartupFailed | DisplayState::CrashLoop
+    ) {
+        menu.add_startup_failure_log();
+    }
     menu.add_separator();
 
     let has_models = state
@@ -920,13 +2610,27 @@ pub fn build_menu(state: &PluginState) -> crate::Result<String> {
         .is_some_and(|m| !m.models.is_empty());
 
     // Show system metrics for all states where they're being collected
-    menu.add_system_metrics_section(&state.metrics_history);
+    menu.add_system_metrics_section(
+        &state.metrics_history,
+        &state.energy,
+        &state.system,
+        &state.alerts,
+    );
+    if let Some(ref hardware) = state.hardware {
+        menu.add_hardware_section(hardware);
+    }
 
     if let Some(ref all_metrics) = state.current_all_metrics {
         let mut sorted_models = all_metrics.models.clone();
         sorted_models.sort_by(|a, b| a.model_name.cmp(&b.model_name));
 
         for model_metrics in &sorted_models {
+            if model_metrics.model_state == crate::models::ModelState::Stopping {
+                menu.add_separator();
+                menu.add_stopping_model(&model_metrics.model_name);
+                continue;
+            }
+
             if let Some(model_history) = state
                 .metrics_history
                 .get_model_history(&model_metrics.model_name)
@@ -937,21 +2641,81 @@ pub fn build_menu(state: &PluginState) -> crate::Result<String> {
                         &model_metrics.model_name,
                         model_history,
                         &model_metrics.metrics,
+                        &state.alerts,
                     );
+                    menu.add_load_time(&state.load_times.recent(&model_metrics.model_name));
+                    if model_metrics.model_state == crate::models::ModelState::Running
+                        && !*crate::constants::READ_ONLY_MODE
+                    {
+                        menu.add_test_prompt(&model_metrics.model_name, exe_str);
+                    }
+                    menu.add_copy_model_name(&model_metrics.model_name, exe_str);
+                    menu.add_model_settings(
+                        &model_metrics.model_name,
+                        state
+                            .configured_models
+                            .concurrency(&model_metrics.model_name),
+                        exe_str,
+                    );
+
+                    #[cfg(feature = "sqlite")]
+                    if let Some(ref store) = state.metrics_store {
+                        menu.add_long_term_history(store, &model_metrics.model_name);
+                    }
                 }
             }
         }
     }
 
-    let exe = std::env::current_exe().unwrap();
-    let exe_str = exe.to_str().unwrap();
+    if let Some(proxy) = state
+        .current_all_metrics
+        .as_ref()
+        .and_then(|metrics| metrics.proxy)
+    {
+        menu.add_proxy_section(&proxy);
+    }
+
+    for remote in &state.remote_hosts {
+        menu.add_remote_host(remote);
+    }
+
+    menu.add_config_issues(state.config_validation.issues(), exe_str);
+    menu.add_model_config_warnings(state.binary_checker.warnings());
+    menu.add_today_section(&state.sessions);
 
     menu.add_separator();
+    if state.config_changed_since_start() {
+        if let Ok(item) = CONFIG_CHANGED_COMMAND.create_item(exe_str) {
+            menu.items.push(MenuItem::Content(
+                item.color(get_hex_color("yellow")).unwrap(),
+            ));
+        }
+    }
+    if state.plist_drift.is_outdated() {
+        if let Ok(item) = REINSTALL_COMMAND.create_item(exe_str) {
+            menu.items.push(MenuItem::Content(
+                item.color(get_hex_color("yellow")).unwrap(),
+            ));
+            menu.items.push(MenuItem::Content(create_colored_item(
+                &format!("Changed: {}", state.plist_drift.changed_keys().join(", ")),
+                "#666666",
+            )));
+        }
+    }
+    menu.add_configured_models(state.configured_models.names(), state, exe_str);
     menu.add_quick_actions_section(display_state, has_models, &state.service_status, exe_str);
+    menu.add_model_switcher(display_state, state, exe_str);
+    menu.add_instances(&state.launch_agents, exe_str);
+    menu.add_recent_logs(exe_str);
+    if let Some(latest) = state
+        .updates
+        .available(state.versions.llama_swap.as_deref())
+    {
+        menu.add_update_available(latest, exe_str);
+    }
     menu.add_settings_section(display_state, has_models, state, exe_str);
 
-    let built_menu = menu.build();
-    Ok(built_menu.to_string())
+    Ok(menu.build())
 }
 
 pub fn build_error_menu(message: &str) -> Result<String, std::fmt::Error> {
@@ -976,6 +2740,66 @@ pub fn build_error_menu(message: &str) -> Result<String, std::fmt::Error> {
 mod tests {
     use super::*;
     use crate::state_model::AgentState;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_log_line_color() {
+        assert_eq!(
+            log_line_color("srv  load_model: ERROR failed to open"),
+            Some(get_hex_color("red"))
+        );
+        assert_eq!(
+            log_line_color("[WARN] context truncated"),
+            Some(get_hex_color("yellow"))
+        );
+        assert_eq!(log_line_color("model loaded"), None);
+    }
+
+    #[test]
+    fn test_error_badge_flashes_on_alternate_frames() {
+        let mut state = PluginState::new().unwrap();
+        state.error_count = *crate::constants::ERROR_BADGE_THRESHOLD;
+
+        // A single run always sees frame 1; the badge must still show
+        state.frame = 1;
+        assert_eq!(state.icon_overlay(true), icons::IconOverlay::ErrorBadge);
+        assert_eq!(state.icon_overlay(false), icons::IconOverlay::ErrorBadge);
+
+        state.streaming = true;
+        state.frame = 4;
+        assert_eq!(state.icon_overlay(true), icons::IconOverlay::ErrorBadge);
+        state.frame = 5;
+        assert_eq!(state.icon_overlay(true), icons::IconOverlay::None);
+        // Backoff polls too slowly for a blink to be seen
+        state.polling_mode = crate::state_model::PollingMode::Backoff { failures: 5 };
+        assert_eq!(state.icon_overlay(true), icons::IconOverlay::ErrorBadge);
+
+        state.error_count = 0;
+        state.frame = 4;
+        assert_ne!(state.icon_overlay(true), icons::IconOverlay::ErrorBadge);
+    }
+
+    #[test]
+    fn test_symbol_title_line() {
+        assert_eq!(
+            symbol_title_line(
+                "brain",
+                DisplayState::ModelReady,
+                icons::IconOverlay::None,
+                2
+            ),
+            "2 | sfimage=brain sfcolor=#34C759"
+        );
+        assert_eq!(
+            symbol_title_line(
+                "cpu",
+                DisplayState::ServiceStopped,
+                icons::IconOverlay::ErrorBadge,
+                0
+            ),
+            " | sfimage=exclamationmark.triangle.fill sfcolor=#FF9500"
+        );
+    }
     #[test]
     fn test_menu_with_running_service() {
         let state = create_test_state_for_running_service();
@@ -991,6 +2815,37 @@ mod tests {
         assert!(!menu_str.contains("Start Llama-Swap Service"));
     }
 
+    #[test]
+    fn test_menu_offers_endpoint_when_ui_unavailable() {
+        let mut state = create_test_state_for_running_service();
+
+        state.service_status.ui_available = false;
+        let menu_str = build_menu(&state).unwrap();
+        assert!(menu_str.contains("Copy API Endpoint"));
+        assert!(!menu_str.contains("Open Llama-Swap UI"));
+
+        state.service_status.ui_available = true;
+        let menu_str = build_menu(&state).unwrap();
+        assert!(menu_str.contains("Open Llama-Swap UI"));
+        // Still copyable from the Advanced menu for pointing clients at it
+        assert!(menu_str.contains("Copy API Endpoint"));
+    }
+
+    #[test]
+    fn test_menu_offers_restart_after_config_change() {
+        let mut state = create_test_state_for_running_service();
+        let started_at = SystemTime::now() - Duration::from_secs(600);
+        state.service_started_at = Some(started_at.duration_since(UNIX_EPOCH).unwrap().as_secs());
+
+        state.config_modified = Some(started_at - Duration::from_secs(60));
+        let menu_str = build_menu(&state).unwrap();
+        assert!(!menu_str.contains("Config changed"));
+
+        state.config_modified = Some(started_at + Duration::from_secs(60));
+        let menu_str = build_menu(&state).unwrap();
+        assert!(menu_str.contains("Config changed — Restart to apply"));
+    }
+
     #[test]
     fn test_menu_with_stopped_service() {
         let state = create_test_state_for_stopped_service();
@@ -1023,6 +2878,157 @@ mod tests {
         assert!(menu_str.contains("AgentNotLoaded"));
     }
 
+    #[test]
+    fn test_menu_with_degraded_service() {
+        let mut state = create_test_state_for_stopped_service();
+        state.agent_state = AgentState::Degraded;
+
+        // The first failed polls after a start are treated as startup
+        state.error_count = 1;
+        assert_eq!(state.get_display_state(), DisplayState::AgentStarting);
+
+        state.error_count = 3;
+        assert_eq!(state.get_display_state(), DisplayState::ServiceDegraded);
+
+        let menu_str = build_menu(&state).unwrap();
+        assert!(menu_str.contains("Service not responding"));
+        assert!(menu_str.contains("Restart Llama-Swap Service"));
+        assert!(menu_str.contains("View Service Logs"));
+        assert!(!menu_str.contains("Start Llama-Swap Service"));
+    }
+
+    #[test]
+    fn test_menu_lists_instances() {
+        let mut state = create_test_state_for_stopped_service();
+        let agent = |name: Option<&str>, pid| crate::launch_agents::LaunchAgent {
+            name: name.map(str::to_string),
+            label: match name {
+                Some(name) => format!("com.user.llama-swap.{name}"),
+                None => "com.user.llama-swap".to_string(),
+            },
+            port: Some(45787),
+            loaded: true,
+            pid,
+        };
+        state.launch_agents = vec![agent(None, None), agent(Some("embeddings"), Some(812))];
+
+        let menu_str = build_menu(&state).unwrap();
+        assert!(menu_str.contains("Instances (1/2 running)"));
+        assert!(menu_str.contains("embeddings — Running (PID 812) · port 45787"));
+        assert!(menu_str.contains("param1=--instance param2=embeddings param3=do_stop"));
+    }
+
+    #[test]
+    fn test_menu_shows_remote_hosts_without_controls() {
+        let mut state = create_test_state_for_stopped_service();
+        let mut remote = crate::remote::RemoteHostState::new(crate::plugin_config::RemoteHost {
+            name: "Studio".to_string(),
+            host: "studio.local".to_string(),
+            port: 45786,
+            ssh: None,
+            ssh_local_port: None,
+        });
+        remote.metrics = Some(crate::models::AllMetrics {
+            models: vec![crate::models::ModelMetrics {
+                model_name: "qwen".to_string(),
+                model_state: crate::models::ModelState::Running,
+                metrics: crate::models::Metrics {
+                    predicted_tokens_per_sec: 42.0,
+                    ..Default::default()
+                },
+            }],
+            proxy: None,
+        });
+        state.remote_hosts = vec![remote];
+
+        let menu_str = build_menu(&state).unwrap();
+        let remote_section = &menu_str[menu_str.find(":server.rack: Studio").unwrap()..];
+        assert!(remote_section.contains("qwen — 42.0 tok/s · Idle"));
+        assert!(remote_section.contains("href=http://studio.local:45786/ui/models"));
+        // Controls below belong to the local service only
+        let remote_section = &remote_section[..remote_section.find("Open Web UI").unwrap()];
+        assert!(!remote_section.contains("do_"));
+    }
+
+    #[test]
+    fn test_model_settings_offer_concurrency_choices() {
+        let mut menu = MenuBuilder::new();
+        let concurrency = crate::config_parser::Concurrency {
+            parallel: Some(4),
+            cont_batching: None,
+        };
+        menu.add_model_settings("qwen", Some(concurrency), "/bin/plugin");
+        let menu_str = menu.build();
+
+        assert!(menu_str.contains("Parallel Slots: 4 · Continuous Batching: Default"));
+        assert!(menu_str.contains("Edit Model Settings…"));
+        assert!(menu_str.contains(":checkmark: 4 |"));
+        assert!(menu_str
+            .contains("param1=set_model_option param2=qwen param3=cont_batching param4=off"));
+    }
+
+    #[test]
+    fn test_menu_rolls_up_hosts() {
+        let mut state = create_test_state_for_running_service();
+        let host = |name: &str| {
+            crate::remote::RemoteHostState::new(crate::plugin_config::RemoteHost {
+                name: name.to_string(),
+                host: format!("{}.local", name.to_lowercase()),
+                port: 45786,
+                ssh: None,
+                ssh_local_port: None,
+            })
+        };
+        let mut studio = host("Studio");
+        studio.metrics = Some(crate::models::AllMetrics {
+            models: vec![crate::models::ModelMetrics {
+                model_name: "qwen".to_string(),
+                model_state: crate::models::ModelState::Running,
+                metrics: crate::models::Metrics {
+                    predicted_tokens_per_sec: 42.0,
+                    ..Default::default()
+                },
+            }],
+            proxy: None,
+        });
+        state.remote_hosts = vec![studio, host("Mini")];
+        let local_tps = state
+            .current_all_metrics
+            .as_ref()
+            .unwrap()
+            .running_totals()
+            .1;
+
+        let menu_str = build_menu(&state).unwrap();
+        let summary = format!("3 hosts · 2 models · {}", format_tps(local_tps + 42.0));
+        let at = menu_str.find(&summary).unwrap();
+        // Ahead of the local service's own sections
+        assert!(at < menu_str.find("test-model").unwrap());
+        assert!(menu_str.contains("Studio — 1 model · 42.0 tok/s"));
+        assert!(menu_str.contains("Mini — connecting…"));
+    }
+
+    #[test]
+    fn test_menu_with_stopping_model() {
+        let mut state = create_test_state_for_running_service();
+        state.model_states.insert(
+            "test-model".to_string(),
+            crate::state_model::ModelStateMachine::new(
+                crate::state_model::ModelState::Stopping,
+                std::time::Instant::now(),
+            ),
+        );
+        if let Some(ref mut all_metrics) = state.current_all_metrics {
+            all_metrics.models[0].model_state = crate::models::ModelState::Stopping;
+        }
+
+        assert_eq!(state.get_display_state(), DisplayState::ModelStopping);
+
+        let menu_str = build_menu(&state).unwrap();
+        assert!(menu_str.contains("Unloading model..."));
+        assert!(menu_str.contains("test-model — unloading…"));
+    }
+
     #[test]
     fn test_error_menu() {
         let error_menu = build_error_menu("Test error message").unwrap();
@@ -1034,7 +3040,7 @@ mod tests {
 
     fn create_test_state_for_running_service() -> PluginState {
         use crate::models::{AllMetrics, Metrics, ModelMetrics, ModelState};
-        use crate::state_model::{AgentState, ModelState as NewModelState};
+        use crate::state_model::{AgentState, ModelState as NewModelState, ModelStateMachine};
 
         let mut state = PluginState::new().unwrap();
 
@@ -1042,9 +3048,10 @@ mod tests {
         state.agent_state = AgentState::Running;
 
         // Add a running model
-        state
-            .model_states
-            .insert("test-model".to_string(), NewModelState::Running);
+        state.model_states.insert(
+            "test-model".to_string(),
+            ModelStateMachine::new(NewModelState::Running, std::time::Instant::now()),
+        );
 
         // Set up some dummy metrics to make the state consistent
         let dummy_metrics = AllMetrics {
@@ -1058,9 +3065,18 @@ mod tests {
                     requests_deferred: 0,
 
                     n_decode_total: 100,
+                    prompt_tokens_total: 500.0,
+                    tokens_predicted_total: 2000.0,
                     memory_mb: 1000.0,
+                    kv_cache_usage_ratio: 0.0,
+                    kv_cache_tokens: 0.0,
+                    request_duration_buckets: Vec::new(),
+                    slots: Vec::new(),
+                    upstream_error: Some(false),
+                    extra: Vec::new(),
                 },
             }],
+            proxy: None,
         };
         state.current_all_metrics = Some(dummy_metrics);
 
@@ -1,9 +1,11 @@
-use crate::constants;
 use crate::models::{
-    AllMetrics, Metrics, ModelMetrics, RunningModel, RunningResponse, SystemMetrics,
+    Aggregation, AllMetrics, HistogramBucket, MemoryPressure, Metrics, ModelMetrics, ProxyMetrics,
+    RunningModel, RunningResponse, SlotInfo, SystemMetrics,
 };
+use crate::plugin_config::ExtraMetric;
 use crate::types::error_helpers::{with_context, CONNECT_API, PARSE_JSON};
 use reqwest::blocking::Client;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -19,66 +21,356 @@ pub struct ProcessInfo {
 #[derive(Debug)]
 struct PrometheusMetric {
     name: String,
+    labels: Vec<(String, String)>,
     value: f64,
 }
 
+impl PrometheusMetric {
+    fn label(&self, name: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parse one sample line of the Prometheus text format (or OpenMetrics)
+///
+/// `name{label="value",...} value [timestamp] [# exemplar]`. Anything that doesn't
+/// follow the format is rejected as a whole rather than partially read.
 fn parse_prometheus_line(line: &str) -> Option<PrometheusMetric> {
-    if line.starts_with('#') || line.trim().is_empty() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
         return None;
     }
 
-    let parts: Vec<&str> = line.splitn(2, ' ').collect();
-    if parts.len() != 2 {
+    let name_end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(line.len());
+    let name = &line[..name_end];
+    if !is_valid_name(name, true) {
         return None;
     }
 
-    let metric_part = parts[0];
-    let value = parts[1].parse::<f64>().ok()?;
+    let mut rest = &line[name_end..];
+    let mut labels = Vec::new();
+    if let Some(after) = rest.trim_start().strip_prefix('{') {
+        (labels, rest) = parse_labels(after)?;
+    }
+    if !rest.starts_with([' ', '\t']) {
+        return None;
+    }
 
-    if let Some(label_start) = metric_part.find('{') {
-        let name = metric_part[..label_start].to_string();
+    // OpenMetrics exemplars follow the value after a `#`
+    let rest = rest.split_once('#').map_or(rest, |(sample, _)| sample);
+    let mut fields = rest.split_whitespace();
+    let value = fields.next()?.parse::<f64>().ok()?;
+    if let Some(timestamp) = fields.next() {
+        timestamp.parse::<f64>().ok()?;
+    }
+    if fields.next().is_some() {
+        return None;
+    }
 
-        Some(PrometheusMetric { name, value })
-    } else {
-        Some(PrometheusMetric {
-            name: metric_part.to_string(),
-            value,
-        })
+    Some(PrometheusMetric {
+        name: name.to_string(),
+        labels,
+        value,
+    })
+}
+
+/// Every well-formed sample in an exposition; NaN and infinite values are
+/// dropped so one bad series can't poison a sum
+fn parse_samples(text: &str) -> impl Iterator<Item = PrometheusMetric> + '_ {
+    text.lines()
+        .filter_map(parse_prometheus_line)
+        .filter(|metric| metric.value.is_finite())
+}
+
+/// Metric names may contain colons, label names may not
+fn is_valid_name(name: &str, allow_colon: bool) -> bool {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || (allow_colon && c == ':');
+    name.chars().next().is_some_and(|c| !c.is_ascii_digit()) && name.chars().all(valid)
+}
+
+/// Parse `key="value",...}` up to the closing brace, returning the labels and
+/// whatever follows the brace
+fn parse_labels(text: &str) -> Option<(Vec<(String, String)>, &str)> {
+    let mut labels = Vec::new();
+    let mut rest = text.trim_start();
+
+    loop {
+        if let Some(after) = rest.strip_prefix('}') {
+            return Some((labels, after));
+        }
+
+        let (key, after_key) = rest.split_once('=')?;
+        let key = key.trim();
+        if !is_valid_name(key, false) {
+            return None;
+        }
+        let (value, after_value) = parse_label_value(after_key.trim_start().strip_prefix('"')?)?;
+        labels.push((key.to_string(), value));
+
+        rest = after_value.trim_start();
+        match rest.strip_prefix(',') {
+            Some(after) => rest = after.trim_start(), // A trailing comma is allowed
+            None if rest.starts_with('}') => {}
+            None => return None,
+        }
+    }
+}
+
+/// Unescape a label value up to its closing quote, returning what follows it
+fn parse_label_value(text: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &text[i + 1..])),
+            '\\' => value.push(match chars.next()?.1 {
+                '\\' => '\\',
+                '"' => '"',
+                'n' => '\n',
+                _ => return None,
+            }),
+            c => value.push(c),
+        }
     }
+    None
 }
 
 fn parse_prometheus_metrics(text: &str) -> HashMap<String, f64> {
-    const METRIC_MAPPINGS: &[(&str, &str)] = &[
-        ("llamacpp:prompt_tokens_seconds", "prompt_tokens_per_sec"),
+    const METRIC_MAPPINGS: &[(&str, &str, Aggregation)] = &[
+        (
+            "llamacpp:prompt_tokens_seconds",
+            "prompt_tokens_per_sec",
+            Aggregation::Sum,
+        ),
         (
             "llamacpp:predicted_tokens_seconds",
             "predicted_tokens_per_sec",
+            Aggregation::Sum,
+        ),
+        (
+            "llamacpp:requests_processing",
+            "requests_processing",
+            Aggregation::Sum,
+        ),
+        (
+            "llamacpp:requests_deferred",
+            "requests_deferred",
+            Aggregation::Sum,
+        ),
+        (
+            "llamacpp:n_decode_total",
+            "n_decode_total",
+            Aggregation::Sum,
+        ),
+        (
+            "llamacpp:prompt_tokens_total",
+            "prompt_tokens_total",
+            Aggregation::Sum,
+        ),
+        (
+            "llamacpp:tokens_predicted_total",
+            "tokens_predicted_total",
+            Aggregation::Sum,
+        ),
+        (
+            "llamacpp:kv_cache_usage_ratio",
+            "kv_cache_usage_ratio",
+            Aggregation::Max,
+        ),
+        (
+            "llamacpp:kv_cache_tokens",
+            "kv_cache_tokens",
+            Aggregation::Sum,
         ),
-        ("llamacpp:requests_processing", "requests_processing"),
-        ("llamacpp:requests_deferred", "requests_deferred"),
-        ("llamacpp:n_decode_total", "n_decode_total"),
     ];
 
-    let parsed_metrics: Vec<_> = text.lines().filter_map(parse_prometheus_line).collect();
+    let mut data = HashMap::new();
+    for metric in parse_samples(text) {
+        if let Some((_, target, aggregation)) = METRIC_MAPPINGS
+            .iter()
+            .find(|(source, ..)| *source == metric.name)
+        {
+            aggregate_into(&mut data, target, *aggregation, metric.value);
+        }
+    }
+    data
+}
 
-    parsed_metrics
-        .into_iter()
-        .filter_map(|metric| {
-            METRIC_MAPPINGS
-                .iter()
-                .find(|(source, _)| *source == metric.name)
-                .map(|(_, target)| ((*target).to_string(), metric.value))
-        })
+/// Values of the user's extra metrics, in config order; missing metrics are left out
+fn parse_extra_metrics(text: &str, extra_metrics: &[ExtraMetric]) -> Vec<(String, f64)> {
+    if extra_metrics.is_empty() {
+        return Vec::new();
+    }
+
+    let mut data = HashMap::new();
+    for metric in parse_samples(text) {
+        for extra in extra_metrics.iter().filter(|e| e.metric == metric.name) {
+            aggregate_into(&mut data, &extra.label, extra.aggregate, metric.value);
+        }
+    }
+
+    extra_metrics
+        .iter()
+        .filter_map(|extra| Some((extra.label.clone(), *data.get(&extra.label)?)))
         .collect()
 }
 
-pub fn collect_system_metrics(system: &mut sysinfo::System) -> SystemMetrics {
-    system.refresh_all();
+/// Fold one labeled series into the running value for its field
+fn aggregate_into(
+    data: &mut HashMap<String, f64>,
+    key: &str,
+    aggregation: Aggregation,
+    value: f64,
+) {
+    data.entry(key.to_string())
+        .and_modify(|acc| *acc = aggregation.combine(*acc, value))
+        .or_insert(value);
+}
 
-    // CPU usage
-    system.refresh_cpu_all();
-    std::thread::sleep(Duration::from_millis(200));
-    system.refresh_cpu_all();
+/// Parse llama-swap's own /metrics, summing each counter across its labels
+/// (requests are labeled by model and status, swaps by model)
+fn parse_proxy_metrics(text: &str) -> Option<ProxyMetrics> {
+    const PROXY_METRICS: &[&str] = &[
+        "llama_swap_requests_total",
+        "llama_swap_upstream_errors_total",
+        "llama_swap_model_swaps_total",
+    ];
+
+    let mut totals = [None::<f64>; 3];
+    for metric in parse_samples(text) {
+        if let Some(i) = PROXY_METRICS.iter().position(|name| *name == metric.name) {
+            *totals[i].get_or_insert(0.0) += metric.value;
+        }
+    }
+
+    // Anything else serving /metrics isn't llama-swap
+    if totals.iter().all(Option::is_none) {
+        return None;
+    }
+    let [requests, errors, swaps] = totals.map(|total| total.unwrap_or(0.0));
+    Some(ProxyMetrics {
+        requests_total: requests,
+        upstream_errors_total: errors,
+        swaps_total: swaps,
+    })
+}
+
+fn fetch_proxy_metrics(client: &Client, endpoint: &str) -> Option<ProxyMetrics> {
+    let url = format!("{endpoint}/metrics");
+
+    client
+        .get(&url)
+        .timeout(Duration::from_secs(1))
+        .send()
+        .ok()
+        .filter(|response| response.status().is_success())
+        .and_then(|response| response.text().ok())
+        .and_then(|text| parse_proxy_metrics(&text))
+}
+
+/// Histogram of end-to-end request durations, in seconds
+const REQUEST_DURATION_HISTOGRAM: &str = "llamacpp:request_duration_seconds";
+
+/// Collect the `_bucket` series of a histogram, sorted by upper bound, summing
+/// buckets with the same bound across any other labels
+fn parse_histogram_buckets(text: &str, name: &str) -> Vec<HistogramBucket> {
+    let bucket_name = format!("{name}_bucket");
+    let mut buckets: Vec<HistogramBucket> = Vec::new();
+    for metric in parse_samples(text) {
+        if metric.name != bucket_name {
+            continue;
+        }
+        let Some(le) = metric.label("le") else {
+            continue;
+        };
+        let le = if le == "+Inf" {
+            f64::INFINITY
+        } else {
+            match le.parse() {
+                Ok(le) => le,
+                Err(_) => continue,
+            }
+        };
+
+        match buckets.iter_mut().find(|bucket| bucket.le == le) {
+            Some(bucket) => bucket.count += metric.value,
+            None => buckets.push(HistogramBucket {
+                le,
+                count: metric.value,
+            }),
+        }
+    }
+    buckets.sort_by(|a, b| a.le.total_cmp(&b.le));
+    buckets
+}
+
+/// A slot as llama-server reports it; field names changed across versions
+#[derive(Debug, Deserialize)]
+struct RawSlot {
+    id: u32,
+    #[serde(default)]
+    is_processing: Option<bool>,
+    #[serde(default)]
+    state: Option<u8>, // Older servers: 0 idle, 1 processing
+    #[serde(default)]
+    n_ctx: u64,
+    #[serde(default)]
+    next_token: serde_json::Value, // An object in older servers, an array in newer ones
+}
+
+impl From<RawSlot> for SlotInfo {
+    fn from(raw: RawSlot) -> Self {
+        let next_token = match &raw.next_token {
+            serde_json::Value::Array(tokens) => tokens.first(),
+            value => Some(value),
+        };
+        Self {
+            id: raw.id,
+            is_processing: raw.is_processing.unwrap_or(raw.state == Some(1)),
+            n_ctx: raw.n_ctx,
+            n_decoded: next_token
+                .and_then(|t| t.get("n_decoded"))
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Parse llama-server's `GET /slots` response
+fn parse_slots(json: &str) -> Vec<SlotInfo> {
+    let mut slots: Vec<SlotInfo> = serde_json::from_str::<Vec<RawSlot>>(json)
+        .map(|raw| raw.into_iter().map(SlotInfo::from).collect())
+        .unwrap_or_default();
+    slots.sort_by_key(|slot| slot.id);
+    slots
+}
+
+/// Refresh only what the plugin reads: memory, and each process's memory and
+/// command line (the command line only once, when a process first appears)
+pub fn refresh_system(system: &mut sysinfo::System) {
+    system.refresh_memory();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::All,
+        sysinfo::ProcessRefreshKind::new()
+            .with_memory()
+            .with_cmd(sysinfo::UpdateKind::OnlyIfNotSet),
+    );
+}
+
+/// Collect system metrics without blocking
+///
+/// CPU usage is the delta between two refreshes, and the previous one is kept in
+/// the long-lived `System`, so this measures usage since the last poll. The
+/// first call on a fresh `System` has no baseline and reads 0.
+pub fn collect_system_metrics(system: &mut sysinfo::System) -> SystemMetrics {
+    refresh_system(system);
+    system.refresh_cpu_usage();
 
     let cpu_usage_percent = f64::from(system.global_cpu_usage());
 
@@ -93,9 +385,28 @@ pub fn collect_system_metrics(system: &mut sysinfo::System) -> SystemMetrics {
         cpu_usage_percent,
         used_memory_gb,
         memory_usage_percent,
+        gpu: crate::gpu::sample(),
+        swap_used_gb: bytes_to_gb(system.used_swap()),
+        memory_pressure: memory_pressure(),
     }
 }
 
+/// The kernel's memory pressure level; more telling than used GB on small machines,
+/// where macOS keeps memory nearly full with cache and compressed pages
+fn memory_pressure() -> Option<MemoryPressure> {
+    let output = std::process::Command::new("sysctl")
+        .args(["-n", "kern.memorystatus_vm_pressure_level"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let level = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    MemoryPressure::from_level(level)
+}
+
 pub fn get_llama_server_memory_mb(system: &sysinfo::System) -> f64 {
     get_detailed_llama_processes(system)
         .iter()
@@ -182,23 +493,60 @@ fn infer_model_from_command(cmd_line: &str) -> Option<String> {
     None
 }
 
-fn fetch_model_metrics(client: &Client, model: &RunningModel) -> HashMap<String, f64> {
+fn fetch_model_metrics(client: &Client, endpoint: &str, model: &RunningModel) -> Metrics {
+    let response = fetch_upstream(client, endpoint, model, "metrics");
+    let upstream_error = Some(response == Err(UpstreamFailure::ServerError));
+    let metrics = response
+        .map(|text| Metrics {
+            request_duration_buckets: parse_histogram_buckets(&text, REQUEST_DURATION_HISTOGRAM),
+            extra: parse_extra_metrics(&text, &crate::plugin_config::get().extra_metrics),
+            ..create_metrics_from_data(&parse_prometheus_metrics(&text))
+        })
+        .unwrap_or_default();
+
+    // Servers started with --no-slots answer 501; the breakdown is just left out
+    let slots = fetch_upstream(client, endpoint, model, "slots")
+        .map(|json| parse_slots(&json))
+        .unwrap_or_default();
+
+    Metrics {
+        slots,
+        upstream_error,
+        ..metrics
+    }
+}
+
+/// Why an upstream request returned nothing
+#[derive(Debug, PartialEq)]
+enum UpstreamFailure {
+    ServerError, // 5xx: the model's server failed, or llama-swap couldn't reach it
+    Unavailable, // Timeouts, other statuses, unreadable bodies
+}
+
+/// GET one of a model's llama-server endpoints through llama-swap's upstream proxy
+fn fetch_upstream(
+    client: &Client,
+    endpoint: &str,
+    model: &RunningModel,
+    path: &str,
+) -> Result<String, UpstreamFailure> {
     let url = format!(
-        "{}:{}/upstream/{}/metrics",
-        *constants::API_BASE_URL,
-        *constants::API_PORT,
+        "{endpoint}/upstream/{}/{path}",
         model.model.replace(':', "%3A")
     );
 
-    client
+    let response = client
         .get(&url)
         .timeout(Duration::from_secs(1))
         .send()
-        .ok()
-        .filter(|response| response.status().is_success())
-        .and_then(|response| response.text().ok())
-        .map(|text| parse_prometheus_metrics(&text))
-        .unwrap_or_default()
+        .map_err(|_| UpstreamFailure::Unavailable)?;
+    if response.status().is_server_error() {
+        return Err(UpstreamFailure::ServerError);
+    }
+    if !response.status().is_success() {
+        return Err(UpstreamFailure::Unavailable);
+    }
+    response.text().map_err(|_| UpstreamFailure::Unavailable)
 }
 
 fn create_metrics_from_data(data: &HashMap<String, f64>) -> Metrics {
@@ -208,16 +556,26 @@ fn create_metrics_from_data(data: &HashMap<String, f64>) -> Metrics {
         requests_processing: get_metric_value(data, "requests_processing") as u32,
         requests_deferred: get_metric_value(data, "requests_deferred") as u32,
         n_decode_total: get_metric_value(data, "n_decode_total") as u32,
+        prompt_tokens_total: get_metric_value(data, "prompt_tokens_total"),
+        tokens_predicted_total: get_metric_value(data, "tokens_predicted_total"),
         memory_mb: 0.0,
+        kv_cache_usage_ratio: get_metric_value(data, "kv_cache_usage_ratio").clamp(0.0, 1.0),
+        kv_cache_tokens: get_metric_value(data, "kv_cache_tokens"),
+        request_duration_buckets: Vec::new(),
+        slots: Vec::new(),
+        upstream_error: None,
+        extra: Vec::new(),
     }
 }
 
 pub fn fetch_all_metrics(client: &Client) -> crate::Result<AllMetrics> {
-    let url = format!(
-        "{}:{}/running",
-        *constants::API_BASE_URL,
-        *constants::API_PORT
-    );
+    fetch_all_metrics_from(client, &crate::commands::api_endpoint())
+}
+
+/// Running models and their metrics from the llama-swap at `endpoint`
+/// (scheme, host and port, e.g. "http://127.0.0.1:45786")
+pub fn fetch_all_metrics_from(client: &Client, endpoint: &str) -> crate::Result<AllMetrics> {
+    let url = format!("{endpoint}/running");
 
     let response = with_context(client.get(&url).send(), CONNECT_API)?;
 
@@ -233,10 +591,9 @@ pub fn fetch_all_metrics(client: &Client) -> crate::Result<AllMetrics> {
         .map(|model| {
             let model_state = model.model_state();
             let metrics = if model_state == crate::models::ModelState::Running {
-                let model_metrics_data = fetch_model_metrics(client, model);
-                create_metrics_from_data(&model_metrics_data)
+                fetch_model_metrics(client, endpoint, model)
             } else {
-                // For loading/unknown models, use empty metrics
+                // For loading/stopping/unknown models, use empty metrics
                 Metrics::default()
             };
 
@@ -248,7 +605,10 @@ pub fn fetch_all_metrics(client: &Client) -> crate::Result<AllMetrics> {
         })
         .collect();
 
-    Ok(AllMetrics { models })
+    Ok(AllMetrics {
+        models,
+        proxy: fetch_proxy_metrics(client, endpoint),
+    })
 }
 
 // Helper functions
@@ -271,6 +631,8 @@ fn get_metric_value(data: &HashMap<String, f64>, key: &str) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::LatencyPercentiles;
+    use proptest::prelude::*;
 
     #[test]
     fn test_prometheus_parsing() {
@@ -282,13 +644,21 @@ llamacpp:prompt_tokens_seconds 150.5
 llamacpp:predicted_tokens_seconds 25.3
 # HELP llamacpp:requests_processing Number of requests being processed
 # TYPE llamacpp:requests_processing gauge
-llamacpp:requests_processing 2";
+llamacpp:requests_processing 2
+# HELP llamacpp:kv_cache_usage_ratio KV-cache usage. 1 means 100 percent usage.
+# TYPE llamacpp:kv_cache_usage_ratio gauge
+llamacpp:kv_cache_usage_ratio 0.25
+# HELP llamacpp:kv_cache_tokens KV-cache tokens.
+# TYPE llamacpp:kv_cache_tokens gauge
+llamacpp:kv_cache_tokens 1024";
 
         let metrics = parse_prometheus_metrics(sample_prometheus);
 
         assert_eq!(metrics.get("prompt_tokens_per_sec"), Some(&150.5));
         assert_eq!(metrics.get("predicted_tokens_per_sec"), Some(&25.3));
         assert_eq!(metrics.get("requests_processing"), Some(&2.0));
+        assert_eq!(metrics.get("kv_cache_usage_ratio"), Some(&0.25));
+        assert_eq!(metrics.get("kv_cache_tokens"), Some(&1024.0));
     }
 
     #[test]
@@ -297,7 +667,200 @@ llamacpp:requests_processing 2";
 
         let metric = parse_prometheus_line(sample).unwrap();
         assert_eq!(metric.name, "llamacpp:prompt_tokens_seconds");
+        assert_eq!(metric.label("model"), Some("llama3.2:1b"));
         assert_eq!(metric.value, 150.5);
+
+        let escaped = r#"llamacpp:requests{path="/v1/chat",note="say \"hi\", ok"} 3"#;
+        let metric = parse_prometheus_line(escaped).unwrap();
+        assert_eq!(metric.label("path"), Some("/v1/chat"));
+        assert_eq!(metric.label("note"), Some(r#"say "hi", ok"#));
+        assert_eq!(metric.value, 3.0);
+    }
+
+    #[test]
+    fn test_llama_server_fixture() {
+        let metrics =
+            parse_prometheus_metrics(include_str!("../tests/fixtures/llama_server_metrics.txt"));
+
+        assert_eq!(metrics.get("prompt_tokens_per_sec"), Some(&526.211));
+        assert_eq!(metrics.get("predicted_tokens_per_sec"), Some(&46.0102));
+        assert_eq!(metrics.get("prompt_tokens_total"), Some(&18432.0));
+        assert_eq!(metrics.get("tokens_predicted_total"), Some(&6210.0));
+        assert_eq!(metrics.get("n_decode_total"), Some(&6274.0));
+        assert_eq!(metrics.get("kv_cache_usage_ratio"), Some(&0.127686));
+        assert_eq!(metrics.get("kv_cache_tokens"), Some(&1046.0));
+        assert_eq!(metrics.get("requests_processing"), Some(&1.0));
+        assert_eq!(metrics.get("requests_deferred"), Some(&2.0));
+    }
+
+    #[test]
+    fn test_exposition_edge_cases() {
+        let text = include_str!("../tests/fixtures/exposition_edge_cases.txt");
+        assert_eq!(text.lines().filter_map(parse_prometheus_line).count(), 10);
+
+        let metrics = parse_prometheus_metrics(text);
+        assert_eq!(metrics.get("prompt_tokens_per_sec"), Some(&120.5));
+        assert_eq!(metrics.get("predicted_tokens_per_sec"), Some(&30.0));
+        assert_eq!(metrics.get("requests_processing"), Some(&2.0));
+        assert_eq!(metrics.get("n_decode_total"), Some(&7000.0));
+        assert_eq!(metrics.get("kv_cache_usage_ratio"), Some(&0.5));
+        assert_eq!(metrics.get("kv_cache_tokens"), None);
+        assert_eq!(metrics.get("requests_deferred"), None);
+
+        let windows_path = text
+            .lines()
+            .filter_map(parse_prometheus_line)
+            .find_map(|metric| metric.label("path").map(str::to_string));
+        assert_eq!(windows_path.as_deref(), Some(r"C:\models\"));
+    }
+
+    proptest! {
+        #[test]
+        fn test_parser_never_panics(line in "\\PC*") {
+            let _ = parse_prometheus_line(&line);
+        }
+
+        #[test]
+        fn test_label_values_round_trip(
+            value in "\\PC*",
+            sample in -1e9f64..1e9,
+        ) {
+            let escaped = value
+                .replace('\\', r"\\")
+                .replace('"', "\\\"")
+                .replace('\n', r"\n");
+            let line = format!("llamacpp:test{{slot=\"0\",value=\"{escaped}\"}} {sample}");
+
+            let metric = parse_prometheus_line(&line).unwrap();
+            prop_assert_eq!(metric.label("value"), Some(value.as_str()));
+            prop_assert_eq!(metric.value, sample);
+        }
+    }
+
+    #[test]
+    fn test_labeled_series_are_aggregated() {
+        let sample = r#"llamacpp:predicted_tokens_seconds{slot="0"} 20.5
+llamacpp:predicted_tokens_seconds{slot="1"} 10
+llamacpp:requests_processing{slot="0"} 1
+llamacpp:requests_processing{slot="1"} 1
+llamacpp:kv_cache_usage_ratio{slot="0"} 0.5
+llamacpp:kv_cache_usage_ratio{slot="1"} 0.25"#;
+
+        let metrics = parse_prometheus_metrics(sample);
+        assert_eq!(metrics.get("predicted_tokens_per_sec"), Some(&30.5));
+        assert_eq!(metrics.get("requests_processing"), Some(&2.0));
+        assert_eq!(metrics.get("kv_cache_usage_ratio"), Some(&0.5));
+    }
+
+    #[test]
+    fn test_extra_metrics() {
+        let sample = "llamacpp:n_busy_slots_per_decode 1.5\nllamacpp:n_tokens_max 4096";
+        let extra = |metric: &str, label: &str| ExtraMetric {
+            metric: metric.to_string(),
+            label: label.to_string(),
+            aggregate: Aggregation::Sum,
+        };
+
+        let values = parse_extra_metrics(
+            sample,
+            &[
+                extra("llamacpp:n_tokens_max", "Max tokens"),
+                extra("llamacpp:not_exported", "Missing"),
+                extra("llamacpp:n_busy_slots_per_decode", "Busy slots"),
+            ],
+        );
+        assert_eq!(
+            values,
+            vec![
+                ("Max tokens".to_string(), 4096.0),
+                ("Busy slots".to_string(), 1.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_request_duration_percentiles() {
+        let sample = r#"# TYPE llamacpp:request_duration_seconds histogram
+llamacpp:request_duration_seconds_bucket{le="+Inf"} 100
+llamacpp:request_duration_seconds_bucket{le="0.5"} 50
+llamacpp:request_duration_seconds_bucket{le="1"} 90
+llamacpp:request_duration_seconds_bucket{le="2"} 98
+llamacpp:request_duration_seconds_sum 80
+llamacpp:request_duration_seconds_count 100"#;
+
+        let buckets = parse_histogram_buckets(sample, REQUEST_DURATION_HISTOGRAM);
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(buckets[0].le, 0.5);
+        assert!(buckets[3].le.is_infinite());
+
+        // Interpolated within buckets; p99 falls in +Inf so reports its lower bound
+        let latency = LatencyPercentiles::between(&[], &buckets).unwrap();
+        assert_eq!(latency.p50_ms, 500.0);
+        assert_eq!(latency.p95_ms, 1625.0);
+        assert_eq!(latency.p99_ms, 2000.0);
+
+        // Only requests since the previous snapshot count: 10 more, all under 0.5s
+        let later: Vec<HistogramBucket> = buckets
+            .iter()
+            .map(|b| HistogramBucket {
+                le: b.le,
+                count: b.count + 10.0,
+            })
+            .collect();
+        let latency = LatencyPercentiles::between(&buckets, &later).unwrap();
+        assert_eq!(latency.p99_ms, 495.0);
+        assert_eq!(LatencyPercentiles::between(&buckets, &buckets), None);
+    }
+
+    #[test]
+    fn test_parse_slots() {
+        let newer = r#"[
+            {"id": 1, "n_ctx": 4096, "is_processing": false, "next_token": [{"has_next_token": true, "n_decoded": 0}]},
+            {"id": 0, "n_ctx": 4096, "is_processing": true, "next_token": [{"has_next_token": true, "n_decoded": 136}]}
+        ]"#;
+        let slots = parse_slots(newer);
+        assert_eq!(
+            slots,
+            vec![
+                SlotInfo {
+                    id: 0,
+                    is_processing: true,
+                    n_ctx: 4096,
+                    n_decoded: 136,
+                },
+                SlotInfo {
+                    id: 1,
+                    is_processing: false,
+                    n_ctx: 4096,
+                    n_decoded: 0,
+                },
+            ]
+        );
+
+        let older = r#"[{"id": 0, "n_ctx": 2048, "state": 1, "next_token": {"n_decoded": 12}}]"#;
+        let slots = parse_slots(older);
+        assert!(slots[0].is_processing);
+        assert_eq!(slots[0].n_decoded, 12);
+
+        assert!(parse_slots(r#"{"error": {"code": 501}}"#).is_empty());
+    }
+
+    #[test]
+    fn test_parse_proxy_metrics() {
+        let sample = r#"# TYPE llama_swap_requests_total counter
+llama_swap_requests_total{model="qwen",status="200"} 120
+llama_swap_requests_total{model="qwen",status="502"} 3
+llama_swap_requests_total{model="llama",status="200"} 77
+llama_swap_upstream_errors_total{model="qwen"} 3
+llama_swap_model_swaps_total{model="qwen"} 4
+llama_swap_model_swaps_total{model="llama"} 5"#;
+
+        let proxy = parse_proxy_metrics(sample).unwrap();
+        assert_eq!(proxy.requests_total, 200.0);
+        assert_eq!(proxy.swaps_total, 9.0);
+        assert_eq!(proxy.error_rate_percent(), Some(1.5));
+
+        assert_eq!(parse_proxy_metrics("go_goroutines 12"), None);
     }
 
     #[test]
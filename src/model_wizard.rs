@@ -0,0 +1,532 @@
+use crate::config_parser::{Concurrency, LlamaSwapConfig};
+use crate::config_validation::{cmd_port, is_key};
+use crate::gguf::GgufInfo;
+use crate::types::error_helpers::with_context;
+use std::path::{Path, PathBuf};
+
+/// Longer trained contexts are capped here: the KV cache for a 128K context
+/// costs memory most chats never use, and `--ctx-size` is easy to raise
+const MAX_DEFAULT_CTX: u64 = 32768;
+
+/// Used when the GGUF doesn't say how long a context the model was trained on
+const FALLBACK_CTX: u64 = 8192;
+
+/// Ports handed to new models start here, as in the default config
+const FIRST_MODEL_PORT: u16 = 8902;
+
+/// Parallel slot counts offered in the menu
+pub const PARALLEL_CHOICES: [u32; 4] = [1, 2, 4, 8];
+
+/// One change to a model's concurrency flags, as given to `set_model_option`;
+/// None removes the flag so llama-server's default applies
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModelOption {
+    Parallel(Option<u32>),
+    ContBatching(Option<bool>),
+}
+
+impl ModelOption {
+    /// `parallel 1-256|default` or `cont_batching on|off|default`
+    pub fn parse(option: &str, value: &str) -> crate::Result<Self> {
+        match (option, value) {
+            ("parallel", "default") => Ok(Self::Parallel(None)),
+            ("parallel", _) => value
+                .parse()
+                .ok()
+                .filter(|slots| (1..=256).contains(slots))
+                .map(|slots| Self::Parallel(Some(slots)))
+                .ok_or_else(|| {
+                    format!("parallel must be from 1 to 256 or default, not {value}").into()
+                }),
+            ("cont_batching", "on") => Ok(Self::ContBatching(Some(true))),
+            ("cont_batching", "off") => Ok(Self::ContBatching(Some(false))),
+            ("cont_batching", "default") => Ok(Self::ContBatching(None)),
+            ("cont_batching", _) => {
+                Err(format!("cont_batching must be on, off or default, not {value}").into())
+            }
+            _ => Err(format!("Unknown model option: {option}").into()),
+        }
+    }
+
+    /// The option's name and value as `set_model_option` takes them
+    pub fn args(self) -> [String; 2] {
+        match self {
+            Self::Parallel(slots) => [
+                "parallel".into(),
+                slots.map_or("default".into(), |slots| slots.to_string()),
+            ],
+            Self::ContBatching(on) => [
+                "cont_batching".into(),
+                match on {
+                    Some(true) => "on",
+                    Some(false) => "off",
+                    None => "default",
+                }
+                .into(),
+            ],
+        }
+    }
+
+    fn apply(self, concurrency: &mut Concurrency) {
+        match self {
+            Self::Parallel(slots) => concurrency.parallel = slots,
+            Self::ContBatching(on) => concurrency.cont_batching = on,
+        }
+    }
+
+    /// The flag written into the command, if any
+    fn flag(self) -> Option<String> {
+        match self {
+            Self::Parallel(slots) => slots.map(|slots| format!("--parallel {slots}")),
+            Self::ContBatching(on) => on.map(|on| {
+                if on {
+                    "--cont-batching".to_string()
+                } else {
+                    "--no-cont-batching".to_string()
+                }
+            }),
+        }
+    }
+
+    /// How many words starting at `word` make up an existing flag for this
+    /// option: 0 when `word` isn't one
+    fn flag_len(self, word: &str) -> usize {
+        match self {
+            Self::Parallel(_) => match word {
+                "--parallel" | "-np" => 2,
+                _ => usize::from(word.starts_with("--parallel=")),
+            },
+            Self::ContBatching(_) => usize::from(matches!(
+                word,
+                "--cont-batching" | "-cb" | "--no-cont-batching" | "-nocb"
+            )),
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Self::Parallel(Some(1)) => "1 parallel slot".to_string(),
+            Self::Parallel(Some(slots)) => format!("{slots} parallel slots"),
+            Self::Parallel(None) => "default parallel slots".to_string(),
+            Self::ContBatching(Some(true)) => "continuous batching on".to_string(),
+            Self::ContBatching(Some(false)) => "continuous batching off".to_string(),
+            Self::ContBatching(None) => "default continuous batching".to_string(),
+        }
+    }
+}
+
+/// Add a model entry for a GGUF file to config.yaml
+///
+/// The entry runs llama-server (the same binary other models use, when they
+/// agree on one) on the next port no other model claims, with every layer on
+/// the GPU and the trained context capped at 32K. config.yaml is backed up
+/// first and the result is validated before it is written.
+pub fn add_model(gguf: &Path) -> crate::Result<String> {
+    let gguf = with_context(std::fs::canonicalize(gguf), "Failed to find GGUF file")?;
+    let info = crate::gguf::read_info(&gguf)?;
+
+    let config_path = PathBuf::from(crate::commands::expand_tilde(
+        &crate::constants::CONFIG_FILE_PATH,
+    )?);
+    let yaml = if config_path.exists() {
+        with_context(
+            std::fs::read_to_string(&config_path),
+            "Failed to read config.yaml",
+        )?
+    } else {
+        String::new()
+    };
+    let config = if yaml.trim().is_empty() {
+        LlamaSwapConfig::default()
+    } else {
+        LlamaSwapConfig::parse(&yaml)?
+    };
+
+    let stem = gguf
+        .file_stem()
+        .map_or("model".into(), |stem| stem.to_string_lossy());
+    let name = unique_name(&stem, &config);
+    let used: Vec<u16> = config
+        .models
+        .values()
+        .filter_map(|model| cmd_port(&model.cmd))
+        .collect();
+    let port = free_port(&used);
+    let program = shared_program(&config).unwrap_or("llama-server");
+
+    let indent = models_indent(&yaml);
+    let entry = model_entry(&name, program, &gguf, port, &info, indent);
+    let updated = insert_model(&yaml, &entry)?;
+
+    let issues = crate::config_validation::validate(&updated);
+    if !issues.is_empty() {
+        let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        return Err(format!(
+            "Adding {name} would break config.yaml: {}",
+            issues.join("; ")
+        )
+        .into());
+    }
+
+    if config_path.exists() {
+        crate::config_backup::backup()?;
+    }
+    crate::persistence::write_atomic(&config_path, updated.as_bytes())?;
+    Ok(format!("Added {name} on port {port}; restart to load it"))
+}
+
+/// Change one concurrency flag in a model's `cmd`
+///
+/// Only that model's command changes; other models, comments and the
+/// command's layout stay as they were. Backed up and validated as in `add_model`.
+pub fn set_model_option(model: &str, option: ModelOption) -> crate::Result<String> {
+    let config_path = PathBuf::from(crate::commands::expand_tilde(
+        &crate::constants::CONFIG_FILE_PATH,
+    )?);
+    let yaml = with_context(
+        std::fs::read_to_string(&config_path),
+        "Failed to read config.yaml",
+    )?;
+    let updated = rewrite_model_option(&yaml, model, option)?;
+
+    let issues = crate::config_validation::validate(&updated);
+    if !issues.is_empty() {
+        let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        return Err(format!(
+            "Changing {model} would break config.yaml: {}",
+            issues.join("; ")
+        )
+        .into());
+    }
+
+    crate::config_backup::backup()?;
+    crate::persistence::write_atomic(&config_path, updated.as_bytes())?;
+    Ok(format!(
+        "{model}: {}; takes effect the next time it loads",
+        option.describe()
+    ))
+}
+
+/// config.yaml with the option's flag in `model`'s command replaced
+fn rewrite_model_option(yaml: &str, model: &str, option: ModelOption) -> crate::Result<String> {
+    let config = LlamaSwapConfig::parse(yaml)?;
+    let Some(before) = config.models.get(model) else {
+        return Err(format!("{model} isn't in config.yaml").into());
+    };
+    let by_hand = || format!("Couldn't find {model}'s cmd in config.yaml; edit it by hand");
+
+    let lines: Vec<&str> = yaml.lines().collect();
+    let cmd = cmd_line(&lines, model).ok_or_else(by_hand)?;
+    let mut updated: Vec<Option<String>> =
+        lines.iter().map(|line| Some(line.to_string())).collect();
+    let (key, value) = lines[cmd].split_once(':').ok_or_else(by_hand)?;
+    let value = value.trim();
+
+    if value.starts_with('>') || value.starts_with('|') {
+        // A block scalar: the command is every more-indented line below the key
+        let cmd_indent = indent(lines[cmd]);
+        let mut pending = 0;
+        let mut last = None;
+        for (index, line) in lines.iter().enumerate().skip(cmd + 1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if indent(line) <= cmd_indent {
+                break;
+            }
+            let (kept, removed) = strip_flag(line, option, &mut pending);
+            if removed {
+                updated[index] =
+                    (!kept.is_empty()).then(|| format!("{}{kept}", &line[..indent(line)]));
+            }
+            if !kept.is_empty() {
+                last = Some(index);
+            }
+        }
+        let last = last.ok_or_else(by_hand)?;
+        if let Some(flag) = option.flag() {
+            let line = updated[last].take().unwrap_or_default();
+            let padding = &lines[last][..indent(lines[last])];
+            updated[last] = Some(format!("{line}\n{padding}{flag}"));
+        }
+    } else {
+        if value.starts_with('"') || value.starts_with('\'') {
+            return Err(format!("{model}'s cmd is quoted; edit it by hand").into());
+        }
+        let (words, comment) = match value.find(" #") {
+            Some(at) => value.split_at(at),
+            None => (value, ""),
+        };
+        let (mut kept, _) = strip_flag(words, option, &mut 0);
+        if let Some(flag) = option.flag() {
+            kept = format!("{kept} {flag}");
+        }
+        updated[cmd] = Some(format!("{key}: {kept}{comment}"));
+    }
+
+    let updated: String = updated
+        .into_iter()
+        .flatten()
+        .map(|line| format!("{line}\n"))
+        .collect();
+
+    // The rewrite must have changed this one setting and nothing else
+    let mut expected = before.concurrency();
+    option.apply(&mut expected);
+    let after = LlamaSwapConfig::parse(&updated)?;
+    if after.models.get(model).map(|model| model.concurrency()) != Some(expected) {
+        return Err(by_hand().into());
+    }
+    Ok(updated)
+}
+
+/// The `cmd:` line of a model's entry under `models:`
+fn cmd_line(lines: &[&str], model: &str) -> Option<usize> {
+    let models = lines.iter().position(|line| is_key(line, "models"))?;
+    let entry = (models + 1..lines.len())
+        .find(|&index| indent(lines[index]) > 0 && is_key(lines[index].trim_start(), model))?;
+    let entry_indent = indent(lines[entry]);
+    lines
+        .iter()
+        .enumerate()
+        .skip(entry + 1)
+        .take_while(|(_, line)| {
+            let trimmed = line.trim();
+            trimmed.is_empty() || trimmed.starts_with('#') || indent(line) > entry_indent
+        })
+        .find(|(_, line)| is_key(line.trim_start(), "cmd"))
+        .map(|(index, _)| index)
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// A command line without this option's flags, and whether any were there;
+/// `pending` carries a flag's value over to the next line
+fn strip_flag(text: &str, option: ModelOption, pending: &mut usize) -> (String, bool) {
+    let mut removed = false;
+    let mut kept = Vec::new();
+    for word in text.split_whitespace() {
+        if *pending > 0 {
+            *pending -= 1;
+            removed = true;
+            continue;
+        }
+        let len = option.flag_len(word);
+        if len > 0 {
+            *pending = len - 1;
+            removed = true;
+            continue;
+        }
+        kept.push(word);
+    }
+    (kept.join(" "), removed)
+}
+
+/// The GGUF's file name, numbered when a model already has it
+fn unique_name(stem: &str, config: &LlamaSwapConfig) -> String {
+    if !config.models.contains_key(stem) {
+        return stem.to_string();
+    }
+    (2..)
+        .map(|n| format!("{stem}-{n}"))
+        .find(|name| !config.models.contains_key(name))
+        .unwrap_or_default()
+}
+
+/// The lowest port from 8902 up that no model (and not llama-swap) listens on
+fn free_port(used: &[u16]) -> u16 {
+    let api_port = *crate::constants::API_PORT;
+    (FIRST_MODEL_PORT..=u16::MAX)
+        .find(|port| *port != api_port && !used.contains(port))
+        .unwrap_or(FIRST_MODEL_PORT)
+}
+
+/// The program every configured model launches, when they all agree
+fn shared_program(config: &LlamaSwapConfig) -> Option<&str> {
+    let mut programs = config.models.values().filter_map(|model| model.program());
+    let first = programs.next()?;
+    programs.all(|program| program == first).then_some(first)
+}
+
+/// A `models:` entry in the default config's layout, `indent` spaces per level
+fn model_entry(
+    name: &str,
+    program: &str,
+    gguf: &Path,
+    port: u16,
+    info: &GgufInfo,
+    indent: usize,
+) -> String {
+    let ctx = info
+        .context_length
+        .map_or(FALLBACK_CTX, |trained| trained.min(MAX_DEFAULT_CTX));
+    // block_count covers the repeating layers; the output layer is one more
+    let gpu_layers = info.block_count.map_or(999, |blocks| blocks + 1);
+    let model = gguf.to_string_lossy();
+    let model = if model.contains(char::is_whitespace) {
+        format!("\"{model}\"")
+    } else {
+        model.into_owned()
+    };
+    let key = name.replace('\\', "\\\\").replace('"', "\\\"");
+
+    let (one, two, three) = (
+        " ".repeat(indent),
+        " ".repeat(indent * 2),
+        " ".repeat(indent * 3),
+    );
+    let mut entry = format!("{one}\"{key}\":\n");
+    if let Some(ref description) = info.name {
+        entry.push_str(&format!("{two}# {description}\n"));
+    }
+    entry.push_str(&format!("{two}cmd: >-\n"));
+    for line in [
+        program.to_string(),
+        "--metrics".to_string(),
+        format!("--port {port}"),
+        format!("--model {model}"),
+        format!("--n-gpu-layers {gpu_layers}"),
+        format!("--ctx-size {ctx}"),
+    ] {
+        entry.push_str(&format!("{three}{line}\n"));
+    }
+    // The port is fixed in the command, so llama-swap must be told where to proxy
+    entry.push_str(&format!("{two}proxy: http://127.0.0.1:{port}\n"));
+    entry
+}
+
+/// How far config.yaml indents model names under `models:`
+fn models_indent(yaml: &str) -> usize {
+    yaml.lines()
+        .skip_while(|line| !is_key(line, "models"))
+        .skip(1)
+        .find(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|line| line.len() - line.trim_start().len())
+        .filter(|indent| *indent > 0)
+        .unwrap_or(2)
+}
+
+/// Insert an entry at the end of the `models:` block, keeping the rest of
+/// the file (comments included) as it was
+fn insert_model(yaml: &str, entry: &str) -> crate::Result<String> {
+    let lines: Vec<&str> = yaml.lines().collect();
+    let Some(models) = lines.iter().position(|line| is_key(line, "models")) else {
+        let mut updated = yaml.to_string();
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str("models:\n");
+        updated.push_str(entry);
+        return Ok(updated);
+    };
+
+    let rest = lines[models]
+        .split_once(':')
+        .map_or("", |(_, rest)| rest.trim());
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err("config.yaml writes `models` inline; add the model by hand".into());
+    }
+
+    // The block ends at the next top-level key; comments and blank lines
+    // before that key stay with it
+    let mut end = models + 1;
+    for (index, line) in lines.iter().enumerate().skip(models + 1) {
+        let top_level = !line.starts_with(char::is_whitespace);
+        if top_level && !line.trim().is_empty() && !line.starts_with('#') {
+            break;
+        }
+        if !top_level && !line.trim().is_empty() {
+            end = index + 1;
+        }
+    }
+
+    let mut updated: String = lines[..end]
+        .iter()
+        .map(|line| format!("{line}\n"))
+        .collect();
+    updated.push_str(entry);
+    for line in &lines[end..] {
+        updated.push_str(line);
+        updated.push('\n');
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_model_before_next_key() {
+        let yaml = "# My models\nmodels:\n    \"qwen\":\n        cmd: llama-server --port 8902\n\n# Groups\ngroups: {}\n";
+        let info = GgufInfo {
+            name: Some("Llama 3.2 3B".to_string()),
+            context_length: Some(131072),
+            block_count: Some(28),
+            ..GgufInfo::default()
+        };
+        let entry = model_entry(
+            "llama",
+            "llama-server",
+            Path::new("/models/llama.gguf"),
+            free_port(&[8902]),
+            &info,
+            models_indent(yaml),
+        );
+        let updated = insert_model(yaml, &entry).unwrap();
+
+        assert_eq!(
+            updated,
+            "# My models\nmodels:\n    \"qwen\":\n        cmd: llama-server --port 8902\n    \"llama\":\n        # Llama 3.2 3B\n        cmd: >-\n            llama-server\n            --metrics\n            --port 8903\n            --model /models/llama.gguf\n            --n-gpu-layers 29\n            --ctx-size 32768\n        proxy: http://127.0.0.1:8903\n\n# Groups\ngroups: {}\n"
+        );
+        assert_eq!(crate::config_validation::validate(&updated), Vec::new());
+    }
+
+    #[test]
+    fn test_insert_model_into_empty_config() {
+        let entry = model_entry(
+            "tiny",
+            "llama-server",
+            Path::new("/My Models/tiny.gguf"),
+            FIRST_MODEL_PORT,
+            &GgufInfo::default(),
+            2,
+        );
+        let updated = insert_model("", &entry).unwrap();
+
+        let config = LlamaSwapConfig::parse(&updated).unwrap();
+        assert_eq!(
+            config.models["tiny"].cmd,
+            "llama-server --metrics --port 8902 --model \"/My Models/tiny.gguf\" --n-gpu-layers 999 --ctx-size 8192"
+        );
+        assert!(insert_model("models: {}\n", &entry).is_err());
+    }
+
+    #[test]
+    fn test_rewrite_model_option_keeps_other_lines() {
+        let yaml = "models:\n  \"qwen\":\n    # Chat model\n    cmd: >-\n      llama-server\n      --port 8902\n      -np 2\n      --ctx-size 8192\n    proxy: http://127.0.0.1:8902\n  \"embed\":\n    cmd: llama-server --port 8903 -cb # keep\n";
+
+        let updated =
+            rewrite_model_option(yaml, "qwen", ModelOption::parse("parallel", "4").unwrap())
+                .unwrap();
+        assert_eq!(
+            updated,
+            "models:\n  \"qwen\":\n    # Chat model\n    cmd: >-\n      llama-server\n      --port 8902\n      --ctx-size 8192\n      --parallel 4\n    proxy: http://127.0.0.1:8902\n  \"embed\":\n    cmd: llama-server --port 8903 -cb # keep\n"
+        );
+
+        let updated = rewrite_model_option(
+            yaml,
+            "embed",
+            ModelOption::parse("cont_batching", "off").unwrap(),
+        )
+        .unwrap();
+        assert!(updated.ends_with("    cmd: llama-server --port 8903 --no-cont-batching # keep\n"));
+        assert!(updated.starts_with(&yaml[..yaml.find("  \"embed\"").unwrap()]));
+
+        assert!(rewrite_model_option(yaml, "missing", ModelOption::Parallel(None)).is_err());
+        assert!(ModelOption::parse("parallel", "0").is_err());
+        assert_eq!(ModelOption::Parallel(None).args(), ["parallel", "default"]);
+    }
+}
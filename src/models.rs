@@ -1,12 +1,52 @@
 use circular_queue::CircularQueue;
 use serde::{Deserialize, Serialize};
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Extra seconds model histories are kept beyond the retention window
+const MODEL_TRIM_GRACE_SECS: u64 = 5;
+
+fn default_retention_secs() -> u64 {
+    *crate::constants::HISTORY_RETENTION_SECS
+}
+
+/// Empty queue for series added after the first persisted format; sized on load
+fn default_queue() -> CircularQueue<TimestampedValue> {
+    CircularQueue::with_capacity(default_history_capacity())
+}
+
+/// Queue capacity covering the full-resolution window plus the downsampled buckets
+pub fn default_history_capacity() -> usize {
+    *crate::constants::HISTORY_SIZE
+        + DownsampleTier::from_env().map_or(0, DownsampleTier::bucket_count)
+}
+
+/// Coarse tier that samples are averaged into once they age past the retention window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DownsampleTier {
+    pub bucket_secs: u64,
+    pub keep_secs: u64, // Maximum sample age, measured from now
+}
+
+impl DownsampleTier {
+    pub fn from_env() -> Option<Self> {
+        let keep_secs = *crate::constants::DOWNSAMPLE_RETENTION_SECS;
+        (keep_secs > 0).then(|| Self {
+            bucket_secs: *crate::constants::DOWNSAMPLE_BUCKET_SECS,
+            keep_secs,
+        })
+    }
+
+    pub fn bucket_count(self) -> usize {
+        self.keep_secs.div_ceil(self.bucket_secs) as usize + 1
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ModelState {
     Running,
     Loading,
+    Stopping,
     Unknown,
 }
 
@@ -20,7 +60,8 @@ impl RunningModel {
     pub fn model_state(&self) -> ModelState {
         match self.state.as_str() {
             "ready" => ModelState::Running,
-            "starting" | "stopping" => ModelState::Loading,
+            "starting" => ModelState::Loading,
+            "stopping" => ModelState::Stopping,
             _ => ModelState::Unknown,
         }
     }
@@ -31,6 +72,15 @@ pub struct RunningResponse {
     pub running: Vec<RunningModel>,
 }
 
+/// One llama-server slot, from `GET /slots` (one per `--parallel` request)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotInfo {
+    pub id: u32,
+    pub is_processing: bool,
+    pub n_ctx: u64,
+    pub n_decoded: u64, // Tokens generated for the slot's current (or last) request
+}
+
 #[derive(Debug, Clone)]
 pub struct ModelMetrics {
     pub model_name: String,
@@ -43,11 +93,107 @@ pub struct SystemMetrics {
     pub cpu_usage_percent: f64,
     pub used_memory_gb: f64,
     pub memory_usage_percent: f64,
+    pub gpu: Option<crate::gpu::GpuSample>, // None when the GPU can't be sampled
+    pub swap_used_gb: f64,
+    pub memory_pressure: Option<MemoryPressure>, // None when the kernel doesn't report it
+}
+
+/// macOS memory pressure, as shown in Activity Monitor's Memory Pressure graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryPressure {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl MemoryPressure {
+    /// From `kern.memorystatus_vm_pressure_level` (1 normal, 2 warn, 4 critical)
+    pub fn from_level(level: u32) -> Option<Self> {
+        match level {
+            1 => Some(Self::Normal),
+            2 => Some(Self::Warning),
+            4 => Some(Self::Critical),
+            _ => None,
+        }
+    }
+
+    /// Stored in history as 0, 1 or 2 so charts rise with pressure
+    pub fn as_value(self) -> f64 {
+        match self {
+            Self::Normal => 0.0,
+            Self::Warning => 1.0,
+            Self::Critical => 2.0,
+        }
+    }
+
+    pub fn from_value(value: f64) -> Self {
+        match value {
+            v if v >= 2.0 => Self::Critical,
+            v if v >= 1.0 => Self::Warning,
+            _ => Self::Normal,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AllMetrics {
     pub models: Vec<ModelMetrics>,
+    pub proxy: Option<ProxyMetrics>, // None when llama-swap doesn't serve /metrics
+}
+
+impl AllMetrics {
+    /// How many models are ready to serve, and their combined generation speed
+    pub fn running_totals(&self) -> (usize, f64) {
+        self.models
+            .iter()
+            .filter(|model| model.model_state == ModelState::Running)
+            .fold((0, 0.0), |(count, tps), model| {
+                (count + 1, tps + model.metrics.predicted_tokens_per_sec)
+            })
+    }
+}
+
+/// llama-swap's own counters, cumulative since the proxy started
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProxyMetrics {
+    pub requests_total: f64,
+    pub upstream_errors_total: f64,
+    pub swaps_total: f64, // Times a model was started to serve a request
+}
+
+impl ProxyMetrics {
+    /// Share of proxied requests that failed upstream, in percent
+    pub fn error_rate_percent(&self) -> Option<f64> {
+        (self.requests_total > 0.0)
+            .then(|| self.upstream_errors_total / self.requests_total * 100.0)
+    }
+}
+
+/// How the labeled series of one Prometheus metric combine into a single value
+/// (llama-server can export a series per slot)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    #[default]
+    Sum, // Counters, token rates and request counts add up across slots
+    Max, // Ratios: the fullest slot is what matters
+}
+
+impl Aggregation {
+    pub fn combine(self, acc: f64, value: f64) -> f64 {
+        match self {
+            Self::Sum => acc + value,
+            Self::Max => acc.max(value),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -57,7 +203,15 @@ pub struct Metrics {
     pub requests_processing: u32,
     pub requests_deferred: u32,
     pub n_decode_total: u32,
+    pub prompt_tokens_total: f64, // Cumulative counter since the model was loaded
+    pub tokens_predicted_total: f64, // Cumulative counter since the model was loaded
     pub memory_mb: f64,
+    pub kv_cache_usage_ratio: f64, // 0.0-1.0 of the context's KV cache in use
+    pub kv_cache_tokens: f64,
+    pub request_duration_buckets: Vec<HistogramBucket>, // Cumulative since the model was loaded
+    pub slots: Vec<SlotInfo>,
+    pub upstream_error: Option<bool>, // Whether the metrics request got a 5xx; None if not made
+    pub extra: Vec<(String, f64)>,    // (label, value) of the plugin config's extra_metrics
 }
 
 impl Metrics {
@@ -71,6 +225,133 @@ impl Metrics {
     }
 }
 
+/// Busy samples before a baseline is trusted
+const BASELINE_MIN_SAMPLES: u32 = 30;
+/// Weight of each new sample once warmed up (roughly the last 50 busy polls)
+const BASELINE_WEIGHT: f64 = 0.02;
+
+/// A model's usual generation speed: a slow moving average of busy polls
+///
+/// Only polls with requests in flight count, so idle periods don't drag it
+/// down. It starts as a plain average and becomes exponential once enough
+/// samples are in, so a sudden slowdown stands out against it for a while.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TpsBaseline {
+    mean: f64,
+    samples: u32,
+}
+
+impl TpsBaseline {
+    pub fn record(&mut self, tps: f64) {
+        self.samples = self.samples.saturating_add(1);
+        let weight = (1.0 / self.samples as f64).max(BASELINE_WEIGHT);
+        self.mean += weight * (tps - self.mean);
+    }
+
+    /// None until enough busy polls have been seen
+    pub fn value(&self) -> Option<f64> {
+        (self.samples >= BASELINE_MIN_SAMPLES).then_some(self.mean)
+    }
+}
+
+/// Derives a per-second rate from successive readings of a cumulative counter
+///
+/// llama-server only adds to its token counters when a request (or its prompt
+/// evaluation) finishes, so the rate is wall-clock throughput over the poll
+/// interval, not decode speed: zero while a long request is generating, then
+/// the whole request at once. The speed charts use the gauges instead.
+#[derive(Debug, Clone, Default)]
+pub struct CounterRate {
+    last: Option<(Instant, f64)>,
+}
+
+impl CounterRate {
+    /// Rate since the previous reading; None for the first reading and after a
+    /// counter reset (model reloaded)
+    pub fn update(&mut self, now: Instant, total: f64) -> Option<f64> {
+        let previous = self.last.replace((now, total));
+        let (at, last_total) = previous?;
+        let elapsed = now.saturating_duration_since(at).as_secs_f64();
+        (elapsed > 0.0 && total >= last_total).then(|| (total - last_total) / elapsed)
+    }
+}
+
+/// One bucket of a Prometheus histogram: observations of at most `le` seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramBucket {
+    pub le: f64, // f64::INFINITY for the +Inf bucket
+    pub count: f64,
+}
+
+/// Request latency percentiles, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyPercentiles {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl LatencyPercentiles {
+    /// Percentiles of the requests that completed between two histogram snapshots
+    ///
+    /// Bucket counts are cumulative since the model loaded, so the difference
+    /// describes recent requests only. A histogram that went backwards (the model
+    /// was reloaded) is used as-is. Returns None if no request completed.
+    pub fn between(previous: &[HistogramBucket], current: &[HistogramBucket]) -> Option<Self> {
+        let reset = previous.len() != current.len()
+            || previous
+                .iter()
+                .zip(current)
+                .any(|(p, c)| p.le != c.le || c.count < p.count);
+        let window: Vec<HistogramBucket> = if reset {
+            current.to_vec()
+        } else {
+            previous
+                .iter()
+                .zip(current)
+                .map(|(p, c)| HistogramBucket {
+                    le: c.le,
+                    count: c.count - p.count,
+                })
+                .collect()
+        };
+
+        Some(Self {
+            p50_ms: histogram_quantile(&window, 0.50)? * 1000.0,
+            p95_ms: histogram_quantile(&window, 0.95)? * 1000.0,
+            p99_ms: histogram_quantile(&window, 0.99)? * 1000.0,
+        })
+    }
+}
+
+/// Estimate a quantile from cumulative buckets sorted by `le`, as Prometheus'
+/// `histogram_quantile` does: linear interpolation within the matching bucket
+pub fn histogram_quantile(buckets: &[HistogramBucket], quantile: f64) -> Option<f64> {
+    let total = buckets.last()?.count;
+    if total <= 0.0 {
+        return None;
+    }
+
+    let rank = quantile * total;
+    let (mut lower, mut below) = (0.0, 0.0);
+    for bucket in buckets {
+        if bucket.count >= rank {
+            // Nothing is known about the +Inf bucket beyond its lower bound
+            if bucket.le.is_infinite() {
+                return Some(lower);
+            }
+            let in_bucket = bucket.count - below;
+            if in_bucket <= 0.0 {
+                return Some(bucket.le);
+            }
+            return Some(lower + (bucket.le - lower) * (rank - below) / in_bucket);
+        }
+        lower = bucket.le;
+        below = bucket.count;
+    }
+    Some(lower)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimestampedValue {
     pub timestamp: u64,
@@ -169,6 +450,74 @@ impl DataAnalyzer {
             cq.push(entry);
         }
     }
+
+    /// Average samples older than `cutoff` into fixed buckets, dropping anything past the tier
+    ///
+    /// Only buckets that lie entirely before the cutoff are compacted, so each bucket is
+    /// averaged exactly once from raw samples and later passes leave it untouched.
+    pub fn downsample_circular_queue(
+        cq: &mut CircularQueue<TimestampedValue>,
+        cutoff: u64,
+        tier: Option<DownsampleTier>,
+        now: u64,
+    ) {
+        let Some(tier) = tier else {
+            return Self::trim_circular_queue(cq, cutoff);
+        };
+
+        let oldest_kept = now.saturating_sub(tier.keep_secs);
+        let mut entries: Vec<TimestampedValue> = Vec::with_capacity(cq.len());
+        let mut bucket: Option<(u64, f64, usize)> = None; // (start, sum, count)
+
+        let flush = |bucket: &mut Option<(u64, f64, usize)>,
+                     entries: &mut Vec<TimestampedValue>| {
+            if let Some((start, sum, count)) = bucket.take() {
+                entries.push(TimestampedValue {
+                    timestamp: start,
+                    value: sum / count as f64,
+                });
+            }
+        };
+
+        for entry in cq.iter().rev().filter(|v| v.timestamp >= oldest_kept) {
+            let start = entry.timestamp - entry.timestamp % tier.bucket_secs;
+            if start + tier.bucket_secs > cutoff {
+                flush(&mut bucket, &mut entries);
+                entries.push(entry.clone());
+                continue;
+            }
+
+            match &mut bucket {
+                Some((current, sum, count)) if *current == start => {
+                    *sum += entry.value;
+                    *count += 1;
+                }
+                _ => {
+                    flush(&mut bucket, &mut entries);
+                    bucket = Some((start, entry.value, 1));
+                }
+            }
+        }
+        flush(&mut bucket, &mut entries);
+
+        cq.clear();
+        for entry in entries {
+            cq.push(entry);
+        }
+    }
+
+    /// Rebuild a queue whose capacity is below `capacity` (e.g. loaded from an older file)
+    pub fn grow_circular_queue(cq: &mut CircularQueue<TimestampedValue>, capacity: usize) {
+        if cq.capacity() >= capacity {
+            return;
+        }
+
+        let mut grown = CircularQueue::with_capacity(capacity);
+        for entry in cq.iter().rev() {
+            grown.push(entry.clone());
+        }
+        *cq = grown;
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -177,6 +526,30 @@ pub struct MetricsHistory {
     pub prompt_tps: CircularQueue<TimestampedValue>,
     pub memory_mb: CircularQueue<TimestampedValue>,
     pub queue_size: CircularQueue<TimestampedValue>,
+    #[serde(default = "default_queue")]
+    pub kv_cache_percent: CircularQueue<TimestampedValue>,
+    #[serde(default = "default_queue")]
+    pub latency_p95_ms: CircularQueue<TimestampedValue>,
+    #[serde(default = "default_queue")]
+    pub upstream_errors: CircularQueue<TimestampedValue>, // 100 for a failed poll, 0 otherwise
+    #[serde(default = "default_queue")]
+    pub throughput_tps: CircularQueue<TimestampedValue>, // Generated tokens/s, from the counter
+    #[serde(default = "default_queue")]
+    pub prompt_throughput_tps: CircularQueue<TimestampedValue>, // Prompt tokens/s, from the counter
+    #[serde(default)]
+    pub tps_baseline: TpsBaseline,
+    #[serde(skip)]
+    pub latency: Option<LatencyPercentiles>, // From the last poll in which requests completed
+    #[serde(skip)]
+    last_latency_buckets: Vec<HistogramBucket>,
+    #[serde(skip)]
+    prompt_rate: CounterRate,
+    #[serde(skip)]
+    generation_rate: CounterRate,
+    #[serde(skip, default = "default_retention_secs")]
+    pub retention_secs: u64,
+    #[serde(skip, default = "DownsampleTier::from_env")]
+    pub downsample: Option<DownsampleTier>,
 }
 
 impl Default for MetricsHistory {
@@ -187,15 +560,31 @@ impl Default for MetricsHistory {
 
 impl MetricsHistory {
     pub fn new() -> Self {
-        Self::with_capacity(*crate::constants::HISTORY_SIZE)
+        Self::with_capacity(default_history_capacity())
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_retention(capacity, default_retention_secs())
+    }
+
+    pub fn with_retention(capacity: usize, retention_secs: u64) -> Self {
         Self {
             tps: CircularQueue::with_capacity(capacity),
             prompt_tps: CircularQueue::with_capacity(capacity),
             memory_mb: CircularQueue::with_capacity(capacity),
             queue_size: CircularQueue::with_capacity(capacity),
+            kv_cache_percent: CircularQueue::with_capacity(capacity),
+            latency_p95_ms: CircularQueue::with_capacity(capacity),
+            upstream_errors: CircularQueue::with_capacity(capacity),
+            throughput_tps: CircularQueue::with_capacity(capacity),
+            prompt_throughput_tps: CircularQueue::with_capacity(capacity),
+            tps_baseline: TpsBaseline::default(),
+            latency: None,
+            last_latency_buckets: Vec::new(),
+            prompt_rate: CounterRate::default(),
+            generation_rate: CounterRate::default(),
+            retention_secs,
+            downsample: DownsampleTier::from_env(),
         }
     }
 
@@ -203,14 +592,39 @@ impl MetricsHistory {
         let timestamp = current_timestamp();
 
         // CircularQueue manages capacity automatically
+        let now = Instant::now();
+
+        let tps = metrics.predicted_tokens_per_sec;
         self.tps.push(TimestampedValue {
             timestamp,
-            value: metrics.predicted_tokens_per_sec,
+            value: tps,
         });
+        if metrics.requests_processing > 0 && tps > 0.0 {
+            self.tps_baseline.record(tps);
+        }
         self.prompt_tps.push(TimestampedValue {
             timestamp,
             value: metrics.prompt_tokens_per_sec,
         });
+        // Throughput from the token counters, once there are two readings
+        if let Some(rate) = Self::derived_rate(
+            &mut self.generation_rate,
+            now,
+            metrics.tokens_predicted_total,
+        ) {
+            self.throughput_tps.push(TimestampedValue {
+                timestamp,
+                value: rate,
+            });
+        }
+        if let Some(rate) =
+            Self::derived_rate(&mut self.prompt_rate, now, metrics.prompt_tokens_total)
+        {
+            self.prompt_throughput_tps.push(TimestampedValue {
+                timestamp,
+                value: rate,
+            });
+        }
         self.memory_mb.push(TimestampedValue {
             timestamp,
             value: metrics.memory_mb,
@@ -219,17 +633,89 @@ impl MetricsHistory {
             timestamp,
             value: (metrics.requests_processing + metrics.requests_deferred) as f64,
         });
+        self.kv_cache_percent.push(TimestampedValue {
+            timestamp,
+            value: metrics.kv_cache_usage_ratio * 100.0,
+        });
+        self.push_latency(timestamp, &metrics.request_duration_buckets);
+        if let Some(failed) = metrics.upstream_error {
+            self.upstream_errors.push(TimestampedValue {
+                timestamp,
+                value: if failed { 100.0 } else { 0.0 },
+            });
+        }
 
         self.trim_old_data();
     }
 
+    /// A zero total means the counter wasn't reported (no counters, a failed
+    /// scrape or a model still loading), so it's neither a rate nor a baseline
+    fn derived_rate(rate: &mut CounterRate, now: Instant, total: f64) -> Option<f64> {
+        if total > 0.0 {
+            rate.update(now, total)
+        } else {
+            None
+        }
+    }
+
+    /// Share of polls over the history window whose metrics request got a 5xx
+    pub fn upstream_error_rate(&self) -> Option<f64> {
+        (!self.upstream_errors.is_empty()).then(|| self.get_stats(&self.upstream_errors).mean)
+    }
+
+    /// Record latency percentiles for requests completed since the previous poll
+    fn push_latency(&mut self, timestamp: u64, buckets: &[HistogramBucket]) {
+        if buckets.is_empty() {
+            return;
+        }
+
+        // The first snapshot after startup only seeds the baseline
+        if !self.last_latency_buckets.is_empty() {
+            if let Some(latency) = LatencyPercentiles::between(&self.last_latency_buckets, buckets)
+            {
+                self.latency_p95_ms.push(TimestampedValue {
+                    timestamp,
+                    value: latency.p95_ms,
+                });
+                self.latency = Some(latency);
+            }
+        }
+        self.last_latency_buckets = buckets.to_vec();
+    }
+
     pub fn trim_old_data(&mut self) {
-        let cutoff = current_timestamp().saturating_sub(305); // 5 minutes
+        let now = current_timestamp();
+        let cutoff = now.saturating_sub(self.retention_secs + MODEL_TRIM_GRACE_SECS);
+
+        for cq in [
+            &mut self.tps,
+            &mut self.prompt_tps,
+            &mut self.memory_mb,
+            &mut self.queue_size,
+            &mut self.kv_cache_percent,
+            &mut self.latency_p95_ms,
+            &mut self.upstream_errors,
+            &mut self.throughput_tps,
+            &mut self.prompt_throughput_tps,
+        ] {
+            DataAnalyzer::downsample_circular_queue(cq, cutoff, self.downsample, now);
+        }
+    }
 
-        DataAnalyzer::trim_circular_queue(&mut self.tps, cutoff);
-        DataAnalyzer::trim_circular_queue(&mut self.prompt_tps, cutoff);
-        DataAnalyzer::trim_circular_queue(&mut self.memory_mb, cutoff);
-        DataAnalyzer::trim_circular_queue(&mut self.queue_size, cutoff);
+    pub fn grow_capacity(&mut self, capacity: usize) {
+        for cq in [
+            &mut self.tps,
+            &mut self.prompt_tps,
+            &mut self.memory_mb,
+            &mut self.queue_size,
+            &mut self.kv_cache_percent,
+            &mut self.latency_p95_ms,
+            &mut self.upstream_errors,
+            &mut self.throughput_tps,
+            &mut self.prompt_throughput_tps,
+        ] {
+            DataAnalyzer::grow_circular_queue(cq, capacity);
+        }
     }
 
     pub fn get_stats(&self, circular_queue: &CircularQueue<TimestampedValue>) -> MetricStats {
@@ -237,6 +723,8 @@ impl MetricsHistory {
     }
 }
 
+// Part of the library API; the binary compiles this module but never builds one
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelMetricsHistory {
     pub model_name: String,
@@ -250,6 +738,20 @@ pub struct AllMetricsHistory {
     pub cpu_usage_percent: CircularQueue<TimestampedValue>,
     pub memory_usage_percent: CircularQueue<TimestampedValue>,
     pub used_memory_gb: CircularQueue<TimestampedValue>,
+    #[serde(default = "default_queue")]
+    pub gpu_usage_percent: CircularQueue<TimestampedValue>,
+    #[serde(default = "default_queue")]
+    pub gpu_memory_mb: CircularQueue<TimestampedValue>,
+    #[serde(default = "default_queue")]
+    pub power_watts: CircularQueue<TimestampedValue>,
+    #[serde(default = "default_queue")]
+    pub swap_used_gb: CircularQueue<TimestampedValue>,
+    #[serde(default = "default_queue")]
+    pub memory_pressure: CircularQueue<TimestampedValue>, // MemoryPressure::as_value
+    #[serde(skip, default = "default_retention_secs")]
+    pub retention_secs: u64,
+    #[serde(skip, default = "DownsampleTier::from_env")]
+    pub downsample: Option<DownsampleTier>,
 }
 
 impl Default for AllMetricsHistory {
@@ -260,33 +762,87 @@ impl Default for AllMetricsHistory {
 
 impl AllMetricsHistory {
     pub fn new() -> Self {
-        Self::with_capacity(*crate::constants::HISTORY_SIZE)
+        Self::with_capacity(default_history_capacity())
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_retention(capacity, default_retention_secs())
+    }
+
+    pub fn with_retention(capacity: usize, retention_secs: u64) -> Self {
         Self {
             models: std::collections::HashMap::new(),
             total_llama_memory_mb: CircularQueue::with_capacity(capacity),
             cpu_usage_percent: CircularQueue::with_capacity(capacity),
             memory_usage_percent: CircularQueue::with_capacity(capacity),
             used_memory_gb: CircularQueue::with_capacity(capacity),
+            gpu_usage_percent: CircularQueue::with_capacity(capacity),
+            gpu_memory_mb: CircularQueue::with_capacity(capacity),
+            power_watts: CircularQueue::with_capacity(capacity),
+            swap_used_gb: CircularQueue::with_capacity(capacity),
+            memory_pressure: CircularQueue::with_capacity(capacity),
+            retention_secs,
+            downsample: DownsampleTier::from_env(),
         }
     }
 
-    pub fn trim_old_data(&mut self) {
-        let cutoff = current_timestamp().saturating_sub(300); // 5 minutes
+    /// History for a model, created with this history's capacity and retention if missing
+    pub fn model_history_mut(&mut self, model_name: &str) -> &mut MetricsHistory {
+        let capacity = self.cpu_usage_percent.capacity();
+        let retention_secs = self.retention_secs;
+        let downsample = self.downsample;
+        self.models
+            .entry(model_name.to_string())
+            .or_insert_with(|| MetricsHistory {
+                downsample,
+                ..MetricsHistory::with_retention(capacity, retention_secs)
+            })
+    }
+
+    /// Grow every queue to at least `capacity`, keeping existing samples
+    pub fn grow_capacity(&mut self, capacity: usize) {
+        for cq in [
+            &mut self.total_llama_memory_mb,
+            &mut self.cpu_usage_percent,
+            &mut self.memory_usage_percent,
+            &mut self.used_memory_gb,
+            &mut self.gpu_usage_percent,
+            &mut self.gpu_memory_mb,
+            &mut self.power_watts,
+            &mut self.swap_used_gb,
+            &mut self.memory_pressure,
+        ] {
+            DataAnalyzer::grow_circular_queue(cq, capacity);
+        }
+        for history in self.models.values_mut() {
+            history.grow_capacity(capacity);
+        }
+    }
 
-        DataAnalyzer::trim_circular_queue(&mut self.total_llama_memory_mb, cutoff);
-        DataAnalyzer::trim_circular_queue(&mut self.cpu_usage_percent, cutoff);
-        DataAnalyzer::trim_circular_queue(&mut self.memory_usage_percent, cutoff);
-        DataAnalyzer::trim_circular_queue(&mut self.used_memory_gb, cutoff);
+    pub fn trim_old_data(&mut self) {
+        let now = current_timestamp();
+        let cutoff = now.saturating_sub(self.retention_secs);
+
+        for cq in [
+            &mut self.total_llama_memory_mb,
+            &mut self.cpu_usage_percent,
+            &mut self.memory_usage_percent,
+            &mut self.used_memory_gb,
+            &mut self.gpu_usage_percent,
+            &mut self.gpu_memory_mb,
+            &mut self.power_watts,
+            &mut self.swap_used_gb,
+            &mut self.memory_pressure,
+        ] {
+            DataAnalyzer::downsample_circular_queue(cq, cutoff, self.downsample, now);
+        }
 
         for (_, history) in self.models.iter_mut() {
             history.trim_old_data();
         }
 
         // Only remove model histories if they have no data at all (never had any metrics)
-        // This preserves historical data for unloaded models for the full 5-minute window
+        // This preserves historical data for unloaded models for the full retention window
         self.models.retain(|_, history| {
             !history.tps.is_empty()
                 || !history.prompt_tps.is_empty()
@@ -304,6 +860,22 @@ impl AllMetricsHistory {
         DataAnalyzer::get_stats_from_circular_queue(&self.cpu_usage_percent)
     }
 
+    pub fn get_gpu_stats(&self) -> MetricStats {
+        DataAnalyzer::get_stats_from_circular_queue(&self.gpu_usage_percent)
+    }
+
+    pub fn get_power_stats(&self) -> MetricStats {
+        DataAnalyzer::get_stats_from_circular_queue(&self.power_watts)
+    }
+
+    /// Pressure at the latest sample; averaged (downsampled) samples round down
+    pub fn current_memory_pressure(&self) -> Option<MemoryPressure> {
+        self.memory_pressure
+            .iter()
+            .next()
+            .map(|v| MemoryPressure::from_value(v.value))
+    }
+
     pub fn get_system_memory_stats(&self) -> MetricStats {
         DataAnalyzer::get_stats_from_circular_queue(&self.memory_usage_percent)
     }
@@ -311,6 +883,12 @@ impl AllMetricsHistory {
     pub fn get_memory_stats(&self) -> MetricStats {
         DataAnalyzer::get_stats_from_circular_queue(&self.total_llama_memory_mb)
     }
+
+    /// Latest Llama memory as a fraction (0–1) of the machine's total memory
+    pub fn llama_memory_fraction(&self, total_memory_mb: f64) -> Option<f64> {
+        let llama_mb = self.total_llama_memory_mb.iter().next()?.value;
+        (total_memory_mb > 0.0).then(|| (llama_mb / total_memory_mb).clamp(0.0, 1.0))
+    }
 }
 
 fn current_timestamp() -> u64 {
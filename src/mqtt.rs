@@ -0,0 +1,183 @@
+use crate::logging;
+use crate::models::{AllMetrics, AllMetricsHistory};
+use crate::state_model::DisplayState;
+use rumqttc::{Client, LastWill, MqttOptions, QoS};
+use serde_json::json;
+use std::time::Duration;
+
+/// Pause between reconnection attempts while the broker is unreachable
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Publishes the display state and key metrics to an MQTT broker
+///
+/// Topics, under the configured prefix (all retained, so a subscriber sees the
+/// current value immediately):
+/// - `<prefix>/availability`: `online`, or `offline` via the last will
+/// - `<prefix>/state`: the display state, e.g. `ModelProcessingQueue`, on change
+/// - `<prefix>/metrics`: a JSON summary, every poll
+pub struct MqttPublisher {
+    client: Client,
+    prefix: String,
+    last_state: Option<DisplayState>,
+}
+
+impl MqttPublisher {
+    /// Connect to the configured broker; None when publishing is disabled
+    ///
+    /// The connection is driven (and re-established) on a background thread;
+    /// publishing only queues messages, so a slow broker never delays polling.
+    pub fn from_env() -> Option<Self> {
+        let host = crate::constants::MQTT_HOST.as_deref()?;
+        let prefix = crate::constants::MQTT_TOPIC_PREFIX.trim_end_matches('/');
+        let availability = format!("{prefix}/availability");
+
+        let mut options = MqttOptions::new(
+            format!("llama-swap-swiftbar-{}", std::process::id()),
+            host,
+            *crate::constants::MQTT_PORT,
+        );
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(
+            &availability,
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let Some(username) = crate::constants::MQTT_USERNAME.as_deref() {
+            let password = crate::constants::MQTT_PASSWORD.as_deref().unwrap_or("");
+            options.set_credentials(username, password);
+        }
+
+        let (client, mut connection) = Client::new(options, 32);
+        std::thread::spawn(move || {
+            let mut warned = false;
+            for event in connection.iter() {
+                if let Err(e) = event {
+                    if !warned {
+                        logging::warn(
+                            "mqtt",
+                            "connection_failed",
+                            &format!("MQTT broker connection failed, retrying: {e}"),
+                        );
+                        warned = true;
+                    }
+                    std::thread::sleep(RECONNECT_DELAY);
+                }
+            }
+        });
+
+        let publisher = Self {
+            client,
+            prefix: prefix.to_string(),
+            last_state: None,
+        };
+        publisher.publish_retained("availability", "online");
+        Some(publisher)
+    }
+
+    pub fn publish(
+        &mut self,
+        display_state: DisplayState,
+        all_metrics: Option<&AllMetrics>,
+        history: &AllMetricsHistory,
+    ) {
+        if self.last_state != Some(display_state) {
+            self.publish_retained("state", &format!("{display_state:?}"));
+            self.last_state = Some(display_state);
+        }
+
+        let payload = metrics_payload(display_state, all_metrics, history);
+        self.publish_retained("metrics", &payload.to_string());
+    }
+
+    fn publish_retained(&self, topic: &str, payload: &str) {
+        let topic = format!("{}/{topic}", self.prefix);
+        if let Err(e) = self
+            .client
+            .try_publish(&topic, QoS::AtLeastOnce, true, payload.as_bytes())
+        {
+            logging::debug(
+                "mqtt",
+                "publish_failed",
+                &format!("Failed to publish to {topic}: {e}"),
+            );
+        }
+    }
+}
+
+/// The JSON published to `<prefix>/metrics`
+pub fn metrics_payload(
+    display_state: DisplayState,
+    all_metrics: Option<&AllMetrics>,
+    history: &AllMetricsHistory,
+) -> serde_json::Value {
+    let latest = |data: &circular_queue::CircularQueue<crate::models::TimestampedValue>| {
+        data.iter().next().map(|v| v.value)
+    };
+    let models: Vec<_> = all_metrics
+        .map(|all| all.models.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|model| {
+            json!({
+                "name": model.model_name,
+                "generation_tokens_per_second": model.metrics.predicted_tokens_per_sec,
+                "prompt_tokens_per_second": model.metrics.prompt_tokens_per_sec,
+                "requests_processing": model.metrics.requests_processing,
+                "requests_deferred": model.metrics.requests_deferred,
+                "memory_mb": model.metrics.memory_mb,
+            })
+        })
+        .collect();
+
+    json!({
+        "state": format!("{display_state:?}"),
+        "busy": display_state == DisplayState::ModelProcessingQueue,
+        "cpu_percent": latest(&history.cpu_usage_percent),
+        "gpu_percent": latest(&history.gpu_usage_percent),
+        "memory_percent": latest(&history.memory_usage_percent),
+        "llama_memory_mb": latest(&history.total_llama_memory_mb),
+        "models": models,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Metrics, ModelMetrics, ModelState, TimestampedValue};
+
+    #[test]
+    fn test_metrics_payload() {
+        let all_metrics = AllMetrics {
+            models: vec![ModelMetrics {
+                model_name: "qwen".to_string(),
+                model_state: ModelState::Running,
+                metrics: Metrics {
+                    predicted_tokens_per_sec: 42.5,
+                    requests_processing: 1,
+                    ..Metrics::default()
+                },
+            }],
+            proxy: None,
+        };
+        let mut history = AllMetricsHistory::with_capacity(10);
+        history.gpu_usage_percent.push(TimestampedValue {
+            timestamp: 0,
+            value: 87.0,
+        });
+
+        let payload = metrics_payload(
+            DisplayState::ModelProcessingQueue,
+            Some(&all_metrics),
+            &history,
+        );
+
+        assert_eq!(payload["state"], "ModelProcessingQueue");
+        assert_eq!(payload["busy"], true);
+        assert_eq!(payload["gpu_percent"], 87.0);
+        assert!(payload["cpu_percent"].is_null());
+        assert_eq!(payload["models"][0]["name"], "qwen");
+        assert_eq!(payload["models"][0]["generation_tokens_per_second"], 42.5);
+        assert_eq!(payload["models"][0]["requests_processing"], 1);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::alerts::Alert;
+use crate::logging;
+use std::process::Command;
+
+/// Posts alerts to macOS Notification Center via osascript
+#[derive(Debug)]
+pub struct DesktopNotifier {
+    categories: Vec<&'static str>, // Alert kinds to post
+}
+
+impl DesktopNotifier {
+    /// None when no notification category is enabled
+    pub fn from_env() -> Option<Self> {
+        let categories = parse_categories(&crate::constants::NOTIFICATIONS);
+        (!categories.is_empty()).then_some(Self { categories })
+    }
+
+    /// Post the alert in the background if its category is enabled
+    pub fn notify(&self, alert: &Alert) {
+        if !self.categories.contains(&alert.kind()) {
+            return;
+        }
+
+        let message = alert.message();
+        std::thread::spawn(move || {
+            if let Err(e) = post(&message) {
+                logging::warn(
+                    "notifications",
+                    "post_failed",
+                    &format!("Failed to post notification: {e}"),
+                );
+            }
+        });
+    }
+}
+
+/// Post a notification and wait for osascript to finish
+pub fn post(message: &str) -> crate::Result<()> {
+    post_titled("Llama-Swap", message)
+}
+
+pub fn post_titled(title: &str, message: &str) -> crate::Result<()> {
+    let output = Command::new("osascript")
+        .args(["-e", &notification_script(title, message)])
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().into())
+    }
+}
+
+/// Enabled alert kinds from a comma-separated list; `all` enables every kind
+fn parse_categories(spec: &str) -> Vec<&'static str> {
+    let mut categories = Vec::new();
+    for name in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if name == "all" {
+            return Alert::KINDS.to_vec();
+        }
+        match Alert::KINDS.iter().find(|&&kind| kind == name) {
+            Some(&kind) => categories.push(kind),
+            None => logging::warn(
+                "notifications",
+                "unknown_category",
+                &format!(
+                    "Ignoring unknown notification category {name:?}; expected one of {}",
+                    Alert::KINDS.join(", ")
+                ),
+            ),
+        }
+    }
+    categories
+}
+
+/// AppleScript posting the message, with quotes and backslashes escaped
+fn notification_script(title: &str, message: &str) -> String {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        "display notification \"{}\" with title \"{}\"",
+        escape(message),
+        escape(title)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_categories() {
+        assert_eq!(
+            parse_categories("service_crashed, memory_high,bogus"),
+            vec!["service_crashed", "memory_high"]
+        );
+        assert_eq!(parse_categories("all"), Alert::KINDS.to_vec());
+        assert!(parse_categories("").is_empty());
+    }
+
+    #[test]
+    fn test_notification_script_escapes_quotes() {
+        assert_eq!(
+            notification_script("Llama-Swap", r#"model "a\b" loaded"#),
+            r#"display notification "model \"a\\b\" loaded" with title "Llama-Swap""#
+        );
+    }
+}
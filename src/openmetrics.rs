@@ -0,0 +1,212 @@
+use crate::models::{AllMetrics, AllMetricsHistory, TimestampedValue};
+use crate::types::ServiceStatus;
+use circular_queue::CircularQueue;
+use std::fmt::Write;
+
+/// Render the current metrics in OpenMetrics text format
+///
+/// Intended for node_exporter's textfile collector: one gauge per metric,
+/// per-model series labelled with `model`, terminated by `# EOF`.
+pub fn render(
+    status: &ServiceStatus,
+    all_metrics: Option<&AllMetrics>,
+    history: &AllMetricsHistory,
+) -> String {
+    let mut out = String::new();
+
+    gauge(
+        &mut out,
+        "llama_swap_up",
+        "Whether the llama-swap API is responding",
+    );
+    sample(
+        &mut out,
+        "llama_swap_up",
+        None,
+        f64::from(u8::from(status.api_responsive)),
+    );
+
+    let system = [
+        (
+            "llama_swap_system_cpu_usage_percent",
+            "System CPU usage",
+            &history.cpu_usage_percent,
+        ),
+        (
+            "llama_swap_system_memory_usage_percent",
+            "System memory usage",
+            &history.memory_usage_percent,
+        ),
+        (
+            "llama_swap_system_memory_used_gigabytes",
+            "System memory in use",
+            &history.used_memory_gb,
+        ),
+        (
+            "llama_swap_llama_memory_megabytes",
+            "Memory used by llama processes",
+            &history.total_llama_memory_mb,
+        ),
+        (
+            "llama_swap_gpu_utilization_percent",
+            "GPU device utilization",
+            &history.gpu_usage_percent,
+        ),
+        (
+            "llama_swap_gpu_memory_megabytes",
+            "Unified memory in use by the GPU",
+            &history.gpu_memory_mb,
+        ),
+        (
+            "llama_swap_power_watts",
+            "Combined CPU, GPU and ANE power draw",
+            &history.power_watts,
+        ),
+        (
+            "llama_swap_system_swap_used_gigabytes",
+            "Swap in use",
+            &history.swap_used_gb,
+        ),
+        (
+            "llama_swap_system_memory_pressure",
+            "Memory pressure level (0 normal, 1 warning, 2 critical)",
+            &history.memory_pressure,
+        ),
+    ];
+    for (name, help, data) in system {
+        if let Some(value) = latest(data) {
+            gauge(&mut out, name, help);
+            sample(&mut out, name, None, value);
+        }
+    }
+
+    if let Some(all_metrics) = all_metrics.filter(|m| !m.models.is_empty()) {
+        type Getter = fn(&crate::models::Metrics) -> f64;
+        let per_model: [(&str, &str, Getter); 7] = [
+            (
+                "llama_swap_model_generation_tokens_per_second",
+                "Generation throughput",
+                |m| m.predicted_tokens_per_sec,
+            ),
+            (
+                "llama_swap_model_prompt_tokens_per_second",
+                "Prompt processing throughput",
+                |m| m.prompt_tokens_per_sec,
+            ),
+            (
+                "llama_swap_model_memory_megabytes",
+                "Memory used by the model",
+                |m| m.memory_mb,
+            ),
+            (
+                "llama_swap_model_requests_processing",
+                "Requests being processed",
+                |m| f64::from(m.requests_processing),
+            ),
+            (
+                "llama_swap_model_requests_deferred",
+                "Requests waiting in the queue",
+                |m| f64::from(m.requests_deferred),
+            ),
+            (
+                "llama_swap_model_kv_cache_usage_ratio",
+                "Fraction of the KV cache in use",
+                |m| m.kv_cache_usage_ratio,
+            ),
+            (
+                "llama_swap_model_kv_cache_tokens",
+                "Tokens held in the KV cache",
+                |m| m.kv_cache_tokens,
+            ),
+        ];
+
+        for (name, help, get) in per_model {
+            gauge(&mut out, name, help);
+            for model in &all_metrics.models {
+                sample(&mut out, name, Some(&model.model_name), get(&model.metrics));
+            }
+        }
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+/// Write a snapshot atomically: to a temporary sibling file, then rename over the target
+///
+/// The textfile collector may read at any moment, so it must never see a partial file.
+pub fn write_snapshot(path: &str, contents: &str) -> crate::Result<()> {
+    let path = std::path::PathBuf::from(crate::commands::expand_tilde(path)?);
+    crate::persistence::write_atomic(&path, contents.as_bytes())
+}
+
+fn gauge(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+}
+
+fn sample(out: &mut String, name: &str, model: Option<&str>, value: f64) {
+    match model {
+        Some(model) => {
+            let _ = writeln!(out, "{name}{{model=\"{}\"}} {value}", escape_label(model));
+        }
+        None => {
+            let _ = writeln!(out, "{name} {value}");
+        }
+    }
+}
+
+fn latest(data: &CircularQueue<TimestampedValue>) -> Option<f64> {
+    data.iter().next().map(|v| v.value) // Most recent value
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Metrics, ModelMetrics, ModelState};
+
+    #[test]
+    fn test_render_includes_model_series() {
+        let status = ServiceStatus {
+            api_responsive: true,
+            ..ServiceStatus::default()
+        };
+        let all_metrics = AllMetrics {
+            models: vec![ModelMetrics {
+                model_name: "qwen \"coder\"".to_string(),
+                model_state: ModelState::Running,
+                metrics: Metrics {
+                    predicted_tokens_per_sec: 42.5,
+                    requests_deferred: 2,
+                    ..Metrics::default()
+                },
+            }],
+            proxy: None,
+        };
+        let mut history = AllMetricsHistory::with_capacity(10);
+        history.cpu_usage_percent.push(TimestampedValue {
+            timestamp: 0,
+            value: 12.0,
+        });
+
+        let text = render(&status, Some(&all_metrics), &history);
+
+        assert!(text.contains("llama_swap_up 1\n"));
+        assert!(text.contains("llama_swap_system_cpu_usage_percent 12\n"));
+        assert!(text.contains(
+            "llama_swap_model_generation_tokens_per_second{model=\"qwen \\\"coder\\\"\"} 42.5\n"
+        ));
+        assert!(
+            text.contains("llama_swap_model_requests_deferred{model=\"qwen \\\"coder\\\"\"} 2\n")
+        );
+        assert!(!text.contains("llama_swap_system_memory_usage_percent"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+}
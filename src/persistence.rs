@@ -0,0 +1,242 @@
+use crate::logging;
+use crate::models::AllMetricsHistory;
+use crate::types::error_helpers::{with_context, CREATE_DIR, CREATE_FILE, PARSE_JSON};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Current on-disk format version
+///
+/// - v1: bare `AllMetricsHistory` JSON (no envelope)
+/// - v2: `{ "version": 2, "history": AllMetricsHistory }`
+/// - v3: `{ "version": 3, "checksum": "<crc32 of history>", "history": AllMetricsHistory }`
+pub const PERSISTENCE_VERSION: u32 = 3;
+
+#[derive(Deserialize)]
+struct PersistedMetrics<'a> {
+    version: u32,
+    #[serde(default)]
+    checksum: Option<String>,
+    #[serde(borrow)]
+    history: &'a RawValue,
+}
+
+/// Write the metrics history to disk so it survives plugin restarts
+pub fn save_metrics(history: &AllMetricsHistory) -> crate::Result<()> {
+    let path = metrics_file_path()?;
+    let json = encode_metrics(history)?;
+    write_atomic(&path, json.as_bytes())
+}
+
+/// Serialize the history in the current format version
+pub fn encode_metrics(history: &AllMetricsHistory) -> crate::Result<String> {
+    let history_json = with_context(
+        serde_json::to_string(history),
+        "Failed to serialize metrics history",
+    )?;
+
+    Ok(format!(
+        r#"{{"version":{PERSISTENCE_VERSION},"checksum":"{}","history":{history_json}}}"#,
+        checksum(&history_json)
+    ))
+}
+
+/// Replace `path` with `contents` without ever leaving a half-written file behind
+///
+/// The data is written and synced to a sibling temp file which is then renamed over
+/// the target, so a crash mid-write leaves either the old or the new file intact.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> crate::Result<()> {
+    if let Some(parent) = path.parent() {
+        with_context(std::fs::create_dir_all(parent), CREATE_DIR)?;
+    }
+
+    let mut tmp_path = path.to_path_buf().into_os_string();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = with_context(std::fs::File::create(&tmp_path), CREATE_FILE)?;
+    with_context(file.write_all(contents), CREATE_FILE)?;
+    with_context(file.sync_all(), CREATE_FILE)?;
+    drop(file);
+
+    with_context(
+        std::fs::rename(&tmp_path, path),
+        "Failed to replace file atomically",
+    )?;
+    Ok(())
+}
+
+/// Load previously saved metrics history, if any
+///
+/// Samples older than the retention window are trimmed immediately so a stale
+/// file from a long-stopped plugin doesn't resurface in the charts. A file that
+/// fails its checksum or can't be decoded is moved aside to `<file>.corrupt` and
+/// treated as absent, so the plugin starts with an empty history instead of failing.
+pub fn load_metrics() -> crate::Result<Option<AllMetricsHistory>> {
+    let path = metrics_file_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let json = with_context(
+        std::fs::read_to_string(&path),
+        "Failed to read metrics history",
+    )?;
+    let mut history = match decode_metrics(&json) {
+        Ok(history) => history,
+        Err(e) => {
+            quarantine(&path, &e.to_string());
+            return Ok(None);
+        }
+    };
+    // Queues keep the capacity they were saved with; make room for the current settings
+    history.grow_capacity(crate::models::default_history_capacity());
+    history.trim_old_data();
+
+    Ok(Some(history))
+}
+
+/// Decode any supported format version into the current in-memory structure
+pub fn decode_metrics(json: &str) -> crate::Result<AllMetricsHistory> {
+    let value: serde_json::Value = with_context(serde_json::from_str(json), PARSE_JSON)?;
+
+    // Files written before versioning are a bare AllMetricsHistory object
+    if value.get("version").is_none() {
+        return with_context(serde_json::from_value(value), PARSE_JSON);
+    }
+
+    let persisted: PersistedMetrics = with_context(serde_json::from_str(json), PARSE_JSON)?;
+    match (persisted.version, persisted.checksum) {
+        (2, _) => {}
+        (PERSISTENCE_VERSION, Some(expected)) => {
+            let actual = checksum(persisted.history.get());
+            if actual != expected {
+                return Err(format!(
+                    "Metrics history checksum mismatch (expected {expected}, got {actual})"
+                )
+                .into());
+            }
+        }
+        (PERSISTENCE_VERSION, None) => return Err("Metrics history has no checksum".into()),
+        (v, _) => return Err(format!("Unsupported metrics history version: {v}").into()),
+    }
+
+    with_context(serde_json::from_str(persisted.history.get()), PARSE_JSON)
+}
+
+fn checksum(data: &str) -> String {
+    format!("{:08x}", crc32fast::hash(data.as_bytes()))
+}
+
+/// Move an unreadable history file out of the way, keeping it for inspection
+fn quarantine(path: &Path, reason: &str) {
+    let mut corrupt_path = path.to_path_buf().into_os_string();
+    corrupt_path.push(".corrupt");
+
+    let moved = std::fs::rename(path, &corrupt_path).is_ok();
+    logging::warn(
+        "persistence",
+        "history_corrupt",
+        &format!(
+            "Discarding unreadable metrics history ({reason}){}",
+            if moved {
+                format!("; kept as {}", Path::new(&corrupt_path).display())
+            } else {
+                String::new()
+            }
+        ),
+    );
+}
+
+/// Delete the saved history file and ask a running streaming instance to reset too
+///
+/// Commands run in their own process, so the in-memory history is cleared by the
+/// streaming loop when it picks up the marker file (see `take_clear_request`).
+pub fn clear_metrics() -> crate::Result<()> {
+    let path = metrics_file_path()?;
+    if path.exists() {
+        with_context(
+            std::fs::remove_file(&path),
+            "Failed to delete metrics history",
+        )?;
+    }
+
+    let marker = clear_marker_path()?;
+    if let Some(parent) = marker.parent() {
+        with_context(std::fs::create_dir_all(parent), CREATE_DIR)?;
+    }
+    with_context(std::fs::write(&marker, ""), CREATE_FILE)?;
+    Ok(())
+}
+
+/// Consume a pending clear request, returning true if there was one
+pub fn take_clear_request() -> bool {
+    clear_marker_path().is_ok_and(|marker| std::fs::remove_file(marker).is_ok())
+}
+
+/// How long a command waits for the streaming instance to save a fresh checkpoint
+const FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Load the metrics history as the running streaming instance has it now
+///
+/// The instance is asked to save a checkpoint through a marker file and
+/// removes the marker once the file is written. Without a running instance the
+/// last checkpoint is used. Fails when checkpoints are disabled, since the
+/// history then only ever exists in the streaming instance's memory.
+pub fn load_current_metrics() -> crate::Result<AllMetricsHistory> {
+    if *crate::constants::PERSIST_INTERVAL_SECS == 0 {
+        return Err(
+            "Metrics history isn't saved to disk (LLAMA_SWAP_PERSIST_INTERVAL_SECS=0); enable checkpoints to export it"
+                .into(),
+        );
+    }
+
+    let marker = flush_marker_path()?;
+    if let Some(parent) = marker.parent() {
+        with_context(std::fs::create_dir_all(parent), CREATE_DIR)?;
+    }
+    with_context(std::fs::write(&marker, ""), CREATE_FILE)?;
+    let start = std::time::Instant::now();
+    while marker.exists() && start.elapsed() < FLUSH_TIMEOUT {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    if std::fs::remove_file(&marker).is_ok() {
+        logging::info(
+            "persistence",
+            "flush_timed_out",
+            "No streaming instance saved a checkpoint; using the last one",
+        );
+    }
+
+    load_metrics()?.ok_or_else(|| "No metrics history has been saved yet".into())
+}
+
+/// Whether a command is waiting for a fresh checkpoint (see `load_current_metrics`)
+pub fn flush_requested() -> bool {
+    flush_marker_path().is_ok_and(|marker| marker.exists())
+}
+
+/// Tell the waiting command its checkpoint has been written
+pub fn complete_flush() {
+    if let Ok(marker) = flush_marker_path() {
+        let _ = std::fs::remove_file(marker);
+    }
+}
+
+fn flush_marker_path() -> crate::Result<PathBuf> {
+    let mut path = metrics_file_path()?.into_os_string();
+    path.push(".flush");
+    Ok(PathBuf::from(path))
+}
+
+fn clear_marker_path() -> crate::Result<PathBuf> {
+    let mut path = metrics_file_path()?.into_os_string();
+    path.push(".clear");
+    Ok(PathBuf::from(path))
+}
+
+fn metrics_file_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::METRICS_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
@@ -0,0 +1,162 @@
+use crate::logging;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the expected plist is regenerated when the installed one hasn't
+/// changed; finding the llama-swap binary runs a login shell
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Notices when the installed launch agent plist no longer matches what
+/// install_service would write: llama-swap moved, the port or a path setting
+/// changed, or a newer plugin generates different keys
+#[derive(Debug, Default)]
+pub struct PlistDrift {
+    plist_modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+    changed_keys: Vec<String>,
+}
+
+impl PlistDrift {
+    /// Plist keys whose values differ from the expected ones; empty when the
+    /// service definition is current
+    pub fn changed_keys(&self) -> &[String] {
+        &self.changed_keys
+    }
+
+    pub fn is_outdated(&self) -> bool {
+        !self.changed_keys.is_empty()
+    }
+
+    /// Compare again if the plist changed or the recheck interval elapsed
+    pub fn refresh(&mut self, installed: bool) {
+        if !installed {
+            self.changed_keys.clear();
+            self.plist_modified = None;
+            return;
+        }
+
+        let Ok(path) = crate::commands::get_plist_path() else {
+            return;
+        };
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let due = self
+            .last_check
+            .is_none_or(|at| at.elapsed() >= RECHECK_INTERVAL);
+        if !due && modified == self.plist_modified {
+            return;
+        }
+        self.plist_modified = modified;
+        self.last_check = Some(Instant::now());
+
+        let expected = crate::commands::find_llama_swap_binary()
+            .and_then(|binary| crate::commands::generate_plist_content(&binary));
+        let (Ok(installed), Ok(expected)) = (std::fs::read_to_string(&path), expected) else {
+            // Without llama-swap there's nothing to reinstall against
+            self.changed_keys.clear();
+            return;
+        };
+
+        let changed = changed_keys(&installed, &expected);
+        if changed != self.changed_keys && !changed.is_empty() {
+            logging::info(
+                "plist_drift",
+                "plist_outdated",
+                &format!("Installed plist differs in: {}", changed.join(", ")),
+            );
+        }
+        self.changed_keys = changed;
+    }
+}
+
+/// Top-level keys whose values differ between two plists, ignoring
+/// indentation and blank lines
+fn changed_keys(installed: &str, expected: &str) -> Vec<String> {
+    let installed = plist_entries(installed);
+    let expected = plist_entries(expected);
+    let mut changed: Vec<String> = expected
+        .iter()
+        .filter(|(key, value)| {
+            installed
+                .iter()
+                .find(|(k, _)| k == key)
+                .is_none_or(|(_, v)| v != value)
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    // Keys install_service no longer writes
+    changed.extend(
+        installed
+            .iter()
+            .filter(|(key, _)| !expected.iter().any(|(k, _)| k == key))
+            .map(|(key, _)| key.clone()),
+    );
+    changed
+}
+
+/// The top-level dict's (key, value) pairs, values as their trimmed lines
+fn plist_entries(plist: &str) -> Vec<(String, Vec<String>)> {
+    let mut entries: Vec<(String, Vec<String>)> = Vec::new();
+    let mut depth = 0usize;
+    for line in plist.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if depth == 1 {
+            if let Some(key) = line
+                .strip_prefix("<key>")
+                .and_then(|rest| rest.strip_suffix("</key>"))
+            {
+                entries.push((key.to_string(), Vec::new()));
+                continue;
+            }
+        }
+
+        let opens = line.matches("<dict>").count() + line.matches("<array>").count();
+        let closes = line.matches("</dict>").count() + line.matches("</array>").count();
+        let inside = depth >= 1;
+        depth = (depth + opens).saturating_sub(closes);
+        // The top-level dict's own tags aren't part of any value
+        if inside && depth >= 1 {
+            if let Some((_, value)) = entries.last_mut() {
+                value.push(line.to_string());
+            }
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPECTED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.user.llama-swap</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>/opt/homebrew/bin/llama-swap</string>
+        <string>-listen</string>
+        <string>:45786</string>
+    </array>
+    <key>KeepAlive</key>
+    <false/>
+</dict>
+</plist>"#;
+
+    #[test]
+    fn test_changed_keys() {
+        // Reformatting alone isn't drift
+        let reindented: String = EXPECTED
+            .lines()
+            .map(|line| format!("\t{}\n\n", line.trim()))
+            .collect();
+        assert!(changed_keys(&reindented, EXPECTED).is_empty());
+
+        let moved = EXPECTED.replace("/opt/homebrew/bin", "/usr/local/bin");
+        assert_eq!(changed_keys(&moved, EXPECTED), ["ProgramArguments"]);
+
+        let extra = EXPECTED.replace(
+            "</dict>\n</plist>",
+            "<key>RunAtLoad</key>\n<true/>\n</dict>\n</plist>",
+        );
+        assert_eq!(changed_keys(&extra, EXPECTED), ["RunAtLoad"]);
+    }
+}
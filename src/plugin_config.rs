@@ -0,0 +1,190 @@
+use crate::logging;
+use crate::models::Aggregation;
+use crate::types::error_helpers::{with_context, PARSE_JSON};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+/// Settings that don't fit in an environment variable
+///
+/// Read once at startup; edits take effect the next time the plugin starts.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PluginConfig {
+    pub extra_metrics: Vec<ExtraMetric>,
+    pub icon: Option<CustomIcon>, // LLAMA_SWAP_ICON_PATH takes precedence
+    pub remote_hosts: Vec<RemoteHost>,
+    pub service_args: Vec<String>, // Appended to llama-swap's arguments in the plist
+    pub service_env: BTreeMap<String, String>, // The plist's EnvironmentVariables
+}
+
+/// A llama-swap on another machine, monitored alongside the local one
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RemoteHost {
+    pub name: String, // Section title in the menu
+    #[serde(default = "default_remote_host")]
+    pub host: String, // e.g. "studio.local"; a scheme ("https://...") may be given
+    #[serde(default = "default_remote_port")]
+    pub port: u16,
+    pub ssh: Option<String>, // Reach the host through an SSH tunnel to this destination
+    pub ssh_local_port: Option<u16>, // The tunnel's local end; a free port when unset
+}
+
+impl RemoteHost {
+    /// Base URL of the host's llama-swap API, e.g. "http://studio.local:45786"
+    pub fn endpoint(&self) -> String {
+        let host = self.host.trim_end_matches('/');
+        if host.contains("://") {
+            format!("{host}:{}", self.port)
+        } else {
+            format!("http://{host}:{}", self.port)
+        }
+    }
+
+    /// host:port for an SSH tunnel to forward to, resolved by the SSH server
+    pub fn tunnel_target(&self) -> String {
+        let host = self.host.trim_end_matches('/');
+        let host = host.split_once("://").map_or(host, |(_, host)| host);
+        format!("{host}:{}", self.port)
+    }
+}
+
+fn default_remote_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_remote_port() -> u16 {
+    45786
+}
+
+/// User-supplied menu bar icon PNGs
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CustomIcon {
+    pub light: String,        // Path, `~` expanded
+    pub dark: Option<String>, // Same as light when unset
+}
+
+/// A Prometheus metric from llama-server's /metrics shown as its own row
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExtraMetric {
+    pub metric: String, // e.g. "llamacpp:n_busy_slots_per_decode"
+    pub label: String,  // Row title in the menu
+    #[serde(default)]
+    pub aggregate: Aggregation, // How labeled series combine; sum unless "max"
+}
+
+static CONFIG: LazyLock<PluginConfig> = LazyLock::new(|| match load() {
+    Ok(config) => config,
+    Err(e) => {
+        logging::warn(
+            "plugin_config",
+            "load_failed",
+            &format!("Ignoring plugin config: {e}"),
+        );
+        PluginConfig::default()
+    }
+});
+
+/// The plugin config, or the defaults when there's no (valid) config file
+pub fn get() -> &'static PluginConfig {
+    &CONFIG
+}
+
+fn load() -> crate::Result<PluginConfig> {
+    let path = config_file_path()?;
+    if !path.exists() {
+        return Ok(PluginConfig::default());
+    }
+
+    let json = with_context(
+        std::fs::read_to_string(&path),
+        "Failed to read plugin config",
+    )?;
+    parse(&json)
+}
+
+pub fn parse(json: &str) -> crate::Result<PluginConfig> {
+    with_context(serde_json::from_str(json), PARSE_JSON)
+}
+
+fn config_file_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::PLUGIN_CONFIG_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_extra_metrics() {
+        let config = parse(
+            r#"{"extra_metrics": [
+                {"metric": "llamacpp:n_busy_slots_per_decode", "label": "Busy slots per decode"},
+                {"metric": "llamacpp:slot_fill_ratio", "label": "Fullest slot", "aggregate": "max"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.extra_metrics,
+            vec![
+                ExtraMetric {
+                    metric: "llamacpp:n_busy_slots_per_decode".to_string(),
+                    label: "Busy slots per decode".to_string(),
+                    aggregate: Aggregation::Sum,
+                },
+                ExtraMetric {
+                    metric: "llamacpp:slot_fill_ratio".to_string(),
+                    label: "Fullest slot".to_string(),
+                    aggregate: Aggregation::Max,
+                },
+            ]
+        );
+
+        assert_eq!(parse("{}").unwrap(), PluginConfig::default());
+        assert!(parse(r#"{"extra_metrics": [{"label": "No metric"}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_icon() {
+        assert_eq!(
+            parse(r#"{"icon": {"light": "~/icon.png"}}"#).unwrap().icon,
+            Some(CustomIcon {
+                light: "~/icon.png".to_string(),
+                dark: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_hosts() {
+        let config =
+            parse(r#"{"remote_hosts": [{"name": "Studio", "host": "studio.local"}]}"#).unwrap();
+        assert_eq!(
+            config.remote_hosts[0].endpoint(),
+            "http://studio.local:45786"
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_host_over_ssh() {
+        let config =
+            parse(r#"{"remote_hosts": [{"name": "Studio", "ssh": "me@studio.local"}]}"#).unwrap();
+        assert_eq!(
+            config.remote_hosts[0].ssh.as_deref(),
+            Some("me@studio.local")
+        );
+        assert_eq!(config.remote_hosts[0].tunnel_target(), "127.0.0.1:45786");
+    }
+
+    #[test]
+    fn test_parse_service_settings() {
+        let config = parse(
+            r#"{"service_args": ["-watch-config"], "service_env": {"GGML_METAL_PATH": "/opt/metal"}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.service_args, ["-watch-config"]);
+        assert_eq!(config.service_env["GGML_METAL_PATH"], "/opt/metal");
+    }
+}
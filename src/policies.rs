@@ -0,0 +1,339 @@
+use crate::types::ServiceStatus;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Number of automatic actions remembered for display in the menu
+const RECENT_ACTIONS_LIMIT: usize = 10;
+
+/// Window used to enforce each policy's action budget
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Service condition a recovery policy watches for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolicyCondition {
+    ApiUnresponsive, // Process running but API not answering
+    ProcessStopped,  // Agent loaded in launchctl but no running process
+}
+
+impl PolicyCondition {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "api_unresponsive" => Some(Self::ApiUnresponsive),
+            "process_stopped" => Some(Self::ProcessStopped),
+            _ => None,
+        }
+    }
+
+    fn is_met(self, status: &ServiceStatus) -> bool {
+        match self {
+            Self::ApiUnresponsive => status.process_running && !status.api_responsive,
+            Self::ProcessStopped => status.launchctl_loaded && !status.process_running,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::ApiUnresponsive => "API unresponsive while process running",
+            Self::ProcessStopped => "process stopped while agent loaded",
+        }
+    }
+}
+
+/// Action a recovery policy performs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolicyAction {
+    Restart,
+    Start,
+    Unload,
+}
+
+impl PolicyAction {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "restart" => Some(Self::Restart),
+            "start" => Some(Self::Start),
+            "unload" => Some(Self::Unload),
+            _ => None,
+        }
+    }
+
+    /// Command passed to commands::handle_command
+    pub fn command(self) -> &'static str {
+        match self {
+            Self::Restart => "do_restart",
+            Self::Start => "do_start",
+            Self::Unload => "do_unload",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Restart => "restart service",
+            Self::Start => "start service",
+            Self::Unload => "unload models",
+        }
+    }
+}
+
+/// One user-defined rule: "if <condition> persists for <after> → <action>, max N/hour"
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryPolicy {
+    pub condition: PolicyCondition,
+    pub after: Duration,
+    pub action: PolicyAction,
+    pub max_per_hour: usize,
+}
+
+impl RecoveryPolicy {
+    pub fn description(&self) -> String {
+        format!(
+            "If {} for {}s → {} (max {}/h)",
+            self.condition.description(),
+            self.after.as_secs(),
+            self.action.description(),
+            self.max_per_hour
+        )
+    }
+}
+
+/// Parse policies from `condition:after_secs:action:max_per_hour` entries separated by `;`
+///
+/// Example: `api_unresponsive:120:restart:2;process_stopped:60:start:3`.
+/// Malformed entries are skipped.
+pub fn parse_policies(spec: &str) -> Vec<RecoveryPolicy> {
+    spec.split(';')
+        .filter_map(|entry| {
+            let parts: Vec<&str> = entry.trim().split(':').map(str::trim).collect();
+            if parts.len() != 4 {
+                return None;
+            }
+
+            Some(RecoveryPolicy {
+                condition: PolicyCondition::parse(parts[0])?,
+                after: Duration::from_secs(parts[1].parse().ok()?),
+                action: PolicyAction::parse(parts[2])?,
+                max_per_hour: parts[3].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Record of an action the engine performed (or skipped because of its budget)
+#[derive(Debug, Clone)]
+pub struct AutomaticAction {
+    pub at: SystemTime,
+    pub policy: String,
+    pub outcome: String,
+}
+
+#[derive(Debug)]
+struct PolicyRuntime {
+    policy: RecoveryPolicy,
+    condition_since: Option<Instant>,
+    fired_at: VecDeque<Instant>,
+    budget_exhausted_logged: bool,
+}
+
+/// Evaluates recovery policies against the service status on each poll
+#[derive(Debug)]
+pub struct PolicyEngine {
+    policies: Vec<PolicyRuntime>,
+    pub recent_actions: VecDeque<AutomaticAction>,
+}
+
+impl PolicyEngine {
+    pub fn new(policies: Vec<RecoveryPolicy>) -> Self {
+        Self {
+            policies: policies
+                .into_iter()
+                .map(|policy| PolicyRuntime {
+                    policy,
+                    condition_since: None,
+                    fired_at: VecDeque::new(),
+                    budget_exhausted_logged: false,
+                })
+                .collect(),
+            recent_actions: VecDeque::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(parse_policies(&crate::constants::RECOVERY_POLICIES))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    pub fn policies(&self) -> impl Iterator<Item = &RecoveryPolicy> {
+        self.policies.iter().map(|runtime| &runtime.policy)
+    }
+
+    /// Return policies whose action is due now, updating condition timers and budgets
+    pub fn evaluate(&mut self, status: &ServiceStatus, now: Instant) -> Vec<RecoveryPolicy> {
+        let mut due = Vec::new();
+
+        for runtime in &mut self.policies {
+            if !runtime.policy.condition.is_met(status) {
+                runtime.condition_since = None;
+                runtime.budget_exhausted_logged = false;
+                continue;
+            }
+
+            let since = *runtime.condition_since.get_or_insert(now);
+            if now.duration_since(since) < runtime.policy.after {
+                continue;
+            }
+
+            while runtime
+                .fired_at
+                .front()
+                .is_some_and(|at| now.duration_since(*at) >= RATE_LIMIT_WINDOW)
+            {
+                runtime.fired_at.pop_front();
+            }
+
+            if runtime.fired_at.len() >= runtime.policy.max_per_hour {
+                if !runtime.budget_exhausted_logged {
+                    runtime.budget_exhausted_logged = true;
+                    Self::push_action(
+                        &mut self.recent_actions,
+                        &runtime.policy,
+                        "skipped: hourly limit reached".to_string(),
+                    );
+                }
+                continue;
+            }
+
+            runtime.fired_at.push_back(now);
+            // Restart the condition timer so the action gets time to take effect
+            runtime.condition_since = Some(now);
+            due.push(runtime.policy.clone());
+        }
+
+        due
+    }
+
+    /// Execute due actions and record each one in the audit log
+    pub fn run(&mut self, status: &ServiceStatus) {
+        // Every action is a mutating command, which read-only mode rejects
+        if *crate::constants::READ_ONLY_MODE {
+            return;
+        }
+        let now = Instant::now();
+        let due = self.evaluate(status, now);
+
+        for policy in due {
+            let outcome = match crate::commands::handle_command(policy.action.command()) {
+                Ok(()) => "succeeded".to_string(),
+                Err(e) => format!("failed: {e}"),
+            };
+            Self::push_action(&mut self.recent_actions, &policy, outcome);
+        }
+    }
+
+    fn push_action(
+        recent: &mut VecDeque<AutomaticAction>,
+        policy: &RecoveryPolicy,
+        outcome: String,
+    ) {
+        let record = AutomaticAction {
+            at: SystemTime::now(),
+            policy: policy.description(),
+            outcome,
+        };
+
+        crate::logging::audit(
+            "automatic_action",
+            &format!("{} — {}", record.policy, record.outcome),
+        );
+
+        recent.push_front(record);
+        recent.truncate(RECENT_ACTIONS_LIMIT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unresponsive_status() -> ServiceStatus {
+        ServiceStatus {
+            plist_installed: true,
+            launchctl_loaded: true,
+            process_running: true,
+            api_responsive: false,
+            ui_available: false,
+            pid: Some(4242),
+        }
+    }
+
+    #[test]
+    fn test_parse_policies() {
+        let policies = parse_policies(
+            "api_unresponsive:120:restart:2; bogus:1:x:1;process_stopped:60:start:3",
+        );
+
+        assert_eq!(policies.len(), 2);
+        assert_eq!(policies[0].condition, PolicyCondition::ApiUnresponsive);
+        assert_eq!(policies[0].after, Duration::from_secs(120));
+        assert_eq!(policies[0].action, PolicyAction::Restart);
+        assert_eq!(policies[0].max_per_hour, 2);
+        assert_eq!(policies[1].action, PolicyAction::Start);
+        assert!(parse_policies("").is_empty());
+    }
+
+    #[test]
+    fn test_policy_waits_for_condition_duration() {
+        let mut engine = PolicyEngine::new(parse_policies("api_unresponsive:120:restart:2"));
+        let status = unresponsive_status();
+        let start = Instant::now();
+
+        assert!(engine.evaluate(&status, start).is_empty());
+        assert!(engine
+            .evaluate(&status, start + Duration::from_secs(60))
+            .is_empty());
+        let due = engine.evaluate(&status, start + Duration::from_secs(120));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].action, PolicyAction::Restart);
+    }
+
+    #[test]
+    fn test_policy_resets_when_condition_clears() {
+        let mut engine = PolicyEngine::new(parse_policies("api_unresponsive:120:restart:2"));
+        let start = Instant::now();
+
+        engine.evaluate(&unresponsive_status(), start);
+        engine.evaluate(&ServiceStatus::default(), start + Duration::from_secs(100));
+
+        // Timer restarted, so 130s after the first failure is only 10s into the new one
+        assert!(engine
+            .evaluate(&unresponsive_status(), start + Duration::from_secs(130))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_policy_respects_hourly_limit() {
+        let mut engine = PolicyEngine::new(parse_policies("api_unresponsive:10:restart:2"));
+        let status = unresponsive_status();
+        let start = Instant::now();
+        engine.evaluate(&status, start);
+
+        let fired: usize = (1..=10)
+            .map(|i| {
+                engine
+                    .evaluate(&status, start + Duration::from_secs(i * 10))
+                    .len()
+            })
+            .sum();
+        assert_eq!(fired, 2);
+
+        // Budget frees up once the window has passed
+        assert_eq!(
+            engine
+                .evaluate(&status, start + Duration::from_secs(3700))
+                .len(),
+            1
+        );
+    }
+}
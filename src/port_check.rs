@@ -0,0 +1,132 @@
+use std::net::TcpListener;
+use std::process::Command;
+
+/// A process listening on a TCP port
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub command: String,
+}
+
+/// Why llama-swap's API port isn't answering even though its process runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum PortProblem {
+    InUseBy(PortOwner), // Another process got the port first
+    InUse,              // Taken, but lsof couldn't say by whom
+    NotListening,       // Free: llama-swap listens elsewhere, or never got that far
+}
+
+impl PortProblem {
+    pub fn message(&self, port: u16) -> String {
+        match self {
+            Self::InUseBy(owner) => {
+                format!(
+                    "Port {port} in use by {} (PID {})",
+                    owner.command, owner.pid
+                )
+            }
+            Self::InUse => format!("Port {port} in use by another process"),
+            Self::NotListening => format!("Nothing is listening on port {port}"),
+        }
+    }
+
+    pub fn suggested_fix(&self) -> &'static str {
+        match self {
+            Self::InUseBy(_) | Self::InUse => {
+                "Quit that process, or set LLAMA_SWAP_API_PORT to a free port and reinstall the service"
+            }
+            Self::NotListening => {
+                "Check the service log; llama-swap may have failed to bind or be listening on another port"
+            }
+        }
+    }
+}
+
+/// Find out who, if anyone, holds the API port when llama-swap's API is silent
+///
+/// Returns None when the service itself is listening (it is hung, not blocked).
+pub fn diagnose(port: u16, service_pid: Option<u32>) -> Option<PortProblem> {
+    let Some(listeners) = listeners(port) else {
+        // No lsof: a bind probe at least says whether the port is free
+        return Some(match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(_) => PortProblem::NotListening,
+            Err(_) => PortProblem::InUse,
+        });
+    };
+
+    if listeners.iter().any(|owner| Some(owner.pid) == service_pid) {
+        return None;
+    }
+    Some(match listeners.into_iter().next() {
+        Some(owner) => PortProblem::InUseBy(owner),
+        None => PortProblem::NotListening,
+    })
+}
+
+/// Processes listening on the port, per lsof; None if lsof can't be run
+fn listeners(port: u16) -> Option<Vec<PortOwner>> {
+    let output = Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{port}"), "-sTCP:LISTEN", "-Fpc"])
+        .output()
+        .ok()?;
+    // lsof exits 1 both when nothing matches and on errors; stderr tells them apart
+    if !output.status.success() && !output.stderr.is_empty() {
+        return None;
+    }
+    Some(parse_listeners(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `lsof -F pc` output: a `p<pid>` line, then `c<command>`, per process
+fn parse_listeners(output: &str) -> Vec<PortOwner> {
+    let mut owners: Vec<PortOwner> = Vec::new();
+    for line in output.lines() {
+        if let Some(pid) = line.strip_prefix('p') {
+            if let Ok(pid) = pid.parse() {
+                owners.push(PortOwner {
+                    pid,
+                    command: String::new(),
+                });
+            }
+        } else if let Some(command) = line.strip_prefix('c') {
+            if let Some(owner) = owners.last_mut() {
+                owner.command = command.to_string();
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listeners() {
+        assert_eq!(
+            parse_listeners("p812\ncnode\nf23\np913\ncllama-swap\n"),
+            [
+                PortOwner {
+                    pid: 812,
+                    command: "node".to_string()
+                },
+                PortOwner {
+                    pid: 913,
+                    command: "llama-swap".to_string()
+                },
+            ]
+        );
+        assert!(parse_listeners("").is_empty());
+    }
+
+    #[test]
+    fn test_problem_message() {
+        let problem = PortProblem::InUseBy(PortOwner {
+            pid: 812,
+            command: "node".to_string(),
+        });
+        assert_eq!(
+            problem.message(45786),
+            "Port 45786 in use by node (PID 812)"
+        );
+    }
+}
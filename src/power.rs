@@ -0,0 +1,136 @@
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Longest gap between samples still integrated into the session's energy
+const MAX_SAMPLE_GAP: Duration = Duration::from_secs(60);
+
+/// Sample combined CPU + GPU + ANE power draw, in watts
+///
+/// powermetrics needs root, so it's run through `sudo -n`: that only succeeds
+/// with a sudoers rule allowing it without a password, and never prompts.
+/// Returns None when disabled, not permitted or not available.
+pub fn sample() -> Option<f64> {
+    if !*crate::constants::POWER_METRICS_ENABLED {
+        return None;
+    }
+
+    let output = Command::new("sudo")
+        .args([
+            "-n",
+            "/usr/bin/powermetrics",
+            "--samplers",
+            "cpu_power,gpu_power",
+            "-n",
+            "1",
+            "-i",
+            "200",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    parse_powermetrics(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Package power from powermetrics' text output, falling back to the sum of the
+/// CPU and GPU lines on machines that don't print a combined figure
+pub fn parse_powermetrics(output: &str) -> Option<f64> {
+    let milliwatts = |prefix: &str| {
+        output.lines().find_map(|line| {
+            let value = line.trim().strip_prefix(prefix)?.trim();
+            value.strip_suffix("mW")?.trim().parse::<f64>().ok()
+        })
+    };
+
+    let total = milliwatts("Combined Power (CPU + GPU + ANE):").or_else(|| {
+        match (milliwatts("CPU Power:"), milliwatts("GPU Power:")) {
+            (None, None) => None,
+            (cpu, gpu) => Some(cpu.unwrap_or(0.0) + gpu.unwrap_or(0.0)),
+        }
+    })?;
+    Some(total / 1000.0)
+}
+
+/// Energy used since models were last loaded, integrated from power samples
+#[derive(Debug, Default)]
+pub struct EnergyMeter {
+    session: Option<EnergySession>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnergySession {
+    pub started: Instant,
+    pub watt_hours: f64,
+    last_sample: Option<(Instant, f64)>,
+}
+
+impl EnergyMeter {
+    pub fn session(&self) -> Option<&EnergySession> {
+        self.session.as_ref()
+    }
+
+    /// Add a power sample taken while models are active (trapezoidal integration)
+    pub fn record(&mut self, watts: f64, now: Instant) {
+        let session = self.session.get_or_insert(EnergySession {
+            started: now,
+            watt_hours: 0.0,
+            last_sample: None,
+        });
+
+        if let Some((at, previous_watts)) = session.last_sample {
+            let elapsed = now.saturating_duration_since(at);
+            if elapsed <= MAX_SAMPLE_GAP {
+                session.watt_hours +=
+                    (previous_watts + watts) / 2.0 * elapsed.as_secs_f64() / 3600.0;
+            }
+        }
+        session.last_sample = Some((now, watts));
+    }
+
+    /// The next sample starts a new session
+    pub fn end_session(&mut self) {
+        self.session = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_powermetrics() {
+        let output =
+            "*** Sampled system activity (Tue Mar  5 10:00:00 2024 +0000) (200.51ms elapsed) ***
+
+**** Processor usage ****
+
+CPU Power: 4210 mW
+GPU Power: 11950 mW
+ANE Power: 0 mW
+Combined Power (CPU + GPU + ANE): 16160 mW
+";
+        assert_eq!(parse_powermetrics(output), Some(16.16));
+        assert_eq!(
+            parse_powermetrics("CPU Power: 1000 mW\nGPU Power: 500 mW\n"),
+            Some(1.5)
+        );
+        assert_eq!(
+            parse_powermetrics("powermetrics must be invoked as the superuser"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_energy_meter_integrates_samples() {
+        let start = Instant::now();
+        let mut meter = EnergyMeter::default();
+
+        meter.record(10.0, start);
+        meter.record(30.0, start + Duration::from_secs(36));
+        let session = meter.session().unwrap();
+        assert!((session.watt_hours - 0.2).abs() < 1e-9);
+
+        meter.end_session();
+        assert!(meter.session().is_none());
+    }
+}
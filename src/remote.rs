@@ -0,0 +1,160 @@
+use crate::logging;
+use crate::models::AllMetrics;
+use crate::plugin_config::RemoteHost;
+use reqwest::blocking::Client;
+use std::time::{Duration, Instant};
+
+/// How long before a host that didn't answer is tried again; polling it every
+/// cycle would hold up the local metrics by the request timeout each time
+const UNREACHABLE_RETRY: Duration = Duration::from_secs(30);
+
+/// Whether a remote llama-swap answered its last poll
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RemoteStatus {
+    Unknown, // Not polled yet
+    Online,
+    Unreachable,
+}
+
+/// A remote llama-swap's latest metrics, tracked apart from the local service
+///
+/// Remote hosts are only watched: the plugin can't start or stop a service
+/// on another machine, so their menu sections have no service controls.
+#[derive(Debug)]
+pub struct RemoteHostState {
+    pub host: RemoteHost,
+    pub status: RemoteStatus,
+    pub metrics: Option<AllMetrics>, // From the last poll, while online
+    pub last_error: Option<String>,
+    pub tunnel: Option<crate::ssh_tunnel::SshTunnel>, // When the host is reached over SSH
+    since: Instant,                                   // When the status last changed
+    last_attempt: Option<Instant>,
+}
+
+impl RemoteHostState {
+    pub fn new(host: RemoteHost) -> Self {
+        let tunnel = host.ssh.as_deref().map(|destination| {
+            crate::ssh_tunnel::SshTunnel::new(
+                &host.name,
+                destination,
+                host.tunnel_target(),
+                host.ssh_local_port,
+            )
+        });
+        Self {
+            host,
+            status: RemoteStatus::Unknown,
+            metrics: None,
+            last_error: None,
+            tunnel,
+            since: Instant::now(),
+            last_attempt: None,
+        }
+    }
+
+    /// Fetch the host's running models and metrics, unless it is unreachable
+    /// and was tried too recently; a tunnel is brought up first
+    pub fn poll(&mut self, client: &Client) {
+        let now = Instant::now();
+        if let Some(ref mut tunnel) = self.tunnel {
+            if !tunnel.ensure() {
+                self.record(Err("SSH tunnel is down".to_string()), now);
+                return;
+            }
+        }
+        if !self.is_due(now) {
+            return;
+        }
+        self.last_attempt = Some(now);
+        let result = crate::metrics::fetch_all_metrics_from(client, &self.endpoint());
+        self.record(result.map_err(|e| e.to_string()), now);
+    }
+
+    /// Where the host's API is reached from here: its own address, or the
+    /// local end of its tunnel
+    pub fn endpoint(&self) -> String {
+        match self.tunnel {
+            Some(ref tunnel) => format!("http://127.0.0.1:{}", tunnel.local_port()),
+            None => self.host.endpoint(),
+        }
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.status != RemoteStatus::Unreachable
+            || self
+                .last_attempt
+                .is_none_or(|at| now.duration_since(at) >= UNREACHABLE_RETRY)
+    }
+
+    fn record(&mut self, result: Result<AllMetrics, String>, now: Instant) {
+        let status = match result {
+            Ok(metrics) => {
+                self.metrics = Some(metrics);
+                self.last_error = None;
+                RemoteStatus::Online
+            }
+            Err(e) => {
+                self.metrics = None;
+                self.last_error = Some(e);
+                RemoteStatus::Unreachable
+            }
+        };
+        if status != self.status {
+            logging::info(
+                "remote",
+                "host_status_changed",
+                &format!("{}: {:?} -> {status:?}", self.host.name, self.status),
+            );
+            self.status = status;
+            self.since = now;
+        }
+    }
+
+    /// How long the host has been in its current status
+    pub fn status_age(&self) -> Duration {
+        self.since.elapsed()
+    }
+}
+
+/// A state per remote host in the plugin config
+pub fn from_config() -> Vec<RemoteHostState> {
+    crate::plugin_config::get()
+        .remote_hosts
+        .iter()
+        .cloned()
+        .map(RemoteHostState::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unreachable_host_retried_later() {
+        let mut state = RemoteHostState::new(RemoteHost {
+            name: "Studio".to_string(),
+            host: "studio.local".to_string(),
+            port: 45786,
+            ssh: None,
+            ssh_local_port: None,
+        });
+        let now = Instant::now();
+        assert!(state.is_due(now));
+
+        state.last_attempt = Some(now);
+        state.record(Err("connection refused".to_string()), now);
+        assert_eq!(state.status, RemoteStatus::Unreachable);
+        assert!(!state.is_due(now + Duration::from_secs(10)));
+        assert!(state.is_due(now + UNREACHABLE_RETRY));
+
+        let metrics = AllMetrics {
+            models: Vec::new(),
+            proxy: None,
+        };
+        state.record(Ok(metrics), now + UNREACHABLE_RETRY);
+        assert_eq!(state.status, RemoteStatus::Online);
+        assert_eq!(state.last_error, None);
+        assert!(state.is_due(now + UNREACHABLE_RETRY));
+    }
+}
@@ -1,45 +1,347 @@
 use crate::constants::LAUNCH_AGENT_LABEL;
+use crate::types::error_helpers::{with_context, CREATE_DIR, CREATE_FILE};
+use reqwest::blocking::Client;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
 use std::process::Command;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Bytes read from the end of the service log when showing its last lines
+const LOG_TAIL_BYTES: u64 = 16 * 1024;
+
+/// How often `launchctl print` runs again while the service keeps its PID;
+/// spawn counts and exit reasons only change when it restarts
+const LAUNCHD_RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Check whether the web UI route loads (independently of the API)
+pub fn is_ui_available(client: &Client) -> bool {
+    client
+        .get(crate::commands::ui_url())
+        .timeout(Duration::from_secs(1))
+        .send()
+        .is_ok_and(|response| response.status().is_success())
+}
 
 /// Check if service is loaded in launchctl (registered but may not be running)
 pub fn is_service_loaded() -> bool {
-    Command::new("launchctl")
-        .args(["list", LAUNCH_AGENT_LABEL])
+    is_label_loaded(&LAUNCH_AGENT_LABEL)
+}
+
+/// Check if the launch agent with this label is loaded
+pub fn is_label_loaded(label: &str) -> bool {
+    label_state(label).0
+}
+
+/// PID of the running service, according to launchctl (None while it isn't running)
+pub fn service_pid() -> Option<u32> {
+    label_pid(&LAUNCH_AGENT_LABEL)
+}
+
+/// PID of the launch agent with this label, while it runs
+pub fn label_pid(label: &str) -> Option<u32> {
+    label_state(label).1
+}
+
+/// Whether the launch agent with this label is loaded, and its PID while it
+/// runs, from a single `launchctl list`
+pub fn label_state(label: &str) -> (bool, Option<u32>) {
+    let Some(result) = Command::new("launchctl")
+        .args(["list", label])
         .output()
         .ok()
-        .map(|result| result.status.success())
-        .unwrap_or(false)
+        .filter(|result| result.status.success())
+    else {
+        return (false, None);
+    };
+    (
+        true,
+        parse_list_pid(&String::from_utf8_lossy(&result.stdout)),
+    )
 }
 
-/// Check if service is running via launchctl (has an active PID)
-pub fn is_service_running() -> bool {
+fn parse_list_pid(output: &str) -> Option<u32> {
+    // When a service is loaded but not running, launchctl returns a config dict without a PID
+    // When a service is running, the output contains '"PID" = 12345;'
+    output
+        .lines()
+        .find(|line| line.trim().starts_with("\"PID\""))
+        .and_then(|line| line.split('=').nth(1))
+        .map(|pid_str| {
+            pid_str
+                .trim()
+                .trim_end_matches(';')
+                .trim_matches('"')
+                .trim()
+                .to_string()
+        })
+        .and_then(|pid| pid.parse::<u32>().ok())
+        .filter(|&pid| pid != 0)
+}
+
+/// What launchd itself knows about the service, from `launchctl print`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchdDetails {
+    pub state: Option<String>, // "running", "not running", "spawn scheduled", ...
+    pub pid: Option<u32>,
+    pub runs: Option<u32>, // Times launchd has spawned the service since it was loaded
+    pub last_exit_code: Option<String>, // e.g. "0", "1", "(never exited)"
+    pub last_exit_reason: Option<String>, // Terminating signal or exit reason, when launchd gives one
+}
+
+impl LaunchdDetails {
+    /// launchd is holding back a respawn because the service exited too soon
+    pub fn is_throttled(&self) -> bool {
+        self.state
+            .as_deref()
+            .is_some_and(|state| state.contains("spawn scheduled") || state.contains("throttled"))
+    }
+
+    /// One-line summary for the Advanced menu
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!(
+            "launchd: {}",
+            self.state.as_deref().unwrap_or("unknown")
+        )];
+        if let Some(pid) = self.pid {
+            parts.push(format!("PID {pid}"));
+        }
+        if let Some(runs) = self.runs {
+            parts.push(format!("Spawns: {runs}"));
+        }
+        if let Some(ref code) = self.last_exit_code {
+            match self.last_exit_reason {
+                Some(ref reason) => parts.push(format!("Last Exit: {code} ({reason})")),
+                None => parts.push(format!("Last Exit: {code}")),
+            }
+        }
+        if self.is_throttled() {
+            parts.push("Throttled".to_string());
+        }
+        parts.join(" | ")
+    }
+}
+
+/// Ask launchd for the service's state, PID, spawn count and last exit
+///
+/// `launchctl list` only says whether the service is loaded and its PID;
+/// `print` also says why it last exited and whether a respawn is throttled.
+pub fn launchd_details() -> Option<LaunchdDetails> {
+    // SAFETY: getuid() has no preconditions and cannot fail
+    let uid = unsafe { libc::getuid() };
     Command::new("launchctl")
-        .args(["list", LAUNCH_AGENT_LABEL])
+        .args(["print", &format!("gui/{uid}/{}", *LAUNCH_AGENT_LABEL)])
         .output()
         .ok()
         .filter(|result| result.status.success())
-        .map(|result| {
-            let output_str = String::from_utf8_lossy(&result.stdout);
-
-            // Check if the output contains a PID (indicating the process is actually running)
-            // When a service is loaded but not running, launchctl returns a config dict without a PID
-            // When a service is running, the output contains '"PID" = 12345;'
-            if output_str.contains("\"PID\"") {
-                // Extract the PID value and check if it's a valid number
-                for line in output_str.lines() {
-                    if line.trim().starts_with("\"PID\"") {
-                        if let Some(pid_str) = line.split('=').nth(1) {
-                            let pid_clean = pid_str
-                                .trim()
-                                .trim_end_matches(';')
-                                .trim_matches('"')
-                                .trim();
-                            return pid_clean.parse::<i32>().is_ok() && pid_clean != "0";
-                        }
-                    }
-                }
+        .map(|result| parse_launchctl_print(&String::from_utf8_lossy(&result.stdout)))
+}
+
+/// `launchd_details`, asked again only when the service is loaded or
+/// unloaded, its PID changes, or the recheck interval elapses
+#[derive(Debug, Default)]
+pub struct LaunchdWatch {
+    details: Option<LaunchdDetails>,
+    last_check: Option<(Instant, Option<u32>)>, // When, and the PID at the time
+}
+
+impl LaunchdWatch {
+    pub fn details(&self) -> Option<&LaunchdDetails> {
+        self.details.as_ref()
+    }
+
+    pub fn refresh(&mut self, loaded: bool, pid: Option<u32>) {
+        if !loaded {
+            self.details = None;
+            self.last_check = None;
+            return;
+        }
+
+        let due = self.last_check.is_none_or(|(at, checked_pid)| {
+            checked_pid != pid || at.elapsed() >= LAUNCHD_RECHECK_INTERVAL
+        });
+        if !due {
+            return;
+        }
+        self.last_check = Some((Instant::now(), pid));
+        self.details = launchd_details();
+    }
+}
+
+/// Parse the service's own `key = value` lines out of `launchctl print`,
+/// skipping nested dictionaries such as `environment = { ... }`
+fn parse_launchctl_print(output: &str) -> LaunchdDetails {
+    let mut details = LaunchdDetails::default();
+    let mut depth = 0usize;
+    for line in output.lines() {
+        let line = line.trim();
+        if line.ends_with('{') {
+            depth += 1;
+            continue;
+        }
+        if line == "}" {
+            depth = depth.saturating_sub(1);
+            continue;
+        }
+        if depth != 1 {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "state" => details.state = Some(value),
+            "pid" => details.pid = value.parse().ok().filter(|&pid| pid != 0),
+            "runs" => details.runs = value.parse().ok(),
+            "last exit code" => details.last_exit_code = Some(value),
+            "last terminating signal" | "last exit reason" => {
+                details.last_exit_reason = Some(value);
             }
-            false
-        })
-        .unwrap_or(false)
+            _ => {}
+        }
+    }
+    details
+}
+
+/// Remember that a start was just requested
+///
+/// Commands run in their own process, so the streaming instance learns about the
+/// start (and times it out, see `AgentState::with_startup_timeout`) through this file.
+pub fn mark_start_requested() -> crate::Result<()> {
+    let path = startup_marker_path()?;
+    if let Some(parent) = path.parent() {
+        with_context(std::fs::create_dir_all(parent), CREATE_DIR)?;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    with_context(std::fs::write(&path, now.to_string()), CREATE_FILE)?;
+    Ok(())
+}
+
+/// Forget a pending start (it came up, or the service was stopped)
+pub fn clear_start_request() {
+    if let Ok(path) = startup_marker_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Time since the pending start request, if there is one
+pub fn start_request_age() -> Option<Duration> {
+    let contents = std::fs::read_to_string(startup_marker_path().ok()?).ok()?;
+    let requested_at = UNIX_EPOCH + Duration::from_secs(contents.trim().parse().ok()?);
+    Some(
+        SystemTime::now()
+            .duration_since(requested_at)
+            .unwrap_or_default(),
+    )
+}
+
+/// Record whether the user wants the service running, for the watchdog
+pub fn set_wanted_running(wanted: bool) -> crate::Result<()> {
+    let path = crate::commands::expand_tilde(&crate::constants::WANTED_RUNNING_FILE_PATH)?;
+    if wanted {
+        crate::persistence::write_atomic(std::path::Path::new(&path), b"")
+    } else {
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to clear {path}: {e}").into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Whether Start or Restart was run more recently than Stop
+pub fn is_wanted_running() -> bool {
+    crate::commands::expand_tilde(&crate::constants::WANTED_RUNNING_FILE_PATH)
+        .is_ok_and(|path| std::path::Path::new(&path).exists())
+}
+
+/// Last non-empty lines of the service log, oldest first
+pub fn tail_service_log(lines: usize) -> Vec<String> {
+    let Ok(path) = crate::commands::expand_tilde(&crate::constants::LOG_FILE_PATH) else {
+        return Vec::new();
+    };
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+
+    // Only the end of the file matters; the log can grow large
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let offset = len.saturating_sub(LOG_TAIL_BYTES);
+    let mut bytes = Vec::new();
+    if file.seek(SeekFrom::Start(offset)).is_err() || file.read_to_end(&mut bytes).is_err() {
+        return Vec::new();
+    }
+
+    let text = String::from_utf8_lossy(&bytes);
+    // Starting mid-file, the first line is probably cut off
+    let text = match text.split_once('\n') {
+        Some((_, rest)) if offset > 0 => rest,
+        _ => &text,
+    };
+    let mut tail: Vec<String> = text
+        .lines()
+        .rev()
+        .filter(|line| !line.trim().is_empty())
+        .take(lines)
+        .map(str::to_string)
+        .collect();
+    tail.reverse();
+    tail
+}
+
+fn startup_marker_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::STARTUP_MARKER_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_launchctl_print() {
+        let output = "gui/501/com.llama-swap = {
+\tactive count = 0
+\tpath = /Users/me/Library/LaunchAgents/com.llama-swap.plist
+\tstate = spawn scheduled
+
+\tprogram = /opt/homebrew/bin/llama-swap
+\tenvironment = {
+\t\tstate = ignored
+\t}
+
+\truns = 4
+\tlast exit code = 1
+\tlast terminating signal = Killed: 9
+}
+";
+        let details = parse_launchctl_print(output);
+        assert_eq!(
+            details,
+            LaunchdDetails {
+                state: Some("spawn scheduled".to_string()),
+                pid: None,
+                runs: Some(4),
+                last_exit_code: Some("1".to_string()),
+                last_exit_reason: Some("Killed: 9".to_string()),
+            }
+        );
+        assert!(details.is_throttled());
+        assert_eq!(
+            details.summary(),
+            "launchd: spawn scheduled | Spawns: 4 | Last Exit: 1 (Killed: 9) | Throttled"
+        );
+    }
+
+    #[test]
+    fn test_parse_list_pid() {
+        let running = "{\n\t\"LimitLoadToSessionType\" = \"Aqua\";\n\t\"PID\" = 812;\n};";
+        assert_eq!(parse_list_pid(running), Some(812));
+        assert_eq!(parse_list_pid("{\n\t\"LastExitStatus\" = 0;\n};"), None);
+    }
 }
@@ -0,0 +1,207 @@
+use crate::models::AllMetrics;
+use crate::types::error_helpers::{with_context, PARSE_JSON};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Number of daily summaries kept on disk
+const DAYS_KEPT: usize = 30;
+
+/// Longest gap between polls still counted as continuous activity
+const MAX_ACTIVE_GAP_SECS: u64 = 60;
+
+/// Aggregate usage of one model over one day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelDayStats {
+    pub tokens_decoded: u64,
+    pub peak_memory_mb: f64,
+    pub active_secs: u64, // Time spent with at least one request processing
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaySummary {
+    pub date: String, // Local date, YYYY-MM-DD
+    pub models: BTreeMap<String, ModelDayStats>,
+    pub peak_llama_memory_mb: f64,
+}
+
+impl DaySummary {
+    pub fn total_tokens(&self) -> u64 {
+        self.models.values().map(|m| m.tokens_decoded).sum()
+    }
+
+    pub fn total_active_secs(&self) -> u64 {
+        self.models.values().map(|m| m.active_secs).sum()
+    }
+}
+
+/// Per-day usage summaries that survive plugin restarts
+///
+/// Token totals come from llama-server's `tokens_predicted_total` counter, so
+/// only the increase between polls is added; a counter that goes backwards
+/// means the model was reloaded and its new value is counted from zero.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionLog {
+    pub days: Vec<DaySummary>, // Oldest first
+    #[serde(skip)]
+    last_tokens_total: HashMap<String, f64>,
+    #[serde(skip)]
+    last_poll: Option<u64>,
+}
+
+impl SessionLog {
+    /// Fold one successful poll into the summary for `date`
+    pub fn record(
+        &mut self,
+        timestamp: u64,
+        date: &str,
+        all_metrics: &AllMetrics,
+        llama_memory_mb: f64,
+    ) {
+        let elapsed = self
+            .last_poll
+            .map(|last| timestamp.saturating_sub(last))
+            .filter(|&secs| secs <= MAX_ACTIVE_GAP_SECS)
+            .unwrap_or(0);
+        self.last_poll = Some(timestamp);
+
+        if self.days.last().is_none_or(|day| day.date != date) {
+            self.days.push(DaySummary {
+                date: date.to_string(),
+                ..DaySummary::default()
+            });
+            if self.days.len() > DAYS_KEPT {
+                self.days.remove(0);
+            }
+        }
+        let day = self.days.last_mut().expect("day was just ensured");
+        day.peak_llama_memory_mb = day.peak_llama_memory_mb.max(llama_memory_mb);
+
+        for model in &all_metrics.models {
+            let stats = day.models.entry(model.model_name.clone()).or_default();
+            let total = model.metrics.tokens_predicted_total;
+
+            let decoded = match self
+                .last_tokens_total
+                .insert(model.model_name.clone(), total)
+            {
+                Some(previous) if total >= previous => total - previous,
+                Some(_) => total, // Counter reset: model was reloaded
+                None => 0.0,      // First sighting establishes the baseline
+            };
+            stats.tokens_decoded += decoded as u64;
+            stats.peak_memory_mb = stats.peak_memory_mb.max(model.metrics.memory_mb);
+            if model.metrics.requests_processing > 0 {
+                stats.active_secs += elapsed;
+            }
+        }
+    }
+
+    pub fn day(&self, date: &str) -> Option<&DaySummary> {
+        self.days.iter().rev().find(|day| day.date == date)
+    }
+
+    pub fn load() -> crate::Result<Self> {
+        let path = sessions_file_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let json = with_context(std::fs::read_to_string(&path), "Failed to read sessions")?;
+        with_context(serde_json::from_str(&json), PARSE_JSON)
+    }
+
+    pub fn save(&self) -> crate::Result<()> {
+        let path = sessions_file_path()?;
+        let json = with_context(serde_json::to_string(self), "Failed to serialize sessions")?;
+        crate::persistence::write_atomic(&path, json.as_bytes())
+    }
+}
+
+/// Local calendar date (YYYY-MM-DD) for a Unix timestamp
+pub fn local_date(timestamp: u64) -> String {
+    let Some(tm) = local_time(timestamp) else {
+        // Fall back to the UTC date
+        let utc = crate::logging::format_rfc3339(
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(timestamp),
+        );
+        return utc[..10].to_string();
+    };
+
+    format!(
+        "{:04}-{:02}-{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday
+    )
+}
+
+/// Minutes since local midnight for a Unix timestamp
+pub fn local_minute_of_day(timestamp: u64) -> Option<u32> {
+    let tm = local_time(timestamp)?;
+    Some((tm.tm_hour * 60 + tm.tm_min) as u32)
+}
+
+fn local_time(timestamp: u64) -> Option<libc::tm> {
+    let time = timestamp as libc::time_t;
+    // SAFETY: an all-zero `tm` is a valid value, and localtime_r only writes into it
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return None;
+    }
+    Some(tm)
+}
+
+fn sessions_file_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::SESSIONS_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Metrics, ModelMetrics, ModelState};
+
+    fn poll(tokens_total: f64, processing: u32) -> AllMetrics {
+        AllMetrics {
+            models: vec![ModelMetrics {
+                model_name: "test-model".to_string(),
+                model_state: ModelState::Running,
+                metrics: Metrics {
+                    tokens_predicted_total: tokens_total,
+                    requests_processing: processing,
+                    memory_mb: 512.0,
+                    ..Metrics::default()
+                },
+            }],
+            proxy: None,
+        }
+    }
+
+    #[test]
+    fn test_record_accumulates_deltas() {
+        let mut log = SessionLog::default();
+        log.record(1_000, "2024-03-01", &poll(500.0, 0), 1024.0);
+        log.record(1_005, "2024-03-01", &poll(700.0, 1), 2048.0);
+        log.record(1_010, "2024-03-01", &poll(50.0, 1), 1024.0); // Reloaded
+        log.record(1_500, "2024-03-01", &poll(80.0, 1), 1024.0); // Gap too long to count
+
+        let day = log.day("2024-03-01").unwrap();
+        let stats = &day.models["test-model"];
+        assert_eq!(stats.tokens_decoded, 200 + 50 + 30);
+        assert_eq!(stats.active_secs, 5 + 5);
+        assert_eq!(stats.peak_memory_mb, 512.0);
+        assert_eq!(day.peak_llama_memory_mb, 2048.0);
+    }
+
+    #[test]
+    fn test_record_starts_new_day() {
+        let mut log = SessionLog::default();
+        log.record(1_000, "2024-03-01", &poll(100.0, 0), 0.0);
+        log.record(1_005, "2024-03-02", &poll(150.0, 0), 0.0);
+
+        assert_eq!(log.days.len(), 2);
+        assert_eq!(log.day("2024-03-01").unwrap().total_tokens(), 0);
+        assert_eq!(log.day("2024-03-02").unwrap().total_tokens(), 50);
+    }
+}
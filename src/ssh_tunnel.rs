@@ -0,0 +1,284 @@
+use crate::logging;
+use std::io::Read;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a new tunnel gets to start forwarding before the poll moves on
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wait before restarting a failed tunnel, doubled per consecutive failure
+const FIRST_RETRY: Duration = Duration::from_secs(5);
+const MAX_RETRY: Duration = Duration::from_secs(300);
+
+/// Whether a tunnel is forwarding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TunnelHealth {
+    Starting, // ssh is running, the forward isn't accepting connections yet
+    Up,
+    Down, // ssh exited or couldn't be started; restarted after a backoff
+}
+
+/// An `ssh -L` port forward to a remote llama-swap, run and restarted by the plugin
+///
+/// The forward listens on 127.0.0.1 only. ssh runs in batch mode, so the key
+/// must be usable without a prompt (ssh-agent or an unencrypted key).
+#[derive(Debug)]
+pub struct SshTunnel {
+    destination: String, // ssh destination, e.g. "me@studio.local"
+    target: String,      // host:port the SSH server forwards to
+    local_port: u16,
+    pid_file: Option<PathBuf>, // Lets the next plugin run stop an ssh left behind
+    child: Option<Child>,
+    started_at: Option<Instant>,
+    up_since: Option<Instant>,
+    retry_at: Option<Instant>,
+    failures: u32, // Consecutive, reset once the forward works
+    pub last_error: Option<String>,
+}
+
+impl SshTunnel {
+    /// A tunnel to `target` as seen from the SSH server; a free local port is
+    /// picked when none is given
+    pub fn new(name: &str, destination: &str, target: String, local_port: Option<u16>) -> Self {
+        let local_port = local_port.or_else(free_port).unwrap_or_default();
+        let pid_file = crate::commands::expand_tilde(&crate::constants::PID_FILE_PATH)
+            .ok()
+            .and_then(|path| {
+                let dir = PathBuf::from(path).parent()?.to_path_buf();
+                let name = crate::constants::instance_name(name);
+                Some(dir.join(format!("tunnel-{name}.pid")))
+            });
+        Self {
+            destination: destination.to_string(),
+            target,
+            local_port,
+            pid_file,
+            child: None,
+            started_at: None,
+            up_since: None,
+            retry_at: None,
+            failures: 0,
+            last_error: None,
+        }
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// Keep the tunnel running: notice an ssh that exited, start a new one
+    /// once its backoff has passed, and report whether the forward works
+    pub fn ensure(&mut self) -> bool {
+        self.reap();
+        if self.child.is_none() {
+            if self.retry_at.is_some_and(|at| Instant::now() < at) {
+                return false;
+            }
+            if let Err(e) = self.spawn() {
+                self.fail(e.to_string());
+                return false;
+            }
+        }
+
+        // A new tunnel gets a moment to connect; an established one is checked once
+        let deadline = self.started_at.unwrap_or_else(Instant::now) + CONNECT_TIMEOUT;
+        loop {
+            if is_listening(self.local_port) {
+                if self.up_since.is_none() {
+                    logging::info(
+                        "ssh_tunnel",
+                        "tunnel_up",
+                        &format!(
+                            "Forwarding 127.0.0.1:{} to {} via {}",
+                            self.local_port, self.target, self.destination
+                        ),
+                    );
+                    self.up_since = Some(Instant::now());
+                }
+                self.failures = 0;
+                self.last_error = None;
+                return true;
+            }
+            self.up_since = None;
+            if Instant::now() >= deadline || !self.reap() {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    pub fn health(&self) -> TunnelHealth {
+        match (&self.child, self.up_since) {
+            (Some(_), Some(_)) => TunnelHealth::Up,
+            (Some(_), None) => TunnelHealth::Starting,
+            (None, _) => TunnelHealth::Down,
+        }
+    }
+
+    /// How long the forward has been working
+    pub fn uptime(&self) -> Option<Duration> {
+        self.up_since.map(|since| since.elapsed())
+    }
+
+    /// Time until a failed tunnel is restarted
+    pub fn retry_in(&self) -> Option<Duration> {
+        self.retry_at
+            .filter(|_| self.child.is_none())
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Collect an ssh that exited; returns whether ssh is still running
+    fn reap(&mut self) -> bool {
+        let Some(ref mut child) = self.child else {
+            return false;
+        };
+        let status = match child.try_wait() {
+            Ok(None) => return true,
+            Ok(Some(status)) => status.to_string(),
+            Err(e) => e.to_string(),
+        };
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = pipe.read_to_string(&mut stderr);
+        }
+        self.child = None;
+        self.up_since = None;
+        self.remove_pid_file();
+        match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+            Some(line) => self.fail(format!("ssh {status}: {}", line.trim())),
+            None => self.fail(format!("ssh {status}")),
+        }
+        false
+    }
+
+    fn spawn(&mut self) -> crate::Result<()> {
+        self.stop_stale();
+        let forward = format!("127.0.0.1:{}:{}", self.local_port, self.target);
+        let child = Command::new("ssh")
+            .args([
+                "-N",
+                "-o",
+                "BatchMode=yes",
+                "-o",
+                "ExitOnForwardFailure=yes",
+                "-o",
+                "ServerAliveInterval=15",
+                "-o",
+                "ServerAliveCountMax=3",
+                "-L",
+                &forward,
+                &self.destination,
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run ssh: {e}"))?;
+
+        if let Some(ref path) = self.pid_file {
+            let _ = crate::persistence::write_atomic(path, format!("{}\n", child.id()).as_bytes());
+        }
+        self.child = Some(child);
+        self.started_at = Some(Instant::now());
+        Ok(())
+    }
+
+    fn fail(&mut self, error: String) {
+        self.failures += 1;
+        let retry = retry_delay(self.failures);
+        logging::warn(
+            "ssh_tunnel",
+            "tunnel_down",
+            &format!(
+                "Tunnel via {} failed ({error}); retrying in {}s",
+                self.destination,
+                retry.as_secs()
+            ),
+        );
+        self.retry_at = Some(Instant::now() + retry);
+        self.last_error = Some(error);
+    }
+
+    /// Stop an ssh a previous plugin run started and couldn't clean up, which
+    /// would otherwise hold the local port
+    fn stop_stale(&self) {
+        let Some(ref path) = self.pid_file else {
+            return;
+        };
+        let Some(pid) = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|pid| pid.trim().parse::<u32>().ok())
+        else {
+            return;
+        };
+        if is_ssh_process(pid) {
+            // SAFETY: kill() has no memory-safety preconditions
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    fn remove_pid_file(&self) {
+        if let Some(ref path) = self.pid_file {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        if let Some(ref mut child) = self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+            self.remove_pid_file();
+        }
+    }
+}
+
+/// 5s after the first failure, then 10s, 20s, ... up to five minutes
+fn retry_delay(failures: u32) -> Duration {
+    FIRST_RETRY
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(MAX_RETRY)
+}
+
+fn free_port() -> Option<u16> {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).ok()?;
+    listener.local_addr().ok().map(|addr| addr.port())
+}
+
+fn is_listening(port: u16) -> bool {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    TcpStream::connect_timeout(&addr, Duration::from_millis(200)).is_ok()
+}
+
+/// Whether `pid` is alive and is ssh (pids get reused)
+fn is_ssh_process(pid: u32) -> bool {
+    let sys_pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[sys_pid]),
+        sysinfo::ProcessRefreshKind::new(),
+    );
+    system
+        .process(sys_pid)
+        .is_some_and(|process| process.name() == "ssh")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), FIRST_RETRY);
+        assert_eq!(retry_delay(3), FIRST_RETRY * 4);
+        assert_eq!(retry_delay(20), MAX_RETRY);
+    }
+}
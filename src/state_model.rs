@@ -1,4 +1,5 @@
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
 /// Reason why the agent is not ready
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -12,6 +13,8 @@ pub enum NotReadyReason {
 pub enum AgentState {
     NotReady { reason: NotReadyReason },
     Stopped,
+    Degraded,      // Process running but API not answering
+    FailedToStart, // Started, but the API never answered within the startup timeout
 
     Running,
 }
@@ -21,10 +24,12 @@ impl AgentState {
         plist_installed: bool,
         binary_available: bool,
         service_running: bool,
+        process_running: bool,
     ) -> Self {
         match (plist_installed, binary_available, service_running) {
             (_, _, true) => AgentState::Running,
-            (true, true, false) => AgentState::Stopped, // Ready to start
+            (true, _, false) if process_running => AgentState::Degraded, // Hung or still starting
+            (true, true, false) => AgentState::Stopped,                  // Ready to start
             (false, false, _) => AgentState::NotReady {
                 reason: NotReadyReason::BinaryNotFound,
             },
@@ -36,6 +41,45 @@ impl AgentState {
             }, // Fix: plist exists but binary missing
         }
     }
+
+    /// Turn a start that never came up into FailedToStart
+    ///
+    /// `since_start` is the time since a start was requested, or None when no start
+    /// is pending. Only states that a successful start would have left count as failure.
+    pub fn with_startup_timeout(self, since_start: Option<Duration>, timeout: Duration) -> Self {
+        match (self, since_start) {
+            (AgentState::Stopped | AgentState::Degraded, Some(elapsed)) if elapsed >= timeout => {
+                AgentState::FailedToStart
+            }
+            (state, _) => state,
+        }
+    }
+
+    /// Stable identifier, e.g. for hook scripts
+    pub fn name(&self) -> &'static str {
+        match self {
+            AgentState::NotReady { .. } => "NotReady",
+            AgentState::Stopped => "Stopped",
+            AgentState::Degraded => "Degraded",
+            AgentState::FailedToStart => "FailedToStart",
+            AgentState::Running => "Running",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AgentState::NotReady {
+                reason: NotReadyReason::BinaryNotFound,
+            } => "Not ready (binary not found)",
+            AgentState::NotReady {
+                reason: NotReadyReason::PlistMissing,
+            } => "Not ready (plist missing)",
+            AgentState::Stopped => "Stopped",
+            AgentState::Degraded => "Degraded (API unresponsive)",
+            AgentState::FailedToStart => "Failed to start",
+            AgentState::Running => "Running",
+        }
+    }
 }
 
 /// Display state computed from agent and model states
@@ -44,20 +88,85 @@ pub enum DisplayState {
     AgentNotLoaded,
     AgentStarting,
     ServiceStopped,       // Service stopped but ready to start
+    ServiceDegraded,      // Process running but API unresponsive
+    StartupFailed,        // Start requested but the API never came up
+    CrashLoop,            // Crashed repeatedly; the watchdog stopped restarting it
     ServiceLoadedNoModel, // Service running but no models
     ModelLoading,
+    ModelStopping,
     ModelProcessingQueue,
     ModelReady,
 }
 
+/// Everything besides the agent state that decides what the icon shows
+///
+/// Kept separate from PluginState so the mapping can be exercised without a
+/// live service (see tests/state_machine_tests.rs).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayInputs {
+    pub start_pending: bool, // A start was requested and hasn't come up yet
+    pub error_count: usize,  // Consecutive failed API polls
+    pub has_models: bool,
+    pub has_loading_models: bool,
+    pub has_queue_activity: bool,
+    pub has_stopping_models: bool,
+    pub crash_loop: bool, // The watchdog gave up on a service that keeps crashing
+}
+
 impl DisplayState {
+    /// Consecutive failed API polls before a running process is reported as degraded
+    pub const DEGRADED_AFTER_ERRORS: usize = 3;
+
+    /// Map the agent state and the latest poll to what the icon should show
+    pub fn compute(agent_state: AgentState, inputs: &DisplayInputs) -> Self {
+        match agent_state {
+            AgentState::NotReady { .. } => DisplayState::AgentNotLoaded,
+
+            // Flapping rather than merely stopped, until it is started by hand
+            AgentState::Stopped | AgentState::Degraded | AgentState::FailedToStart
+                if inputs.crash_loop =>
+            {
+                DisplayState::CrashLoop
+            }
+
+            // Until the startup timeout, a just-started service is expected to be down
+            AgentState::Stopped | AgentState::Degraded if inputs.start_pending => {
+                DisplayState::AgentStarting
+            }
+            AgentState::FailedToStart => DisplayState::StartupFailed,
+            AgentState::Stopped => DisplayState::ServiceStopped, // Fix: Ready to start
+            // A freshly started process needs a few polls before its API answers
+            AgentState::Degraded if inputs.error_count < Self::DEGRADED_AFTER_ERRORS => {
+                DisplayState::AgentStarting
+            }
+            AgentState::Degraded => DisplayState::ServiceDegraded,
+            AgentState::Running => {
+                if !inputs.has_models {
+                    DisplayState::ServiceLoadedNoModel
+                } else if inputs.has_loading_models {
+                    DisplayState::ModelLoading
+                } else if inputs.has_queue_activity {
+                    DisplayState::ModelProcessingQueue
+                } else if inputs.has_stopping_models {
+                    DisplayState::ModelStopping
+                } else {
+                    DisplayState::ModelReady
+                }
+            }
+        }
+    }
+
     pub fn status_message(&self) -> &'static str {
         match self {
             DisplayState::AgentNotLoaded => "Missing requirements",
             DisplayState::AgentStarting => "Starting agent...",
             DisplayState::ServiceStopped => "Service stopped",
+            DisplayState::ServiceDegraded => "Service not responding",
+            DisplayState::StartupFailed => "Failed to start",
+            DisplayState::CrashLoop => "Crashing repeatedly",
             DisplayState::ServiceLoadedNoModel => "No models loaded",
             DisplayState::ModelLoading => "Loading model...",
+            DisplayState::ModelStopping => "Unloading model...",
             DisplayState::ModelProcessingQueue => "Processing queue...",
             DisplayState::ModelReady => "Model ready",
         }
@@ -67,46 +176,156 @@ impl DisplayState {
         match self {
             DisplayState::AgentNotLoaded => "red", // Problems - missing requirements
             DisplayState::ServiceStopped => "red", // Problems - service needs to be started
+            DisplayState::ServiceDegraded => "purple", // Problems - process hung, needs a restart
+            DisplayState::StartupFailed => "red",  // Problems - service crashed or hung on startup
+            DisplayState::CrashLoop => "red",      // Problems - service keeps crashing
             DisplayState::ServiceLoadedNoModel => "grey", // Idle - service running but no models
             DisplayState::AgentStarting => "yellow", // Transitional - starting up
             DisplayState::ModelLoading => "yellow", // Transitional - loading model
+            DisplayState::ModelStopping => "yellow", // Transitional - unloading model
             DisplayState::ModelReady => "green",   // Ready - models loaded and idle
             DisplayState::ModelProcessingQueue => "blue", // Active - processing requests
         }
     }
 }
 
+/// Hysteresis guard for display state changes
+///
+/// Short bursts of queue activity would otherwise flip the icon between
+/// ModelReady and ModelProcessingQueue on every poll. Between those two states
+/// a switch needs the new state on `min_polls` consecutive polls *and* the shown
+/// state to have been visible for `min_dwell`. Every other transition (errors,
+/// loading, stopping) is shown immediately.
+#[derive(Debug)]
+pub struct DisplayDebouncer {
+    min_polls: usize,
+    min_dwell: Duration,
+    shown: Option<(DisplayState, Instant)>,
+    candidate: Option<(DisplayState, usize)>,
+}
+
+impl DisplayDebouncer {
+    pub fn new(min_polls: usize, min_dwell: Duration) -> Self {
+        Self {
+            min_polls,
+            min_dwell,
+            shown: None,
+            candidate: None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            *crate::constants::DISPLAY_DEBOUNCE_POLLS,
+            Duration::from_secs(*crate::constants::DISPLAY_MIN_DWELL_SECS),
+        )
+    }
+
+    /// State currently shown, if any poll has been applied yet
+    pub fn shown(&self) -> Option<DisplayState> {
+        self.shown.map(|(state, _)| state)
+    }
+
+    /// Feed one poll's computed state and return the state to show
+    pub fn apply(&mut self, computed: DisplayState, now: Instant) -> DisplayState {
+        let Some((shown, since)) = self.shown else {
+            return self.show(computed, now);
+        };
+
+        if computed == shown {
+            self.candidate = None;
+            return shown;
+        }
+        if !Self::is_debounced(shown, computed) {
+            return self.show(computed, now);
+        }
+
+        let polls = match self.candidate {
+            Some((state, polls)) if state == computed => polls + 1,
+            _ => 1,
+        };
+        self.candidate = Some((computed, polls));
+
+        if polls >= self.min_polls && now.duration_since(since) >= self.min_dwell {
+            self.show(computed, now)
+        } else {
+            shown
+        }
+    }
+
+    /// Whether a transition between these states is subject to hysteresis
+    pub fn is_debounced(from: DisplayState, to: DisplayState) -> bool {
+        let busy_states = [DisplayState::ModelReady, DisplayState::ModelProcessingQueue];
+        busy_states.contains(&from) && busy_states.contains(&to)
+    }
+
+    fn show(&mut self, state: DisplayState, now: Instant) -> DisplayState {
+        self.shown = Some((state, now));
+        self.candidate = None;
+        state
+    }
+}
+
 /// Simplified polling mode
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PollingMode {
-    Idle,   // 3s - no activity
-    Active, // 1s - active processing
+    Idle,                        // 3s - no activity
+    Active,                      // 1s - active processing
+    Backoff { failures: usize }, // 6s doubling up to the cap - API down
 }
 
 impl PollingMode {
+    /// Consecutive API failures before polling backs off
+    pub const BACKOFF_AFTER_ERRORS: usize = 5;
+
     pub fn interval(&self) -> Duration {
         match self {
             PollingMode::Idle => Duration::from_secs(3),
             PollingMode::Active => Duration::from_secs(1),
+            PollingMode::Backoff { failures } => {
+                let max = Duration::from_secs(*crate::constants::MAX_BACKOFF_SECS);
+                Self::backoff_interval(*failures, max)
+            }
         }
     }
 
+    /// Idle interval doubled for each failure past the threshold, capped at `max`
+    fn backoff_interval(failures: usize, max: Duration) -> Duration {
+        let doublings = (failures + 1)
+            .saturating_sub(Self::BACKOFF_AFTER_ERRORS)
+            .min(16) as u32;
+        PollingMode::Idle
+            .interval()
+            .saturating_mul(1 << doublings)
+            .min(max)
+            .max(PollingMode::Idle.interval())
+    }
+
     pub fn description(&self) -> &'static str {
         match self {
             PollingMode::Idle => "Idle",
             PollingMode::Active => "Active",
+            PollingMode::Backoff { .. } => "Backoff",
         }
     }
 
-    /// Determine polling mode based on state changes and activity
+    /// Determine polling mode based on state changes, activity and API failures
     pub fn compute(
         _current: PollingMode,
         state_changed: bool,
         has_activity: bool,
         last_change_elapsed: Duration,
+        consecutive_failures: usize,
     ) -> PollingMode {
         const STATE_CHANGE_DURATION: Duration = Duration::from_secs(5);
 
+        if consecutive_failures >= Self::BACKOFF_AFTER_ERRORS {
+            // Nothing to watch closely while the API is down; reset by the first success
+            return PollingMode::Backoff {
+                failures: consecutive_failures,
+            };
+        }
+
         match (
             state_changed,
             has_activity,
@@ -126,6 +345,7 @@ pub enum ModelState {
     Unknown,
     Loading,
     Running,
+    Stopping, // Being unloaded; gone from llama-swap once fully stopped
 }
 
 impl ModelState {
@@ -133,3 +353,255 @@ impl ModelState {
         matches!(self, ModelState::Loading)
     }
 }
+
+/// Per-model lifecycle tracking, one instance per model name
+///
+/// Unlike a bare ModelState this outlives failed polls: a transient API error
+/// leaves the last known state, load timing and transition history in place.
+#[derive(Debug, Clone)]
+pub struct ModelStateMachine {
+    state: ModelState,
+    loading_since: Option<Instant>,
+    last_load_duration: Option<Duration>,
+    transitions: VecDeque<Instant>, // Recent state changes, for flap detection
+}
+
+impl ModelStateMachine {
+    /// State changes within this window count towards flapping
+    const FLAP_WINDOW: Duration = Duration::from_secs(300);
+    /// Number of changes within the window that makes a model "flapping"
+    const FLAP_THRESHOLD: usize = 4;
+
+    pub fn new(state: ModelState, now: Instant) -> Self {
+        Self {
+            state,
+            loading_since: state.is_loading().then_some(now),
+            last_load_duration: None,
+            transitions: VecDeque::new(),
+        }
+    }
+
+    pub fn state(&self) -> ModelState {
+        self.state
+    }
+
+    /// Feed the state reported by the latest successful poll; true if it changed
+    pub fn observe(&mut self, state: ModelState, now: Instant) -> bool {
+        if state == self.state {
+            return false;
+        }
+
+        match (self.state, state) {
+            (_, ModelState::Loading) => self.loading_since = Some(now),
+            (ModelState::Loading, ModelState::Running) => {
+                self.last_load_duration = self.loading_since.map(|since| now - since);
+                self.loading_since = None;
+            }
+            _ => self.loading_since = None,
+        }
+
+        self.state = state;
+        self.transitions.push_back(now);
+        while self
+            .transitions
+            .front()
+            .is_some_and(|&at| now.duration_since(at) > Self::FLAP_WINDOW)
+        {
+            self.transitions.pop_front();
+        }
+        true
+    }
+
+    /// How long the model has been loading, if it is
+    pub fn loading_for(&self, now: Instant) -> Option<Duration> {
+        self.loading_since.map(|since| now.duration_since(since))
+    }
+
+    /// Duration of the most recent completed load
+    pub fn last_load_duration(&self) -> Option<Duration> {
+        self.last_load_duration
+    }
+
+    /// Whether the model keeps changing state (e.g. crashing and reloading)
+    pub fn is_flapping(&self, now: Instant) -> bool {
+        self.transitions
+            .iter()
+            .filter(|&&at| now.duration_since(at) <= Self::FLAP_WINDOW)
+            .count()
+            >= Self::FLAP_THRESHOLD
+    }
+}
+
+/// Recent load durations per model, kept after the model unloads for comparison
+#[derive(Debug, Default)]
+pub struct LoadTimes {
+    per_model: HashMap<String, VecDeque<Duration>>,
+}
+
+impl LoadTimes {
+    /// Loads remembered per model
+    const KEPT: usize = 10;
+
+    pub fn record(&mut self, model: &str, duration: Duration) {
+        let durations = self.per_model.entry(model.to_string()).or_default();
+        durations.push_back(duration);
+        if durations.len() > Self::KEPT {
+            durations.pop_front();
+        }
+    }
+
+    /// Oldest first; empty if the model hasn't finished loading yet
+    pub fn recent(&self, model: &str) -> Vec<Duration> {
+        self.per_model
+            .get(model)
+            .map(|durations| durations.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_timeout() {
+        let timeout = Duration::from_secs(30);
+        let early = Some(Duration::from_secs(5));
+        let late = Some(Duration::from_secs(31));
+
+        assert_eq!(
+            AgentState::Degraded.with_startup_timeout(early, timeout),
+            AgentState::Degraded
+        );
+        assert_eq!(
+            AgentState::Degraded.with_startup_timeout(late, timeout),
+            AgentState::FailedToStart
+        );
+        assert_eq!(
+            AgentState::Stopped.with_startup_timeout(late, timeout),
+            AgentState::FailedToStart
+        );
+        assert_eq!(
+            AgentState::Running.with_startup_timeout(late, timeout),
+            AgentState::Running
+        );
+        // No start pending: a stopped service is just stopped
+        assert_eq!(
+            AgentState::Stopped.with_startup_timeout(None, timeout),
+            AgentState::Stopped
+        );
+    }
+
+    #[test]
+    fn test_model_state_machine_tracks_loads_and_flapping() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let mut machine = ModelStateMachine::new(ModelState::Loading, at(0));
+        assert_eq!(machine.loading_for(at(4)), Some(Duration::from_secs(4)));
+        assert!(!machine.observe(ModelState::Loading, at(5)));
+        assert!(machine.observe(ModelState::Running, at(12)));
+        assert_eq!(machine.last_load_duration(), Some(Duration::from_secs(12)));
+        assert_eq!(machine.loading_for(at(12)), None);
+        assert!(!machine.is_flapping(at(12)));
+
+        // Crash/reload cycles
+        machine.observe(ModelState::Loading, at(20));
+        machine.observe(ModelState::Running, at(30));
+        machine.observe(ModelState::Loading, at(40));
+        assert!(machine.is_flapping(at(40)));
+        assert_eq!(machine.last_load_duration(), Some(Duration::from_secs(10)));
+
+        // ...and calm again once the changes age out of the window
+        assert!(!machine.is_flapping(at(400)));
+    }
+
+    #[test]
+    fn test_backoff_after_repeated_failures() {
+        let quiet = Duration::from_secs(60);
+        let compute =
+            |failures| PollingMode::compute(PollingMode::Idle, false, false, quiet, failures);
+
+        assert_eq!(compute(4), PollingMode::Idle);
+        assert_eq!(compute(5), PollingMode::Backoff { failures: 5 });
+        assert_eq!(compute(0), PollingMode::Idle);
+
+        let max = Duration::from_secs(60);
+        let interval = |failures| PollingMode::backoff_interval(failures, max).as_secs();
+        assert_eq!(interval(5), 6);
+        assert_eq!(interval(6), 12);
+        assert_eq!(interval(8), 48);
+        assert_eq!(interval(9), 60);
+        assert_eq!(interval(10_000), 60);
+    }
+
+    #[test]
+    fn test_debouncer_ignores_short_bursts() {
+        let mut debouncer = DisplayDebouncer::new(2, Duration::from_secs(3));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert_eq!(
+            debouncer.apply(DisplayState::ModelReady, at(0)),
+            DisplayState::ModelReady
+        );
+
+        // One busy poll is a blip
+        assert_eq!(
+            debouncer.apply(DisplayState::ModelProcessingQueue, at(3)),
+            DisplayState::ModelReady
+        );
+        assert_eq!(
+            debouncer.apply(DisplayState::ModelReady, at(4)),
+            DisplayState::ModelReady
+        );
+
+        // Sustained activity switches on the second consecutive poll
+        debouncer.apply(DisplayState::ModelProcessingQueue, at(5));
+        assert_eq!(
+            debouncer.apply(DisplayState::ModelProcessingQueue, at(6)),
+            DisplayState::ModelProcessingQueue
+        );
+
+        // ...and stays until it has been shown for the minimum dwell time
+        debouncer.apply(DisplayState::ModelReady, at(7));
+        assert_eq!(
+            debouncer.apply(DisplayState::ModelReady, at(8)),
+            DisplayState::ModelProcessingQueue
+        );
+        assert_eq!(
+            debouncer.apply(DisplayState::ModelReady, at(9)),
+            DisplayState::ModelReady
+        );
+    }
+
+    #[test]
+    fn test_debouncer_passes_other_transitions_through() {
+        let mut debouncer = DisplayDebouncer::new(5, Duration::from_secs(60));
+        let now = Instant::now();
+
+        debouncer.apply(DisplayState::ModelProcessingQueue, now);
+        assert_eq!(
+            debouncer.apply(DisplayState::ServiceDegraded, now),
+            DisplayState::ServiceDegraded
+        );
+        assert_eq!(
+            debouncer.apply(DisplayState::ModelReady, now),
+            DisplayState::ModelReady
+        );
+    }
+
+    #[test]
+    fn test_load_times_keep_recent() {
+        let mut load_times = LoadTimes::default();
+        assert!(load_times.recent("qwen").is_empty());
+
+        for secs in 1..=12 {
+            load_times.record("qwen", Duration::from_secs(secs));
+        }
+        let recent = load_times.recent("qwen");
+        assert_eq!(recent.len(), LoadTimes::KEPT);
+        assert_eq!(recent.first(), Some(&Duration::from_secs(3)));
+        assert_eq!(recent.last(), Some(&Duration::from_secs(12)));
+    }
+}
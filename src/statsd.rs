@@ -0,0 +1,146 @@
+use crate::logging;
+use crate::models::AllMetrics;
+use std::net::UdpSocket;
+
+/// Keep datagrams under a typical MTU so none get fragmented or dropped
+const MAX_PACKET_BYTES: usize = 1400;
+
+/// Pushes per-model gauges to a StatsD (or DogStatsD) agent each poll
+#[derive(Debug)]
+pub struct StatsdForwarder {
+    socket: UdpSocket,
+    dogstatsd: bool,
+}
+
+impl StatsdForwarder {
+    /// Connect to the configured agent; None when forwarding is disabled
+    pub fn from_env() -> Option<Self> {
+        let addr = crate::constants::STATSD_ADDR.as_deref()?;
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .and_then(|socket| socket.connect(addr).map(|()| socket))
+            .inspect_err(|e| {
+                logging::warn(
+                    "statsd",
+                    "connect_failed",
+                    &format!("StatsD forwarding disabled, can't reach {addr}: {e}"),
+                );
+            })
+            .ok()?;
+
+        Some(Self {
+            socket,
+            dogstatsd: *crate::constants::STATSD_DOGSTATSD,
+        })
+    }
+
+    /// Send the current per-model gauges; UDP, so an absent agent costs nothing
+    pub fn send(&self, all_metrics: &AllMetrics) {
+        for packet in packets(&format_gauges(all_metrics, self.dogstatsd)) {
+            if let Err(e) = self.socket.send(packet.as_bytes()) {
+                logging::debug("statsd", "send_failed", &format!("StatsD send failed: {e}"));
+                return;
+            }
+        }
+    }
+}
+
+/// One gauge line per model and metric
+///
+/// DogStatsD gets the model as a tag; plain StatsD has no tags, so the model
+/// becomes part of the metric name instead.
+pub fn format_gauges(all_metrics: &AllMetrics, dogstatsd: bool) -> Vec<String> {
+    type Getter = fn(&crate::models::Metrics) -> f64;
+    const GAUGES: [(&str, Getter); 5] = [
+        ("generation_tokens_per_second", |m| {
+            m.predicted_tokens_per_sec
+        }),
+        ("prompt_tokens_per_second", |m| m.prompt_tokens_per_sec),
+        ("requests_processing", |m| f64::from(m.requests_processing)),
+        ("requests_deferred", |m| f64::from(m.requests_deferred)),
+        ("memory_megabytes", |m| m.memory_mb),
+    ];
+
+    all_metrics
+        .models
+        .iter()
+        .flat_map(|model| {
+            GAUGES.iter().map(move |(name, get)| {
+                let value = get(&model.metrics);
+                if dogstatsd {
+                    let tag = model.model_name.replace([',', '|', '#'], "_");
+                    format!("llama_swap.model.{name}:{value}|g|#model:{tag}")
+                } else {
+                    let model_name = model.model_name.replace(
+                        |c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+                        "_",
+                    );
+                    format!("llama_swap.model.{model_name}.{name}:{value}|g")
+                }
+            })
+        })
+        .collect()
+}
+
+/// Join lines into newline-separated datagrams of at most MAX_PACKET_BYTES
+fn packets(lines: &[String]) -> Vec<String> {
+    let mut packets: Vec<String> = Vec::new();
+    for line in lines {
+        match packets.last_mut() {
+            Some(packet) if packet.len() + 1 + line.len() <= MAX_PACKET_BYTES => {
+                packet.push('\n');
+                packet.push_str(line);
+            }
+            _ => packets.push(line.clone()),
+        }
+    }
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Metrics, ModelMetrics, ModelState};
+
+    fn all_metrics() -> AllMetrics {
+        AllMetrics {
+            models: vec![ModelMetrics {
+                model_name: "qwen2.5:7b".to_string(),
+                model_state: ModelState::Running,
+                metrics: Metrics {
+                    predicted_tokens_per_sec: 42.5,
+                    requests_deferred: 3,
+                    memory_mb: 5120.0,
+                    ..Metrics::default()
+                },
+            }],
+            proxy: None,
+        }
+    }
+
+    #[test]
+    fn test_format_gauges() {
+        let dogstatsd = format_gauges(&all_metrics(), true);
+        assert_eq!(dogstatsd.len(), 5);
+        assert_eq!(
+            dogstatsd[0],
+            "llama_swap.model.generation_tokens_per_second:42.5|g|#model:qwen2.5:7b"
+        );
+        assert!(dogstatsd
+            .contains(&"llama_swap.model.requests_deferred:3|g|#model:qwen2.5:7b".to_string()));
+
+        let statsd = format_gauges(&all_metrics(), false);
+        assert!(statsd.contains(&"llama_swap.model.qwen2_5_7b.memory_megabytes:5120|g".to_string()));
+    }
+
+    #[test]
+    fn test_packets_stay_under_limit() {
+        let lines: Vec<String> = (0..100)
+            .map(|i| format!("llama_swap.test_{i}:1|g"))
+            .collect();
+        let packets = packets(&lines);
+
+        assert!(packets.len() > 1);
+        assert!(packets.iter().all(|p| p.len() <= MAX_PACKET_BYTES));
+        assert_eq!(packets.join("\n"), lines.join("\n"));
+    }
+}
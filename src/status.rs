@@ -0,0 +1,147 @@
+use crate::models::{MetricStats, MetricsHistory};
+use crate::types::PluginState;
+use serde::Serialize;
+
+/// Everything the menu shows, as machine-readable data (`status_json` / `--json`)
+///
+/// Field names are part of the output format other scripts rely on: add fields
+/// rather than renaming them.
+#[derive(Debug, Serialize)]
+pub struct StatusSnapshot {
+    pub display_state: String, // e.g. "ModelProcessingQueue"
+    pub status: &'static str,  // The menu's status line, e.g. "Processing requests"
+    pub agent_state: &'static str,
+    pub service: ServiceSnapshot,
+    pub system: SystemSnapshot,
+    pub models: Vec<ModelSnapshot>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceSnapshot {
+    pub plist_installed: bool,
+    pub launchctl_loaded: bool,
+    pub process_running: bool,
+    pub api_responsive: bool,
+    pub pid: Option<u32>,
+    pub started_at: Option<u64>, // Unix time
+}
+
+#[derive(Debug, Serialize)]
+pub struct SystemSnapshot {
+    pub cpu_percent: MetricStats,
+    pub memory_percent: MetricStats,
+    pub gpu_percent: MetricStats,
+    pub llama_memory_mb: MetricStats,
+    pub power_watts: MetricStats,
+    pub memory_pressure: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelSnapshot {
+    pub name: String,
+    pub state: String,
+    pub generation_tokens_per_second: f64,
+    pub prompt_tokens_per_second: f64,
+    pub requests_processing: u32,
+    pub requests_deferred: u32,
+    pub memory_mb: f64,
+    pub kv_cache_usage_ratio: f64,
+    pub latency_p95_ms: Option<f64>,
+    pub upstream_error_rate_percent: Option<f64>,
+    pub generation_stats: MetricStats, // Over the history window
+    pub prompt_stats: MetricStats,
+}
+
+impl StatusSnapshot {
+    pub fn from_state(state: &PluginState) -> Self {
+        let display_state = state.get_display_state();
+        let history = &state.metrics_history;
+        let status = &state.service_status;
+
+        let models = state
+            .current_all_metrics
+            .iter()
+            .flat_map(|all| &all.models)
+            .map(|model| {
+                let model_history = history.models.get(&model.model_name);
+                let stats = |get: fn(&MetricsHistory) -> &_| {
+                    model_history.map_or_else(MetricStats::default, |h| h.get_stats(get(h)))
+                };
+                ModelSnapshot {
+                    name: model.model_name.clone(),
+                    state: format!("{:?}", model.model_state),
+                    generation_tokens_per_second: model.metrics.predicted_tokens_per_sec,
+                    prompt_tokens_per_second: model.metrics.prompt_tokens_per_sec,
+                    requests_processing: model.metrics.requests_processing,
+                    requests_deferred: model.metrics.requests_deferred,
+                    memory_mb: model.metrics.memory_mb,
+                    kv_cache_usage_ratio: model.metrics.kv_cache_usage_ratio,
+                    latency_p95_ms: model_history
+                        .and_then(|h| h.latency)
+                        .map(|latency| latency.p95_ms),
+                    upstream_error_rate_percent: model_history
+                        .and_then(MetricsHistory::upstream_error_rate),
+                    generation_stats: stats(|h| &h.tps),
+                    prompt_stats: stats(|h| &h.prompt_tps),
+                }
+            })
+            .collect();
+
+        Self {
+            display_state: format!("{display_state:?}"),
+            status: display_state.status_message(),
+            agent_state: state.agent_state.name(),
+            service: ServiceSnapshot {
+                plist_installed: status.plist_installed,
+                launchctl_loaded: status.launchctl_loaded,
+                process_running: status.process_running,
+                api_responsive: status.api_responsive,
+                pid: status.pid,
+                started_at: state.service_started_at,
+            },
+            system: SystemSnapshot {
+                cpu_percent: history.get_cpu_stats(),
+                memory_percent: history.get_system_memory_stats(),
+                gpu_percent: history.get_gpu_stats(),
+                llama_memory_mb: history.get_memory_stats(),
+                power_watts: history.get_power_stats(),
+                memory_pressure: history.current_memory_pressure().map(|p| p.label()),
+            },
+            models,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AllMetrics, Metrics, ModelMetrics, ModelState};
+    use crate::state_model::AgentState;
+
+    #[test]
+    fn test_snapshot_serializes_models() {
+        let mut state = PluginState::new().unwrap();
+        state.agent_state = AgentState::Running;
+        state.current_all_metrics = Some(AllMetrics {
+            models: vec![ModelMetrics {
+                model_name: "qwen".to_string(),
+                model_state: ModelState::Running,
+                metrics: Metrics {
+                    predicted_tokens_per_sec: 42.5,
+                    requests_deferred: 1,
+                    ..Metrics::default()
+                },
+            }],
+            proxy: None,
+        });
+
+        let json = serde_json::to_value(StatusSnapshot::from_state(&state)).unwrap();
+
+        assert_eq!(json["agent_state"], "Running");
+        assert_eq!(json["models"][0]["name"], "qwen");
+        assert_eq!(json["models"][0]["state"], "Running");
+        assert_eq!(json["models"][0]["generation_tokens_per_second"], 42.5);
+        assert_eq!(json["models"][0]["requests_deferred"], 1);
+        assert!(json["system"]["cpu_percent"]["mean"].is_number());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::models::Metrics;
+use crate::types::error_helpers::{with_context, CREATE_DIR};
+use rusqlite::{params, Connection};
+use std::time::{Duration, Instant};
+
+/// How often expired rows are pruned and the database is vacuumed
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Summary windows shown in the menu (label, length in seconds)
+pub const SUMMARY_WINDOWS: &[(&str, u64)] = &[("1h", 3600), ("24h", 86_400), ("7d", 604_800)];
+
+/// Aggregated statistics for one model over a time window
+#[derive(Debug, Default)]
+pub struct WindowSummary {
+    pub samples: u64,
+    pub avg_tps: f64,
+    pub peak_tps: f64,
+    pub avg_queue: f64,
+    pub peak_memory_mb: f64,
+}
+
+/// SQLite-backed long-term metric history (behind the `sqlite` feature)
+///
+/// The in-memory `AllMetricsHistory` only covers the last few minutes; when
+/// `LLAMA_SWAP_HISTORY_DB` is enabled each successful poll is also recorded
+/// here so the menu can summarise hours and days.
+pub struct MetricsStore {
+    conn: Connection,
+    last_maintenance: Option<Instant>,
+}
+
+impl MetricsStore {
+    /// Open (or create) the database at the configured path
+    pub fn open_default() -> crate::Result<Self> {
+        let path = crate::commands::expand_tilde(&crate::constants::HISTORY_DB_PATH)?;
+        Self::open(&path)
+    }
+
+    pub fn open(path: &str) -> crate::Result<Self> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            with_context(std::fs::create_dir_all(parent), CREATE_DIR)?;
+        }
+
+        let conn = with_context(Connection::open(path), "Failed to open history database")?;
+        with_context(
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS samples (
+                    ts INTEGER NOT NULL,
+                    model TEXT NOT NULL,
+                    tps REAL NOT NULL,
+                    prompt_tps REAL NOT NULL,
+                    queue REAL NOT NULL,
+                    memory_mb REAL NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS samples_model_ts ON samples (model, ts);",
+            ),
+            "Failed to initialise history database",
+        )?;
+
+        Ok(Self {
+            conn,
+            last_maintenance: None, // Run maintenance on the first poll after startup
+        })
+    }
+
+    /// Record one poll's worth of metrics for a model
+    pub fn record(&self, timestamp: u64, model_name: &str, metrics: &Metrics) -> crate::Result<()> {
+        with_context(
+            self.conn.execute(
+                "INSERT INTO samples (ts, model, tps, prompt_tps, queue, memory_mb)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    timestamp as i64,
+                    model_name,
+                    metrics.predicted_tokens_per_sec,
+                    metrics.prompt_tokens_per_sec,
+                    f64::from(metrics.requests_processing + metrics.requests_deferred),
+                    metrics.memory_mb,
+                ],
+            ),
+            "Failed to record metrics sample",
+        )?;
+        Ok(())
+    }
+
+    /// Summarise a model's samples newer than `since`
+    pub fn window_summary(&self, model_name: &str, since: u64) -> crate::Result<WindowSummary> {
+        with_context(
+            self.conn.query_row(
+                "SELECT COUNT(*), COALESCE(AVG(tps), 0), COALESCE(MAX(tps), 0),
+                        COALESCE(AVG(queue), 0), COALESCE(MAX(memory_mb), 0)
+                 FROM samples WHERE model = ?1 AND ts >= ?2",
+                params![model_name, since as i64],
+                |row| {
+                    Ok(WindowSummary {
+                        samples: row.get::<_, i64>(0)? as u64,
+                        avg_tps: row.get(1)?,
+                        peak_tps: row.get(2)?,
+                        avg_queue: row.get(3)?,
+                        peak_memory_mb: row.get(4)?,
+                    })
+                },
+            ),
+            "Failed to query history database",
+        )
+    }
+
+    /// Delete every recorded sample
+    pub fn clear(&self) -> crate::Result<()> {
+        with_context(
+            self.conn.execute_batch("DELETE FROM samples; VACUUM;"),
+            "Failed to clear history database",
+        )
+    }
+
+    /// Drop samples older than the retention window and reclaim space (at most hourly)
+    pub fn run_maintenance(&mut self, now: u64) -> crate::Result<()> {
+        if self
+            .last_maintenance
+            .is_some_and(|last| last.elapsed() < MAINTENANCE_INTERVAL)
+        {
+            return Ok(());
+        }
+        self.last_maintenance = Some(Instant::now());
+
+        let retention_secs = *crate::constants::HISTORY_DB_RETENTION_DAYS * 86_400;
+        let cutoff = now.saturating_sub(retention_secs);
+        let deleted = with_context(
+            self.conn
+                .execute("DELETE FROM samples WHERE ts < ?1", params![cutoff as i64]),
+            "Failed to prune history database",
+        )?;
+
+        if deleted > 0 {
+            with_context(
+                self.conn.execute_batch("VACUUM"),
+                "Failed to vacuum history database",
+            )?;
+            crate::logging::info(
+                "storage",
+                "history_pruned",
+                &format!("History database: pruned {deleted} samples older than {cutoff}"),
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use crate::menu::{format_memory_mb, format_percent, format_tps};
+use crate::models::TimestampedValue;
+use crate::types::PluginState;
+use circular_queue::CircularQueue;
+use std::fmt::Write;
+use std::io::Write as _;
+
+/// Block characters from lowest to highest
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Most recent samples shown in each sparkline
+const SPARK_WIDTH: usize = 30;
+
+/// Print the status once, or redraw it every poll until interrupted
+///
+/// What a terminal gets instead of SwiftBar markup; the `tui` feature has a
+/// full-screen dashboard with key bindings.
+pub fn run(watch: bool) -> crate::Result<()> {
+    let mut state = PluginState::new()?;
+    if !watch {
+        state.prime_cpu_usage();
+        state.update_state();
+        print!("{}", render(&state));
+        return Ok(());
+    }
+
+    crate::logging::disable_stderr();
+    loop {
+        state.update_state();
+        // Clear the screen and move to the top left, then draw the new frame
+        print!("\x1b[2J\x1b[H{}", render(&state));
+        std::io::stdout().flush()?;
+        std::thread::sleep(state.polling_mode.interval());
+        state.checkpoint_metrics();
+    }
+}
+
+/// Human-readable status: state, system stats and per-model metrics with sparklines
+pub fn render(state: &PluginState) -> String {
+    let mut out = String::new();
+    let display_state = state.get_display_state();
+    let history = &state.metrics_history;
+
+    let _ = writeln!(out, "Llama-Swap: {}", display_state.status_message());
+    let _ = writeln!(
+        out,
+        "Service:    {}",
+        state.service_status.status_description()
+    );
+    if let Some(pid) = state.service_status.pid {
+        let _ = writeln!(out, "PID:        {pid}");
+    }
+    let _ = writeln!(out);
+
+    let system = [
+        (
+            "CPU",
+            &history.cpu_usage_percent,
+            format_percent as fn(f64) -> String,
+        ),
+        ("Memory", &history.memory_usage_percent, format_percent),
+        ("GPU", &history.gpu_usage_percent, format_percent),
+        ("Llama", &history.total_llama_memory_mb, format_memory_mb),
+    ];
+    for (name, data, format_fn) in system {
+        if let Some(line) = metric_line(name, data, format_fn) {
+            let _ = writeln!(out, "{line}");
+        }
+    }
+
+    let mut models: Vec<_> = state
+        .current_all_metrics
+        .iter()
+        .flat_map(|all| &all.models)
+        .collect();
+    models.sort_by(|a, b| a.model_name.cmp(&b.model_name));
+
+    for model in models {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{} ({:?})", model.model_name, model.model_state);
+        if let Some(history) = history.get_model_history(&model.model_name) {
+            let rows = [
+                ("Generation", &history.tps),
+                ("Prompt", &history.prompt_tps),
+            ];
+            for (name, data) in rows {
+                if let Some(line) = metric_line(name, data, format_tps) {
+                    let _ = writeln!(out, "  {line}");
+                }
+            }
+        }
+        if model.metrics.memory_mb > 0.0 {
+            let _ = writeln!(
+                out,
+                "  {:<11}{}",
+                "Memory",
+                format_memory_mb(model.metrics.memory_mb)
+            );
+        }
+        let _ = writeln!(out, "  {:<11}{}", "Queue", model.metrics.queue_status());
+    }
+
+    out
+}
+
+/// One-line summary for tmux status bars and menu bar scripts
+///
+/// "2 models · 43 tok/s · queue 1" while models are loaded, otherwise the
+/// display state's status message, e.g. "Service stopped".
+pub fn status_line(state: &PluginState) -> String {
+    let models = state
+        .current_all_metrics
+        .as_ref()
+        .map(|all| all.models.as_slice())
+        .unwrap_or_default();
+    if models.is_empty() {
+        return state.get_display_state().status_message().to_string();
+    }
+
+    let tps: f64 = models
+        .iter()
+        .map(|model| model.metrics.predicted_tokens_per_sec)
+        .sum();
+    let queued: u32 = models
+        .iter()
+        .map(|model| model.metrics.requests_deferred)
+        .sum();
+    let noun = if models.len() == 1 { "model" } else { "models" };
+    format!("{} {noun} · {tps:.0} tok/s · queue {queued}", models.len())
+}
+
+/// `Name       value  ▁▂▃▅▇`, or None before the first sample
+fn metric_line(
+    name: &str,
+    data: &CircularQueue<TimestampedValue>,
+    format_fn: fn(f64) -> String,
+) -> Option<String> {
+    let latest = data.iter().next()?.value;
+    let mut values: Vec<f64> = data.iter().take(SPARK_WIDTH).map(|v| v.value).collect();
+    values.reverse(); // Oldest first, so the chart reads left to right
+    Some(format!(
+        "{name:<11}{:<12}{}",
+        format_fn(latest),
+        sparkline(&values)
+    ))
+}
+
+/// One block character per value, scaled from zero to the largest value
+pub fn sparkline(values: &[f64]) -> String {
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|&value| {
+            if max <= 0.0 {
+                return SPARK_LEVELS[0];
+            }
+            let level = (value.max(0.0) / max * (SPARK_LEVELS.len() - 1) as f64).round();
+            SPARK_LEVELS[level as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AllMetrics, Metrics, ModelMetrics, ModelState};
+
+    fn model(name: &str, tps: f64, deferred: u32) -> ModelMetrics {
+        ModelMetrics {
+            model_name: name.to_string(),
+            model_state: ModelState::Running,
+            metrics: Metrics {
+                predicted_tokens_per_sec: tps,
+                requests_deferred: deferred,
+                ..Metrics::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_status_line() {
+        let mut state = PluginState::new().unwrap();
+        state.current_all_metrics = Some(AllMetrics {
+            models: vec![model("qwen", 30.4, 1), model("llama", 12.8, 0)],
+            proxy: None,
+        });
+        assert_eq!(status_line(&state), "2 models · 43 tok/s · queue 1");
+
+        state.current_all_metrics = Some(AllMetrics {
+            models: vec![model("qwen", 0.0, 0)],
+            proxy: None,
+        });
+        assert_eq!(status_line(&state), "1 model · 0 tok/s · queue 0");
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 3.5, 7.0]), "▁▅█");
+        assert_eq!(sparkline(&[0.0, 0.0]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::menu::{format_memory_mb, format_percent, format_tps};
+use crate::models::TimestampedValue;
+use crate::types::PluginState;
+use circular_queue::CircularQueue;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+use std::time::Instant;
+
+/// Key bindings mapped onto the same commands the SwiftBar menu runs
+const KEY_COMMANDS: &[(char, &str, &str)] = &[
+    ('s', "do_start", "start"),
+    ('x', "do_stop", "stop"),
+    ('r', "do_restart", "restart"),
+    ('u', "do_unload", "unload"),
+];
+
+/// Terminal UI showing the same state and charts as the menu bar (`tui` subcommand)
+///
+/// Reuses PluginState for collection and the adaptive polling interval for refreshes,
+/// which makes it usable over SSH where SwiftBar isn't available.
+pub fn run() -> crate::Result<()> {
+    crate::logging::disable_stderr();
+    let mut state = PluginState::new()?;
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &mut state);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, state: &mut PluginState) -> crate::Result<()> {
+    let mut last_message = String::new();
+
+    loop {
+        state.update_state();
+        terminal.draw(|frame| draw(frame, state, &last_message))?;
+
+        // Wait for a key until the next poll is due
+        let deadline = Instant::now() + state.polling_mode.interval();
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            if !event::poll(timeout)? {
+                break;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                _ if ctrl_c => return Ok(()),
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    if *crate::constants::PERSIST_INTERVAL_SECS > 0 {
+                        state.persist_metrics();
+                    }
+                    return Ok(());
+                }
+                KeyCode::Char(c) => {
+                    if let Some((_, command, label)) = KEY_COMMANDS.iter().find(|(k, ..)| *k == c) {
+                        last_message = match crate::commands::handle_command(command) {
+                            Ok(()) => format!("{label}: done"),
+                            Err(e) => format!("{label}: {e}"),
+                        };
+                        break; // Refresh immediately to reflect the new state
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        state.checkpoint_metrics();
+    }
+}
+
+fn draw(frame: &mut Frame, state: &PluginState, last_message: &str) {
+    let models = state
+        .current_all_metrics
+        .as_ref()
+        .map(|m| {
+            let mut models = m.models.clone();
+            models.sort_by(|a, b| a.model_name.cmp(&b.model_name));
+            models
+        })
+        .unwrap_or_default();
+
+    let mut constraints = vec![Constraint::Length(3), Constraint::Length(5)];
+    constraints.extend(models.iter().map(|_| Constraint::Length(4)));
+    constraints.push(Constraint::Min(0));
+    constraints.push(Constraint::Length(1));
+    let areas = Layout::vertical(constraints).split(frame.area());
+
+    draw_status(frame, areas[0], state);
+    draw_system(frame, areas[1], state);
+
+    for (model, area) in models.iter().zip(&areas[2..]) {
+        let history = state.metrics_history.get_model_history(&model.model_name);
+        let title = format!(
+            " {} — {} | prompt {} | {} | queue: {} ",
+            model.model_name,
+            format_tps(model.metrics.predicted_tokens_per_sec),
+            format_tps(model.metrics.prompt_tokens_per_sec),
+            format_memory_mb(model.metrics.memory_mb),
+            model.metrics.queue_status()
+        );
+        let data = history.map(|h| sparkline_data(&h.tps)).unwrap_or_default();
+        frame.render_widget(
+            Sparkline::default()
+                .block(Block::bordered().title(title))
+                .data(data)
+                .style(Style::default().fg(Color::Green)),
+            *area,
+        );
+    }
+
+    let footer = Line::from(vec![
+        Span::raw(" q quit  s start  x stop  r restart  u unload "),
+        Span::raw(last_message).bold(),
+    ]);
+    frame.render_widget(Paragraph::new(footer), areas[areas.len() - 1]);
+}
+
+fn draw_status(frame: &mut Frame, area: Rect, state: &PluginState) {
+    let display_state = state.get_display_state();
+    let color = match display_state.icon_color() {
+        "red" => Color::Red,
+        "yellow" => Color::Yellow,
+        "green" => Color::Green,
+        "blue" => Color::Blue,
+        _ => Color::Gray,
+    };
+
+    let status = Line::from(vec![
+        Span::styled("● ", Style::default().fg(color)),
+        Span::raw(display_state.status_message()).bold(),
+        Span::raw(format!(
+            "  |  {}  |  polling: {}",
+            state.service_status.status_description(),
+            state.polling_mode.description()
+        )),
+    ]);
+    frame.render_widget(
+        Paragraph::new(status).block(Block::bordered().title(" Llama-Swap ")),
+        area,
+    );
+}
+
+fn draw_system(frame: &mut Frame, area: Rect, state: &PluginState) {
+    let history = &state.metrics_history;
+    let latest = |data: &CircularQueue<TimestampedValue>| data.iter().next().map(|v| v.value);
+
+    let columns = Layout::horizontal([Constraint::Ratio(1, 3); 3]).split(area);
+    let system = [
+        (
+            "CPU",
+            &history.cpu_usage_percent,
+            format_percent as fn(f64) -> String,
+        ),
+        ("Memory", &history.memory_usage_percent, format_percent),
+        (
+            "Llama Memory",
+            &history.total_llama_memory_mb,
+            format_memory_mb,
+        ),
+    ];
+
+    for ((name, data, format_fn), area) in system.into_iter().zip(columns.iter()) {
+        let title = match latest(data) {
+            Some(value) => format!(" {name}: {} ", format_fn(value)),
+            None => format!(" {name} "),
+        };
+        frame.render_widget(
+            Sparkline::default()
+                .block(Block::bordered().title(title))
+                .data(sparkline_data(data))
+                .style(Style::default().fg(Color::Cyan)),
+            *area,
+        );
+    }
+}
+
+/// Sparkline wants unsigned integers, oldest first; keep one decimal of precision
+fn sparkline_data(data: &CircularQueue<TimestampedValue>) -> Vec<u64> {
+    data.iter()
+        .rev()
+        .map(|v| (v.value.max(0.0) * 10.0) as u64)
+        .collect()
+}
@@ -1,9 +1,13 @@
+use crate::logging;
 use crate::models::{AllMetrics, AllMetricsHistory};
-use crate::state_model::{AgentState, DisplayState, ModelState, PollingMode};
+use crate::state_model::{
+    AgentState, DisplayDebouncer, DisplayInputs, DisplayState, ModelState, ModelStateMachine,
+    PollingMode,
+};
 use reqwest::blocking::Client;
 use std::collections::HashMap;
 use std::error::Error;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Detailed service status tracking different layers of service management
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
@@ -12,6 +16,8 @@ pub struct ServiceStatus {
     pub launchctl_loaded: bool,
     pub process_running: bool,
     pub api_responsive: bool,
+    pub ui_available: bool, // Web UI checked separately; proxies may expose only one of the two
+    pub pid: Option<u32>,   // launchd's PID for the service while it runs
 }
 
 impl ServiceStatus {
@@ -21,13 +27,16 @@ impl ServiceStatus {
             launchctl_loaded: false,
             process_running: false,
             api_responsive: false,
+            ui_available: false,
+            pid: None,
         }
     }
 
     pub fn update(&mut self, api_success: bool) {
         self.plist_installed = crate::commands::is_service_installed().unwrap_or(false);
         self.launchctl_loaded = crate::service::is_service_loaded();
-        self.process_running = crate::service::is_service_running();
+        self.pid = crate::service::service_pid();
+        self.process_running = self.pid.is_some();
         self.api_responsive = api_success;
     }
 
@@ -89,22 +98,105 @@ pub struct PluginState {
     pub metrics_history: AllMetricsHistory,
     pub current_all_metrics: Option<AllMetrics>,
     pub error_count: usize,
+    // Polls so far; the error badge blinks off on odd ones
+    pub frame: u64,
+    pub streaming: bool, // Frames keep coming, so a blink can be seen
 
     // Simplified state
     pub agent_state: AgentState,
     pub polling_mode: PollingMode,
-    pub model_states: HashMap<String, ModelState>,
+    pub model_states: HashMap<String, ModelStateMachine>,
+    pub load_times: crate::state_model::LoadTimes,
     pub service_status: ServiceStatus,
 
     // Timing for state transitions
     last_state_change: Instant,
+
+    // Unix time the running service started, for the uptime line
+    pub service_started_at: Option<u64>,
+
+    // A start was requested and the API hasn't answered yet
+    start_pending: bool,
+
+    // Timing for metrics history checkpoints
+    last_metrics_save: Instant,
+
+    // Timing for web UI availability checks
+    last_ui_check: Option<Instant>,
+
+    // Automatic recovery actions
+    pub recovery: crate::policies::PolicyEngine,
+    pub watchdog: crate::watchdog::Watchdog,
+
+    // Who holds the API port while the process runs but the API is silent
+    pub port_problem: Option<crate::port_check::PortProblem>,
+
+    // launchd's view of the service, for the Advanced service details
+    pub launchd: crate::service::LaunchdWatch,
+
+    // Every installed llama-swap launch agent, this instance's included
+    pub launch_agents: Vec<crate::launch_agents::LaunchAgent>,
+    launch_agent_scan: crate::launch_agents::AgentScan,
+
+    // llama-swap servers on other machines, from the plugin config
+    pub remote_hosts: Vec<crate::remote::RemoteHostState>,
+
+    // Whether the installed plist still matches what install_service writes
+    pub plist_drift: crate::plist_drift::PlistDrift,
+    pub sessions: crate::sessions::SessionLog,
+
+    // Cached hardware summary for the "This Mac" submenu
+    pub hardware: Option<crate::hardware::HardwareInfo>,
+
+    // Reused across polls; refreshed selectively by collect_system_metrics
+    pub system: sysinfo::System,
+    // Previous CPU refresh, the baseline for this poll's CPU usage
+    last_cpu_refresh: Option<Instant>,
+
+    // Energy used while models are loaded (when power sampling is enabled)
+    pub energy: crate::power::EnergyMeter,
+
+    // Per-model warnings for config.yaml commands that can't start
+    pub binary_checker: crate::binary_check::BinaryChecker,
+    pub configured_models: crate::config_parser::ConfiguredModels,
+
+    // Syntax and schema problems in config.yaml
+    pub config_validation: crate::config_validation::ConfigValidation,
+
+    // config.yaml's last modification, compared with the service's start time
+    pub config_modified: Option<SystemTime>,
+
+    // Recent errors and warnings in the service log
+    pub log_analyzer: crate::log_analysis::LogAnalyzer,
+
+    // llama-swap and llama.cpp versions for the Advanced menu
+    pub versions: crate::versions::VersionInfo,
+    pub updates: crate::updates::UpdateChecker,
+
+    // State transition history
+    pub events: crate::events::EventLog,
+    last_display_state: Option<DisplayState>,
+    display_debouncer: DisplayDebouncer,
+
+    // Optional long-term history store
+    #[cfg(feature = "sqlite")]
+    pub metrics_store: Option<crate::storage::MetricsStore>,
+
+    // Prometheus /metrics endpoint (streaming mode only)
+    pub exporter: Option<crate::exporter::Exporter>,
+    statsd: Option<crate::statsd::StatsdForwarder>,
+    pub alerts: crate::alerts::AlertMonitor,
+    webhooks: Option<crate::webhooks::WebhookNotifier>,
+    notifications: Option<crate::notifications::DesktopNotifier>,
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<crate::mqtt::MqttPublisher>,
 }
 
 impl PluginState {
     pub fn new() -> Result<Self> {
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(*crate::constants::API_TIMEOUT_SECS))
-            .build()?;
+        let http_client = crate::http::api_client(Some(Duration::from_secs(
+            *crate::constants::API_TIMEOUT_SECS,
+        )))?;
 
         // Initialize service status
         let mut service_status = ServiceStatus::new();
@@ -116,39 +208,245 @@ impl PluginState {
             service_status.plist_installed,
             binary_available,
             service_status.is_fully_running(),
+            service_status.process_running,
         );
 
+        // Restore history from the previous plugin run, if any
+        let metrics_history = match crate::persistence::load_metrics() {
+            Ok(Some(history)) => history,
+            Ok(None) => AllMetricsHistory::new(),
+            Err(e) => {
+                logging::warn(
+                    "persistence",
+                    "load_failed",
+                    &format!("Failed to load persisted metrics history: {e}"),
+                );
+                AllMetricsHistory::new()
+            }
+        };
+
         Ok(Self {
             http_client,
-            metrics_history: AllMetricsHistory::new(),
+            metrics_history,
             current_all_metrics: None,
             error_count: 0,
+            frame: 0,
+            streaming: false,
             agent_state,
             polling_mode: PollingMode::Idle,
             model_states: HashMap::new(),
+            load_times: crate::state_model::LoadTimes::default(),
             service_status,
             last_state_change: Instant::now(),
+            service_started_at: None,
+            start_pending: false,
+            last_metrics_save: Instant::now(),
+            last_ui_check: None,
+            recovery: crate::policies::PolicyEngine::from_env(),
+            watchdog: crate::watchdog::Watchdog::default(),
+            port_problem: None,
+            launchd: crate::service::LaunchdWatch::default(),
+            launch_agents: Vec::new(),
+            launch_agent_scan: crate::launch_agents::AgentScan::default(),
+            remote_hosts: crate::remote::from_config(),
+            plist_drift: crate::plist_drift::PlistDrift::default(),
+            sessions: load_sessions(),
+            hardware: crate::hardware::HardwareInfo::load_or_detect(),
+            system: sysinfo::System::new(),
+            last_cpu_refresh: None,
+            energy: crate::power::EnergyMeter::default(),
+            binary_checker: crate::binary_check::BinaryChecker::default(),
+            configured_models: crate::config_parser::ConfiguredModels::default(),
+            config_validation: crate::config_validation::ConfigValidation::default(),
+            config_modified: None,
+            log_analyzer: crate::log_analysis::LogAnalyzer::default(),
+            versions: crate::versions::VersionInfo::default(),
+            updates: crate::updates::UpdateChecker::default(),
+            events: load_events(),
+            last_display_state: None,
+            display_debouncer: DisplayDebouncer::from_env(),
+            #[cfg(feature = "sqlite")]
+            metrics_store: open_metrics_store(),
+            exporter: None,
+            statsd: crate::statsd::StatsdForwarder::from_env(),
+            alerts: crate::alerts::AlertMonitor::from_env(),
+            webhooks: crate::webhooks::WebhookNotifier::from_env(),
+            notifications: crate::notifications::DesktopNotifier::from_env(),
+            #[cfg(feature = "mqtt")]
+            mqtt: crate::mqtt::MqttPublisher::from_env(),
         })
     }
 
+    /// Evaluate the watchdog and recovery policies against the latest service status
+    pub fn apply_recovery_policies(&mut self) {
+        if let Some(alert) = self.watchdog.run(&self.service_status) {
+            self.raise_alert(alert);
+        }
+        if self.recovery.is_empty() {
+            return;
+        }
+
+        self.recovery.run(&self.service_status);
+    }
+
+    /// Drop all in-memory metrics history (requested via the clear_history command)
+    pub fn clear_history(&mut self) {
+        self.metrics_history = AllMetricsHistory::new();
+        logging::info(
+            "state",
+            "history_cleared",
+            "Metrics history cleared on request",
+        );
+    }
+
+    /// Start the HTTP exporter, if an address is configured
+    pub fn start_exporter(&mut self) {
+        self.exporter = crate::constants::EXPORTER_ADDR
+            .as_deref()
+            .map(crate::exporter::Exporter::start);
+    }
+
+    /// Hand the current metrics to the OpenMetrics textfile and exporter, if configured
+    pub fn export_openmetrics(&self) {
+        let path = crate::constants::OPENMETRICS_FILE_PATH.as_deref();
+        if path.is_none() && self.exporter.is_none() {
+            return;
+        }
+
+        let text = crate::openmetrics::render(
+            &self.service_status,
+            self.current_all_metrics.as_ref(),
+            &self.metrics_history,
+        );
+        if let Some(exporter) = &self.exporter {
+            exporter.update(text.clone());
+        }
+        let Some(path) = path else {
+            return;
+        };
+        if let Err(e) = crate::openmetrics::write_snapshot(path, &text) {
+            logging::warn(
+                "openmetrics",
+                "write_failed",
+                &format!("Failed to write OpenMetrics file: {e}"),
+            );
+        }
+    }
+
+    /// Push the current per-model gauges to StatsD, if configured
+    pub fn forward_statsd(&self) {
+        if let (Some(statsd), Some(all_metrics)) = (&self.statsd, &self.current_all_metrics) {
+            statsd.send(all_metrics);
+        }
+    }
+
+    /// Publish the display state and metrics to MQTT, if configured
+    #[cfg(feature = "mqtt")]
+    pub fn publish_mqtt(&mut self) {
+        let display_state = self.get_display_state();
+        if let Some(mqtt) = self.mqtt.as_mut() {
+            mqtt.publish(
+                display_state,
+                self.current_all_metrics.as_ref(),
+                &self.metrics_history,
+            );
+        }
+    }
+
+    /// Save metrics history if the checkpoint interval has elapsed
+    pub fn checkpoint_metrics(&mut self) {
+        let interval = *crate::constants::PERSIST_INTERVAL_SECS;
+        if interval == 0 || self.last_metrics_save.elapsed() < Duration::from_secs(interval) {
+            return;
+        }
+
+        self.persist_metrics();
+    }
+
+    /// Save metrics history and session summaries immediately (e.g. on shutdown)
+    pub fn persist_metrics(&mut self) {
+        if let Err(e) = crate::persistence::save_metrics(&self.metrics_history) {
+            logging::warn(
+                "persistence",
+                "save_failed",
+                &format!("Failed to persist metrics history: {e}"),
+            );
+        }
+        if let Err(e) = self.sessions.save() {
+            logging::warn(
+                "sessions",
+                "save_failed",
+                &format!("Failed to persist session summaries: {e}"),
+            );
+        }
+        self.last_metrics_save = Instant::now();
+    }
+
+    /// Re-check the web UI at most every UI_CHECK_INTERVAL_SECS while the API is up
+    fn update_ui_availability(&mut self) {
+        if !self.service_status.api_responsive {
+            self.service_status.ui_available = false;
+            self.last_ui_check = None;
+            return;
+        }
+
+        let interval = Duration::from_secs(crate::constants::UI_CHECK_INTERVAL_SECS);
+        if self
+            .last_ui_check
+            .is_some_and(|last| last.elapsed() < interval)
+        {
+            return;
+        }
+
+        self.last_ui_check = Some(Instant::now());
+        let available = crate::service::is_ui_available(&self.http_client);
+        if available != self.service_status.ui_available {
+            logging::info(
+                "state",
+                "ui_availability_changed",
+                &format!("Web UI available: {available}"),
+            );
+        }
+        self.service_status.ui_available = available;
+    }
+
     pub fn update_polling_mode(&mut self) {
         let old_mode = self.polling_mode;
         let state_changed = self.last_state_change.elapsed() < Duration::from_millis(100);
         let has_activity = self.has_queue_activity();
 
+        // A pending start should be noticed promptly, not after a long backoff
+        let failures = if self.start_pending {
+            0
+        } else {
+            self.error_count
+        };
+
         self.polling_mode = PollingMode::compute(
             self.polling_mode,
             state_changed,
             has_activity,
             self.last_state_change.elapsed(),
+            failures,
         );
 
-        if self.polling_mode != old_mode {
-            eprintln!(
-                "Polling mode: {} -> {} ({})",
+        // Backoff changes its interval on every failure; only log entering or leaving it
+        if self.polling_mode.description() != old_mode.description() {
+            let reason = self.get_mode_reason();
+            self.record_event(
+                crate::events::EventKind::Polling,
                 old_mode.description(),
                 self.polling_mode.description(),
-                self.get_mode_reason()
+                Some(&reason),
+            );
+            logging::info(
+                "state",
+                "polling_mode_changed",
+                &format!(
+                    "Polling mode: {} -> {} ({reason})",
+                    old_mode.description(),
+                    self.polling_mode.description(),
+                ),
             );
         }
     }
@@ -164,6 +462,13 @@ impl PluginState {
     }
 
     pub fn get_mode_reason(&self) -> String {
+        if let PollingMode::Backoff { failures } = self.polling_mode {
+            return format!(
+                "{failures} consecutive API failures, polling every {}s",
+                self.polling_mode.interval().as_secs()
+            );
+        }
+
         if let Some(ref all_metrics) = self.current_all_metrics {
             let (total_processing, total_deferred) =
                 all_metrics
@@ -187,22 +492,38 @@ impl PluginState {
     }
 
     pub fn update_state(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+        if crate::persistence::take_clear_request() {
+            self.clear_history();
+        }
+        // An export or CSV copy is waiting for the charts as they are now
+        if crate::persistence::flush_requested() {
+            self.persist_metrics();
+            crate::persistence::complete_flush();
+        }
+
         // Always collect system metrics regardless of API state
-        let mut system = sysinfo::System::new_all();
-        let system_metrics = crate::metrics::collect_system_metrics(&mut system);
-        let llama_memory_mb = crate::metrics::get_llama_server_memory_mb(&system);
+        // Without a recent enough baseline the CPU reading is meaningless, so skip it
+        let cpu_valid = self
+            .last_cpu_refresh
+            .is_some_and(|at| at.elapsed() >= sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        let system_metrics = crate::metrics::collect_system_metrics(&mut self.system);
+        self.last_cpu_refresh = Some(Instant::now());
+        let llama_memory_mb = crate::metrics::get_llama_server_memory_mb(&self.system);
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
 
         // Store system metrics independently using CircularQueue direct push
-        self.metrics_history
-            .cpu_usage_percent
-            .push(crate::models::TimestampedValue {
-                timestamp,
-                value: system_metrics.cpu_usage_percent,
-            });
+        if cpu_valid {
+            self.metrics_history
+                .cpu_usage_percent
+                .push(crate::models::TimestampedValue {
+                    timestamp,
+                    value: system_metrics.cpu_usage_percent,
+                });
+        }
         self.metrics_history
             .memory_usage_percent
             .push(crate::models::TimestampedValue {
@@ -215,36 +536,293 @@ impl PluginState {
                 timestamp,
                 value: system_metrics.used_memory_gb,
             });
+        if let Some(gpu) = system_metrics.gpu {
+            self.metrics_history
+                .gpu_usage_percent
+                .push(crate::models::TimestampedValue {
+                    timestamp,
+                    value: gpu.utilization_percent,
+                });
+            self.metrics_history
+                .gpu_memory_mb
+                .push(crate::models::TimestampedValue {
+                    timestamp,
+                    value: gpu.memory_in_use_mb,
+                });
+        }
+        self.metrics_history
+            .swap_used_gb
+            .push(crate::models::TimestampedValue {
+                timestamp,
+                value: system_metrics.swap_used_gb,
+            });
+        if let Some(pressure) = system_metrics.memory_pressure {
+            self.metrics_history
+                .memory_pressure
+                .push(crate::models::TimestampedValue {
+                    timestamp,
+                    value: pressure.as_value(),
+                });
+        }
         self.metrics_history
             .total_llama_memory_mb
             .push(crate::models::TimestampedValue {
                 timestamp,
                 value: llama_memory_mb,
             });
+        self.sample_power(timestamp);
 
         // Check API connectivity first, then update agent state based on that
         let api_success = match crate::metrics::fetch_all_metrics(&self.http_client) {
             Ok(all_metrics) => {
+                self.sessions.record(
+                    timestamp,
+                    &crate::sessions::local_date(timestamp),
+                    &all_metrics,
+                    llama_memory_mb,
+                );
                 self.handle_metrics_success(all_metrics);
                 true
             }
             Err(e) => {
-                eprintln!(
-                    "Debug: Metrics fetch failed in state {:?}: {}",
-                    self.agent_state, e
+                logging::debug(
+                    "state",
+                    "metrics_fetch_failed",
+                    &format!(
+                        "Debug: Metrics fetch failed in state {:?}: {}",
+                        self.agent_state, e
+                    ),
                 );
                 self.handle_metrics_error(e);
                 false
             }
         };
 
+        let alerts = self
+            .alerts
+            .evaluate(self.current_all_metrics.as_ref(), &self.metrics_history);
+        for alert in alerts {
+            self.raise_alert(alert);
+        }
+
+        self.binary_checker.refresh();
+        self.configured_models.refresh();
+        self.config_validation.refresh();
+        self.config_modified = crate::config_parser::modified_time();
+        self.log_analyzer.refresh();
+        let running_model = self
+            .current_all_metrics
+            .iter()
+            .flat_map(|all| &all.models)
+            .find(|model| model.model_state == crate::models::ModelState::Running)
+            .map(|model| model.model_name.as_str());
+        self.versions.refresh(&self.http_client, running_model);
+        self.updates.refresh(&self.http_client);
+
         // Update service status with API connectivity result
         self.service_status.update(api_success);
+        self.update_ui_availability();
+        self.port_problem = (self.service_status.process_running
+            && !self.service_status.api_responsive)
+            .then(|| {
+                crate::port_check::diagnose(*crate::constants::API_PORT, self.service_status.pid)
+            })
+            .flatten();
+        self.launchd.refresh(
+            self.service_status.launchctl_loaded,
+            self.service_status.pid,
+        );
+        self.launch_agent_scan
+            .refresh(&mut self.launch_agents, &self.service_status);
+        self.plist_drift
+            .refresh(self.service_status.plist_installed);
+        for host in &mut self.remote_hosts {
+            host.poll(&self.http_client);
+        }
 
         // Update agent state with proper transitions, using comprehensive service status
         self.update_agent_state();
+        self.service_started_at = self.service_start_time();
 
         self.update_polling_mode();
+        self.display_debouncer
+            .apply(self.compute_display_state(), Instant::now());
+        self.record_display_transition();
+    }
+
+    /// When the running service started: its process's start time, or failing
+    /// that the last time the agent became Running (the event log outlives
+    /// plugin restarts, so this survives them too)
+    fn service_start_time(&self) -> Option<u64> {
+        if self.agent_state != AgentState::Running {
+            return None;
+        }
+
+        self.service_status
+            .pid
+            .and_then(|pid| self.system.process(sysinfo::Pid::from_u32(pid)))
+            .map(sysinfo::Process::start_time)
+            .or_else(|| {
+                self.events
+                    .last_transition_to(crate::events::EventKind::Agent, AgentState::Running.name())
+                    .map(|event| event.timestamp)
+            })
+    }
+
+    /// Whether config.yaml was saved after the running service started, so the
+    /// service is still using the old config. Invalid configs don't count:
+    /// restarting into one would fail, and the menu lists its errors instead.
+    pub fn config_changed_since_start(&self) -> bool {
+        let (Some(modified), Some(started_at)) = (self.config_modified, self.service_started_at)
+        else {
+            return false;
+        };
+        let modified = modified
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        modified > started_at && self.config_validation.issues().is_empty()
+    }
+
+    /// Take a CPU baseline now, for a single poll that has no previous one
+    ///
+    /// Streaming mode never needs this: each poll is the baseline for the next.
+    pub fn prime_cpu_usage(&mut self) {
+        self.system.refresh_cpu_usage();
+        self.last_cpu_refresh = Some(Instant::now());
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    }
+
+    /// Sample power draw while models are loaded; idle power isn't interesting
+    fn sample_power(&mut self, timestamp: u64) {
+        let models_loaded = self
+            .current_all_metrics
+            .as_ref()
+            .is_some_and(|metrics| !metrics.models.is_empty());
+        if !models_loaded {
+            self.energy.end_session();
+            return;
+        }
+
+        if let Some(watts) = crate::power::sample() {
+            self.metrics_history
+                .power_watts
+                .push(crate::models::TimestampedValue {
+                    timestamp,
+                    value: watts,
+                });
+            self.energy.record(watts, Instant::now());
+        }
+    }
+
+    fn record_display_transition(&mut self) {
+        let display_state = self.get_display_state();
+        let previous = self.last_display_state.replace(display_state);
+
+        if let Some(previous) = previous.filter(|&previous| previous != display_state) {
+            let reason = if self.agent_state == AgentState::Running {
+                self.get_mode_reason()
+            } else {
+                self.service_status.status_description().to_string()
+            };
+            self.record_event(
+                crate::events::EventKind::Display,
+                previous.status_message(),
+                display_state.status_message(),
+                Some(&reason),
+            );
+            crate::hooks::on_transition(
+                crate::events::EventKind::Display,
+                &format!("{previous:?}"),
+                &format!("{display_state:?}"),
+            );
+            self.notify_if_crashed(previous, display_state, &reason);
+        }
+    }
+
+    /// A serving llama-swap that stops answering, or exits while launchd still has
+    /// it loaded, crashed; a stop from the menu boots the service out first
+    fn notify_if_crashed(&mut self, previous: DisplayState, current: DisplayState, reason: &str) {
+        let was_serving = matches!(
+            previous,
+            DisplayState::ServiceLoadedNoModel
+                | DisplayState::ModelLoading
+                | DisplayState::ModelStopping
+                | DisplayState::ModelProcessingQueue
+                | DisplayState::ModelReady
+        );
+        let crashed = current == DisplayState::ServiceDegraded
+            || (current == DisplayState::ServiceStopped && self.service_status.launchctl_loaded);
+        if !was_serving || !crashed {
+            return;
+        }
+
+        self.raise_alert(crate::alerts::Alert::ServiceCrashed {
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Send an alert to every configured channel and record it in the event
+    /// history, unless one like it just went out; in quiet hours it is only
+    /// recorded, and with no channel configured it is dropped
+    fn raise_alert(&mut self, alert: crate::alerts::Alert) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let delivery = self.alerts.delivery(
+            &alert,
+            Instant::now(),
+            crate::sessions::local_minute_of_day(timestamp),
+        );
+        let quiet = match delivery {
+            crate::alerts::Delivery::Skip => return,
+            crate::alerts::Delivery::Quiet => true,
+            crate::alerts::Delivery::Send => false,
+        };
+        if !quiet && self.webhooks.is_none() && self.notifications.is_none() {
+            return;
+        }
+
+        logging::info("alerts", alert.kind(), &alert.message());
+        self.record_event(
+            crate::events::EventKind::Alert,
+            "",
+            &alert.message(),
+            quiet.then_some("quiet hours, not sent"),
+        );
+        if quiet {
+            return;
+        }
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.notify(&alert);
+        }
+        if let Some(notifications) = &self.notifications {
+            notifications.notify(&alert);
+        }
+    }
+
+    fn record_event(
+        &mut self,
+        kind: crate::events::EventKind,
+        from: &str,
+        to: &str,
+        reason: Option<&str>,
+    ) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        if !self.events.record(timestamp, kind, from, to, reason) {
+            return;
+        }
+        if let Err(e) = self.events.save() {
+            logging::warn(
+                "events",
+                "save_failed",
+                &format!("Failed to persist event log: {e}"),
+            );
+        }
     }
 
     pub fn update_agent_state(&mut self) {
@@ -254,12 +832,24 @@ impl PluginState {
         let binary_available = crate::commands::find_llama_swap_binary().is_ok();
 
         // Compute new state using comprehensive service status
+        let since_start = crate::service::start_request_age();
         let new_state = AgentState::from_system_check(
             self.service_status.plist_installed,
             binary_available,
             self.service_status.is_fully_running(),
+            self.service_status.process_running,
+        )
+        .with_startup_timeout(
+            since_start,
+            Duration::from_secs(*crate::constants::STARTUP_TIMEOUT_SECS),
         );
 
+        // A failed start stays reported until the next start or stop
+        if since_start.is_some() && new_state == AgentState::Running {
+            crate::service::clear_start_request();
+        }
+        self.start_pending = since_start.is_some() && new_state != AgentState::Running;
+
         if matches!(old_state, AgentState::Stopped) && matches!(new_state, AgentState::Running) {
             // Direct transition to Running
             self.agent_state = AgentState::Running;
@@ -269,7 +859,28 @@ impl PluginState {
 
         if self.agent_state != old_state {
             self.last_state_change = Instant::now();
-            eprintln!("Agent state: {old_state:?} -> {:?}", self.agent_state);
+            logging::info(
+                "state",
+                "agent_state_changed",
+                &format!("Agent state: {old_state:?} -> {:?}", self.agent_state),
+            );
+            let reason = match (self.agent_state, since_start) {
+                (AgentState::FailedToStart, Some(elapsed)) => {
+                    format!("no API response {}s after start", elapsed.as_secs())
+                }
+                _ => self.service_status.status_description().to_string(),
+            };
+            self.record_event(
+                crate::events::EventKind::Agent,
+                old_state.description(),
+                self.agent_state.description(),
+                Some(&reason),
+            );
+            crate::hooks::on_transition(
+                crate::events::EventKind::Agent,
+                old_state.name(),
+                self.agent_state.name(),
+            );
         }
     }
 
@@ -279,12 +890,9 @@ impl PluginState {
 
         // Update model histories
         for model_metrics in &all_metrics.models {
-            let history = self
-                .metrics_history
-                .models
-                .entry(model_metrics.model_name.clone())
-                .or_default();
-            history.push(&model_metrics.metrics);
+            self.metrics_history
+                .model_history_mut(&model_metrics.model_name)
+                .push(&model_metrics.metrics);
         }
 
         // Don't update llama memory here - it's collected independently in update_state
@@ -293,6 +901,9 @@ impl PluginState {
         // Trim old data for all metrics
         self.metrics_history.trim_old_data();
 
+        #[cfg(feature = "sqlite")]
+        self.record_long_term_history(&all_metrics);
+
         self.current_all_metrics = Some(all_metrics.clone());
         self.error_count = 0;
 
@@ -300,20 +911,57 @@ impl PluginState {
         self.update_model_states(&all_metrics);
     }
 
+    #[cfg(feature = "sqlite")]
+    fn record_long_term_history(&mut self, all_metrics: &AllMetrics) {
+        let Some(store) = self.metrics_store.as_mut() else {
+            return;
+        };
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        for model_metrics in &all_metrics.models {
+            if let Err(e) =
+                store.record(timestamp, &model_metrics.model_name, &model_metrics.metrics)
+            {
+                logging::warn(
+                    "storage",
+                    "record_failed",
+                    &format!("History database error: {e}"),
+                );
+                return;
+            }
+        }
+
+        if let Err(e) = store.run_maintenance(timestamp) {
+            logging::warn(
+                "storage",
+                "maintenance_failed",
+                &format!("History database maintenance failed: {e}"),
+            );
+        }
+    }
+
     pub fn handle_metrics_error(&mut self, error: Box<dyn Error>) {
-        eprintln!("Metrics fetch failed: {error}");
+        logging::warn(
+            "state",
+            "metrics_error",
+            &format!("Metrics fetch failed: {error}"),
+        );
         self.error_count += 1;
 
-        // Clear current model states since we can't verify their current status
-        self.model_states.clear();
+        // Model states are kept: a transient failure says nothing about the models, and
+        // clearing them would lose load timing and flap history
 
         // Clear current metrics snapshot, but preserve all historical data
-        // All metrics (system, model, llama memory) are preserved within the 5-minute retention window
+        // All metrics (system, model, llama memory) are preserved within the retention window
         // Natural time-based cleanup will handle old data automatically
         self.current_all_metrics = None;
 
         // Note: All historical metrics (system, model, llama memory) are preserved across API failures
-        // and service issues, only cleaned up by the natural 5-minute retention window
+        // and service issues, only cleaned up by the natural retention window
     }
 
     pub fn update_model_states(&mut self, all_metrics: &AllMetrics) {
@@ -323,41 +971,209 @@ impl PluginState {
             .iter()
             .map(|m| m.model_name.clone())
             .collect();
-        self.model_states
-            .retain(|name, _| current_model_names.contains(name));
+        let mut transitions = Vec::new();
+        self.model_states.retain(|name, machine| {
+            let keep = current_model_names.contains(name);
+            if !keep {
+                transitions.push((name.clone(), machine.state(), None));
+            }
+            keep
+        });
 
-        // Update or create states for each model
+        // Update or create a state machine for each model
+        let now = Instant::now();
+        let mut alerts = Vec::new();
         for model_data in &all_metrics.models {
             let state = match model_data.model_state {
                 crate::models::ModelState::Loading => ModelState::Loading,
                 crate::models::ModelState::Running => ModelState::Running,
+                crate::models::ModelState::Stopping => ModelState::Stopping,
                 crate::models::ModelState::Unknown => ModelState::Unknown,
             };
-            self.model_states
-                .insert(model_data.model_name.clone(), state);
+
+            match self.model_states.get_mut(&model_data.model_name) {
+                Some(machine) => {
+                    let was_flapping = machine.is_flapping(now);
+                    let previous = machine.state();
+                    if !machine.observe(state, now) {
+                        continue;
+                    }
+                    transitions.push((model_data.model_name.clone(), previous, Some(state)));
+                    if previous == ModelState::Loading && state == ModelState::Running {
+                        if let Some(loaded_in) = machine.last_load_duration() {
+                            self.load_times.record(&model_data.model_name, loaded_in);
+                            alerts.push(crate::alerts::Alert::ModelLoaded {
+                                model: model_data.model_name.clone(),
+                                took: loaded_in,
+                            });
+                        }
+                    }
+                    if !was_flapping && machine.is_flapping(now) {
+                        logging::warn(
+                            "state",
+                            "model_flapping",
+                            &format!(
+                                "Model {} keeps changing state (now {state:?})",
+                                model_data.model_name
+                            ),
+                        );
+                    }
+                }
+                None => {
+                    self.model_states.insert(
+                        model_data.model_name.clone(),
+                        ModelStateMachine::new(state, now),
+                    );
+                }
+            }
+        }
+
+        for (model_name, from, to) in transitions {
+            let to = to.map_or("Unloaded".to_string(), |state| format!("{state:?}"));
+            self.record_event(
+                crate::events::EventKind::Model,
+                &format!("{from:?}"),
+                &to,
+                Some(&model_name),
+            );
+        }
+
+        for alert in alerts {
+            self.raise_alert(alert);
         }
     }
 
+    /// Display state to show, after the hysteresis guard
     pub fn get_display_state(&self) -> DisplayState {
-        match self.agent_state {
-            AgentState::NotReady { .. } => DisplayState::AgentNotLoaded,
-
-            AgentState::Stopped => DisplayState::ServiceStopped, // Fix: Ready to start
-            AgentState::Running => {
-                if self.model_states.is_empty() {
-                    DisplayState::ServiceLoadedNoModel
-                } else if self.has_loading_models() {
-                    DisplayState::ModelLoading
-                } else if self.has_queue_activity() {
-                    DisplayState::ModelProcessingQueue
-                } else {
-                    DisplayState::ModelReady
-                }
-            }
+        let computed = self.compute_display_state();
+        match self.display_debouncer.shown() {
+            Some(shown) if DisplayDebouncer::is_debounced(shown, computed) => shown,
+            _ => computed,
         }
     }
 
+    /// Display state implied by the latest poll alone
+    fn compute_display_state(&self) -> DisplayState {
+        DisplayState::compute(
+            self.agent_state,
+            &DisplayInputs {
+                start_pending: self.start_pending,
+                error_count: self.error_count,
+                has_models: !self.model_states.is_empty(),
+                has_loading_models: self.has_loading_models(),
+                has_queue_activity: self.has_queue_activity(),
+                has_stopping_models: self.has_stopping_models(),
+                crash_loop: self.watchdog.crash_loop(),
+            },
+        )
+    }
+
+    /// Badge the menu bar icon once API errors persist past the configured threshold
+    ///
+    /// With flashing enabled the badge also covers failed starts and an
+    /// unresponsive service. While streaming at the normal poll rate it is left
+    /// off every other frame so the icon blinks; a single run, or a poll slowed
+    /// by backoff, shows it steadily instead.
+    pub fn get_icon_overlay(&self) -> crate::icons::IconOverlay {
+        self.icon_overlay(*crate::constants::FLASH_ERROR_ICON)
+    }
+
+    pub(crate) fn icon_overlay(&self, flash: bool) -> crate::icons::IconOverlay {
+        let erroring = self.error_count >= *crate::constants::ERROR_BADGE_THRESHOLD;
+        let failing = erroring
+            || matches!(
+                self.get_display_state(),
+                DisplayState::StartupFailed
+                    | DisplayState::CrashLoop
+                    | DisplayState::ServiceDegraded
+            );
+        if flash && failing {
+            let blinking =
+                self.streaming && !matches!(self.polling_mode, PollingMode::Backoff { .. });
+            return if !blinking || self.frame.is_multiple_of(2) {
+                crate::icons::IconOverlay::ErrorBadge
+            } else {
+                crate::icons::IconOverlay::None
+            };
+        }
+
+        if erroring {
+            crate::icons::IconOverlay::ErrorBadge
+        } else if self.alerts.has_breaches() {
+            crate::icons::IconOverlay::WarningBadge
+        } else {
+            crate::icons::IconOverlay::None
+        }
+    }
+
+    /// Status dot colour from Llama's share of system memory while models are
+    /// loaded, when the memory gradient is enabled
+    pub fn memory_dot_color(&self, display_state: DisplayState) -> Option<(u8, u8, u8)> {
+        if !*crate::constants::DOT_MEMORY_GRADIENT
+            || !matches!(
+                display_state,
+                DisplayState::ModelReady | DisplayState::ModelProcessingQueue
+            )
+        {
+            return None;
+        }
+        let total_memory_mb = self.hardware.as_ref()?.memory_gb() * 1024.0;
+        let fraction = self
+            .metrics_history
+            .llama_memory_fraction(total_memory_mb)?;
+        Some(crate::icons::memory_gradient_color(fraction))
+    }
+
     pub fn has_loading_models(&self) -> bool {
-        self.model_states.values().any(|state| state.is_loading())
+        self.model_states
+            .values()
+            .any(|machine| machine.state().is_loading())
+    }
+
+    pub fn has_stopping_models(&self) -> bool {
+        self.model_states
+            .values()
+            .any(|machine| machine.state() == ModelState::Stopping)
+    }
+}
+
+fn load_sessions() -> crate::sessions::SessionLog {
+    crate::sessions::SessionLog::load().unwrap_or_else(|e| {
+        logging::warn(
+            "sessions",
+            "load_failed",
+            &format!("Failed to load session summaries: {e}"),
+        );
+        crate::sessions::SessionLog::default()
+    })
+}
+
+fn load_events() -> crate::events::EventLog {
+    crate::events::EventLog::load().unwrap_or_else(|e| {
+        logging::warn(
+            "events",
+            "load_failed",
+            &format!("Failed to load event log: {e}"),
+        );
+        crate::events::EventLog::default()
+    })
+}
+
+#[cfg(feature = "sqlite")]
+fn open_metrics_store() -> Option<crate::storage::MetricsStore> {
+    if !*crate::constants::HISTORY_DB_ENABLED {
+        return None;
+    }
+
+    match crate::storage::MetricsStore::open_default() {
+        Ok(store) => Some(store),
+        Err(e) => {
+            logging::warn(
+                "storage",
+                "open_failed",
+                &format!("Long-term history disabled: {e}"),
+            );
+            None
+        }
     }
 }
@@ -0,0 +1,153 @@
+use crate::logging;
+use crate::types::error_helpers::{with_context, PARSE_JSON};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often GitHub is asked for the latest llama-swap release
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+const LATEST_RELEASE_API: &str =
+    "https://api.github.com/repos/mostlygeek/llama-swap/releases/latest";
+
+/// Opened instead of upgrading when llama-swap wasn't installed with Homebrew
+pub const LATEST_RELEASE_PAGE: &str = "https://github.com/mostlygeek/llama-swap/releases/latest";
+
+/// The last check, kept on disk so one-shot runs don't ask GitHub every refresh
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct UpdateCache {
+    checked_at: u64, // Unix time
+    latest: Option<String>,
+}
+
+/// Periodically looks up the newest llama-swap release
+#[derive(Debug, Default)]
+pub struct UpdateChecker {
+    cache: Option<UpdateCache>,
+}
+
+impl UpdateChecker {
+    pub fn refresh(&mut self, client: &Client) {
+        if !*crate::constants::UPDATE_CHECK {
+            return;
+        }
+        if self.cache.is_none() {
+            self.cache = load_cache().ok().flatten();
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if self
+            .cache
+            .as_ref()
+            .is_some_and(|cache| now.saturating_sub(cache.checked_at) < CHECK_INTERVAL.as_secs())
+        {
+            return;
+        }
+
+        let latest = match fetch_latest_release(client) {
+            Ok(tag) => Some(tag),
+            Err(e) => {
+                logging::warn(
+                    "updates",
+                    "check_failed",
+                    &format!("Failed to check for a llama-swap update: {e}"),
+                );
+                // Keep what was known; try again after the next interval
+                self.cache.as_ref().and_then(|cache| cache.latest.clone())
+            }
+        };
+        let cache = UpdateCache {
+            checked_at: now,
+            latest,
+        };
+        if let Err(e) = save_cache(&cache) {
+            logging::warn(
+                "updates",
+                "cache_failed",
+                &format!("Failed to save update check: {e}"),
+            );
+        }
+        self.cache = Some(cache);
+    }
+
+    /// The latest release tag, e.g. "v140", when it is newer than the installed version
+    pub fn available(&self, installed: Option<&str>) -> Option<&str> {
+        let latest = self.cache.as_ref()?.latest.as_deref()?;
+        let newer =
+            crate::versions::parse_version(latest)? > crate::versions::parse_version(installed?)?;
+        newer.then_some(latest)
+    }
+}
+
+fn fetch_latest_release(client: &Client) -> crate::Result<String> {
+    #[derive(Deserialize)]
+    struct Release {
+        tag_name: String,
+    }
+
+    let response = client
+        .get(LATEST_RELEASE_API)
+        // GitHub rejects API requests without a user agent
+        .header(
+            reqwest::header::USER_AGENT,
+            concat!("llama-swap-swiftbar/", env!("CARGO_PKG_VERSION")),
+        )
+        .timeout(Duration::from_secs(5))
+        .send()?
+        .error_for_status()?;
+    let release: Release = with_context(response.json(), PARSE_JSON)?;
+    Ok(release.tag_name)
+}
+
+fn load_cache() -> crate::Result<Option<UpdateCache>> {
+    let path = cache_file_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let json = with_context(
+        std::fs::read_to_string(&path),
+        "Failed to read update check",
+    )?;
+    with_context(serde_json::from_str(&json), PARSE_JSON).map(Some)
+}
+
+fn save_cache(cache: &UpdateCache) -> crate::Result<()> {
+    let path = cache_file_path()?;
+    let json = with_context(
+        serde_json::to_string(cache),
+        "Failed to serialize update check",
+    )?;
+    crate::persistence::write_atomic(&path, json.as_bytes())
+}
+
+fn cache_file_path() -> crate::Result<PathBuf> {
+    let path = crate::commands::expand_tilde(&crate::constants::UPDATE_CACHE_FILE_PATH)?;
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_available_only_when_newer() {
+        let checker = UpdateChecker {
+            cache: Some(UpdateCache {
+                checked_at: 0,
+                latest: Some("v140".to_string()),
+            }),
+        };
+        assert_eq!(
+            checker.available(Some("version: 136 (7f3e1c2)")),
+            Some("v140")
+        );
+        assert_eq!(checker.available(Some("version: 140 (a1b2c3d)")), None);
+        assert_eq!(checker.available(None), None);
+        assert_eq!(UpdateChecker::default().available(Some("136")), None);
+    }
+}
@@ -0,0 +1,99 @@
+use reqwest::blocking::Client;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// How often `llama-swap --version` is re-run, to notice upgrades
+const RECHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Versions of llama-swap and of the llama.cpp server behind it
+#[derive(Debug, Default)]
+pub struct VersionInfo {
+    pub llama_swap: Option<String>,
+    pub llama_cpp: Option<String>, // llama-server's build, e.g. "b5120-3c1f2a4"
+    llama_cpp_model: Option<String>, // Model whose /props supplied llama_cpp
+    last_check: Option<Instant>,
+}
+
+impl VersionInfo {
+    /// Re-run `llama-swap --version` when due, and ask a running model's
+    /// llama-server for its build once per model
+    pub fn refresh(&mut self, client: &Client, running_model: Option<&str>) {
+        if self
+            .last_check
+            .is_none_or(|last| last.elapsed() >= RECHECK_INTERVAL)
+        {
+            self.last_check = Some(Instant::now());
+            self.llama_swap = llama_swap_version();
+        }
+
+        let Some(model) = running_model else {
+            return;
+        };
+        if self.llama_cpp_model.as_deref() != Some(model) {
+            self.llama_cpp_model = Some(model.to_string());
+            if let Some(build) = llama_cpp_build(client, model) {
+                self.llama_cpp = Some(build);
+            }
+        }
+    }
+
+    /// The configured minimum when the installed llama-swap is older than it
+    pub fn outdated_minimum(&self) -> Option<&'static str> {
+        let minimum = crate::constants::MIN_LLAMA_SWAP_VERSION.as_deref()?;
+        let installed = parse_version(self.llama_swap.as_deref()?)?;
+        (installed < parse_version(minimum)?).then_some(minimum)
+    }
+}
+
+/// First line `llama-swap --version` prints, or None when it isn't installed
+pub fn llama_swap_version() -> Option<String> {
+    let binary = crate::commands::find_llama_swap_binary().ok()?;
+    let output = Command::new(binary).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?.trim();
+    (!line.is_empty()).then(|| line.to_string())
+}
+
+/// `build_info` from the model's llama-server `/props`
+fn llama_cpp_build(client: &Client, model: &str) -> Option<String> {
+    let url = crate::commands::model_url(&["upstream", model, "props"]).ok()?;
+    let props: serde_json::Value = client
+        .get(url)
+        .timeout(Duration::from_secs(2))
+        .send()
+        .ok()?
+        .json()
+        .ok()?;
+    props["build_info"].as_str().map(str::to_string)
+}
+
+/// Numeric components of the first version in the text, e.g. "version: 1.2.3 (abc)" -> [1, 2, 3]
+pub fn parse_version(text: &str) -> Option<Vec<u64>> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let version: Vec<u64> = text[start..]
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()?
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect();
+    (!version.is_empty()).then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version("version: 136 (7f3e1c2), built at 2025-06-01"),
+            Some(vec![136])
+        );
+        assert_eq!(parse_version("v1.2.3"), Some(vec![1, 2, 3]));
+        assert_eq!(parse_version("unknown"), None);
+        assert!(parse_version("v1.10") > parse_version("v1.9"));
+    }
+}
@@ -0,0 +1,278 @@
+use crate::logging;
+use crate::types::ServiceStatus;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Window the restart budget applies to
+const BUDGET_WINDOW: Duration = Duration::from_secs(3600);
+
+/// This many crashes within the window is a crash loop, not bad luck
+const CRASH_LOOP_CRASHES: usize = 3;
+const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Wait before restarting after a crash, doubled for each earlier crash in the window
+const FIRST_BACKOFF: Duration = Duration::from_secs(5);
+
+/// What the watchdog wants done after a poll
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decision {
+    Wait,
+    Restart,
+    CrashLoop { crashes: usize }, // Just detected; restarts have stopped
+}
+
+/// Restarts a service that exited while the user wanted it running
+///
+/// By default launchd is told not to keep llama-swap alive, so a crash
+/// otherwise leaves the agent loaded with no process; when the KeepAlive
+/// install option hands restarts to launchd, the watchdog stands aside. A
+/// crash is a poll that finds the process gone after the previous one saw it
+/// running; stopping from the menu unloads the agent and clears the wanted
+/// flag, so it never counts. Restarts back off exponentially, and a service
+/// that keeps crashing is left down as a crash loop until the user starts it.
+#[derive(Debug, Default)]
+pub struct Watchdog {
+    was_running: bool,
+    crashes: VecDeque<Instant>,  // Within the crash loop window
+    restarts: VecDeque<Instant>, // Within the budget window
+    restart_at: Option<Instant>, // Pending restart, once its backoff has passed
+    crash_loop: bool,
+    gave_up: bool,
+}
+
+impl Watchdog {
+    /// Decide what to do after a poll, updating the crash history
+    pub fn evaluate(
+        &mut self,
+        status: &ServiceStatus,
+        wanted_running: bool,
+        now: Instant,
+    ) -> Decision {
+        prune(&mut self.restarts, now, BUDGET_WINDOW);
+        prune(&mut self.crashes, now, CRASH_LOOP_WINDOW);
+
+        // Running again, or stopped on purpose: nothing left to recover from
+        if status.process_running || !wanted_running {
+            self.restart_at = None;
+            self.crash_loop = false;
+            self.gave_up = false;
+        }
+
+        let crashed = self.was_running
+            && !status.process_running
+            && status.plist_installed
+            && status.launchctl_loaded
+            && wanted_running;
+        self.was_running = status.process_running;
+
+        if crashed {
+            self.crashes.push_back(now);
+            if self.crashes.len() >= CRASH_LOOP_CRASHES {
+                self.crash_loop = true;
+                self.restart_at = None;
+                return Decision::CrashLoop {
+                    crashes: self.crashes.len(),
+                };
+            }
+            if self.restarts.len() >= *crate::constants::WATCHDOG_MAX_RESTARTS {
+                self.gave_up = true;
+                return Decision::Wait;
+            }
+            self.restart_at = Some(now + backoff(self.crashes.len()));
+        }
+
+        match self.restart_at {
+            Some(at) if now >= at => {
+                self.restart_at = None;
+                self.restarts.push_back(now);
+                Decision::Restart
+            }
+            _ => Decision::Wait,
+        }
+    }
+
+    /// Restart the service when a crash's backoff has passed, returning the
+    /// alert to raise when a crash loop is detected
+    pub fn run(&mut self, status: &ServiceStatus) -> Option<crate::alerts::Alert> {
+        // Read-only mode would reject the restart; don't spend the budget on it
+        if !*crate::constants::WATCHDOG || *crate::constants::READ_ONLY_MODE {
+            return None;
+        }
+        // launchd restarts the service itself; a second restart would race it
+        let keep_alive = crate::install_options::InstallOptions::load().keep_alive;
+        if keep_alive != crate::install_options::KeepAlive::Never {
+            return None;
+        }
+
+        let gave_up = self.gave_up;
+        match self.evaluate(status, crate::service::is_wanted_running(), Instant::now()) {
+            Decision::Restart => {
+                let outcome = match crate::commands::handle_command("do_start") {
+                    Ok(()) => "succeeded".to_string(),
+                    Err(e) => format!("failed: {e}"),
+                };
+                logging::audit(
+                    "watchdog",
+                    &format!("Service exited unexpectedly; restart {outcome}"),
+                );
+                None
+            }
+            Decision::CrashLoop { crashes } => {
+                logging::audit(
+                    "watchdog",
+                    &format!(
+                        "Service crashed {crashes} times in {} minutes; not restarting",
+                        CRASH_LOOP_WINDOW.as_secs() / 60
+                    ),
+                );
+                Some(crate::alerts::Alert::CrashLoop {
+                    crashes,
+                    window: CRASH_LOOP_WINDOW,
+                    last_log: crate::service::tail_service_log(1).pop(),
+                })
+            }
+            Decision::Wait => {
+                if self.gave_up && !gave_up {
+                    logging::audit(
+                        "watchdog",
+                        &format!(
+                            "Service crashed again after {} restarts in the last hour; not restarting",
+                            self.restarts.len()
+                        ),
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    /// Restarts performed within the last hour
+    pub fn restarts_last_hour(&self) -> usize {
+        self.restarts.len()
+    }
+
+    /// Time until a pending restart
+    pub fn restart_in(&self) -> Option<Duration> {
+        self.restart_at
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// The service keeps crashing and has been left down
+    pub fn crash_loop(&self) -> bool {
+        self.crash_loop
+    }
+
+    /// The service crashed with the hourly budget already spent, and is still down
+    pub fn gave_up(&self) -> bool {
+        self.gave_up
+    }
+}
+
+fn prune(times: &mut VecDeque<Instant>, now: Instant, window: Duration) {
+    while times
+        .front()
+        .is_some_and(|at| now.duration_since(*at) >= window)
+    {
+        times.pop_front();
+    }
+}
+
+/// 5s after the first crash in the window, 10s after the second, ...
+fn backoff(crashes: usize) -> Duration {
+    FIRST_BACKOFF * 2u32.saturating_pow(crashes.saturating_sub(1) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(process_running: bool) -> ServiceStatus {
+        ServiceStatus {
+            plist_installed: true,
+            launchctl_loaded: true,
+            process_running,
+            api_responsive: process_running,
+            ui_available: false,
+            pid: process_running.then_some(4242),
+        }
+    }
+
+    #[test]
+    fn test_restarts_only_after_crash() {
+        let mut watchdog = Watchdog::default();
+        let now = Instant::now();
+
+        // Never seen running: a start that failed, not a crash
+        assert_eq!(watchdog.evaluate(&status(false), true, now), Decision::Wait);
+        watchdog.evaluate(&status(true), true, now);
+        // Stopped deliberately
+        watchdog.evaluate(&status(false), false, now);
+        assert_eq!(watchdog.restart_in(), None);
+
+        watchdog.evaluate(&status(true), true, now);
+        assert_eq!(watchdog.evaluate(&status(false), true, now), Decision::Wait);
+        let after_backoff = now + FIRST_BACKOFF;
+        assert_eq!(
+            watchdog.evaluate(&status(false), true, after_backoff),
+            Decision::Restart
+        );
+        assert_eq!(watchdog.restarts_last_hour(), 1);
+    }
+
+    #[test]
+    fn test_backoff_then_crash_loop() {
+        let mut watchdog = Watchdog::default();
+        let mut now = Instant::now();
+
+        for crash in 1..CRASH_LOOP_CRASHES {
+            watchdog.evaluate(&status(true), true, now);
+            watchdog.evaluate(&status(false), true, now);
+            // Each crash waits twice as long as the one before
+            let wait = backoff(crash);
+            assert_eq!(wait, FIRST_BACKOFF * (1 << (crash - 1)));
+            assert_eq!(
+                watchdog.evaluate(&status(false), true, now + wait / 2),
+                Decision::Wait
+            );
+            now += wait;
+            assert_eq!(
+                watchdog.evaluate(&status(false), true, now),
+                Decision::Restart
+            );
+        }
+
+        watchdog.evaluate(&status(true), true, now);
+        assert_eq!(
+            watchdog.evaluate(&status(false), true, now),
+            Decision::CrashLoop {
+                crashes: CRASH_LOOP_CRASHES
+            }
+        );
+        assert!(watchdog.crash_loop());
+        assert_eq!(
+            watchdog.evaluate(&status(false), true, now + Duration::from_secs(600)),
+            Decision::Wait
+        );
+
+        // Started again by hand
+        watchdog.evaluate(&status(true), true, now + Duration::from_secs(900));
+        assert!(!watchdog.crash_loop());
+    }
+
+    #[test]
+    fn test_gives_up_when_budget_spent() {
+        let mut watchdog = Watchdog::default();
+        let start = Instant::now();
+        let max = *crate::constants::WATCHDOG_MAX_RESTARTS;
+
+        // Crashes far enough apart not to be a crash loop
+        for i in 0..=max as u64 {
+            let now = start + CRASH_LOOP_WINDOW * i as u32 / 2;
+            watchdog.evaluate(&status(true), true, now);
+            watchdog.evaluate(&status(false), true, now);
+            let restarted = watchdog.evaluate(&status(false), true, now + Duration::from_secs(60));
+            assert_eq!(restarted == Decision::Restart, i < max as u64);
+        }
+        assert!(watchdog.gave_up());
+    }
+}
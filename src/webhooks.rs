@@ -0,0 +1,105 @@
+use crate::alerts::Alert;
+use crate::logging;
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Attempts per notification, with doubling delays in between
+const MAX_ATTEMPTS: u32 = 3;
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Posts alerts to the configured Slack/Discord-compatible webhook
+#[derive(Debug)]
+pub struct WebhookNotifier {
+    url: String,
+}
+
+impl WebhookNotifier {
+    /// None when no webhook URL is configured
+    pub fn from_env() -> Option<Self> {
+        let url = crate::constants::WEBHOOK_URL.as_deref()?;
+        Some(Self {
+            url: url.to_string(),
+        })
+    }
+
+    /// Send the alert in the background
+    pub fn notify(&self, alert: &Alert) {
+        let url = self.url.clone();
+        let payload = payload(alert);
+        let kind = alert.kind();
+        std::thread::spawn(move || {
+            if let Err(e) = post_with_retries(&url, &payload) {
+                logging::warn(
+                    "webhooks",
+                    "send_failed",
+                    &format!("Failed to send {kind} webhook: {e}"),
+                );
+            }
+        });
+    }
+}
+
+/// JSON body accepted by both Slack (`text`) and Discord (`content`) webhooks
+pub fn payload(alert: &Alert) -> serde_json::Value {
+    let message = alert.message();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    json!({
+        "text": message,
+        "content": message,
+        "event": alert.kind(),
+        "resolved": alert.is_resolved(),
+        "timestamp": timestamp,
+    })
+}
+
+/// POST the payload, retrying network errors, 429s and 5xx responses
+fn post_with_retries(url: &str, payload: &serde_json::Value) -> crate::Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let mut delay = FIRST_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let error = match client.post(url).json(payload).send() {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response)
+                if !response.status().is_server_error()
+                    && response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS =>
+            {
+                return Err(format!("webhook returned {}", response.status()).into());
+            }
+            Ok(response) => format!("webhook returned {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= MAX_ATTEMPTS {
+            return Err(format!("{error} (after {attempt} attempts)").into());
+        }
+        std::thread::sleep(delay);
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_is_slack_and_discord_compatible() {
+        let alert = Alert::ModelLoaded {
+            model: "qwen".to_string(),
+            took: Duration::from_millis(12_340),
+        };
+        let payload = payload(&alert);
+
+        assert_eq!(payload["text"], "qwen finished loading in 12.3s");
+        assert_eq!(payload["content"], payload["text"]);
+        assert_eq!(payload["event"], "model_loaded");
+        assert_eq!(payload["resolved"], false);
+    }
+}
@@ -0,0 +1,203 @@
+use llama_swap_swiftbar::models::{
+    AllMetricsHistory, CounterRate, DownsampleTier, MemoryPressure, Metrics, TimestampedValue,
+    TpsBaseline,
+};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[test]
+fn test_trim_respects_retention_window() {
+    let mut history = AllMetricsHistory::with_retention(100, 1800);
+    history.downsample = None;
+    for age in [3600, 1200, 10] {
+        history.cpu_usage_percent.push(TimestampedValue {
+            timestamp: now() - age,
+            value: age as f64,
+        });
+    }
+
+    history.trim_old_data();
+
+    // Only the sample older than 30 minutes is dropped
+    let kept: Vec<f64> = history.cpu_usage_percent.iter().map(|v| v.value).collect();
+    assert_eq!(kept, vec![10.0, 1200.0]);
+}
+
+#[test]
+fn test_model_history_inherits_retention() {
+    let mut history = AllMetricsHistory::with_retention(50, 3600);
+    history
+        .model_history_mut("test-model")
+        .push(&Metrics::default());
+
+    let model = history.get_model_history("test-model").unwrap();
+    assert_eq!(model.retention_secs, 3600);
+    assert_eq!(model.tps.capacity(), 50);
+    assert_eq!(model.tps.len(), 1);
+}
+
+#[test]
+fn test_downsampling_averages_aged_samples() {
+    let mut history = AllMetricsHistory::with_retention(1000, 300);
+    history.downsample = Some(DownsampleTier {
+        bucket_secs: 30,
+        keep_secs: 3600,
+    });
+
+    // Two full buckets of aged samples, plus recent samples and one too old to keep
+    let now = now();
+    let aged_start = (now - 1200) - (now - 1200) % 30;
+    history.cpu_usage_percent.push(TimestampedValue {
+        timestamp: now - 7200,
+        value: 99.0,
+    });
+    for i in 0..60 {
+        history.cpu_usage_percent.push(TimestampedValue {
+            timestamp: aged_start + i,
+            value: if i < 30 { 10.0 } else { 20.0 },
+        });
+    }
+    for i in (0..60).rev() {
+        history.cpu_usage_percent.push(TimestampedValue {
+            timestamp: now - i,
+            value: 50.0,
+        });
+    }
+
+    history.trim_old_data();
+    let oldest: Vec<(u64, f64)> = history
+        .cpu_usage_percent
+        .iter()
+        .rev()
+        .take(3)
+        .map(|v| (v.timestamp, v.value))
+        .collect();
+    assert_eq!(
+        oldest,
+        vec![
+            (aged_start, 10.0),
+            (aged_start + 30, 20.0),
+            (now - 59, 50.0)
+        ]
+    );
+    assert_eq!(history.cpu_usage_percent.len(), 62);
+
+    // Compacted buckets are stable across later passes
+    history.trim_old_data();
+    assert_eq!(history.cpu_usage_percent.len(), 62);
+}
+
+#[test]
+fn test_grow_capacity_keeps_samples() {
+    let mut history = AllMetricsHistory::with_capacity(2);
+    history.cpu_usage_percent.push(TimestampedValue {
+        timestamp: now(),
+        value: 1.0,
+    });
+
+    history.grow_capacity(10);
+    assert_eq!(history.cpu_usage_percent.capacity(), 10);
+    assert_eq!(history.cpu_usage_percent.len(), 1);
+}
+
+#[test]
+fn test_counter_rate_tracks_deltas() {
+    let start = Instant::now();
+    let mut rate = CounterRate::default();
+
+    assert_eq!(rate.update(start, 1000.0), None);
+    assert_eq!(
+        rate.update(start + Duration::from_secs(2), 1100.0),
+        Some(50.0)
+    );
+    // An idle model reports zero throughput, whatever the gauges still say
+    assert_eq!(
+        rate.update(start + Duration::from_secs(4), 1100.0),
+        Some(0.0)
+    );
+    // A reloaded model restarts its counters
+    assert_eq!(rate.update(start + Duration::from_secs(6), 40.0), None);
+    assert_eq!(
+        rate.update(start + Duration::from_secs(8), 80.0),
+        Some(20.0)
+    );
+}
+
+#[test]
+fn test_speed_charts_use_gauges_while_counters_wait() {
+    let mut history = AllMetricsHistory::with_capacity(10);
+    let busy = Metrics {
+        predicted_tokens_per_sec: 48.0,
+        prompt_tokens_per_sec: 900.0,
+        requests_processing: 1,
+        tokens_predicted_total: 2000.0,
+        prompt_tokens_total: 500.0,
+        ..Metrics::default()
+    };
+    // Mid-request: the counters haven't moved since the previous poll
+    history.model_history_mut("m").push(&busy);
+    history.model_history_mut("m").push(&busy);
+
+    let model = history.get_model_history("m").unwrap();
+    assert_eq!(model.tps.iter().next().map(|v| v.value), Some(48.0));
+    assert_eq!(model.prompt_tps.iter().next().map(|v| v.value), Some(900.0));
+    // The counter-derived series says nothing finished
+    assert!(model.throughput_tps.iter().all(|v| v.value == 0.0));
+}
+
+#[test]
+fn test_current_memory_pressure_uses_latest_sample() {
+    let mut history = AllMetricsHistory::with_capacity(10);
+    assert_eq!(history.current_memory_pressure(), None);
+
+    for level in [1, 4, 2] {
+        let pressure = MemoryPressure::from_level(level).unwrap();
+        history.memory_pressure.push(TimestampedValue {
+            timestamp: now(),
+            value: pressure.as_value(),
+        });
+    }
+    assert_eq!(
+        history.current_memory_pressure(),
+        Some(MemoryPressure::Warning)
+    );
+    assert_eq!(MemoryPressure::from_level(3), None);
+}
+
+#[test]
+fn test_upstream_error_rate() {
+    let mut history = AllMetricsHistory::with_capacity(10);
+    let model = history.model_history_mut("m");
+    assert_eq!(model.upstream_error_rate(), None);
+
+    for failed in [Some(false), Some(true), None, Some(false), Some(false)] {
+        model.push(&Metrics {
+            upstream_error: failed,
+            ..Metrics::default()
+        });
+    }
+    assert_eq!(model.upstream_error_rate(), Some(25.0));
+}
+
+#[test]
+fn test_tps_baseline_needs_warm_up_and_moves_slowly() {
+    let mut baseline = TpsBaseline::default();
+    for _ in 0..29 {
+        baseline.record(50.0);
+    }
+    assert_eq!(baseline.value(), None);
+    baseline.record(50.0);
+    assert_eq!(baseline.value(), Some(50.0));
+
+    // A few slow polls barely move it
+    for _ in 0..5 {
+        baseline.record(20.0);
+    }
+    assert!(baseline.value().unwrap() > 45.0);
+}
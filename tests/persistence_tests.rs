@@ -0,0 +1,110 @@
+use llama_swap_swiftbar::models::{AllMetricsHistory, Metrics, TimestampedValue};
+use llama_swap_swiftbar::persistence::{
+    clear_metrics, decode_metrics, encode_metrics, load_metrics, save_metrics, take_clear_request,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[test]
+fn test_metrics_history_round_trip() {
+    let dir = std::env::temp_dir().join(format!("llama-swap-persist-{}", std::process::id()));
+    let path = dir.join("metrics_history.json");
+    std::env::set_var("LLAMA_SWAP_METRICS_FILE_PATH", &path);
+
+    // Nothing saved yet
+    assert!(load_metrics().unwrap().is_none());
+
+    let mut history = create_test_history();
+    save_metrics(&history).unwrap();
+
+    let loaded = load_metrics()
+        .unwrap()
+        .expect("history should have been saved");
+    assert_eq!(loaded.cpu_usage_percent.len(), 1);
+    assert_eq!(loaded.cpu_usage_percent.iter().next().unwrap().value, 42.0);
+    assert_eq!(
+        loaded.get_model_history("test-model").unwrap().tps.len(),
+        history.models["test-model"].tps.len()
+    );
+
+    // Samples older than the retention window are dropped on load
+    history.cpu_usage_percent.push(TimestampedValue {
+        timestamp: 1,
+        value: 99.0,
+    });
+    save_metrics(&history).unwrap();
+    let loaded = load_metrics().unwrap().unwrap();
+    assert!(loaded.cpu_usage_percent.iter().all(|tv| tv.timestamp > 1));
+
+    // Clearing removes the file and leaves a one-shot request for the running instance
+    assert!(!take_clear_request());
+    clear_metrics().unwrap();
+    assert!(load_metrics().unwrap().is_none());
+    assert!(take_clear_request());
+    assert!(!take_clear_request());
+
+    // A truncated file (e.g. from a crash before writes were atomic) loads as empty
+    std::fs::write(&path, r#"{"version":3,"checksum":"0000"#).unwrap();
+    assert!(load_metrics().unwrap().is_none());
+    assert!(!path.exists());
+    assert!(dir.join("metrics_history.json.corrupt").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_decode_unversioned_history() {
+    // Files written before the versioned envelope are still readable
+    let history = create_test_history();
+    let legacy_json = serde_json::to_string(&history).unwrap();
+
+    let decoded = decode_metrics(&legacy_json).unwrap();
+    assert_eq!(decoded.cpu_usage_percent.len(), 1);
+    assert!(decoded.get_model_history("test-model").is_some());
+}
+
+#[test]
+fn test_decode_rejects_unknown_version() {
+    let json = r#"{"version": 99, "history": {}}"#;
+    assert!(decode_metrics(json).is_err());
+}
+
+#[test]
+fn test_decode_rejects_checksum_mismatch() {
+    let json = encode_metrics(&create_test_history()).unwrap();
+    assert!(decode_metrics(&json).is_ok());
+
+    let tampered = json.replace("42.0", "43.0");
+    assert_ne!(tampered, json);
+    assert!(decode_metrics(&tampered).is_err());
+}
+
+#[test]
+fn test_decode_v2_history_without_checksum() {
+    let history_json = serde_json::to_string(&create_test_history()).unwrap();
+    let json = format!(r#"{{"version":2,"history":{history_json}}}"#);
+
+    let decoded = decode_metrics(&json).unwrap();
+    assert_eq!(decoded.cpu_usage_percent.len(), 1);
+}
+
+fn create_test_history() -> AllMetricsHistory {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let mut history = AllMetricsHistory::new();
+    history.cpu_usage_percent.push(TimestampedValue {
+        timestamp: now,
+        value: 42.0,
+    });
+    history
+        .models
+        .entry("test-model".to_string())
+        .or_default()
+        .push(&Metrics {
+            predicted_tokens_per_sec: 25.0,
+            ..Metrics::default()
+        });
+    history
+}
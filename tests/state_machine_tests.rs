@@ -0,0 +1,244 @@
+//! Randomized tests for the state machines in state_model.rs
+//!
+//! Every machine takes its inputs (service checks, poll results, clock) as
+//! arguments, so arbitrary event sequences can be replayed without a service.
+
+use llama_swap_swiftbar::state_model::{
+    AgentState, DisplayDebouncer, DisplayInputs, DisplayState, ModelState, ModelStateMachine,
+    PollingMode,
+};
+use proptest::prelude::*;
+use std::time::{Duration, Instant};
+
+const MODEL_STATES: &[DisplayState] = &[
+    DisplayState::ServiceLoadedNoModel,
+    DisplayState::ModelLoading,
+    DisplayState::ModelStopping,
+    DisplayState::ModelProcessingQueue,
+    DisplayState::ModelReady,
+];
+
+/// One poll's worth of service checks
+#[derive(Debug, Clone)]
+struct SystemCheck {
+    plist_installed: bool,
+    binary_available: bool,
+    service_running: bool,
+    process_running: bool,
+    since_start_secs: Option<u64>,
+}
+
+fn system_check() -> impl Strategy<Value = SystemCheck> {
+    (
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        proptest::option::of(0u64..120),
+    )
+        .prop_map(
+            |(plist_installed, binary_available, service_running, process_running, since)| {
+                SystemCheck {
+                    plist_installed,
+                    binary_available,
+                    service_running,
+                    process_running,
+                    since_start_secs: since,
+                }
+            },
+        )
+}
+
+fn display_inputs() -> impl Strategy<Value = DisplayInputs> {
+    (
+        any::<bool>(),
+        0usize..10,
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                start_pending,
+                error_count,
+                has_models,
+                has_loading_models,
+                has_queue_activity,
+                has_stopping_models,
+                crash_loop,
+            )| DisplayInputs {
+                start_pending,
+                error_count,
+                has_models,
+                has_loading_models,
+                has_queue_activity,
+                has_stopping_models,
+                crash_loop,
+            },
+        )
+}
+
+fn display_state() -> impl Strategy<Value = DisplayState> {
+    prop_oneof![
+        Just(DisplayState::AgentNotLoaded),
+        Just(DisplayState::AgentStarting),
+        Just(DisplayState::ServiceStopped),
+        Just(DisplayState::ServiceDegraded),
+        Just(DisplayState::StartupFailed),
+        Just(DisplayState::CrashLoop),
+        Just(DisplayState::ServiceLoadedNoModel),
+        Just(DisplayState::ModelLoading),
+        Just(DisplayState::ModelStopping),
+        Just(DisplayState::ModelProcessingQueue),
+        Just(DisplayState::ModelReady),
+    ]
+}
+
+fn model_state() -> impl Strategy<Value = ModelState> {
+    prop_oneof![
+        Just(ModelState::Unknown),
+        Just(ModelState::Loading),
+        Just(ModelState::Running),
+        Just(ModelState::Stopping),
+    ]
+}
+
+fn agent_state(check: &SystemCheck, timeout: Duration) -> AgentState {
+    AgentState::from_system_check(
+        check.plist_installed,
+        check.binary_available,
+        check.service_running,
+        check.process_running,
+    )
+    .with_startup_timeout(check.since_start_secs.map(Duration::from_secs), timeout)
+}
+
+proptest! {
+    #[test]
+    fn agent_and_display_states_stay_consistent(
+        checks in prop::collection::vec((system_check(), display_inputs()), 1..50),
+    ) {
+        let timeout = Duration::from_secs(30);
+
+        for (check, inputs) in &checks {
+            let agent = agent_state(check, timeout);
+            let display = DisplayState::compute(agent, inputs);
+
+            // Only a responsive API makes the agent Running
+            prop_assert_eq!(agent == AgentState::Running, check.service_running);
+
+            // A start is only declared failed once the timeout has passed
+            if agent == AgentState::FailedToStart {
+                prop_assert!(check.since_start_secs.is_some_and(|s| s >= timeout.as_secs()));
+                let expected = if inputs.crash_loop {
+                    DisplayState::CrashLoop
+                } else {
+                    DisplayState::StartupFailed
+                };
+                prop_assert_eq!(display, expected);
+            }
+
+            // A crash loop is only shown while the service is down
+            if display == DisplayState::CrashLoop {
+                prop_assert!(inputs.crash_loop);
+                prop_assert!(agent != AgentState::Running);
+            }
+
+            // Never show model states without a running agent
+            if MODEL_STATES.contains(&display) {
+                prop_assert_eq!(agent, AgentState::Running);
+            }
+            if matches!(agent, AgentState::NotReady { .. }) {
+                prop_assert_eq!(display, DisplayState::AgentNotLoaded);
+            }
+
+            // A running agent with models never claims there are none
+            if agent == AgentState::Running && inputs.has_models {
+                prop_assert_ne!(display, DisplayState::ServiceLoadedNoModel);
+            }
+        }
+    }
+
+    #[test]
+    fn debouncer_only_delays_busy_transitions(
+        polls in prop::collection::vec((display_state(), 0u64..5), 1..100),
+        min_polls in 1usize..4,
+        min_dwell_secs in 0u64..10,
+    ) {
+        let mut debouncer = DisplayDebouncer::new(min_polls, Duration::from_secs(min_dwell_secs));
+        let mut now = Instant::now();
+        let mut shown: Option<DisplayState> = None;
+
+        for (computed, step) in polls {
+            now += Duration::from_secs(step);
+            let result = debouncer.apply(computed, now);
+
+            match shown {
+                // Anything that isn't a ModelReady <-> ProcessingQueue flip shows immediately
+                Some(previous) if DisplayDebouncer::is_debounced(previous, computed) => {
+                    prop_assert!(result == computed || result == previous);
+                }
+                _ => prop_assert_eq!(result, computed),
+            }
+            prop_assert_eq!(debouncer.shown(), Some(result));
+            shown = Some(result);
+        }
+    }
+
+    #[test]
+    fn polling_mode_follows_failures_and_activity(
+        state_changed in any::<bool>(),
+        has_activity in any::<bool>(),
+        since_change_secs in 0u64..600,
+        failures in 0usize..1000,
+    ) {
+        let mode = PollingMode::compute(
+            PollingMode::Idle,
+            state_changed,
+            has_activity,
+            Duration::from_secs(since_change_secs),
+            failures,
+        );
+
+        if failures >= PollingMode::BACKOFF_AFTER_ERRORS {
+            prop_assert_eq!(mode, PollingMode::Backoff { failures });
+            prop_assert!(mode.interval() >= PollingMode::Idle.interval());
+        } else if state_changed || has_activity || since_change_secs < 5 {
+            prop_assert_eq!(mode, PollingMode::Active);
+        } else {
+            prop_assert_eq!(mode, PollingMode::Idle);
+        }
+        prop_assert!(mode.interval() >= PollingMode::Active.interval());
+    }
+
+    #[test]
+    fn model_state_machine_tracks_loading(
+        initial in model_state(),
+        observations in prop::collection::vec((model_state(), 0u64..30), 0..60),
+    ) {
+        let start = Instant::now();
+        let mut now = start;
+        let mut machine = ModelStateMachine::new(initial, now);
+        let mut changes = 0;
+
+        for (state, step) in observations {
+            now += Duration::from_secs(step);
+            let previous = machine.state();
+            let changed = machine.observe(state, now);
+
+            prop_assert_eq!(changed, previous != state);
+            prop_assert_eq!(machine.state(), state);
+            prop_assert_eq!(machine.loading_for(now).is_some(), state == ModelState::Loading);
+            if let Some(loaded_in) = machine.last_load_duration() {
+                prop_assert!(loaded_in <= now - start);
+            }
+
+            changes += usize::from(changed);
+            if changes < 4 {
+                prop_assert!(!machine.is_flapping(now));
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "sqlite")]
+
+use llama_swap_swiftbar::models::Metrics;
+use llama_swap_swiftbar::storage::MetricsStore;
+
+#[test]
+fn test_window_summary_aggregates_recent_samples() {
+    let dir = std::env::temp_dir().join(format!("llama-swap-storage-{}", std::process::id()));
+    let path = dir.join("history.db");
+    let store = MetricsStore::open(path.to_str().unwrap()).unwrap();
+
+    for (ts, tps) in [(1_000, 10.0), (2_000, 20.0), (3_000, 30.0)] {
+        let metrics = Metrics {
+            predicted_tokens_per_sec: tps,
+            requests_processing: 1,
+            ..Metrics::default()
+        };
+        store.record(ts, "test-model", &metrics).unwrap();
+    }
+
+    let summary = store.window_summary("test-model", 1_500).unwrap();
+    assert_eq!(summary.samples, 2);
+    assert_eq!(summary.avg_tps, 25.0);
+    assert_eq!(summary.peak_tps, 30.0);
+    assert_eq!(summary.avg_queue, 1.0);
+
+    // Other models are kept separate
+    let other = store.window_summary("other-model", 0).unwrap();
+    assert_eq!(other.samples, 0);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}